@@ -0,0 +1,81 @@
+use log::warn;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+/// A console or computer whose ROMs are kept in a few folders and run by one emulator
+#[derive(Serialize, Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct System {
+    /// Shown and stored with every game of the system, so it can't contain `:`
+    pub name: String,
+    /// Searched with their subfolders, a leading `~` stands for the home folder
+    pub rom_dirs: Vec<PathBuf>,
+    /// Extensions of the ROM files, archives are searched for them unless listed themselves
+    pub extensions: Vec<String>,
+    /// Emulator command split like a shell would, `{rom}`, `{entry}`, `{core}` and `{system}` are
+    /// filled in
+    ///
+    /// `{rom}` is the archive for ROMs inside one and `{entry}` the ROM's name in it, e.g.
+    /// `retroarch {rom}#{entry}`. Commands without `{entry}` only launch archives holding one ROM.
+    pub command: String,
+    /// Libretro core or any other file the command refers to as `{core}`
+    #[serde(default)]
+    pub core: Option<String>,
+}
+
+impl System {
+    pub fn accepts(&self, extension: &str) -> bool {
+        self.extensions
+            .iter()
+            .any(|ext| ext.trim_start_matches('.').eq_ignore_ascii_case(extension))
+    }
+}
+
+#[derive(Default, Debug)]
+pub struct Config {
+    pub systems: Vec<System>,
+}
+
+impl Config {
+    /// Reads the systems from the add-on config, where they are stored as JSON text
+    pub fn load() -> Config {
+        let path = gami_sdk::resolve_addon_config_json_path(crate::ID);
+        let Ok(file) = std::fs::File::open(path) else {
+            return Config::default();
+        };
+        let values: HashMap<String, String> = match serde_json::from_reader(file) {
+            Ok(values) => values,
+            Err(err) => {
+                warn!("Invalid emulation config: {}", err);
+                return Config::default();
+            }
+        };
+        let systems: Vec<System> = match values.get("systems") {
+            Some(systems) => serde_json::from_str(systems).unwrap_or_else(|err| {
+                warn!("Invalid emulation systems: {}", err);
+                Vec::new()
+            }),
+            None => Vec::new(),
+        };
+        Config {
+            systems: systems
+                .into_iter()
+                .filter(|system| {
+                    let valid = !system.name.is_empty() && !system.name.contains(':');
+                    if !valid {
+                        warn!(
+                            "Skipping system {:?}, its name can't contain ':'",
+                            system.name
+                        );
+                    }
+                    valid
+                })
+                .collect(),
+        }
+    }
+
+    pub fn system(&self, name: &str) -> Option<&System> {
+        self.systems.iter().find(|system| system.name == name)
+    }
+}
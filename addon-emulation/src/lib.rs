@@ -0,0 +1,231 @@
+mod conf;
+mod naming;
+mod scanner;
+
+use crate::conf::{Config, System};
+use gami_sdk::{
+    register_plugin, ActionError, ConfigSchemaKind, ConfigSchemaMetadata, GameInstallStatus,
+    GameLibrary, GameLibraryRef, LaunchCommand, LibraryAccount, PluginRegistrar,
+    ScannedGameLibraryMetadata,
+};
+use std::collections::HashMap;
+use std::path::Path;
+use std::process::Command;
+use std::sync::Arc;
+
+const ID: &str = "emulation";
+
+#[derive(Default)]
+pub struct EmulationLibrary;
+
+/// Splits a command template like a shell would and fills in its placeholders, each argument
+/// staying one argument even when a path has spaces
+///
+/// `entry` is the ROM inside the archive `rom`, if it is one.
+fn fill_template(
+    system: &System,
+    rom: &Path,
+    entry: Option<&str>,
+) -> Result<LaunchCommand, ActionError> {
+    let words = shlex::split(&system.command).ok_or_else(|| {
+        ActionError::Failed(format!(
+            "the command of {} has an unclosed quote or a trailing backslash",
+            system.name
+        ))
+    })?;
+    let mut args = Vec::new();
+    for word in words {
+        if word.contains("{core}") && system.core.is_none() {
+            return Err(ActionError::Failed(format!(
+                "the command of {} needs a core, but none is set",
+                system.name
+            )));
+        }
+        args.push(
+            word.replace("{rom}", &rom.to_string_lossy())
+                .replace("{entry}", entry.unwrap_or_default())
+                .replace("{core}", system.core.as_deref().unwrap_or_default())
+                .replace("{system}", &system.name),
+        );
+    }
+    if args.is_empty() {
+        return Err(ActionError::Failed(format!(
+            "{} has no emulator command",
+            system.name
+        )));
+    }
+    let program = args.remove(0);
+    Ok(LaunchCommand {
+        program,
+        args,
+        working_dir: None,
+    })
+}
+
+impl EmulationLibrary {
+    fn command(&self, game: GameLibraryRef) -> Result<LaunchCommand, ActionError> {
+        let library_id: &str = &game.library_id;
+        let Some((system, rom, entry)) = scanner::parse_library_id(library_id) else {
+            return Err(ActionError::Failed(format!(
+                "{} is not an emulated game",
+                library_id
+            )));
+        };
+        let config = Config::load();
+        let system = config.system(system).ok_or_else(|| {
+            ActionError::Failed(format!("no system named {} is configured", system))
+        })?;
+        // without {entry} the emulator picks the ROM of the archive, which must then be the only one
+        if entry.is_some() && !system.command.contains("{entry}") {
+            let roms = scanner::archive_roms(system, &rom).map_err(|err| {
+                ActionError::Failed(format!("could not read {}: {}", rom.display(), err))
+            })?;
+            if roms.len() > 1 {
+                return Err(ActionError::Failed(format!(
+                    "{} holds several ROMs, add {{entry}} to the command of {} to pick one",
+                    rom.display(),
+                    system.name
+                )));
+            }
+        }
+        fill_template(system, &rom, entry)
+    }
+}
+
+impl GameLibrary for EmulationLibrary {
+    fn get_accounts(&self) -> Vec<LibraryAccount> {
+        Vec::new()
+    }
+    fn scan(&self, account_id: &str) -> Vec<ScannedGameLibraryMetadata> {
+        Config::load()
+            .systems
+            .iter()
+            .flat_map(|system| {
+                let roms = scanner::scan_system(system);
+                let names = scanner::display_names(&roms);
+                roms.into_iter()
+                    .zip(names)
+                    .map(|(rom, name)| ScannedGameLibraryMetadata {
+                        name: name.into(),
+                        library_type: ID.into(),
+                        library_id: rom.library_id().into(),
+                        account_id: account_id.into(),
+                        install_status: GameInstallStatus::Installed,
+                        ..ScannedGameLibraryMetadata::default()
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect()
+    }
+    fn launch(&self, game: GameLibraryRef) -> Result<(), ActionError> {
+        let launch = self.command(game)?;
+        Command::new(&launch.program)
+            .args(&launch.args)
+            .spawn()
+            .map(drop)
+            .map_err(|err| {
+                ActionError::Failed(format!("could not run {}: {}", launch.program, err))
+            })
+    }
+    fn launch_command(&self, game: GameLibraryRef) -> Option<LaunchCommand> {
+        self.command(game).ok()
+    }
+    fn install(&self, _game: GameLibraryRef) -> Result<(), ActionError> {
+        Err(ActionError::Failed(
+            "ROMs are installed by copying them into a system's folder".into(),
+        ))
+    }
+    fn uninstall(&self, _game: GameLibraryRef) -> Result<(), ActionError> {
+        Err(ActionError::Failed(
+            "ROMs are uninstalled by deleting them from a system's folder".into(),
+        ))
+    }
+    fn check_install_status(&self, game: GameLibraryRef) -> GameInstallStatus {
+        match scanner::parse_library_id(&game.library_id) {
+            Some((_, rom, _)) if rom.exists() => GameInstallStatus::Installed,
+            _ => GameInstallStatus::InLibrary,
+        }
+    }
+}
+
+register_plugin!(register, ID, "Emulation");
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register(registrar: &mut dyn PluginRegistrar) {
+    registrar.register_library(ID, Arc::new(EmulationLibrary));
+
+    let mut conf: HashMap<String, ConfigSchemaMetadata> = HashMap::with_capacity(1);
+    conf.insert(
+        "systems".into(),
+        ConfigSchemaMetadata {
+            name: "Systems".into(),
+            hint: "JSON list of systems with a name, romDirs, extensions, command and core".into(),
+            kind: ConfigSchemaKind::Json,
+        },
+    );
+    registrar.register_config(ID, conf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fill_template() {
+        let system = System {
+            name: "SNES".into(),
+            command: "retroarch -L {core} {rom}".into(),
+            core: Some("/usr/lib/libretro/snes9x_libretro.so".into()),
+            ..System::default()
+        };
+
+        let launch =
+            fill_template(&system, Path::new("/roms/Super Metroid (USA).sfc"), None).unwrap();
+
+        assert_eq!(launch.program, "retroarch");
+        assert_eq!(
+            launch.args,
+            [
+                "-L",
+                "/usr/lib/libretro/snes9x_libretro.so",
+                "/roms/Super Metroid (USA).sfc"
+            ]
+        );
+        let coreless = System {
+            core: None,
+            ..system
+        };
+        assert!(fill_template(&coreless, Path::new("rom.sfc"), None).is_err());
+    }
+
+    #[test]
+    fn test_fill_template_keeps_quoted_words_and_entries() {
+        let system = System {
+            name: "SNES".into(),
+            command: r#""/opt/My Emulator/run" --title "{system} game" {rom}#{entry}"#.into(),
+            ..System::default()
+        };
+
+        let launch = fill_template(
+            &system,
+            Path::new("/roms/Mario Collection.zip"),
+            Some("Super Mario World (USA).sfc"),
+        )
+        .unwrap();
+
+        assert_eq!(launch.program, "/opt/My Emulator/run");
+        assert_eq!(
+            launch.args,
+            [
+                "--title",
+                "SNES game",
+                "/roms/Mario Collection.zip#Super Mario World (USA).sfc"
+            ]
+        );
+        let unclosed = System {
+            command: "\"emulator {rom}".into(),
+            ..system
+        };
+        assert!(fill_template(&unclosed, Path::new("rom.sfc"), None).is_err());
+    }
+}
@@ -0,0 +1,161 @@
+//! Reading titles and tags from ROM file names following the No-Intro and Redump conventions
+//!
+//! Those look like `Legend of Zelda, The - A Link to the Past (USA) (Rev 1) [!]`: the title,
+//! with a leading article moved behind a comma, then tags in parentheses and dump flags in brackets.
+
+const REGIONS: &[&str] = &[
+    "World",
+    "USA",
+    "Europe",
+    "Japan",
+    "Asia",
+    "Australia",
+    "Brazil",
+    "Canada",
+    "China",
+    "France",
+    "Germany",
+    "Hong Kong",
+    "Italy",
+    "Korea",
+    "Netherlands",
+    "Russia",
+    "Scandinavia",
+    "Spain",
+    "Sweden",
+    "Taiwan",
+    "UK",
+    "Unknown",
+];
+const ARTICLES: &[&str] = &["The", "A", "An"];
+
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct RomName {
+    pub title: String,
+    pub regions: Vec<String>,
+    /// Such as `Rev 1` or `v1.1`
+    pub revision: Option<String>,
+    /// Such as `Disc 2`
+    pub disc: Option<String>,
+    /// Every other tag, e.g. languages, `Beta` or the `!` of verified dumps
+    pub tags: Vec<String>,
+}
+
+impl RomName {
+    pub fn parse(file_stem: &str) -> RomName {
+        let title_end = file_stem.find(['(', '[']).unwrap_or(file_stem.len());
+        let mut name = RomName {
+            title: move_article(file_stem[..title_end].trim()),
+            ..RomName::default()
+        };
+        let mut rest = &file_stem[title_end..];
+        while let Some(start) = rest.find(['(', '[']) {
+            let close = if rest[start..].starts_with('(') {
+                ')'
+            } else {
+                ']'
+            };
+            let Some(len) = rest[start..].find(close) else {
+                break;
+            };
+            let tag = rest[start + 1..start + len].trim();
+            rest = &rest[start + len + 1..];
+            if close == ']' {
+                name.tags.push(tag.to_string());
+            } else if tag
+                .split(',')
+                .all(|region| REGIONS.contains(&region.trim()))
+            {
+                name.regions
+                    .extend(tag.split(',').map(|region| region.trim().to_string()));
+            } else if is_revision(tag) {
+                name.revision = Some(tag.to_string());
+            } else if tag.starts_with("Disc ") {
+                name.disc = Some(tag.to_string());
+            } else {
+                name.tags.push(tag.to_string());
+            }
+        }
+        name
+    }
+
+    /// The title with the disc, and with the regions and revision when `distinguish` is set
+    pub fn display_name(&self, distinguish: bool) -> String {
+        let mut name = self.title.clone();
+        let mut tags: Vec<&str> = Vec::new();
+        if distinguish {
+            tags.extend(self.regions.iter().map(String::as_str));
+            tags.extend(self.revision.as_deref());
+        }
+        if !tags.is_empty() {
+            name.push_str(&format!(" ({})", tags.join(", ")));
+        }
+        if let Some(disc) = &self.disc {
+            name.push_str(&format!(" ({})", disc));
+        }
+        name
+    }
+}
+
+fn is_revision(tag: &str) -> bool {
+    match tag.strip_prefix("Rev ") {
+        Some(revision) => !revision.is_empty(),
+        None => tag.strip_prefix('v').is_some_and(|version| {
+            version.starts_with(|c: char| c.is_ascii_digit()) && !version.contains(' ')
+        }),
+    }
+}
+
+/// Turns `Legend of Zelda, The - A Link to the Past` into `The Legend of Zelda - A Link to the Past`
+fn move_article(title: &str) -> String {
+    let (main, subtitle) = match title.split_once(" - ") {
+        Some((main, subtitle)) => (main, Some(subtitle)),
+        None => (title, None),
+    };
+    let main = ARTICLES
+        .iter()
+        .find_map(|article| {
+            main.strip_suffix(&format!(", {}", article))
+                .map(|rest| format!("{} {}", article, rest))
+        })
+        .unwrap_or_else(|| main.to_string());
+    match subtitle {
+        Some(subtitle) => format!("{} - {}", main, subtitle),
+        None => main,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_no_intro_name() {
+        let name =
+            RomName::parse("Legend of Zelda, The - A Link to the Past (USA, Europe) (Rev 1) [!]");
+        assert_eq!(
+            name,
+            RomName {
+                title: "The Legend of Zelda - A Link to the Past".into(),
+                regions: vec!["USA".into(), "Europe".into()],
+                revision: Some("Rev 1".into()),
+                disc: None,
+                tags: vec!["!".into()],
+            }
+        );
+        assert_eq!(
+            name.display_name(true),
+            "The Legend of Zelda - A Link to the Past (USA, Europe, Rev 1)"
+        );
+    }
+
+    #[test]
+    fn test_parse_redump_name() {
+        let name = RomName::parse("Final Fantasy VII (Europe) (En,Fr) (Disc 2) (v1.1)");
+        assert_eq!(name.title, "Final Fantasy VII");
+        assert_eq!(name.regions, ["Europe"]);
+        assert_eq!(name.revision.as_deref(), Some("v1.1"));
+        assert_eq!(name.tags, ["En,Fr"]);
+        assert_eq!(name.display_name(false), "Final Fantasy VII (Disc 2)");
+    }
+}
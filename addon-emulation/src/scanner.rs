@@ -0,0 +1,299 @@
+use crate::conf::System;
+use crate::naming::RomName;
+use log::warn;
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+
+/// Separates an archive from the ROM it contains in library IDs
+const ENTRY_SEPARATOR: char = '#';
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Rom {
+    pub system: String,
+    /// The ROM file, or the archive holding it
+    pub path: PathBuf,
+    /// Name of the ROM inside the archive
+    pub entry: Option<String>,
+    pub name: RomName,
+}
+
+impl Rom {
+    /// `<system>:<path>`, followed by `#<entry>` for a ROM inside an archive
+    pub fn library_id(&self) -> String {
+        let mut id = format!("{}:{}", self.system, self.path.display());
+        if let Some(entry) = &self.entry {
+            id.push(ENTRY_SEPARATOR);
+            id.push_str(entry);
+        }
+        id
+    }
+}
+
+/// The system name, the file to open and the archive entry of a ROM's library ID
+pub fn parse_library_id(library_id: &str) -> Option<(&str, PathBuf, Option<&str>)> {
+    let (system, path) = library_id.split_once(':')?;
+    // file names may contain the separator too, so only one following an archive counts
+    let split = path
+        .match_indices(ENTRY_SEPARATOR)
+        .map(|(index, _)| (&path[..index], &path[index + 1..]))
+        .find(|(archive, _)| is_archive(Path::new(archive)));
+    Some(match split {
+        Some((archive, entry)) => (system, PathBuf::from(archive), Some(entry)),
+        None => (system, PathBuf::from(path), None),
+    })
+}
+
+fn extension(path: &Path) -> Option<String> {
+    Some(path.extension()?.to_str()?.to_ascii_lowercase())
+}
+
+fn is_archive(path: &Path) -> bool {
+    matches!(extension(path).as_deref(), Some("zip" | "7z"))
+}
+
+fn list_archive(path: &Path) -> io::Result<Vec<String>> {
+    let mut file = File::open(path)?;
+    if extension(path).as_deref() == Some("zip") {
+        let archive = zip::ZipArchive::new(file).map_err(io::Error::other)?;
+        return Ok(archive
+            .file_names()
+            .filter(|name| !name.ends_with('/'))
+            .map(String::from)
+            .collect());
+    }
+    let len = file.metadata()?.len();
+    let archive = sevenz_rust::Archive::read(&mut file, len, &[]).map_err(io::Error::other)?;
+    Ok(archive
+        .files
+        .into_iter()
+        .filter(|entry| entry.has_stream() && !entry.is_directory())
+        .map(|entry| entry.name)
+        .collect())
+}
+
+/// ROMs of `system` inside an archive
+pub fn archive_roms(system: &System, path: &Path) -> io::Result<Vec<String>> {
+    Ok(list_archive(path)?
+        .into_iter()
+        .filter(|entry| extension(Path::new(entry)).is_some_and(|ext| system.accepts(&ext)))
+        .collect())
+}
+
+/// Collects the files below `dir`, reading each folder once even when symlinks lead back to it
+///
+/// Folders that can't be read are logged and skipped, the rest is still scanned.
+fn walk(dir: &Path, visited: &mut HashSet<PathBuf>, files: &mut Vec<PathBuf>) {
+    let entries = match fs::canonicalize(dir) {
+        Ok(real) => {
+            if !visited.insert(real) {
+                return;
+            }
+            fs::read_dir(dir)
+        }
+        Err(err) => Err(err),
+    };
+    let entries = match entries {
+        Ok(entries) => entries,
+        Err(err) => {
+            warn!("Could not read {}: {}", dir.display(), err);
+            return;
+        }
+    };
+    for entry in entries {
+        let path = match entry {
+            Ok(entry) => entry.path(),
+            Err(err) => {
+                warn!("Could not read an entry of {}: {}", dir.display(), err);
+                continue;
+            }
+        };
+        if path.is_dir() {
+            walk(&path, visited, files);
+        } else {
+            files.push(path);
+        }
+    }
+}
+
+/// Replaces a leading `~` with the home folder, as config paths are written like in a shell
+fn expand_home(dir: &Path) -> PathBuf {
+    match (dir.strip_prefix("~"), dirs::home_dir()) {
+        (Ok(rest), Some(home)) => home.join(rest),
+        _ => dir.to_path_buf(),
+    }
+}
+
+fn file_stem(name: &str) -> &str {
+    let name = name.rsplit(['/', '\\']).next().unwrap_or(name);
+    name.rsplit_once('.').map_or(name, |(stem, _)| stem)
+}
+
+/// Finds the ROMs in the folders of a system and in the archives there
+pub fn scan_system(system: &System) -> Vec<Rom> {
+    let mut files = Vec::new();
+    let mut visited = HashSet::new();
+    for dir in &system.rom_dirs {
+        walk(&expand_home(dir), &mut visited, &mut files);
+    }
+    files.sort();
+    let mut roms = Vec::new();
+    for path in files {
+        let Some(ext) = extension(&path) else {
+            continue;
+        };
+        if system.accepts(&ext) {
+            let name = path.file_stem().unwrap_or_default().to_string_lossy();
+            roms.push(Rom {
+                system: system.name.clone(),
+                name: RomName::parse(&name),
+                path,
+                entry: None,
+            });
+        } else if is_archive(&path) {
+            let entries = match archive_roms(system, &path) {
+                Ok(entries) => entries,
+                Err(err) => {
+                    warn!("Could not read the archive {}: {}", path.display(), err);
+                    continue;
+                }
+            };
+            roms.extend(entries.into_iter().map(|entry| Rom {
+                system: system.name.clone(),
+                path: path.clone(),
+                name: RomName::parse(file_stem(&entry)),
+                entry: Some(entry),
+            }));
+        }
+    }
+    roms
+}
+
+/// Game names of `roms`, with regions and revisions added where titles would be ambiguous
+pub fn display_names(roms: &[Rom]) -> Vec<String> {
+    let mut counts: HashMap<String, usize> = HashMap::new();
+    for rom in roms {
+        *counts.entry(rom.name.display_name(false)).or_default() += 1;
+    }
+    roms.iter()
+        .map(|rom| {
+            let plain = rom.name.display_name(false);
+            if counts[&plain] > 1 {
+                rom.name.display_name(true)
+            } else {
+                plain
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn test_scans_folders_and_archives() {
+        let dir = std::env::temp_dir().join(format!("gami-emulation-{}", std::process::id()));
+        fs::create_dir_all(dir.join("hacks")).unwrap();
+        fs::write(dir.join("Super Metroid (Japan, USA) (En,Ja).sfc"), b"").unwrap();
+        fs::write(dir.join("hacks/Super Metroid (Europe) (En,Fr,De).sfc"), b"").unwrap();
+        fs::write(dir.join("notes.txt"), b"").unwrap();
+        let mut zip = zip::ZipWriter::new(File::create(dir.join("zelda.zip")).unwrap());
+        zip.start_file::<_, ()>(
+            "Legend of Zelda, The - A Link to the Past (USA).sfc",
+            Default::default(),
+        )
+        .unwrap();
+        zip.write_all(b"rom").unwrap();
+        zip.finish().unwrap();
+        let system = System {
+            name: "SNES".into(),
+            rom_dirs: vec![dir.clone()],
+            extensions: vec![".sfc".into()],
+            ..System::default()
+        };
+
+        let roms = scan_system(&system);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(
+            display_names(&roms),
+            [
+                "Super Metroid (Japan, USA)",
+                "Super Metroid (Europe)",
+                "The Legend of Zelda - A Link to the Past",
+            ]
+        );
+        let zelda = &roms[2];
+        assert_eq!(
+            parse_library_id(&zelda.library_id()),
+            Some((
+                "SNES",
+                dir.join("zelda.zip"),
+                Some("Legend of Zelda, The - A Link to the Past (USA).sfc")
+            ))
+        );
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_symlink_loops_are_read_once() {
+        let dir = std::env::temp_dir().join(format!("gami-emulation-loop-{}", std::process::id()));
+        fs::create_dir_all(dir.join("sub")).unwrap();
+        fs::write(dir.join("sub/Celeste Classic.p8"), b"").unwrap();
+        std::os::unix::fs::symlink(&dir, dir.join("sub/up")).unwrap();
+        let system = System {
+            name: "PICO-8".into(),
+            rom_dirs: vec![dir.clone(), dir.join("sub")],
+            extensions: vec!["p8".into()],
+            ..System::default()
+        };
+
+        let roms = scan_system(&system);
+        fs::remove_dir_all(&dir).unwrap();
+
+        assert_eq!(display_names(&roms), ["Celeste Classic"]);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_unreadable_folders_are_skipped() {
+        use std::os::unix::fs::PermissionsExt;
+        let dir =
+            std::env::temp_dir().join(format!("gami-emulation-locked-{}", std::process::id()));
+        fs::create_dir_all(dir.join("locked")).unwrap();
+        fs::write(dir.join("Celeste Classic.p8"), b"").unwrap();
+        fs::write(dir.join("locked/Hidden.p8"), b"").unwrap();
+        fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o000)).unwrap();
+        let system = System {
+            name: "PICO-8".into(),
+            rom_dirs: vec![dir.clone(), dir.join("missing")],
+            extensions: vec!["p8".into()],
+            ..System::default()
+        };
+
+        let roms = scan_system(&system);
+        fs::set_permissions(dir.join("locked"), fs::Permissions::from_mode(0o755)).unwrap();
+        fs::remove_dir_all(&dir).unwrap();
+
+        // root reads the locked folder anyway
+        assert!(display_names(&roms).contains(&"Celeste Classic".to_string()));
+    }
+
+    #[test]
+    fn test_rom_dirs_expand_the_home_folder() {
+        let home = dirs::home_dir().unwrap();
+        assert_eq!(
+            expand_home(Path::new("~/roms/snes")),
+            home.join("roms/snes")
+        );
+        assert_eq!(expand_home(Path::new("~")), home);
+        assert_eq!(
+            expand_home(Path::new("/roms/~/snes")),
+            Path::new("/roms/~/snes")
+        );
+        assert_eq!(expand_home(Path::new("~snes")), Path::new("~snes"));
+    }
+}
@@ -0,0 +1,94 @@
+use log::warn;
+use std::collections::HashMap;
+use std::path::PathBuf;
+
+const DEFAULT_EXECUTABLE: &str = "retroarch";
+const FLATPAK_DIR: &str = ".var/app/org.libretro.RetroArch/config/retroarch";
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Config {
+    /// RetroArch's own config folder, holding `playlists`
+    pub dir: PathBuf,
+    /// Program and leading arguments starting RetroArch, or why the configured command is invalid
+    pub executable: Result<Vec<String>, String>,
+}
+
+impl Config {
+    /// Reads the add-on config, falling back to the usual install locations
+    pub fn load() -> Config {
+        let path = gami_sdk::resolve_addon_config_json_path(crate::ID);
+        let values: HashMap<String, String> = match std::fs::File::open(path) {
+            Ok(file) => serde_json::from_reader(file).unwrap_or_else(|err| {
+                warn!("Invalid RetroArch config: {}", err);
+                HashMap::new()
+            }),
+            Err(_) => HashMap::new(),
+        };
+        let dir = values
+            .get("retroarchDir")
+            .map(|dir| dir.trim())
+            .filter(|dir| !dir.is_empty())
+            .map(PathBuf::from)
+            .unwrap_or_else(default_dir);
+        let executable = parse_executable(values.get("executable").map(String::as_str));
+        Config { dir, executable }
+    }
+
+    pub fn playlists_dir(&self) -> PathBuf {
+        self.dir.join("playlists")
+    }
+
+    pub fn runtime_logs_dir(&self) -> PathBuf {
+        self.playlists_dir().join("logs")
+    }
+}
+
+/// Splits the configured command like a shell, so a quoted path with spaces stays one word
+fn parse_executable(executable: Option<&str>) -> Result<Vec<String>, String> {
+    match executable.map(str::trim).filter(|e| !e.is_empty()) {
+        Some(executable) => match shlex::split(executable) {
+            Some(words) if !words.is_empty() => Ok(words),
+            Some(_) => Err(format!("the executable `{}` names no program", executable)),
+            None => Err(format!(
+                "the executable `{}` has an unclosed quote",
+                executable
+            )),
+        },
+        None => Ok(vec![DEFAULT_EXECUTABLE.into()]),
+    }
+}
+
+/// The native config folder, or the Flatpak one when only that exists
+fn default_dir() -> PathBuf {
+    let name = if cfg!(target_os = "linux") {
+        "retroarch"
+    } else {
+        "RetroArch"
+    };
+    let native = dirs::config_dir().unwrap_or_default().join(name);
+    if cfg!(target_os = "linux") && !native.exists() {
+        if let Some(flatpak) = dirs::home_dir().map(|home| home.join(FLATPAK_DIR)) {
+            if flatpak.exists() {
+                return flatpak;
+            }
+        }
+    }
+    native
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_executable_splits_like_a_shell() {
+        assert_eq!(parse_executable(None).unwrap(), [DEFAULT_EXECUTABLE]);
+        assert_eq!(parse_executable(Some("  ")).unwrap(), [DEFAULT_EXECUTABLE]);
+        assert_eq!(
+            parse_executable(Some("'/opt/Retro Arch/retroarch' --verbose")).unwrap(),
+            ["/opt/Retro Arch/retroarch", "--verbose"]
+        );
+        assert!(parse_executable(Some("\"/opt/Retro Arch/retroarch")).is_err());
+        assert!(parse_executable(Some("# retroarch")).is_err());
+    }
+}
@@ -0,0 +1,189 @@
+mod conf;
+mod playlist;
+mod runtime_log;
+
+use crate::conf::Config;
+use crate::playlist::Entry;
+use gami_sdk::{
+    register_plugin, ActionError, ConfigSchemaKind, ConfigSchemaMetadata, GameInstallStatus,
+    GameLibrary, GameLibraryRef, LaunchCommand, LibraryAccount, PluginRegistrar,
+    ScannedGameLibraryMetadata,
+};
+use std::collections::HashMap;
+use std::io;
+use std::process::Command;
+use std::sync::Arc;
+
+const ID: &str = "retroarch";
+
+#[derive(Default)]
+pub struct RetroArchLibrary;
+
+/// Runs the content of `entry` with its core, through the configured RetroArch command
+fn entry_command(config: &Config, entry: &Entry) -> Result<LaunchCommand, ActionError> {
+    let Some(core) = &entry.core_path else {
+        return Err(ActionError::Failed(format!(
+            "no core is set for {} in its playlist",
+            entry.name()
+        )));
+    };
+    let mut args = config.executable.clone().map_err(ActionError::Failed)?;
+    let program = args.remove(0);
+    args.extend([
+        "-L".into(),
+        core.to_string_lossy().into_owned(),
+        entry.item.path.clone(),
+    ]);
+    Ok(LaunchCommand {
+        program,
+        args,
+        working_dir: None,
+    })
+}
+
+impl RetroArchLibrary {
+    fn command(&self, game: GameLibraryRef) -> Result<LaunchCommand, ActionError> {
+        let library_id: &str = &game.library_id;
+        let config = Config::load();
+        let entry = playlist::read_playlists(&config.playlists_dir())
+            .into_iter()
+            .find(|entry| entry.item.path == library_id)
+            .ok_or_else(|| {
+                ActionError::Failed(format!("{} is in no RetroArch playlist", library_id))
+            })?;
+        entry_command(&config, &entry)
+    }
+}
+
+impl GameLibrary for RetroArchLibrary {
+    fn get_accounts(&self) -> Vec<LibraryAccount> {
+        Vec::new()
+    }
+    fn scan(&self, account_id: &str) -> Vec<ScannedGameLibraryMetadata> {
+        let config = Config::load();
+        let stats = runtime_log::read_runtime_logs(&config.runtime_logs_dir());
+        playlist::read_playlists(&config.playlists_dir())
+            .into_iter()
+            .map(|entry| {
+                let played = stats
+                    .get(&playlist::content_name(&entry.item.path))
+                    .copied()
+                    .unwrap_or_default();
+                let install_status = if playlist::content_file(&entry.item.path).exists() {
+                    GameInstallStatus::Installed
+                } else {
+                    GameInstallStatus::InLibrary
+                };
+                ScannedGameLibraryMetadata {
+                    name: entry.name().into(),
+                    library_type: ID.into(),
+                    library_id: entry.item.path.into(),
+                    account_id: account_id.into(),
+                    install_status,
+                    playtime_secs: played.runtime_secs,
+                    last_played_epoch: played
+                        .last_played
+                        .map(|date| date.timestamp() as u64)
+                        .into(),
+                    ..ScannedGameLibraryMetadata::default()
+                }
+            })
+            .collect()
+    }
+    fn launch(&self, game: GameLibraryRef) -> Result<(), ActionError> {
+        let launch = self.command(game)?;
+        match Command::new(&launch.program).args(&launch.args).spawn() {
+            Ok(_) => Ok(()),
+            Err(err) if err.kind() == io::ErrorKind::NotFound => {
+                Err(ActionError::ClientMissing("RetroArch".into()))
+            }
+            Err(err) => Err(ActionError::Failed(format!(
+                "could not run {}: {}",
+                launch.program, err
+            ))),
+        }
+    }
+    fn launch_command(&self, game: GameLibraryRef) -> Option<LaunchCommand> {
+        self.command(game).ok()
+    }
+    fn reports_play_stats(&self) -> bool {
+        true
+    }
+    fn install(&self, _game: GameLibraryRef) -> Result<(), ActionError> {
+        Err(ActionError::Failed(
+            "content is added to RetroArch playlists from RetroArch itself".into(),
+        ))
+    }
+    fn uninstall(&self, _game: GameLibraryRef) -> Result<(), ActionError> {
+        Err(ActionError::Failed(
+            "content is removed from RetroArch playlists from RetroArch itself".into(),
+        ))
+    }
+    fn check_install_status(&self, game: GameLibraryRef) -> GameInstallStatus {
+        if playlist::content_file(&game.library_id).exists() {
+            GameInstallStatus::Installed
+        } else {
+            GameInstallStatus::InLibrary
+        }
+    }
+}
+
+register_plugin!(register, ID, "RetroArch");
+#[no_mangle]
+#[allow(improper_ctypes_definitions)]
+extern "C" fn register(registrar: &mut dyn PluginRegistrar) {
+    registrar.register_library(ID, Arc::new(RetroArchLibrary));
+
+    let mut conf: HashMap<String, ConfigSchemaMetadata> = HashMap::with_capacity(2);
+    conf.insert(
+        "retroarchDir".into(),
+        ConfigSchemaMetadata {
+            name: "RetroArch folder".into(),
+            hint: "Folder holding retroarch.cfg and the playlists folder".into(),
+            kind: ConfigSchemaKind::String,
+        },
+    );
+    conf.insert(
+        "executable".into(),
+        ConfigSchemaMetadata {
+            name: "Executable".into(),
+            hint: "Command starting RetroArch, e.g. flatpak run org.libretro.RetroArch".into(),
+            kind: ConfigSchemaKind::String,
+        },
+    );
+    registrar.register_config(ID, conf);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::path::Path;
+
+    #[test]
+    fn test_entry_command() {
+        let config = Config {
+            dir: Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/retroarch"),
+            executable: Ok(vec![
+                "flatpak".into(),
+                "run".into(),
+                "org.libretro.RetroArch".into(),
+            ]),
+        };
+        let entries = playlist::read_playlists(&config.playlists_dir());
+
+        let launch = entry_command(&config, &entries[1]).unwrap();
+
+        assert_eq!(launch.program, "flatpak");
+        assert_eq!(
+            launch.args,
+            [
+                "run",
+                "org.libretro.RetroArch",
+                "-L",
+                "/usr/lib/libretro/snes9x_libretro.so",
+                "/roms/snes/Chrono Trigger (USA).sfc"
+            ]
+        );
+        assert!(entry_command(&config, &entries[3]).is_err());
+    }
+}
@@ -0,0 +1,170 @@
+//! Reading RetroArch's JSON playlists, the `.lpl` files of its `playlists` folder
+
+use log::warn;
+use serde::Deserialize;
+use std::collections::HashSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// Stands for "pick a core when launching" in place of a core path or name
+const DETECT: &str = "DETECT";
+/// Separates an archive from the file it contains in content paths
+const ARCHIVE_SEPARATOR: char = '#';
+
+#[derive(Deserialize, Default, Debug)]
+#[serde(default)]
+pub struct Playlist {
+    pub default_core_path: String,
+    pub default_core_name: String,
+    pub items: Vec<PlaylistItem>,
+}
+
+#[derive(Deserialize, Default, Debug, Clone, PartialEq, Eq)]
+#[serde(default)]
+pub struct PlaylistItem {
+    /// Content path, `<archive>#<file>` for content inside an archive
+    pub path: String,
+    pub label: String,
+    pub core_path: String,
+    pub core_name: String,
+    pub crc32: String,
+    pub db_name: String,
+}
+
+/// A playlist entry with the core it runs on resolved
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Entry {
+    pub item: PlaylistItem,
+    pub core_path: Option<PathBuf>,
+    pub core_name: Option<String>,
+}
+
+fn chosen(value: &str) -> Option<&str> {
+    Some(value).filter(|value| !value.is_empty() && *value != DETECT)
+}
+
+impl Entry {
+    fn new(item: PlaylistItem, playlist: &Playlist) -> Entry {
+        let core_path = chosen(&item.core_path)
+            .or_else(|| chosen(&playlist.default_core_path))
+            .map(PathBuf::from);
+        let core_name = chosen(&item.core_name)
+            .or_else(|| chosen(&playlist.default_core_name))
+            .map(String::from);
+        Entry {
+            item,
+            core_path,
+            core_name,
+        }
+    }
+
+    /// The label, or the content's file name for unlabelled entries
+    pub fn name(&self) -> String {
+        if !self.item.label.is_empty() {
+            return self.item.label.clone();
+        }
+        content_name(&self.item.path)
+    }
+}
+
+/// The file on disk of a content path, the archive for content inside one
+pub fn content_file(path: &str) -> PathBuf {
+    let archive = path
+        .match_indices(ARCHIVE_SEPARATOR)
+        .map(|(index, _)| &path[..index])
+        .find(|archive| Path::new(archive).is_file());
+    PathBuf::from(archive.unwrap_or(path))
+}
+
+/// File name without extension of the content, as RetroArch names its runtime logs
+pub fn content_name(path: &str) -> String {
+    let name = path
+        .rsplit(['/', '\\', ARCHIVE_SEPARATOR])
+        .next()
+        .unwrap_or(path);
+    name.rsplit_once('.')
+        .map_or(name, |(stem, _)| stem)
+        .to_string()
+}
+
+fn is_history(path: &Path) -> bool {
+    path.file_stem()
+        .is_some_and(|stem| stem.to_string_lossy().ends_with("_history"))
+}
+
+pub fn read_playlist(path: &Path) -> Result<Playlist, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    if !text.trim_start().starts_with('{') {
+        return Err("playlists from before RetroArch 1.7.6 aren't supported".into());
+    }
+    serde_json::from_str(&text).map_err(|err| err.to_string())
+}
+
+/// Entries of every playlist in `dir` but the history ones, each content only once
+pub fn read_playlists(dir: &Path) -> Vec<Entry> {
+    let mut files: Vec<PathBuf> = match fs::read_dir(dir) {
+        Ok(files) => files
+            .filter_map(|file| Some(file.ok()?.path()))
+            .filter(|path| path.extension().is_some_and(|ext| ext == "lpl"))
+            .filter(|path| !is_history(path))
+            .collect(),
+        Err(err) => {
+            warn!("Could not read playlists in {}: {}", dir.display(), err);
+            return Vec::new();
+        }
+    };
+    files.sort();
+    let mut seen = HashSet::new();
+    let mut entries = Vec::new();
+    for file in files {
+        let playlist = match read_playlist(&file) {
+            Ok(playlist) => playlist,
+            Err(err) => {
+                warn!("Skipping playlist {}: {}", file.display(), err);
+                continue;
+            }
+        };
+        for item in &playlist.items {
+            if !item.path.is_empty() && seen.insert(item.path.clone()) {
+                entries.push(Entry::new(item.clone(), &playlist));
+            }
+        }
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_reads_playlists_with_cores() {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/retroarch/playlists");
+
+        let entries = read_playlists(&dir);
+
+        let names: Vec<String> = entries.iter().map(Entry::name).collect();
+        assert_eq!(
+            names,
+            [
+                "Super Metroid (Japan, USA)",
+                "Chrono Trigger (USA)",
+                "Sonic the Hedgehog (USA, Europe)",
+                "F-Zero (USA)"
+            ]
+        );
+        let core = |entry: &Entry| entry.core_path.clone();
+        assert_eq!(
+            core(&entries[0]),
+            Some("/usr/lib/libretro/bsnes_libretro.so".into())
+        );
+        // DETECT falls back to the playlist's default core
+        assert_eq!(
+            core(&entries[1]),
+            Some("/usr/lib/libretro/snes9x_libretro.so".into())
+        );
+        assert_eq!(entries[1].core_name.as_deref(), Some("Snes9x"));
+        assert_eq!(core(&entries[3]), None);
+        assert_eq!(content_name(&entries[3].item.path), "F-Zero (USA)");
+    }
+}
@@ -0,0 +1,129 @@
+//! Reading the per-content runtime logs RetroArch writes when `content_runtime_log` or
+//! `content_runtime_log_aggregate` is enabled
+//!
+//! Each `.lrtl` file is named after the content's file and lives in `logs/<core>` for per-core
+//! logs, or in `logs/retroarch` for the aggregate of all cores.
+
+use chrono::{DateTime, Local, NaiveDateTime, TimeZone, Utc};
+use log::warn;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+const AGGREGATE_DIR: &str = "retroarch";
+
+#[derive(Deserialize)]
+struct RuntimeLog {
+    /// `H:MM:SS`
+    runtime: String,
+    /// `YYYY-MM-DD HH:MM:SS` in local time
+    last_played: String,
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct PlayStats {
+    pub runtime_secs: u64,
+    pub last_played: Option<DateTime<Utc>>,
+}
+
+impl PlayStats {
+    fn add(&mut self, other: PlayStats) {
+        self.runtime_secs += other.runtime_secs;
+        self.last_played = self.last_played.max(other.last_played);
+    }
+}
+
+fn parse_runtime(runtime: &str) -> Option<u64> {
+    let mut parts = runtime.split(':').map(|part| part.trim().parse::<u64>());
+    let (Some(Ok(hours)), Some(Ok(minutes)), Some(Ok(seconds)), None) =
+        (parts.next(), parts.next(), parts.next(), parts.next())
+    else {
+        return None;
+    };
+    Some(hours * 3600 + minutes * 60 + seconds)
+}
+
+fn parse_last_played(last_played: &str) -> Option<DateTime<Utc>> {
+    let local = NaiveDateTime::parse_from_str(last_played, "%Y-%m-%d %H:%M:%S").ok()?;
+    Some(Local.from_local_datetime(&local).earliest()?.to_utc())
+}
+
+fn read_log(path: &Path) -> Result<PlayStats, String> {
+    let text = fs::read_to_string(path).map_err(|err| err.to_string())?;
+    let log: RuntimeLog = serde_json::from_str(&text).map_err(|err| err.to_string())?;
+    Ok(PlayStats {
+        runtime_secs: parse_runtime(&log.runtime)
+            .ok_or_else(|| format!("invalid runtime {:?}", log.runtime))?,
+        last_played: parse_last_played(&log.last_played),
+    })
+}
+
+/// Stats of the logs in one folder, by content name
+fn read_dir(dir: &Path) -> HashMap<String, PlayStats> {
+    let Ok(files) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    let mut stats = HashMap::new();
+    for path in files.filter_map(|file| Some(file.ok()?.path())) {
+        if path.extension().is_none_or(|ext| ext != "lrtl") {
+            continue;
+        }
+        match read_log(&path) {
+            Ok(log) => {
+                let name = path.file_stem().unwrap_or_default().to_string_lossy();
+                stats.insert(name.into_owned(), log);
+            }
+            Err(err) => warn!("Skipping runtime log {}: {}", path.display(), err),
+        }
+    }
+    stats
+}
+
+/// Play stats by content name, from the aggregate log where there is one and summed over the
+/// per-core logs otherwise
+pub fn read_runtime_logs(dir: &Path) -> HashMap<String, PlayStats> {
+    let Ok(core_dirs) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    let mut aggregate = HashMap::new();
+    let mut per_core: HashMap<String, PlayStats> = HashMap::new();
+    for core_dir in core_dirs.filter_map(|dir| Some(dir.ok()?.path())) {
+        if !core_dir.is_dir() {
+            continue;
+        }
+        let is_aggregate = core_dir
+            .file_name()
+            .is_some_and(|name| name.eq_ignore_ascii_case(AGGREGATE_DIR));
+        if is_aggregate {
+            aggregate = read_dir(&core_dir);
+            continue;
+        }
+        for (name, stats) in read_dir(&core_dir) {
+            per_core.entry(name).or_default().add(stats);
+        }
+    }
+    per_core.extend(aggregate);
+    per_core
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_prefers_aggregate_logs() {
+        let dir =
+            Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/retroarch/playlists/logs");
+
+        let stats = read_runtime_logs(&dir);
+
+        let metroid = stats["Super Metroid (Japan, USA) (En,Ja)"];
+        assert_eq!(metroid.runtime_secs, 3723);
+        let chrono = stats["Chrono Trigger (USA)"];
+        assert_eq!(chrono.runtime_secs, 45 * 60 + 30);
+        assert_eq!(chrono.last_played, parse_last_played("2024-01-02 09:30:00"));
+        assert!(chrono.last_played.is_some());
+        assert_eq!(parse_runtime("12:3"), None);
+    }
+}
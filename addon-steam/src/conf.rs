@@ -9,15 +9,22 @@ pub struct Config {
 }
 
 impl Config {
-    pub async fn load() -> Config {
-        if !CONF_PATH.exists() {
+    /// Loads the config for an account, falling back to the add-on wide config
+    pub async fn load(account_id: &str) -> Config {
+        let account_path = gami_sdk::resolve_account_config_json_path(crate::ID, account_id);
+        let path = if !account_id.is_empty() && account_path.exists() {
+            account_path
+        } else {
+            CONF_PATH.clone()
+        };
+        if !path.exists() {
             return Config::default();
         }
-        tokio::task::spawn_blocking(|| {
-            serde_json::from_reader(std::fs::File::open(CONF_PATH.as_path()).unwrap()).unwrap()
+        tokio::task::spawn_blocking(move || {
+            serde_json::from_reader(std::fs::File::open(path).unwrap()).unwrap()
         })
         .await
         .unwrap()
     }
 }
-const CONF_PATH: Lazy<PathBuf> = Lazy::new(|| gami_sdk::resolve_addon_config_json_path("steam"));
+static CONF_PATH: Lazy<PathBuf> = Lazy::new(|| gami_sdk::resolve_addon_config_json_path("steam"));
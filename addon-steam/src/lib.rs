@@ -8,28 +8,27 @@ mod store_models;
 use crate::conf::Config;
 use crate::store::StoreMetadataScanner;
 use gami_sdk::{
-    register_plugin, ConfigSchemaKind, ConfigSchemaMetadata, GameLibrary, PluginRegistrar,
+    register_plugin, ActionError, ConfigSchemaKind, ConfigSchemaMetadata, GameLibrary,
+    PluginRegistrar,
 };
-use gami_sdk::{GameInstallStatus, GameLibraryRef, ScannedGameLibraryMetadata};
+use gami_sdk::{GameInstallStatus, GameLibraryRef, LibraryAccount, ScannedGameLibraryMetadata};
 use log::*;
 pub use models::*;
 use once_cell::sync::Lazy;
 use safer_ffi::option::TaggedOption;
 use std::collections::{BTreeMap, HashMap};
+#[cfg(target_os = "windows")]
 use std::ffi::{OsStr, OsString};
 use std::process::Command;
 use std::sync::Arc;
-use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use tokio::runtime::{self, Runtime};
-use tokio::sync::Mutex;
 use url::Url;
 
 #[derive(Default)]
-pub struct SteamLibrary {
-    user_id: Mutex<Option<String>>,
-}
+pub struct SteamLibrary;
 
-const RUNTIME: Lazy<Runtime> = Lazy::new(|| {
+static RUNTIME: Lazy<Runtime> = Lazy::new(|| {
     runtime::Builder::new_multi_thread()
         .enable_time()
         .enable_io()
@@ -37,6 +36,7 @@ const RUNTIME: Lazy<Runtime> = Lazy::new(|| {
         .unwrap()
 });
 
+#[cfg(target_os = "windows")]
 fn wrap_in_quotes<T: AsRef<OsStr>>(path: T) -> OsString {
     let mut result = OsString::from("\"");
     result.push(path);
@@ -67,14 +67,76 @@ fn map_open_url_command(url: &str) -> Command {
     cmd.arg(url);
     cmd
 }
+/// Checks that something opens `steam://` links, telling a missing Steam from a missing handler
+#[cfg(target_os = "linux")]
+fn check_url_handler() -> Result<(), ActionError> {
+    let Ok(query) = Command::new("xdg-mime")
+        .args(["query", "default", "x-scheme-handler/steam"])
+        .output()
+    else {
+        // without xdg-mime there's no telling, xdg-open will report what went wrong
+        return Ok(());
+    };
+    if !query.status.success() || !query.stdout.trim_ascii().is_empty() {
+        return Ok(());
+    }
+    let steam_in_path = std::env::var_os("PATH")
+        .is_some_and(|path| std::env::split_paths(&path).any(|dir| dir.join("steam").is_file()));
+    Err(if steam_in_path {
+        ActionError::NoUrlHandler("steam".into())
+    } else {
+        ActionError::ClientMissing("Steam".into())
+    })
+}
+#[cfg(target_os = "windows")]
+fn check_url_handler() -> Result<(), ActionError> {
+    use std::os::windows::process::CommandExt;
+    let registered = Command::new("reg")
+        .args(["query", r"HKCR\steam"])
+        .creation_flags(CREATE_NO_WINDOW)
+        .output()
+        .map(|output| output.status.success());
+    match registered {
+        Ok(false) => Err(ActionError::ClientMissing("Steam".into())),
+        _ => Ok(()),
+    }
+}
+#[cfg(target_os = "macos")]
+fn check_url_handler() -> Result<(), ActionError> {
+    Ok(())
+}
+#[cfg(target_os = "windows")]
 const CREATE_NO_WINDOW: u32 = 0x08000000;
-fn run_cmd(cmd: &'static str, id: &str) {
+/// How long the URL opener may take to hand a link over before it's left running on its own
+const OPEN_URL_TIMEOUT: Duration = Duration::from_secs(10);
+fn run_cmd(cmd: &'static str, id: &str) -> Result<(), ActionError> {
+    check_url_handler()?;
     let raw = format!("steam://{}/{}", cmd, id);
     let mut cmd = map_open_url_command(&raw);
     debug!("run : {:?} raw={}", cmd, raw);
-    cmd.spawn().unwrap().wait().unwrap();
+    let program = cmd.get_program().to_string_lossy().into_owned();
+    let mut child = cmd
+        .spawn()
+        .map_err(|err| ActionError::Failed(format!("could not run {}: {}", program, err)))?;
+    let started = Instant::now();
+    while started.elapsed() < OPEN_URL_TIMEOUT {
+        match child.try_wait() {
+            Ok(Some(status)) if status.success() => return Ok(()),
+            Ok(Some(status)) => {
+                return Err(ActionError::Failed(format!(
+                    "{} could not open {}: {}",
+                    program, raw, status
+                )))
+            }
+            Ok(None) => std::thread::sleep(Duration::from_millis(100)),
+            Err(err) => return Err(ActionError::Failed(err.to_string())),
+        }
+    }
+    // probably starting Steam itself, reap it whenever it's done
+    std::thread::spawn(move || child.wait());
+    Ok(())
 }
-fn run_cmd_ref(cmd: &'static str, my_ref: GameLibraryRef) {
+fn run_cmd_ref(cmd: &'static str, my_ref: GameLibraryRef) -> Result<(), ActionError> {
     run_cmd(cmd, &my_ref.library_id)
 }
 fn from_epoch(secs: u64) -> SystemTime {
@@ -93,25 +155,12 @@ pub fn auto_cache_map(id: &str, postfix: &str) -> TaggedOption<safer_ffi::string
 
 const ID: &str = "steam";
 impl SteamLibrary {
-    async fn auto_get_id(&self) -> String {
-        let my_id = self.user_id.lock().await.clone();
-
-        if let Some(id) = my_id {
-            id
-        } else {
-            let id = local_scanner::get_steam_id().await;
-
-            *self.user_id.lock().await = Some(id.clone());
-            id
-        }
-    }
-    async fn get_owned_games(&self, conf: &Config) -> models::OwnedGamesResponse {
-        let steam_id = self.auto_get_id().await;
+    async fn get_owned_games(&self, conf: &Config, steam_id: &str) -> models::OwnedGamesResponse {
         let mut url =
             Url::parse("https://api.steampowered.com/IPlayerService/GetOwnedGames/v0001/").unwrap();
         url.query_pairs_mut()
             .append_pair("key", conf.api_key.as_str())
-            .append_pair("steamid", steam_id.as_str())
+            .append_pair("steamid", steam_id)
             .append_pair("include_appinfo", "1")
             .append_pair("format", "json");
         reqwest::get(url)
@@ -123,11 +172,14 @@ impl SteamLibrary {
     }
 }
 impl GameLibrary for SteamLibrary {
-    fn scan(&self) -> Vec<ScannedGameLibraryMetadata> {
+    fn get_accounts(&self) -> Vec<LibraryAccount> {
+        RUNTIME.block_on(local_scanner::get_steam_users())
+    }
+    fn scan(&self, account_id: &str) -> Vec<ScannedGameLibraryMetadata> {
         RUNTIME.block_on(async move {
-            let conf = Config::load().await;
-            let local_games = local_scanner::scan_local_dir_auto().await;
-            if conf.api_key.is_empty() || (self.auto_get_id().await).is_empty() {
+            let conf = Config::load(account_id).await;
+            let local_games = local_scanner::scan_local_dir_auto(account_id).await;
+            if conf.api_key.is_empty() || account_id.is_empty() {
                 return local_games;
             }
             let local_by_id: BTreeMap<String, ScannedGameLibraryMetadata> = BTreeMap::from_iter(
@@ -135,7 +187,7 @@ impl GameLibrary for SteamLibrary {
                     .into_iter()
                     .map(|g| (g.library_id.to_string(), g)),
             );
-            self.get_owned_games(&conf)
+            self.get_owned_games(&conf, account_id)
                 .await
                 .response
                 .games
@@ -145,8 +197,9 @@ impl GameLibrary for SteamLibrary {
                     ScannedGameLibraryMetadata {
                         library_type: ID.into(),
                         library_id: id_str.clone().into(),
+                        account_id: account_id.into(),
                         name: g.name.into(),
-                        icon_url: auto_cache_map(&id_str, "_icon.jpg").into(),
+                        icon_url: auto_cache_map(&id_str, "_icon.jpg"),
                         last_played_epoch: TaggedOption::Some(g.rtime_last_played),
                         playtime_secs: g.playtime_forever,
                         install_status: local_by_id
@@ -158,13 +211,16 @@ impl GameLibrary for SteamLibrary {
                 .collect()
         })
     }
-    fn launch(&self, game: GameLibraryRef) {
+    fn launch(&self, game: GameLibraryRef) -> Result<(), ActionError> {
         run_cmd_ref("rungameid", game)
     }
-    fn install(&self, game: GameLibraryRef) {
+    fn reports_play_stats(&self) -> bool {
+        true
+    }
+    fn install(&self, game: GameLibraryRef) -> Result<(), ActionError> {
         run_cmd_ref("install", game)
     }
-    fn uninstall(&self, game: GameLibraryRef) {
+    fn uninstall(&self, game: GameLibraryRef) -> Result<(), ActionError> {
         run_cmd_ref("uninstall", game)
     }
     fn check_install_status(&self, _game: GameLibraryRef) -> GameInstallStatus {
@@ -173,8 +229,9 @@ impl GameLibrary for SteamLibrary {
 }
 register_plugin!(register, ID, "Steam");
 #[no_mangle]
+#[allow(improper_ctypes_definitions)]
 extern "C" fn register(registrar: &mut dyn PluginRegistrar) {
-    registrar.register_library("steam", Arc::new(SteamLibrary::default()));
+    registrar.register_library("steam", Arc::new(SteamLibrary));
     registrar.register_metadata_scanner("steam", Arc::new(StoreMetadataScanner));
 
     let mut conf: HashMap<String, ConfigSchemaMetadata> = HashMap::with_capacity(2);
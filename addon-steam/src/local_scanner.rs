@@ -2,15 +2,16 @@ use crate::kv::ast::KvValue;
 use crate::kv::parser::full_parse;
 use crate::{auto_cache_map, from_epoch};
 use gami_sdk::GameInstallStatus::Queued;
-use gami_sdk::{GameInstallStatus, ScannedGameLibraryMetadata};
+use gami_sdk::{GameInstallStatus, LibraryAccount, ScannedGameLibraryMetadata};
 use log::{debug, error};
 use once_cell::sync::Lazy;
+use std::collections::BTreeMap;
 use std::path::{Path, PathBuf};
 use std::time::UNIX_EPOCH;
 use std::{env, io};
 use tokio::fs;
 
-const BASE_PATH: Lazy<PathBuf> = Lazy::new(|| {
+static BASE_PATH: Lazy<PathBuf> = Lazy::new(|| {
     if cfg!(windows) {
         "C:/Program Files (x86)/Steam".into()
     } else {
@@ -30,21 +31,63 @@ const BASE_PATH: Lazy<PathBuf> = Lazy::new(|| {
         }
     }
 });
-const APPS_PATH: Lazy<PathBuf> = Lazy::new(|| BASE_PATH.join("steamapps"));
-const USERS_CONF_PATH: Lazy<PathBuf> = Lazy::new(|| BASE_PATH.join("config/loginusers.vdf"));
-pub(crate) const LIB_CACHE_PATH: Lazy<PathBuf> =
+static APPS_PATH: Lazy<PathBuf> = Lazy::new(|| BASE_PATH.join("steamapps"));
+static USERS_CONF_PATH: Lazy<PathBuf> = Lazy::new(|| BASE_PATH.join("config/loginusers.vdf"));
+pub(crate) static LIB_CACHE_PATH: Lazy<PathBuf> =
     Lazy::new(|| BASE_PATH.join("appcache/librarycache"));
-pub async fn get_steam_id() -> String {
+/// Users that logged into Steam on this machine, by account ID
+async fn login_users() -> BTreeMap<String, KvValue> {
+    if !USERS_CONF_PATH.exists() {
+        return BTreeMap::new();
+    }
     let reader = fs::File::open(&*USERS_CONF_PATH).await.unwrap();
     let parsed = full_parse(reader).await.unwrap();
     if let KvValue::Object(users) = parsed.value {
-        users.keys().cloned().next().unwrap()
+        users
     } else {
         panic!("Steam users must be objects");
     }
 }
-pub async fn scan_local_dir_auto() -> Vec<ScannedGameLibraryMetadata> {
+pub async fn get_steam_users() -> Vec<LibraryAccount> {
+    login_users()
+        .await
+        .into_iter()
+        .map(|(id, user)| {
+            let name = match user {
+                KvValue::Object(ref fields) => match fields.get("PersonaName") {
+                    Some(KvValue::String(name)) => name.clone(),
+                    _ => id.clone(),
+                },
+                KvValue::String(_) => id.clone(),
+            };
+            LibraryAccount {
+                account_id: id.into(),
+                name: name.into(),
+            }
+        })
+        .collect()
+}
+/// The account installs without a `LastOwner` are listed under, so they show up once
+///
+/// That is the user who logged in most recently, or the first one when Steam marks none.
+fn owner_of_unowned(users: &BTreeMap<String, KvValue>) -> Option<&str> {
+    let most_recent = users.iter().find(|(_, user)| match user {
+        KvValue::Object(fields) => {
+            matches!(fields.get("MostRecent"), Some(KvValue::String(v)) if v == "1")
+        }
+        KvValue::String(_) => false,
+    });
+    most_recent
+        .or_else(|| users.iter().next())
+        .map(|(id, _)| id.as_str())
+}
+/// Scans installed games; when `account_id` is set only games last owned by that account are kept
+///
+/// Games without an owner are kept for the account from [`owner_of_unowned`].
+pub async fn scan_local_dir_auto(account_id: &str) -> Vec<ScannedGameLibraryMetadata> {
     println!("Scanning local folders: {}", APPS_PATH.display());
+    let users = login_users().await;
+    let fallback_owner = owner_of_unowned(&users);
     let mut reader = fs::read_dir(APPS_PATH.as_path()).await.unwrap();
 
     let mut items = Vec::with_capacity(8);
@@ -55,11 +98,17 @@ pub async fn scan_local_dir_auto() -> Vec<ScannedGameLibraryMetadata> {
         if !name.starts_with("appmanifest") {
             continue;
         }
-        items.push(scan_local(&path).await.unwrap());
+        let (owner, mut item) = scan_local(&path).await.unwrap();
+        if !account_id.is_empty() && owner.as_deref().or(fallback_owner) != Some(account_id) {
+            continue;
+        }
+        item.account_id = account_id.into();
+        items.push(item);
     }
     items
 }
-pub async fn scan_local(path: &Path) -> io::Result<ScannedGameLibraryMetadata> {
+/// Parses an app manifest, returning the ID of the account that last owned it alongside the game
+pub async fn scan_local(path: &Path) -> io::Result<(Option<String>, ScannedGameLibraryMetadata)> {
     let reader = fs::File::open(&path).await?;
     debug!("Parsing file: {:?}", path);
     let parsed = full_parse(reader).await.unwrap();
@@ -95,21 +144,51 @@ pub async fn scan_local(path: &Path) -> io::Result<ScannedGameLibraryMetadata> {
     let bytes_to_dl = get_obj_text_opt("BytesToDownload");
     let bytes_dl = get_obj_text_opt("BytesDownloaded");
     let app_id = get_obj_text("appid");
-    Ok(ScannedGameLibraryMetadata {
-        library_id: app_id.into(),
-        name: get_obj_text("name").into(),
-        icon_url: auto_cache_map(app_id, "_icon.jpg").into(),
-        last_played_epoch: get_obj_unix_opt("LastPlayed")
-            .map(|time| time.duration_since(UNIX_EPOCH).unwrap().as_secs())
-            .into(),
-        library_type: "steam".into(),
-        install_status: if bytes_dl == None {
-            Queued
-        } else if bytes_dl == bytes_to_dl {
-            GameInstallStatus::Installed
-        } else {
-            GameInstallStatus::Installing
+    let owner = get_obj_text_opt("LastOwner")
+        .filter(|v| *v != "0")
+        .map(str::to_owned);
+    Ok((
+        owner,
+        ScannedGameLibraryMetadata {
+            library_id: app_id.into(),
+            name: get_obj_text("name").into(),
+            icon_url: auto_cache_map(app_id, "_icon.jpg"),
+            last_played_epoch: get_obj_unix_opt("LastPlayed")
+                .map(|time| time.duration_since(UNIX_EPOCH).unwrap().as_secs())
+                .into(),
+            library_type: "steam".into(),
+            install_status: if bytes_dl.is_none() {
+                Queued
+            } else if bytes_dl == bytes_to_dl {
+                GameInstallStatus::Installed
+            } else {
+                GameInstallStatus::Installing
+            },
+            ..Default::default()
         },
-        ..Default::default()
-    })
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn user(most_recent: &str) -> KvValue {
+        KvValue::Object(BTreeMap::from([(
+            "MostRecent".to_string(),
+            KvValue::String(most_recent.into()),
+        )]))
+    }
+
+    #[test]
+    fn test_unowned_installs_go_to_one_account() {
+        let mut users = BTreeMap::from([
+            ("76561198000000001".to_string(), user("0")),
+            ("76561198000000002".to_string(), user("1")),
+        ]);
+        assert_eq!(owner_of_unowned(&users), Some("76561198000000002"));
+        users.insert("76561198000000002".into(), user("0"));
+        assert_eq!(owner_of_unowned(&users), Some("76561198000000001"));
+        assert_eq!(owner_of_unowned(&BTreeMap::new()), None);
+    }
 }
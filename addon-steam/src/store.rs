@@ -30,7 +30,7 @@ fn to_month(month: &str) -> u8 {
         _ => panic!("Invalid month: {:?}", month),
     }
 }
-const RELEASE_DATE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(RELEASE_DATE_RAW).unwrap());
+static RELEASE_DATE_REGEX: Lazy<Regex> = Lazy::new(|| Regex::new(RELEASE_DATE_RAW).unwrap());
 
 fn parse_release_date(date: &str) -> Option<NaiveDate> {
     RELEASE_DATE_REGEX.captures(date).map(|parts| {
@@ -57,7 +57,7 @@ async fn get_metadata<'a>(game: GameLibraryRef<'a>) -> Option<GameMetadata> {
     }
     let mut url = Url::parse("https://store.steampowered.com/api/appdetails").unwrap();
     url.query_pairs_mut()
-        .append_pair("appids", &*game.library_id);
+        .append_pair("appids", &game.library_id);
 
     println!("Fetch URL: {}", url);
     let pre_res = match reqwest::get(url.clone()).await {
@@ -112,7 +112,7 @@ async fn get_metadata<'a>(game: GameLibraryRef<'a>) -> Option<GameMetadata> {
             .and_then(|v| v.date)
             .as_ref()
             .and_then(|v| if v.is_empty() { None } else { Some(v) })
-            .and_then(|v| parse_release_date(&v))
+            .and_then(|v| parse_release_date(v))
             .map(|d| d.and_hms_opt(0, 0, 0).unwrap().and_utc().timestamp() as u32)
             .into(),
         ..Default::default()
@@ -123,7 +123,7 @@ async fn get_metadatas<'a>(
     games: &[GameLibraryRef<'a>],
 ) -> HashMap<GameLibraryRefOwned, GameMetadata> {
     let games: Vec<GameLibraryRefOwned> = games
-        .into_iter()
+        .iter()
         .cloned()
         .map(GameLibraryRefOwned::from)
         .collect();
@@ -150,6 +150,10 @@ async fn get_metadatas<'a>(
     my_data
 }
 impl GameMetadataScanner for StoreMetadataScanner {
+    fn supported_library_types(&self) -> Vec<String> {
+        vec!["steam".into()]
+    }
+
     fn get_metadata(&self, game: GameLibraryRef) -> Option<GameMetadata> {
         RUNTIME.block_on(async move { get_metadata(game).await })
     }
@@ -10,7 +10,7 @@ pub enum StyleVariant {
     Danger,
 }
 impl StyleVariant {
-    pub fn button_style<'a, Message>(self, theme: &Theme, status: Status) -> button::Style {
+    pub fn button_style<Message>(self, theme: &Theme, status: Status) -> button::Style {
         match self {
             StyleVariant::Primary => button::primary(theme, status),
             StyleVariant::Secondary => button::secondary(theme, status),
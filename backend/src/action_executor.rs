@@ -0,0 +1,88 @@
+//! Runs the launch, install and uninstall actions of library addons in the background
+//!
+//! Addons may block until their client took the action over, so they run on Tokio's blocking
+//! threads and are given up on after [`ACTION_TIMEOUT`].
+
+use crate::plugin::{ExternalAddons, GameLibraryProxy};
+use gami_sdk::{ActionError, GameCommon, GameData, GameLibrary};
+use std::fmt;
+use std::time::Duration;
+
+/// How long a library may take to hand an action over to its client
+pub const ACTION_TIMEOUT: Duration = Duration::from_secs(30);
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LibraryAction {
+    Launch,
+    Install,
+    Uninstall,
+}
+impl fmt::Display for LibraryAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LibraryAction::Launch => "launch",
+            LibraryAction::Install => "install",
+            LibraryAction::Uninstall => "uninstall",
+        })
+    }
+}
+
+#[derive(Debug)]
+pub enum ActionFailure {
+    /// No addon provides the library of the game
+    NoLibrary(String),
+    Library(ActionError),
+    TimedOut(LibraryAction),
+    /// The addon panicked while running the action
+    Crashed(String),
+}
+impl fmt::Display for ActionFailure {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionFailure::NoLibrary(library) => {
+                write!(f, "no addon provides the {} library", library)
+            }
+            ActionFailure::Library(err) => err.fmt(f),
+            ActionFailure::TimedOut(action) => write!(
+                f,
+                "the library did not {} the game within {} seconds",
+                action,
+                ACTION_TIMEOUT.as_secs()
+            ),
+            ActionFailure::Crashed(err) => write!(f, "the library crashed: {}", err),
+        }
+    }
+}
+impl std::error::Error for ActionFailure {}
+
+/// Runs `action` on the preferred source of `game` through the library that provides it
+pub async fn run_library_action(
+    addons: &ExternalAddons,
+    action: LibraryAction,
+    game: &GameData,
+) -> Result<(), ActionFailure> {
+    let source = game.preferred_source();
+    let library = addons
+        .get_game_library(&source.library_type)
+        .cloned()
+        .ok_or_else(|| ActionFailure::NoLibrary(source.library_type.clone()))?;
+    run_on(library, action, source).await
+}
+
+pub(crate) async fn run_on(
+    library: GameLibraryProxy,
+    action: LibraryAction,
+    source: GameData,
+) -> Result<(), ActionFailure> {
+    let task = tokio::task::spawn_blocking(move || match action {
+        LibraryAction::Launch => library.launch(source.get_ref()),
+        LibraryAction::Install => library.install(source.get_ref()),
+        LibraryAction::Uninstall => library.uninstall(source.get_ref()),
+    });
+    // a timed out action keeps its blocking thread, which can't be cancelled
+    match tokio::time::timeout(ACTION_TIMEOUT, task).await {
+        Ok(Ok(result)) => result.map_err(ActionFailure::Library),
+        Ok(Err(err)) => Err(ActionFailure::Crashed(err.to_string())),
+        Err(_) => Err(ActionFailure::TimedOut(action)),
+    }
+}
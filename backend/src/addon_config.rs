@@ -0,0 +1,62 @@
+use gami_sdk::{
+    load_schema, resolve_account_config_json_path, resolve_addon_config_json_path, ConfigSchemaKind,
+};
+use std::collections::HashMap;
+use std::fs::File;
+use std::io;
+use std::path::PathBuf;
+
+/// Values of an addon's config by key
+pub type AddonConfig = HashMap<String, String>;
+
+/// Config file of an addon, or its override for one account
+pub fn config_path(addon_id: &str, account_id: Option<&str>) -> PathBuf {
+    match account_id {
+        None => resolve_addon_config_json_path(addon_id),
+        Some(account_id) => resolve_account_config_json_path(addon_id, account_id),
+    }
+}
+
+/// Reads a config file, empty if it was never written
+pub fn read_config(addon_id: &str, account_id: Option<&str>) -> io::Result<AddonConfig> {
+    let path = config_path(addon_id, account_id);
+    if !path.exists() {
+        return Ok(AddonConfig::new());
+    }
+    serde_json::from_reader(File::open(path)?).map_err(io::Error::other)
+}
+
+pub fn write_config(
+    addon_id: &str,
+    account_id: Option<&str>,
+    config: &AddonConfig,
+) -> io::Result<()> {
+    let file = File::create(config_path(addon_id, account_id))?;
+    serde_json::to_writer(file, config).map_err(io::Error::other)
+}
+
+/// Checks `value` against the kind the addon's schema declares for `key`
+///
+/// Addons without a schema accept anything.
+pub fn validate_value(addon_id: &str, key: &str, value: &str) -> Result<(), String> {
+    let schema = load_schema(addon_id);
+    if schema.is_empty() {
+        return Ok(());
+    }
+    let Some(meta) = schema.get(key) else {
+        let mut keys: Vec<&str> = schema.keys().map(String::as_str).collect();
+        keys.sort();
+        return Err(format!("unknown key, expected one of: {}", keys.join(", ")));
+    };
+    let valid = match meta.kind {
+        ConfigSchemaKind::String => true,
+        ConfigSchemaKind::Int => value.parse::<i64>().is_ok(),
+        ConfigSchemaKind::Boolean => value.parse::<bool>().is_ok(),
+        ConfigSchemaKind::Json => serde_json::from_str::<serde_json::Value>(value).is_ok(),
+    };
+    if valid {
+        Ok(())
+    } else {
+        Err(format!("expected a value of kind {:?}", meta.kind))
+    }
+}
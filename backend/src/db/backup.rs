@@ -0,0 +1,402 @@
+use super::{DbLocation, DbResult, LibraryStore};
+use chrono::{DateTime, Datelike, Local, NaiveDateTime};
+use libsqlite3_sys as ffi;
+use sea_orm::{ConnectionTrait, Database, DbBackend, DbErr, Statement};
+use std::ffi::{CStr, CString};
+use std::fmt;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::ptr;
+
+/// Folder next to the database file that keeps its snapshots
+const BACKUPS_DIR: &str = "backups";
+const FILE_PREFIX: &str = "data-";
+const TIMESTAMP_FORMAT: &str = "%Y%m%d-%H%M%S";
+const BEFORE_PREFIX: &str = "before-";
+
+/// Why a snapshot was taken
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SnapshotKind {
+    Daily,
+    Weekly,
+    /// Taken by the user, never rotated away
+    Manual,
+    /// Taken before a destructive operation, named by the operation
+    Before(String),
+}
+impl SnapshotKind {
+    fn file_label(&self) -> String {
+        match self {
+            SnapshotKind::Daily => "daily".into(),
+            SnapshotKind::Weekly => "weekly".into(),
+            SnapshotKind::Manual => "manual".into(),
+            SnapshotKind::Before(operation) => format!("{}{}", BEFORE_PREFIX, operation),
+        }
+    }
+    fn from_file_label(label: &str) -> Option<Self> {
+        Some(match label {
+            "daily" => SnapshotKind::Daily,
+            "weekly" => SnapshotKind::Weekly,
+            "manual" => SnapshotKind::Manual,
+            other => SnapshotKind::Before(other.strip_prefix(BEFORE_PREFIX)?.to_string()),
+        })
+    }
+}
+impl fmt::Display for SnapshotKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SnapshotKind::Daily => f.write_str("Daily"),
+            SnapshotKind::Weekly => f.write_str("Weekly"),
+            SnapshotKind::Manual => f.write_str("Manual"),
+            SnapshotKind::Before(operation) => write!(f, "Before {}", operation),
+        }
+    }
+}
+
+/// A copy of the library database
+#[derive(Debug, Clone, PartialEq)]
+pub struct Snapshot {
+    pub path: PathBuf,
+    pub kind: SnapshotKind,
+    pub created: DateTime<Local>,
+    pub size: u64,
+    /// Games shown in the library, `None` if the snapshot can't be read
+    pub games: Option<i64>,
+}
+
+/// How many scheduled snapshots to keep
+///
+/// Snapshots taken before destructive operations are kept like daily ones.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct BackupPolicy {
+    pub keep_daily: usize,
+    pub keep_weekly: usize,
+}
+impl Default for BackupPolicy {
+    fn default() -> Self {
+        Self {
+            keep_daily: 7,
+            keep_weekly: 4,
+        }
+    }
+}
+
+fn snapshot_file_name(kind: &SnapshotKind, created: DateTime<Local>) -> String {
+    format!(
+        "{}{}-{}.db",
+        FILE_PREFIX,
+        created.format(TIMESTAMP_FORMAT),
+        kind.file_label()
+    )
+}
+
+/// Kind and creation time of a snapshot file name
+fn parse_file_name(name: &str) -> Option<(SnapshotKind, DateTime<Local>)> {
+    let rest = name.strip_prefix(FILE_PREFIX)?.strip_suffix(".db")?;
+    let (timestamp, label) = (rest.get(..15)?, rest.get(16..)?);
+    let created = NaiveDateTime::parse_from_str(timestamp, TIMESTAMP_FORMAT)
+        .ok()?
+        .and_local_timezone(Local)
+        .earliest()?;
+    Some((SnapshotKind::from_file_label(label)?, created))
+}
+
+fn sqlite_error(db: *mut ffi::sqlite3, code: i32) -> io::Error {
+    let message = if db.is_null() {
+        format!("SQLite error {}", code)
+    } else {
+        unsafe { CStr::from_ptr(ffi::sqlite3_errmsg(db)) }
+            .to_string_lossy()
+            .into_owned()
+    };
+    io::Error::other(message)
+}
+
+/// Opens a database file outside the connection pool, closed again by [`close`]
+fn open_file(path: &Path, flags: i32) -> io::Result<*mut ffi::sqlite3> {
+    let path = CString::new(path.to_string_lossy().as_bytes())?;
+    let mut db = ptr::null_mut();
+    let code = unsafe { ffi::sqlite3_open_v2(path.as_ptr(), &mut db, flags, ptr::null()) };
+    if code != ffi::SQLITE_OK {
+        let err = sqlite_error(db, code);
+        close(db);
+        return Err(err);
+    }
+    Ok(db)
+}
+
+fn close(db: *mut ffi::sqlite3) {
+    unsafe {
+        ffi::sqlite3_close(db);
+    }
+}
+
+/// Copies the whole main database of `source` into `dest` with the SQLite online backup API
+fn copy_database(source: *mut ffi::sqlite3, dest: *mut ffi::sqlite3) -> io::Result<()> {
+    unsafe {
+        let backup = ffi::sqlite3_backup_init(dest, c"main".as_ptr(), source, c"main".as_ptr());
+        if backup.is_null() {
+            return Err(sqlite_error(dest, ffi::sqlite3_errcode(dest)));
+        }
+        let step = ffi::sqlite3_backup_step(backup, -1);
+        // finishing releases the backup either way, its code repeats any error of the step
+        let code = ffi::sqlite3_backup_finish(backup);
+        if step != ffi::SQLITE_DONE {
+            let reason = CStr::from_ptr(ffi::sqlite3_errstr(step)).to_string_lossy();
+            return Err(io::Error::other(format!(
+                "backup stopped early: {}",
+                reason
+            )));
+        }
+        if code != ffi::SQLITE_OK {
+            return Err(sqlite_error(dest, code));
+        }
+    }
+    Ok(())
+}
+
+impl LibraryStore {
+    /// Folder of this database's snapshots, in-memory databases have none
+    fn backups_dir(&self) -> Option<PathBuf> {
+        match self.location() {
+            DbLocation::File(path) => Some(path.parent()?.join(BACKUPS_DIR)),
+            DbLocation::Memory => None,
+        }
+    }
+
+    fn require_backups_dir(&self) -> io::Result<PathBuf> {
+        self.backups_dir().ok_or_else(|| {
+            io::Error::new(
+                io::ErrorKind::Unsupported,
+                "in-memory databases have no snapshots",
+            )
+        })
+    }
+
+    /// Copies the live database into a new snapshot while the library stays usable
+    pub async fn create_snapshot(&self, kind: SnapshotKind) -> io::Result<Snapshot> {
+        let dir = self.require_backups_dir()?;
+        std::fs::create_dir_all(&dir)?;
+        let created = Local::now();
+        let path = dir.join(snapshot_file_name(&kind, created));
+        // written under another name first so a failed copy never shows up as a snapshot
+        let partial = path.with_extension("partial");
+
+        let mut source = self
+            .connection()
+            .get_sqlite_connection_pool()
+            .acquire()
+            .await
+            .map_err(io::Error::other)?;
+        let mut handle = source.lock_handle().await.map_err(io::Error::other)?;
+        let dest = open_file(
+            &partial,
+            ffi::SQLITE_OPEN_READWRITE | ffi::SQLITE_OPEN_CREATE,
+        )?;
+        let res = copy_database(handle.as_raw_handle().as_ptr(), dest);
+        close(dest);
+        drop(handle);
+        drop(source);
+        if let Err(err) = res {
+            let _ = std::fs::remove_file(&partial);
+            return Err(err);
+        }
+        std::fs::rename(&partial, &path)?;
+        log::info!("Created {} database snapshot {}", kind, path.display());
+        Ok(Snapshot {
+            size: std::fs::metadata(&path)?.len(),
+            games: count_games(&path).await,
+            path,
+            kind,
+            created,
+        })
+    }
+
+    /// Snapshots the library before a destructive operation, unless it lives in memory
+    pub(crate) async fn snapshot_before(&self, operation: &str) -> DbResult<()> {
+        if self.backups_dir().is_none() {
+            return Ok(());
+        }
+        self.create_snapshot(SnapshotKind::Before(operation.into()))
+            .await
+            .map_err(|err| DbErr::Custom(format!("could not snapshot the library: {}", err)))?;
+        Ok(())
+    }
+
+    /// Every snapshot with its game count, newest first
+    pub async fn list_snapshots(&self) -> Vec<Snapshot> {
+        let mut snapshots = Vec::new();
+        for (path, kind, created, size) in self.snapshot_files() {
+            snapshots.push(Snapshot {
+                games: count_games(&path).await,
+                path,
+                kind,
+                created,
+                size,
+            });
+        }
+        snapshots
+    }
+
+    /// Snapshot files with their kind, creation time and size, newest first
+    fn snapshot_files(&self) -> Vec<(PathBuf, SnapshotKind, DateTime<Local>, u64)> {
+        let Some(Ok(entries)) = self.backups_dir().map(std::fs::read_dir) else {
+            return Vec::new();
+        };
+        let mut files: Vec<_> = entries
+            .flatten()
+            .filter_map(|entry| {
+                let (kind, created) = parse_file_name(&entry.file_name().to_string_lossy())?;
+                let size = entry.metadata().ok()?.len();
+                Some((entry.path(), kind, created, size))
+            })
+            .collect();
+        files.sort_by_key(|(_, _, created, _)| std::cmp::Reverse(*created));
+        files
+    }
+
+    /// Deletes snapshots beyond what the policy keeps, manual ones are never deleted
+    pub fn rotate(&self, policy: BackupPolicy) {
+        for path in snapshots_to_remove(&self.snapshot_files(), policy) {
+            match std::fs::remove_file(&path) {
+                Ok(()) => log::info!("Removed old database snapshot {}", path.display()),
+                Err(err) => log::warn!("Could not remove snapshot {}: {}", path.display(), err),
+            }
+        }
+    }
+
+    /// Takes the daily and weekly snapshots that are due, then rotates old ones away
+    pub async fn run_scheduled(&self, policy: BackupPolicy) -> io::Result<()> {
+        if self.backups_dir().is_none() {
+            return Ok(());
+        }
+        let now = Local::now();
+        let files = self.snapshot_files();
+        let has = |kind: SnapshotKind, same_period: &dyn Fn(DateTime<Local>) -> bool| {
+            files
+                .iter()
+                .any(|(_, k, created, _)| *k == kind && same_period(*created))
+        };
+        if policy.keep_daily > 0
+            && !has(SnapshotKind::Daily, &|c| c.date_naive() == now.date_naive())
+        {
+            self.create_snapshot(SnapshotKind::Daily).await?;
+        }
+        if policy.keep_weekly > 0 && !has(SnapshotKind::Weekly, &|c| c.iso_week() == now.iso_week())
+        {
+            self.create_snapshot(SnapshotKind::Weekly).await?;
+        }
+        self.rotate(policy);
+        Ok(())
+    }
+
+    /// Replaces the live database with a snapshot, snapshotting the current state first
+    pub async fn restore_snapshot(&self, path: PathBuf) -> io::Result<()> {
+        self.create_snapshot(SnapshotKind::Before("restore".into()))
+            .await?;
+        {
+            let mut dest = self
+                .connection()
+                .get_sqlite_connection_pool()
+                .acquire()
+                .await
+                .map_err(io::Error::other)?;
+            let mut handle = dest.lock_handle().await.map_err(io::Error::other)?;
+            let source = open_file(&path, ffi::SQLITE_OPEN_READONLY)?;
+            let res = copy_database(source, handle.as_raw_handle().as_ptr());
+            close(source);
+            res?;
+        }
+        // snapshots of older versions are brought up to the current schema
+        super::migrate(self.connection())
+            .await
+            .map_err(io::Error::other)?;
+        log::info!("Restored database snapshot {}", path.display());
+        Ok(())
+    }
+}
+
+async fn count_games(path: &Path) -> Option<i64> {
+    let conn = Database::connect(format!("sqlite:{}?mode=ro", path.to_string_lossy()))
+        .await
+        .ok()?;
+    let count = conn
+        .query_one(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT COUNT(*) FROM games WHERE merged_into IS NULL",
+        ))
+        .await
+        .ok()??
+        .try_get_by_index(0)
+        .ok();
+    conn.close().await.ok();
+    count
+}
+
+/// Snapshots beyond what the policy keeps, given newest first
+fn snapshots_to_remove(
+    snapshots: &[(PathBuf, SnapshotKind, DateTime<Local>, u64)],
+    policy: BackupPolicy,
+) -> Vec<PathBuf> {
+    let (mut daily, mut weekly, mut before) = (0, 0, 0);
+    snapshots
+        .iter()
+        .filter(|(_, kind, _, _)| {
+            let (seen, keep) = match kind {
+                SnapshotKind::Daily => (&mut daily, policy.keep_daily),
+                SnapshotKind::Weekly => (&mut weekly, policy.keep_weekly),
+                SnapshotKind::Before(_) => (&mut before, policy.keep_daily),
+                SnapshotKind::Manual => return false,
+            };
+            *seen += 1;
+            *seen > keep
+        })
+        .map(|(path, _, _, _)| path.clone())
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_name_round_trip() {
+        let created = NaiveDateTime::parse_from_str("20240301-201500", TIMESTAMP_FORMAT)
+            .unwrap()
+            .and_local_timezone(Local)
+            .unwrap();
+        for kind in [
+            SnapshotKind::Daily,
+            SnapshotKind::Manual,
+            SnapshotKind::Before("clear".into()),
+        ] {
+            let name = snapshot_file_name(&kind, created);
+            assert_eq!(parse_file_name(&name), Some((kind, created)));
+        }
+        assert_eq!(parse_file_name("data-20240301-201500-other.db"), None);
+        assert_eq!(parse_file_name("data.db"), None);
+    }
+
+    #[test]
+    fn test_rotation_keeps_newest_of_each_kind() {
+        let policy = BackupPolicy {
+            keep_daily: 2,
+            keep_weekly: 1,
+        };
+        let now = Local::now();
+        let snapshot = |name: &str, kind: SnapshotKind| (PathBuf::from(name), kind, now, 0);
+        let snapshots = vec![
+            snapshot("d1", SnapshotKind::Daily),
+            snapshot("w1", SnapshotKind::Weekly),
+            snapshot("m1", SnapshotKind::Manual),
+            snapshot("d2", SnapshotKind::Daily),
+            snapshot("d3", SnapshotKind::Daily),
+            snapshot("w2", SnapshotKind::Weekly),
+            snapshot("m2", SnapshotKind::Manual),
+        ];
+        assert_eq!(
+            snapshots_to_remove(&snapshots, policy),
+            vec![PathBuf::from("d3"), PathBuf::from("w2")]
+        );
+    }
+}
@@ -0,0 +1,36 @@
+use crate::db::filter::FilterExpr;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "collections")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub name: String,
+    /// JSON serialized [`FilterExpr`]
+    pub filter: String,
+}
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+impl ActiveModelBehavior for ActiveModel {}
+
+/// A saved dynamic query over the library
+#[derive(Clone, Debug, PartialEq)]
+pub struct Collection {
+    pub id: i32,
+    pub name: String,
+    pub filter: FilterExpr,
+}
+impl From<Model> for Collection {
+    fn from(value: Model) -> Self {
+        let filter = serde_json::from_str(&value.filter).unwrap_or_else(|err| {
+            log::warn!("invalid filter for collection {}: {}", value.name, err);
+            FilterExpr::And(Vec::new())
+        });
+        Self {
+            id: value.id,
+            name: value.name,
+            filter,
+        }
+    }
+}
@@ -0,0 +1,316 @@
+use crate::db::game::{Column, DbGameCompletionStatus, DbGameInstallStatus};
+use crate::db::{game_genres, game_tags, genre, tag};
+use chrono::{Duration, NaiveDate, Utc};
+use game_genres::Entity as GameGenresEntity;
+use game_tags::Entity as GameTagsEntity;
+use gami_sdk::{CompletionStatus, GameInstallStatus};
+use genre::Entity as GenreEntity;
+use sea_orm::sea_query::{Expr, Query, SelectStatement, SimpleExpr};
+use sea_orm::{ColumnTrait, Condition};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::str::FromStr;
+use tag::Entity as TagEntity;
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CompareOp {
+    Lt,
+    Le,
+    Eq,
+    Ge,
+    Gt,
+}
+impl CompareOp {
+    pub const ALL: [CompareOp; 5] = [
+        CompareOp::Lt,
+        CompareOp::Le,
+        CompareOp::Eq,
+        CompareOp::Ge,
+        CompareOp::Gt,
+    ];
+    fn compare<V: Into<sea_orm::Value>>(self, column: Column, value: V) -> SimpleExpr {
+        match self {
+            Self::Lt => column.lt(value),
+            Self::Le => column.lte(value),
+            Self::Eq => column.eq(value),
+            Self::Ge => column.gte(value),
+            Self::Gt => column.gt(value),
+        }
+    }
+}
+impl fmt::Display for CompareOp {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Lt => "<",
+            Self::Le => "≤",
+            Self::Eq => "=",
+            Self::Ge => "≥",
+            Self::Gt => ">",
+        })
+    }
+}
+
+/// A release year filters can compare against, between [`Year::MIN`] and [`Year::MAX`]
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(try_from = "i32", into = "i32")]
+pub struct Year(i32);
+impl Year {
+    pub const MIN: i32 = 1;
+    /// Release dates are stored as `YYYY-MM-DD`, so the following year needs four digits too
+    pub const MAX: i32 = 9998;
+    pub fn new(year: i32) -> Option<Self> {
+        (Self::MIN..=Self::MAX)
+            .contains(&year)
+            .then_some(Self(year))
+    }
+    pub fn get(self) -> i32 {
+        self.0
+    }
+    /// First day of the year and of the year after
+    fn bounds(self) -> (NaiveDate, NaiveDate) {
+        let first_day = |year| NaiveDate::from_ymd_opt(year, 1, 1).expect("year is in range");
+        (first_day(self.0), first_day(self.0 + 1))
+    }
+}
+impl TryFrom<i32> for Year {
+    type Error = String;
+    fn try_from(year: i32) -> Result<Self, Self::Error> {
+        Self::new(year).ok_or_else(|| {
+            format!(
+                "year {} is not between {} and {}",
+                year,
+                Self::MIN,
+                Self::MAX
+            )
+        })
+    }
+}
+impl From<Year> for i32 {
+    fn from(year: Year) -> Self {
+        year.0
+    }
+}
+impl FromStr for Year {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        s.parse::<i32>().map_err(|err| err.to_string())?.try_into()
+    }
+}
+impl fmt::Display for Year {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+/// Serializable filter expression over a game, compiled to a sea-orm [`Condition`]
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum FilterExpr {
+    /// Matches when every child matches, an empty list matches everything
+    And(Vec<FilterExpr>),
+    /// Matches when any child matches, an empty list matches nothing
+    Or(Vec<FilterExpr>),
+    Not(Box<FilterExpr>),
+    NameContains(String),
+    LibraryType(String),
+    InstallStatus(GameInstallStatus),
+    CompletionStatus(CompletionStatus),
+    Playtime {
+        op: CompareOp,
+        minutes: i64,
+    },
+    NeverPlayed,
+    PlayedWithinDays(i64),
+    ReleaseYear {
+        op: CompareOp,
+        year: Year,
+    },
+    Genre(String),
+    Tag(String),
+    Favourite,
+    Hidden,
+}
+
+impl FilterExpr {
+    pub fn to_condition(&self) -> Condition {
+        match self {
+            Self::And(children) => children.iter().fold(Condition::all(), |cond, child| {
+                cond.add(child.to_condition())
+            }),
+            Self::Or(children) => children.iter().fold(Condition::any(), |cond, child| {
+                cond.add(child.to_condition())
+            }),
+            Self::Not(child) => child.to_condition().not(),
+            Self::NameContains(name) => Condition::all().add(Column::Name.contains(name)),
+            Self::LibraryType(library_type) => {
+                Condition::all().add(Column::LibraryType.eq(library_type.as_str()))
+            }
+            Self::InstallStatus(status) => {
+                Condition::all().add(Column::InstallStatus.eq(DbGameInstallStatus::from(*status)))
+            }
+            Self::CompletionStatus(status) => Condition::all()
+                .add(Column::CompletionStatus.eq(DbGameCompletionStatus::from(*status))),
+            Self::Playtime { op, minutes } => {
+                Condition::all().add(op.compare(Column::PlayTimeSecs, minutes * 60))
+            }
+            Self::NeverPlayed => Condition::all()
+                .add(Column::LastPlayed.is_null())
+                .add(Column::PlayTimeSecs.eq(0)),
+            Self::PlayedWithinDays(days) => {
+                Condition::all().add(Column::LastPlayed.gte(Utc::now() - Duration::days(*days)))
+            }
+            Self::ReleaseYear { op, year } => {
+                let (start, end) = year.bounds();
+                Condition::all().add(match op {
+                    CompareOp::Lt => Column::ReleaseDate.lt(start),
+                    CompareOp::Le => Column::ReleaseDate.lt(end),
+                    CompareOp::Eq => Column::ReleaseDate
+                        .gte(start)
+                        .and(Column::ReleaseDate.lt(end)),
+                    CompareOp::Ge => Column::ReleaseDate.gte(start),
+                    CompareOp::Gt => Column::ReleaseDate.gte(end),
+                })
+            }
+            Self::Genre(name) => Condition::all().add(Column::Id.in_subquery(genre_query(name))),
+            Self::Tag(name) => Condition::all().add(Column::Id.in_subquery(tag_query(name))),
+            Self::Favourite => Condition::all().add(Column::Favourite.eq(true)),
+            Self::Hidden => Condition::all().add(Column::Hidden.eq(true)),
+        }
+    }
+}
+
+/// Structured filters picked in the library's facet sidebar
+///
+/// Values within a facet are alternatives, different facets must all match.
+#[derive(Debug, Default, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct FacetFilters {
+    pub install_status: Vec<GameInstallStatus>,
+    pub completion_status: Vec<CompletionStatus>,
+    pub library_types: Vec<String>,
+    pub genres: Vec<String>,
+    pub release_year_min: Option<Year>,
+    pub release_year_max: Option<Year>,
+    pub playtime_min_minutes: Option<i64>,
+    pub playtime_max_minutes: Option<i64>,
+    pub played_within_days: Option<i64>,
+}
+
+impl FacetFilters {
+    pub fn is_empty(&self) -> bool {
+        *self == Self::default()
+    }
+    pub fn to_expr(&self) -> FilterExpr {
+        let mut all = Vec::new();
+        let mut any_of = |values: Vec<FilterExpr>| {
+            if !values.is_empty() {
+                all.push(FilterExpr::Or(values));
+            }
+        };
+        any_of(
+            self.install_status
+                .iter()
+                .copied()
+                .map(FilterExpr::InstallStatus)
+                .collect(),
+        );
+        any_of(
+            self.completion_status
+                .iter()
+                .copied()
+                .map(FilterExpr::CompletionStatus)
+                .collect(),
+        );
+        any_of(
+            self.library_types
+                .iter()
+                .cloned()
+                .map(FilterExpr::LibraryType)
+                .collect(),
+        );
+        any_of(self.genres.iter().cloned().map(FilterExpr::Genre).collect());
+        if let Some(year) = self.release_year_min {
+            all.push(FilterExpr::ReleaseYear {
+                op: CompareOp::Ge,
+                year,
+            });
+        }
+        if let Some(year) = self.release_year_max {
+            all.push(FilterExpr::ReleaseYear {
+                op: CompareOp::Le,
+                year,
+            });
+        }
+        if let Some(minutes) = self.playtime_min_minutes {
+            all.push(FilterExpr::Playtime {
+                op: CompareOp::Ge,
+                minutes,
+            });
+        }
+        if let Some(minutes) = self.playtime_max_minutes {
+            all.push(FilterExpr::Playtime {
+                op: CompareOp::Le,
+                minutes,
+            });
+        }
+        if let Some(days) = self.played_within_days {
+            all.push(FilterExpr::PlayedWithinDays(days));
+        }
+        FilterExpr::And(all)
+    }
+}
+
+fn genre_query(name: &str) -> SelectStatement {
+    Query::select()
+        .column((GameGenresEntity, game_genres::Column::GameId))
+        .from(GameGenresEntity)
+        .inner_join(
+            GenreEntity,
+            Expr::col((GenreEntity, genre::Column::Id))
+                .equals((GameGenresEntity, game_genres::Column::GenreId)),
+        )
+        .and_where(Expr::col((GenreEntity, genre::Column::Name)).eq(name))
+        .to_owned()
+}
+
+fn tag_query(name: &str) -> SelectStatement {
+    Query::select()
+        .column((GameTagsEntity, game_tags::Column::GameId))
+        .from(GameTagsEntity)
+        .inner_join(
+            TagEntity,
+            Expr::col((TagEntity, tag::Column::Id))
+                .equals((GameTagsEntity, game_tags::Column::TagId)),
+        )
+        .and_where(Expr::col((TagEntity, tag::Column::Name)).eq(name))
+        .to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_year_rejects_years_out_of_range() {
+        assert_eq!("2011".parse::<Year>().map(Year::get), Ok(2011));
+        assert!("0".parse::<Year>().is_err());
+        assert!("10000".parse::<Year>().is_err());
+        assert!(Year::new(i32::MAX).is_none());
+    }
+
+    #[test]
+    fn test_expr_with_invalid_year_does_not_deserialize() {
+        let valid = r#"{"release_year":{"op":"ge","year":2011}}"#;
+        assert_eq!(
+            serde_json::from_str::<FilterExpr>(valid).unwrap(),
+            FilterExpr::ReleaseYear {
+                op: CompareOp::Ge,
+                year: Year::new(2011).unwrap(),
+            }
+        );
+        let invalid = r#"{"release_year":{"op":"ge","year":300000}}"#;
+        assert!(serde_json::from_str::<FilterExpr>(invalid).is_err());
+        assert!(serde_json::from_str::<FacetFilters>(r#"{"release_year_min":-5}"#).is_err());
+    }
+}
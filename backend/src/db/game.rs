@@ -1,5 +1,5 @@
 use chrono::{DateTime, Duration, NaiveDate, Utc};
-use gami_sdk::{CompletionStatus, GameData, GameInstallStatus, IsGameLibraryRef};
+use gami_sdk::{CompletionStatus, GameData, GameInstallStatus, GameSource, IsGameLibraryRef};
 use sea_orm::entity::prelude::*;
 use sea_orm::{DeriveActiveEnum, DeriveEntityModel, EnumIter};
 #[derive(EnumIter, DeriveActiveEnum, Copy, Clone, Debug, PartialEq, Eq)]
@@ -21,13 +21,13 @@ impl From<GameInstallStatus> for DbGameInstallStatus {
         }
     }
 }
-impl Into<GameInstallStatus> for DbGameInstallStatus {
-    fn into(self) -> GameInstallStatus {
-        match self {
-            Self::Installed => GameInstallStatus::Installed,
-            Self::Installing => GameInstallStatus::Installing,
-            Self::InLibrary => GameInstallStatus::InLibrary,
-            Self::Queued => GameInstallStatus::Queued,
+impl From<DbGameInstallStatus> for GameInstallStatus {
+    fn from(status: DbGameInstallStatus) -> Self {
+        match status {
+            DbGameInstallStatus::Installed => GameInstallStatus::Installed,
+            DbGameInstallStatus::Installing => GameInstallStatus::Installing,
+            DbGameInstallStatus::InLibrary => GameInstallStatus::InLibrary,
+            DbGameInstallStatus::Queued => GameInstallStatus::Queued,
         }
     }
 }
@@ -51,13 +51,13 @@ impl From<CompletionStatus> for DbGameCompletionStatus {
         }
     }
 }
-impl Into<CompletionStatus> for DbGameCompletionStatus {
-    fn into(self) -> CompletionStatus {
-        match self {
-            Self::Backlog => CompletionStatus::Backlog,
-            Self::Playing => CompletionStatus::Playing,
-            Self::Played => CompletionStatus::Played,
-            Self::OnHold => CompletionStatus::OnHold,
+impl From<DbGameCompletionStatus> for CompletionStatus {
+    fn from(status: DbGameCompletionStatus) -> Self {
+        match status {
+            DbGameCompletionStatus::Backlog => CompletionStatus::Backlog,
+            DbGameCompletionStatus::Playing => CompletionStatus::Playing,
+            DbGameCompletionStatus::Played => CompletionStatus::Played,
+            DbGameCompletionStatus::OnHold => CompletionStatus::OnHold,
         }
     }
 }
@@ -77,25 +77,60 @@ pub struct Model {
     pub cover_url: Option<String>,
     pub library_type: String,
     pub library_id: String,
+    pub account_id: String,
     pub completion_status: DbGameCompletionStatus,
+    pub merged_into: Option<i32>,
+    pub preferred_source_id: Option<i32>,
+    pub favourite: bool,
+    pub hidden: bool,
+    /// Newline separated developer names
+    pub developers: String,
+    /// Newline separated publisher names
+    pub publishers: String,
+    pub install_path: Option<String>,
+    /// The user's own notes, never touched by syncs
+    pub notes: String,
 }
-impl Into<GameData> for Model {
-    fn into(self) -> GameData {
-        GameData {
+impl Model {
+    pub fn to_source(&self) -> GameSource {
+        GameSource {
             id: self.id,
-            name: self.name,
-            library_type: self.library_type,
-            library_id: self.library_id,
+            library_type: self.library_type.clone(),
+            library_id: self.library_id.clone(),
+            account_id: self.account_id.clone(),
             install_status: self.install_status.into(),
-            header_url: self.header_url,
-            description: self.description,
-            cover_url: self.cover_url,
-            icon_url: self.icon_url,
-            last_played: self.last_played,
-            release_date: self.release_date,
             play_time: Duration::seconds(self.play_time_secs),
-            completion_status: self.completion_status.into(),
+            last_played: self.last_played,
+        }
+    }
+}
+impl From<Model> for GameData {
+    fn from(game: Model) -> Self {
+        GameData {
+            id: game.id,
+            name: game.name,
+            library_type: game.library_type,
+            library_id: game.library_id,
+            account_id: game.account_id,
+            install_status: game.install_status.into(),
+            header_url: game.header_url,
+            description: game.description,
+            cover_url: game.cover_url,
+            icon_url: game.icon_url,
+            last_played: game.last_played,
+            release_date: game.release_date,
+            play_time: Duration::seconds(game.play_time_secs),
+            completion_status: game.completion_status.into(),
             genres: Vec::new(),
+            sources: Vec::new(),
+            preferred_source: game.preferred_source_id,
+            tags: Vec::new(),
+            favourite: game.favourite,
+            hidden: game.hidden,
+            developers: game.developers.lines().map(Into::into).collect(),
+            publishers: game.publishers.lines().map(Into::into).collect(),
+            install_path: game.install_path,
+            notes: game.notes,
         }
     }
 }
@@ -114,16 +149,32 @@ impl Default for Game {
             cover_url: None,
             library_type: String::new(),
             library_id: String::new(),
+            account_id: String::new(),
             completion_status: CompletionStatus::Backlog.into(),
+            merged_into: None,
+            preferred_source_id: None,
+            favourite: false,
+            hidden: false,
+            developers: String::new(),
+            publishers: String::new(),
+            install_path: None,
+            notes: String::new(),
         }
     }
 }
 
 pub type Game = Model;
 #[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+#[allow(clippy::enum_variant_names)]
 pub enum Relation {
     #[sea_orm(has_many = "super::game_genres::Entity")]
     GameGenres,
+    #[sea_orm(has_many = "super::game_external_ids::Entity")]
+    GameExternalIds,
+    #[sea_orm(has_many = "super::game_tags::Entity")]
+    GameTags,
+    #[sea_orm(has_many = "super::game_field_sources::Entity")]
+    GameFieldSources,
 }
 
 impl Related<super::game_genres::Entity> for Entity {
@@ -131,6 +182,29 @@ impl Related<super::game_genres::Entity> for Entity {
         Relation::GameGenres.def()
     }
 }
+impl Related<super::game_tags::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GameTags.def()
+    }
+}
+impl Related<super::tag::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::game_tags::Relation::Tag.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::game_tags::Relation::Game.def().rev())
+    }
+}
+impl Related<super::game_field_sources::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GameFieldSources.def()
+    }
+}
+impl Related<super::game_external_ids::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::GameExternalIds.def()
+    }
+}
 impl Related<super::genre::Entity> for Entity {
     fn to() -> RelationDef {
         super::game_genres::Relation::Genre.def()
@@ -153,4 +227,8 @@ impl IsGameLibraryRef for Game {
     fn get_library_id(&self) -> &str {
         &self.library_id
     }
+
+    fn get_account_id(&self) -> &str {
+        &self.account_id
+    }
 }
@@ -0,0 +1,131 @@
+use crate::GameField;
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+
+/// Source of values the user typed in themselves
+pub const USER_SOURCE: &str = "user";
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "game_field_sources")]
+pub struct Model {
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub game_id: i32,
+    /// [`GameField::key`] of the field
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub field: String,
+    /// Addon key, or [`USER_SOURCE`]
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+    pub locked: bool,
+}
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::game::Entity",
+        from = "Column::GameId",
+        to = "super::game::Column::Id"
+    )]
+    Game,
+}
+
+impl Related<super::game::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Game.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+/// Who last set a field of a game, and when
+#[derive(Clone, Debug, PartialEq)]
+pub struct FieldSource {
+    pub field: GameField,
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+    /// Locked fields are never changed by syncs or metadata refreshes
+    pub locked: bool,
+}
+impl FieldSource {
+    pub fn from_model(model: Model) -> Option<Self> {
+        Some(Self {
+            field: GameField::from_key(&model.field)?,
+            source: model.source,
+            updated_at: model.updated_at,
+            locked: model.locked,
+        })
+    }
+    pub fn is_user(&self) -> bool {
+        self.source == USER_SOURCE
+    }
+}
+
+/// Whether `source` may replace the current value of a field
+///
+/// Locked fields and user values are kept. Otherwise a source replaces its own values and those
+/// of sources ranked below it in `priority`, where unlisted sources rank last.
+pub fn may_overwrite(current: Option<&FieldSource>, source: &str, priority: &[String]) -> bool {
+    let Some(current) = current else {
+        return true;
+    };
+    if current.locked || current.is_user() {
+        return false;
+    }
+    let rank = |source: &str| {
+        priority
+            .iter()
+            .position(|s| s == source)
+            .unwrap_or(priority.len())
+    };
+    current.source == source || rank(source) <= rank(&current.source)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn current(source: &str, locked: bool) -> FieldSource {
+        FieldSource {
+            field: GameField::Description,
+            source: source.into(),
+            updated_at: DateTime::default(),
+            locked,
+        }
+    }
+
+    #[test]
+    fn test_keeps_user_and_locked_values() {
+        assert!(may_overwrite(None, "steam", &[]));
+        assert!(!may_overwrite(
+            Some(&current(USER_SOURCE, false)),
+            "steam",
+            &[]
+        ));
+        assert!(!may_overwrite(Some(&current("steam", true)), "steam", &[]));
+        assert!(may_overwrite(Some(&current("steam", false)), "steam", &[]));
+    }
+
+    #[test]
+    fn test_respects_priority() {
+        let priority = vec!["igdb".to_string(), "steam".to_string()];
+        assert!(may_overwrite(
+            Some(&current("steam", false)),
+            "igdb",
+            &priority
+        ));
+        assert!(!may_overwrite(
+            Some(&current("igdb", false)),
+            "steam",
+            &priority
+        ));
+        assert!(!may_overwrite(
+            Some(&current("igdb", false)),
+            "local",
+            &priority
+        ));
+        assert!(may_overwrite(
+            Some(&current("local", false)),
+            "steam",
+            &priority
+        ));
+    }
+}
@@ -36,4 +36,3 @@ impl Related<super::genre::Entity> for Entity {
 }
 
 impl ActiveModelBehavior for ActiveModel {}
-pub type GameGenre = Model;
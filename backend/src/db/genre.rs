@@ -24,13 +24,12 @@ impl Related<super::game::Entity> for Entity {
     }
 }
 impl ActiveModelBehavior for ActiveModel {}
-pub type Genre = Model;
 
-impl Into<GenreData> for Model {
-    fn into(self) -> GenreData {
+impl From<Model> for GenreData {
+    fn from(genre: Model) -> Self {
         GenreData {
-            library_id: self.metadata_id.into(),
-            name: self.name.into(),
+            library_id: genre.metadata_id.into(),
+            name: genre.name.into(),
         }
     }
 }
@@ -95,8 +95,4 @@ CREATE UNIQUE INDEX IF NOT EXISTS "genres_metadata" ON "genres" (
     "metadata_source",
     "metadata_id"
     );
-CREATE UNIQUE INDEX IF NOT EXISTS "games_metadata" ON "games" (
-    "library_type",
-    "library_id"
-    );
 COMMIT;
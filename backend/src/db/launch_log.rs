@@ -0,0 +1,73 @@
+use crate::launch::{LaunchLogEntry, LaunchStage};
+use chrono::{DateTime, Utc};
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveActiveEnum, EnumIter};
+
+#[derive(EnumIter, DeriveActiveEnum, Copy, Clone, Debug, PartialEq, Eq)]
+#[sea_orm(rs_type = "u8", db_type = "Integer")]
+#[repr(u8)]
+pub enum DbLaunchStage {
+    PreLaunch = 0,
+    Launch = 1,
+    PostExit = 2,
+}
+impl From<LaunchStage> for DbLaunchStage {
+    fn from(value: LaunchStage) -> Self {
+        match value {
+            LaunchStage::PreLaunch => Self::PreLaunch,
+            LaunchStage::Launch => Self::Launch,
+            LaunchStage::PostExit => Self::PostExit,
+        }
+    }
+}
+impl From<DbLaunchStage> for LaunchStage {
+    fn from(value: DbLaunchStage) -> Self {
+        match value {
+            DbLaunchStage::PreLaunch => Self::PreLaunch,
+            DbLaunchStage::Launch => Self::Launch,
+            DbLaunchStage::PostExit => Self::PostExit,
+        }
+    }
+}
+
+/// Outcome of one step of launching a game
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "launch_log")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub game_id: i32,
+    pub at: DateTime<Utc>,
+    pub stage: DbLaunchStage,
+    pub success: bool,
+    /// Output of the script, or what went wrong
+    pub output: String,
+}
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(
+        belongs_to = "super::game::Entity",
+        from = "Column::GameId",
+        to = "super::game::Column::Id"
+    )]
+    Game,
+}
+
+impl Related<super::game::Entity> for Entity {
+    fn to() -> RelationDef {
+        Relation::Game.def()
+    }
+}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl From<Model> for LaunchLogEntry {
+    fn from(entry: Model) -> Self {
+        Self {
+            at: entry.at,
+            stage: entry.stage.into(),
+            success: entry.success,
+            output: entry.output,
+        }
+    }
+}
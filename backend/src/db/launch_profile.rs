@@ -0,0 +1,71 @@
+use crate::launch::{LaunchProfile, PostLaunchAction};
+use sea_orm::entity::prelude::*;
+use sea_orm::{DeriveActiveEnum, EnumIter};
+
+#[derive(EnumIter, DeriveActiveEnum, Copy, Clone, Debug, PartialEq, Eq)]
+#[sea_orm(rs_type = "u8", db_type = "Integer")]
+#[repr(u8)]
+pub enum DbPostLaunchAction {
+    DoNothing = 0,
+    Minimize = 1,
+    Exit = 2,
+}
+impl From<PostLaunchAction> for DbPostLaunchAction {
+    fn from(value: PostLaunchAction) -> Self {
+        match value {
+            PostLaunchAction::DoNothing => Self::DoNothing,
+            PostLaunchAction::Minimize => Self::Minimize,
+            PostLaunchAction::Exit => Self::Exit,
+        }
+    }
+}
+impl From<DbPostLaunchAction> for PostLaunchAction {
+    fn from(value: DbPostLaunchAction) -> Self {
+        match value {
+            DbPostLaunchAction::DoNothing => Self::DoNothing,
+            DbPostLaunchAction::Minimize => Self::Minimize,
+            DbPostLaunchAction::Exit => Self::Exit,
+        }
+    }
+}
+
+/// Scripts, wrappers and environment a game is launched with
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "launch_profiles")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    /// `None` for the global profile every game starts from
+    pub game_id: Option<i32>,
+    pub pre_launch: Option<String>,
+    pub post_exit: Option<String>,
+    /// One wrapper command per line
+    pub wrappers: String,
+    /// One `KEY=VALUE` pair per line
+    pub env: String,
+    pub working_dir: Option<String>,
+    /// Overrides the launcher's own setting, only set on game profiles
+    pub post_launch_action: Option<DbPostLaunchAction>,
+}
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
+
+impl From<Model> for LaunchProfile {
+    fn from(profile: Model) -> Self {
+        Self {
+            pre_launch: profile.pre_launch,
+            post_exit: profile.post_exit,
+            wrappers: profile.wrappers.lines().map(String::from).collect(),
+            env: profile
+                .env
+                .lines()
+                .filter_map(|line| line.split_once('='))
+                .map(|(key, value)| (key.to_string(), value.to_string()))
+                .collect(),
+            working_dir: profile.working_dir,
+            post_launch_action: profile.post_launch_action.map(Into::into),
+        }
+    }
+}
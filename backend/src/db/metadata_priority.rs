@@ -0,0 +1,19 @@
+use sea_orm::entity::prelude::*;
+
+/// One entry of the user's metadata scanner ranking for a field
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "metadata_priority")]
+pub struct Model {
+    /// [`crate::GameField::key`] of the field
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub field: String,
+    /// Rank of the source, 0 being the best
+    #[sea_orm(primary_key, auto_increment = false)]
+    pub position: i32,
+    /// Metadata scanner key
+    pub source: String,
+}
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {}
+
+impl ActiveModelBehavior for ActiveModel {}
@@ -0,0 +1,21 @@
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "accounts"
+(
+    "id"           INTEGER NOT NULL,
+    "library_type" TEXT    NOT NULL,
+    "account_id"   TEXT    NOT NULL,
+    "name"         TEXT    NOT NULL DEFAULT '',
+    PRIMARY KEY ("id" AUTOINCREMENT)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS "accounts_library" ON "accounts" (
+    "library_type",
+    "account_id"
+);
+ALTER TABLE "games" ADD COLUMN "account_id" TEXT NOT NULL DEFAULT '';
+DROP INDEX IF EXISTS "games_metadata";
+CREATE UNIQUE INDEX IF NOT EXISTS "games_library_ref" ON "games" (
+    "library_type",
+    "account_id",
+    "library_id"
+);
+COMMIT;
@@ -0,0 +1,16 @@
+BEGIN TRANSACTION;
+ALTER TABLE "games" ADD COLUMN "merged_into" INTEGER;
+ALTER TABLE "games" ADD COLUMN "preferred_source_id" INTEGER;
+CREATE INDEX IF NOT EXISTS "games_merged_into" ON "games" ("merged_into");
+CREATE TABLE IF NOT EXISTS "game_external_ids"
+(
+    "game_id"     INTEGER NOT NULL,
+    "source"      TEXT    NOT NULL,
+    "external_id" TEXT    NOT NULL,
+    PRIMARY KEY ("game_id", "source")
+);
+CREATE INDEX IF NOT EXISTS "game_external_ids_lookup" ON "game_external_ids" (
+    "source",
+    "external_id"
+);
+COMMIT;
@@ -0,0 +1,18 @@
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "tags"
+(
+    "id"    INTEGER NOT NULL,
+    "name"  TEXT    NOT NULL,
+    "color" TEXT    NOT NULL DEFAULT '#808080',
+    PRIMARY KEY ("id" AUTOINCREMENT)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS "tags_name" ON "tags" ("name");
+CREATE TABLE IF NOT EXISTS "game_tags"
+(
+    "game_id" INTEGER NOT NULL,
+    "tag_id"  INTEGER NOT NULL,
+    PRIMARY KEY ("game_id", "tag_id")
+);
+ALTER TABLE "games" ADD COLUMN "favourite" BOOLEAN NOT NULL DEFAULT 0;
+ALTER TABLE "games" ADD COLUMN "hidden" BOOLEAN NOT NULL DEFAULT 0;
+COMMIT;
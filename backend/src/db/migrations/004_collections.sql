@@ -0,0 +1,13 @@
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "collections"
+(
+    "id"     INTEGER NOT NULL,
+    "name"   TEXT    NOT NULL,
+    "filter" TEXT    NOT NULL,
+    PRIMARY KEY ("id" AUTOINCREMENT)
+);
+INSERT INTO "collections" ("name", "filter")
+VALUES ('Installed, never played', '{"and":[{"install_status":"Installed"},"never_played"]}'),
+       ('Short RPGs', '{"and":[{"genre":"RPG"},{"playtime":{"op":"lt","minutes":120}}]}'),
+       ('Classic backlog', '{"and":[{"release_year":{"op":"lt","year":2010}},{"completion_status":"Backlog"}]}');
+COMMIT;
@@ -0,0 +1,66 @@
+BEGIN TRANSACTION;
+ALTER TABLE "games" ADD COLUMN "developers" TEXT NOT NULL DEFAULT '';
+CREATE VIRTUAL TABLE IF NOT EXISTS "games_fts" USING fts5
+(
+    "name",
+    "description",
+    "genres",
+    "developers",
+    tokenize = 'unicode61 remove_diacritics 2'
+);
+INSERT INTO "games_fts" ("rowid", "name", "description", "genres", "developers")
+SELECT "id",
+       "name",
+       COALESCE("description", ''),
+       (SELECT group_concat("genres"."name", ' ')
+        FROM "game_genres"
+                 JOIN "genres" ON "genres"."id" = "game_genres"."genre_id"
+        WHERE "game_genres"."game_id" = "games"."id"),
+       "developers"
+FROM "games";
+CREATE TRIGGER IF NOT EXISTS "games_fts_insert"
+    AFTER INSERT
+    ON "games"
+BEGIN
+    INSERT INTO "games_fts" ("rowid", "name", "description", "genres", "developers")
+    VALUES (NEW."id", NEW."name", COALESCE(NEW."description", ''), '', NEW."developers");
+END;
+CREATE TRIGGER IF NOT EXISTS "games_fts_update"
+    AFTER UPDATE OF "name", "description", "developers"
+    ON "games"
+BEGIN
+    UPDATE "games_fts"
+    SET "name"        = NEW."name",
+        "description" = COALESCE(NEW."description", ''),
+        "developers"  = NEW."developers"
+    WHERE "rowid" = NEW."id";
+END;
+CREATE TRIGGER IF NOT EXISTS "games_fts_delete"
+    AFTER DELETE
+    ON "games"
+BEGIN
+    DELETE FROM "games_fts" WHERE "rowid" = OLD."id";
+END;
+CREATE TRIGGER IF NOT EXISTS "games_fts_genre_insert"
+    AFTER INSERT
+    ON "game_genres"
+BEGIN
+    UPDATE "games_fts"
+    SET "genres" = (SELECT group_concat("genres"."name", ' ')
+                    FROM "game_genres"
+                             JOIN "genres" ON "genres"."id" = "game_genres"."genre_id"
+                    WHERE "game_genres"."game_id" = NEW."game_id")
+    WHERE "rowid" = NEW."game_id";
+END;
+CREATE TRIGGER IF NOT EXISTS "games_fts_genre_delete"
+    AFTER DELETE
+    ON "game_genres"
+BEGIN
+    UPDATE "games_fts"
+    SET "genres" = (SELECT group_concat("genres"."name", ' ')
+                    FROM "game_genres"
+                             JOIN "genres" ON "genres"."id" = "game_genres"."genre_id"
+                    WHERE "game_genres"."game_id" = OLD."game_id")
+    WHERE "rowid" = OLD."game_id";
+END;
+COMMIT;
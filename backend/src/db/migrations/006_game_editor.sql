@@ -0,0 +1,5 @@
+BEGIN TRANSACTION;
+ALTER TABLE "games" ADD COLUMN "publishers" TEXT NOT NULL DEFAULT '';
+ALTER TABLE "games" ADD COLUMN "install_path" TEXT;
+ALTER TABLE "games" ADD COLUMN "manual_fields" TEXT NOT NULL DEFAULT '';
+COMMIT;
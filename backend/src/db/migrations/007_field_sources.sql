@@ -0,0 +1,27 @@
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "game_field_sources"
+(
+    "game_id"    INTEGER   NOT NULL,
+    "field"      TEXT      NOT NULL,
+    "source"     TEXT      NOT NULL,
+    "updated_at" TIMESTAMP NOT NULL,
+    "locked"     BOOLEAN   NOT NULL DEFAULT 0,
+    PRIMARY KEY ("game_id", "field")
+);
+-- fields edited by hand so far become user values
+WITH RECURSIVE "split" ("game_id", "field", "rest") AS
+(
+    SELECT "id", '', "manual_fields" || ',' FROM "games" WHERE "manual_fields" != ''
+    UNION ALL
+    SELECT "game_id",
+           substr("rest", 1, instr("rest", ',') - 1),
+           substr("rest", instr("rest", ',') + 1)
+    FROM "split"
+    WHERE "rest" != ''
+)
+INSERT INTO "game_field_sources" ("game_id", "field", "source", "updated_at", "locked")
+SELECT "game_id", "field", 'user', CURRENT_TIMESTAMP, 0
+FROM "split"
+WHERE "field" != '';
+ALTER TABLE "games" DROP COLUMN "manual_fields";
+COMMIT;
@@ -0,0 +1,9 @@
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "metadata_priority"
+(
+    "field"    TEXT    NOT NULL,
+    "position" INTEGER NOT NULL,
+    "source"   TEXT    NOT NULL,
+    PRIMARY KEY ("field", "position")
+);
+COMMIT;
@@ -0,0 +1,25 @@
+BEGIN TRANSACTION;
+CREATE TABLE IF NOT EXISTS "launch_profiles"
+(
+    "id"          INTEGER NOT NULL,
+    "game_id"     INTEGER NULL,
+    "pre_launch"  TEXT    NULL,
+    "post_exit"   TEXT    NULL,
+    "wrappers"    TEXT    NOT NULL DEFAULT '',
+    "env"         TEXT    NOT NULL DEFAULT '',
+    "working_dir" TEXT    NULL,
+    PRIMARY KEY ("id" AUTOINCREMENT)
+);
+CREATE UNIQUE INDEX IF NOT EXISTS "launch_profiles_game" ON "launch_profiles" (IFNULL("game_id", -1));
+CREATE TABLE IF NOT EXISTS "launch_log"
+(
+    "id"      INTEGER  NOT NULL,
+    "game_id" INTEGER  NOT NULL,
+    "at"      DATETIME NOT NULL,
+    "stage"   INTEGER  NOT NULL,
+    "success" BOOLEAN  NOT NULL,
+    "output"  TEXT     NOT NULL DEFAULT '',
+    PRIMARY KEY ("id" AUTOINCREMENT)
+);
+CREATE INDEX IF NOT EXISTS "launch_log_game" ON "launch_log" ("game_id", "at");
+COMMIT;
@@ -0,0 +1,3 @@
+BEGIN TRANSACTION;
+ALTER TABLE "launch_profiles" ADD COLUMN "post_launch_action" INTEGER NULL;
+COMMIT;
@@ -0,0 +1,12 @@
+BEGIN TRANSACTION;
+ALTER TABLE "games" ADD COLUMN "notes" TEXT NOT NULL DEFAULT '';
+CREATE TABLE IF NOT EXISTS "play_sessions"
+(
+    "id"         INTEGER  NOT NULL,
+    "game_id"    INTEGER  NOT NULL,
+    "started_at" DATETIME NOT NULL,
+    "ended_at"   DATETIME NOT NULL,
+    PRIMARY KEY ("id" AUTOINCREMENT)
+);
+CREATE INDEX IF NOT EXISTS "play_sessions_game" ON "play_sessions" ("game_id", "started_at");
+COMMIT;
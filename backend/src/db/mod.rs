@@ -1,35 +1,173 @@
+mod account;
+pub mod backup;
+mod collection;
+pub mod filter;
 mod game;
+mod game_external_ids;
+mod game_field_sources;
 mod game_genres;
+mod game_tags;
 mod genre;
+mod launch_log;
+mod launch_profile;
+mod metadata_priority;
 pub mod ops;
+mod play_session;
+mod search;
+mod tag;
 
+pub use account::Account;
+pub use collection::Collection;
 pub use game::Game;
+pub use game_field_sources::{may_overwrite, FieldSource, USER_SOURCE};
 use gami_sdk::BASE_DATA_DIR;
-use sea_orm::{ConnectOptions, ConnectionTrait, Database, DatabaseConnection};
-use std::cell::LazyCell;
+pub use play_session::PlaySession;
+pub use sea_orm::DbErr;
+use sea_orm::{
+    ConnectOptions, ConnectionTrait, Database, DatabaseConnection, DbBackend, Statement,
+};
 use std::fs;
 use std::path::PathBuf;
-const DB_FILE: LazyCell<PathBuf> = LazyCell::new(|| BASE_DATA_DIR.join("data.db"));
-pub(crate) const DB_OPTIONS: LazyCell<ConnectOptions> = LazyCell::new(|| {
-    let mut opt = ConnectOptions::new(format!("sqlite:{}?mode=rwc", DB_FILE.to_string_lossy()));
-    opt.max_connections(100)
-        .min_connections(5)
-        .sqlx_logging(true)
-        .sqlx_logging_level(log::LevelFilter::Info);
-    opt
-});
-
-async fn connect() -> DatabaseConnection {
-    Database::connect(DB_OPTIONS.clone()).await.unwrap()
-}
-pub async fn init() {
-    log::info!("initializing database");
+use std::sync::OnceLock;
+use std::time::Duration;
+
+const MEMORY_CONNECTION_LIFETIME: Duration = Duration::from_secs(u32::MAX as u64);
+
+pub type DbResult<T> = Result<T, DbErr>;
+
+/// Where the library database is kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum DbLocation {
+    File(PathBuf),
+    /// Private to the store and gone once it is dropped, for tests
+    Memory,
+}
+impl Default for DbLocation {
+    fn default() -> Self {
+        DbLocation::File(BASE_DATA_DIR.join("data.db"))
+    }
+}
+impl DbLocation {
+    fn connect_options(&self) -> ConnectOptions {
+        let mut opt = match self {
+            DbLocation::File(path) => {
+                let mut opt =
+                    ConnectOptions::new(format!("sqlite:{}?mode=rwc", path.to_string_lossy()));
+                opt.max_connections(100).min_connections(5);
+                opt
+            }
+            // a single connection that is never reaped, the database is gone once it closes
+            DbLocation::Memory => {
+                let mut opt = ConnectOptions::new("sqlite::memory:");
+                opt.max_connections(1)
+                    .min_connections(1)
+                    .idle_timeout(MEMORY_CONNECTION_LIFETIME)
+                    .max_lifetime(MEMORY_CONNECTION_LIFETIME);
+                opt
+            }
+        };
+        opt.sqlx_logging(true)
+            .sqlx_logging_level(log::LevelFilter::Info);
+        opt
+    }
+}
+
+/// Handle to the library database, sharing one connection pool between all operations
+///
+/// Cloning is cheap, clones use the same pool.
+#[derive(Debug, Clone)]
+pub struct LibraryStore {
+    conn: DatabaseConnection,
+    location: DbLocation,
+}
+
+static STORE: OnceLock<LibraryStore> = OnceLock::new();
 
-    fs::create_dir_all(&*BASE_DATA_DIR).unwrap();
-    let conn = connect().await;
+/// Schema changes applied on top of `init.sql`, in order; the applied count is kept in `user_version`
+const MIGRATIONS: &[&str] = &[
+    include_str!("migrations/001_accounts.sql"),
+    include_str!("migrations/002_merged_games.sql"),
+    include_str!("migrations/003_tags.sql"),
+    include_str!("migrations/004_collections.sql"),
+    include_str!("migrations/005_search.sql"),
+    include_str!("migrations/006_game_editor.sql"),
+    include_str!("migrations/007_field_sources.sql"),
+    include_str!("migrations/008_metadata_priority.sql"),
+    include_str!("migrations/009_launch_profiles.sql"),
+    include_str!("migrations/010_post_launch_action.sql"),
+    include_str!("migrations/011_play_sessions.sql"),
+];
 
-    conn.execute_unprepared(include_str!("init.sql"))
-        .await
-        .unwrap();
+async fn migrate(conn: &DatabaseConnection) -> DbResult<()> {
+    let version: i32 = match conn
+        .query_one(Statement::from_string(
+            DbBackend::Sqlite,
+            "PRAGMA user_version",
+        ))
+        .await?
+    {
+        Some(row) => row.try_get_by_index(0)?,
+        None => 0,
+    };
+    for (index, migration) in MIGRATIONS.iter().enumerate().skip(version as usize) {
+        log::info!("applying database migration {}", index + 1);
+        conn.execute_unprepared(migration).await?;
+        conn.execute_unprepared(&format!("PRAGMA user_version = {}", index + 1))
+            .await?;
+    }
+    Ok(())
+}
+
+impl LibraryStore {
+    /// Connects to the database at `location`, creating and migrating it as needed
+    pub async fn open(location: DbLocation) -> DbResult<Self> {
+        if let DbLocation::File(path) = &location {
+            if let Some(dir) = path.parent() {
+                fs::create_dir_all(dir).map_err(|err| DbErr::Custom(err.to_string()))?;
+            }
+        }
+        let conn = Database::connect(location.connect_options()).await?;
+        conn.execute_unprepared(include_str!("init.sql")).await?;
+        migrate(&conn).await?;
+        Ok(Self { conn, location })
+    }
+    pub fn connection(&self) -> &DatabaseConnection {
+        &self.conn
+    }
+    pub fn location(&self) -> &DbLocation {
+        &self.location
+    }
+}
+
+/// Opens the database in the data directory as the store shared by the app
+pub async fn init() -> DbResult<&'static LibraryStore> {
+    log::info!("initializing database");
+    let store = LibraryStore::open(DbLocation::default()).await?;
     log::info!("initialized game database");
+    Ok(STORE.get_or_init(|| store))
+}
+
+/// The store opened by [`init`]
+///
+/// # Panics
+/// When called before [`init`] succeeded.
+pub fn store() -> &'static LibraryStore {
+    STORE.get().expect("database is not initialized")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_memory_stores_are_isolated() {
+        let first = LibraryStore::open(DbLocation::Memory).await.unwrap();
+        let second = LibraryStore::open(DbLocation::Memory).await.unwrap();
+        first
+            .create_tag("Co-op".into(), "#808080".into())
+            .await
+            .unwrap();
+        assert_eq!(first.get_tags().await.unwrap().len(), 1);
+        assert!(second.get_tags().await.unwrap().is_empty());
+    }
 }
@@ -1,189 +1,512 @@
-use crate::db::game::Column;
-use crate::db::{game, game_genres, genre};
-use crate::{db, ADDONS};
+use crate::db::filter::{FacetFilters, FilterExpr};
+use crate::db::game::{Column, DbGameCompletionStatus, DbGameInstallStatus};
+use crate::db::{
+    account, collection, game, game_external_ids, game_field_sources, game_genres, game_tags,
+    genre, launch_log, launch_profile, may_overwrite, metadata_priority, play_session, search, tag,
+    Account, Collection, DbResult, FieldSource, LibraryStore, PlaySession, USER_SOURCE,
+};
+use crate::dedupe::{find_duplicate_groups, DedupeCandidate, DuplicateReason};
+use crate::export;
+use crate::export::{
+    Backup, ExportFormat, ExportedAccount, ExportedCollection, ExportedFieldSource, ExportedGame,
+    ExportedGenre, ExportedPlaySession, ExportedTag, ImportMode, LibraryExport, EXPORT_VERSION,
+};
+use crate::importers::ImportedGame;
+use crate::launch::{LaunchLogEntry, LaunchProfile, LaunchStage, LOG_ENTRIES_KEPT};
+use crate::metadata::{
+    diff_metadata, merge_metadata, MergedMetadata, MetadataChange, MetadataPriority,
+};
+use crate::plugin::{ExternalAddons, GameLibraryProxy};
+use crate::{db, GameField};
 use chrono::{DateTime, Local, Utc};
+use db::account::Entity as AccountEntity;
+use db::collection::Entity as CollectionEntity;
 use db::game::Entity as GameEntity;
+use db::game_external_ids::Entity as GameExternalIdsEntity;
+use db::game_field_sources::Entity as GameFieldSourcesEntity;
 use db::game_genres::Entity as GameGenresEntity;
+use db::game_tags::Entity as GameTagsEntity;
 use db::genre::Entity as GenreEntity;
+use db::launch_log::Entity as LaunchLogEntity;
+use db::launch_profile::Entity as LaunchProfileEntity;
+use db::metadata_priority::Entity as MetadataPriorityEntity;
+use db::play_session::Entity as PlaySessionEntity;
+use db::tag::Entity as TagEntity;
 use gami_sdk::{
-    GameCommon, GameData, GameLibraryRefOwned, GameMetadata, GameMetadataScanner, GenreData,
+    CompletionStatus, ExternalId, GameCommon, GameData, GameInstallStatus, GameLibraryRefOwned,
+    GameMetadata, GameMetadataScanner, GenreData, LibraryAccount, TagData,
 };
 use gami_sdk::{GameLibrary, GameLibraryRef};
+use sea_orm::sea_query::{Alias, Expr, OnConflict, Query, SelectStatement};
 use sea_orm::{
-    ActiveValue, ColumnTrait, EntityTrait, Order, QueryFilter, QueryOrder, SelectColumns,
+    ActiveModelTrait, ActiveValue, ColumnTrait, Condition, ConnectionTrait, DatabaseConnection,
+    DatabaseTransaction, DbBackend, DbErr, EntityTrait, JoinType, Order, PaginatorTrait,
+    QueryFilter, QueryOrder, QuerySelect, RelationTrait, SelectColumns, Statement,
     TransactionTrait,
 };
-use std::collections::{HashMap, HashSet};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
 use std::fmt;
+use std::path::PathBuf;
 
-pub async fn delete_game(game_id: i32) {
-    let mut conn = db::connect().await;
+impl LibraryStore {
+    /// Deletes a game like [`Self::delete_games`], without keeping what's needed to undo it
+    pub async fn delete_game(&self, game_id: i32) -> DbResult<()> {
+        self.delete_games(vec![game_id]).await?;
+        Ok(())
+    }
 
-    GameEntity::delete_by_id(game_id)
-        .exec(&mut conn)
-        .await
-        .unwrap();
-}
+    /// Deletes the whole library, after snapshotting it so it can be restored
+    pub async fn clear_all(&self) -> DbResult<()> {
+        self.snapshot_before("clear").await?;
+        let conn = &self.conn;
+        GameEntity::delete_many().exec(conn).await?;
+        GenreEntity::delete_many().exec(conn).await?;
+        GameGenresEntity::delete_many().exec(conn).await?;
+        GameExternalIdsEntity::delete_many().exec(conn).await?;
+        GameTagsEntity::delete_many().exec(conn).await?;
+        TagEntity::delete_many().exec(conn).await?;
+        GameFieldSourcesEntity::delete_many().exec(conn).await?;
+        // the global launch profile is a setting rather than part of the library
+        LaunchProfileEntity::delete_many()
+            .filter(launch_profile::Column::GameId.is_not_null())
+            .exec(conn)
+            .await?;
+        LaunchLogEntity::delete_many().exec(conn).await?;
+        PlaySessionEntity::delete_many().exec(conn).await?;
+        Ok(())
+    }
 
-pub async fn clear_all() {
-    let mut conn = db::connect().await;
-    GameEntity::delete_many().exec(&mut conn).await.unwrap();
-    GenreEntity::delete_many().exec(&mut conn).await.unwrap();
-    GameGenresEntity::delete_many()
-        .exec(&mut conn)
-        .await
-        .unwrap();
-}
-
-pub async fn sync_library() {
-    let mut conn = db::connect().await;
-    for key in ADDONS.get_keys() {
-        if let Some(lib) = ADDONS.get_game_library(key) {
-            let mut items: Vec<GameData> = lib.scan().into_iter().map(|v| v.into()).collect();
-
-            let existing_query = GameEntity::find()
-                .select_column(Column::LibraryId)
-                .filter(Column::LibraryType.eq(key))
-                .filter(Column::LibraryId.is_in(items.iter().map(|v| v.library_id.as_str())));
-            let existing_items: HashSet<String> = existing_query
-                .all(&conn)
-                .await
-                .unwrap()
-                .into_iter()
-                .map(|v| v.library_id)
-                .collect();
-            log::info!("Pushing {} games to DB", items.len());
+    pub async fn get_tags(&self) -> DbResult<Vec<TagData>> {
+        let conn = &self.conn;
+        Ok(TagEntity::find()
+            .order_by_asc(tag::Column::Name)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
 
-            items = items
-                .into_iter()
-                .filter(|v| !existing_items.contains(&v.library_id))
-                .collect();
-            log::info!("Scanning {} games metadata ", items.len());
-            let metadatas = ADDONS
-                .get_game_metadata(key)
-                .map(|scanner| {
-                    scanner.get_metadatas(
-                        &items
-                            .iter()
-                            .map(GameCommon::get_ref)
-                            .collect::<Vec<GameLibraryRef>>(),
-                    )
-                })
-                .unwrap_or_default();
-
-            log::debug!("Got metadatas: {:?}", metadatas);
-            log::info!("Scanned items metadata: {:?}", metadatas);
-            fn get_genres(
-                metadatas: &HashMap<GameLibraryRefOwned, GameMetadata>,
-            ) -> impl Iterator<Item = &str> {
-                metadatas
-                    .values()
-                    .flat_map(|v| v.genres.iter().map(|g| g.library_id.trim_ascii()))
-            }
+    pub async fn create_tag(&self, name: String, color: String) -> DbResult<TagData> {
+        let conn = &self.conn;
+        let res = TagEntity::insert(tag::ActiveModel {
+            id: ActiveValue::NotSet,
+            name: ActiveValue::Set(name.clone()),
+            color: ActiveValue::Set(color.clone()),
+        })
+        .exec(conn)
+        .await?;
+        Ok(TagData {
+            id: res.last_insert_id,
+            name,
+            color,
+        })
+    }
 
-            let existing_genres_query = GenreEntity::find()
-                .select_column(genre::Column::MetadataId)
-                .select_column(genre::Column::Id)
-                .filter(genre::Column::MetadataSource.eq(key))
-                .filter(genre::Column::MetadataId.is_in(get_genres(&metadatas)));
+    pub async fn update_tag(&self, tag: TagData) -> DbResult<()> {
+        let conn = &self.conn;
+        TagEntity::update(tag::ActiveModel {
+            id: ActiveValue::Unchanged(tag.id),
+            name: ActiveValue::Set(tag.name),
+            color: ActiveValue::Set(tag.color),
+        })
+        .exec(conn)
+        .await?;
+        Ok(())
+    }
 
-            let mut existing_genres_by_lib_id: HashMap<String, i32> = existing_genres_query
-                .all(&conn)
-                .await
-                .unwrap()
-                .into_iter()
-                .map(|v| (v.metadata_id, v.id))
-                .collect();
+    pub async fn delete_tag(&self, tag_id: i32) -> DbResult<()> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        GameTagsEntity::delete_many()
+            .filter(game_tags::Column::TagId.eq(tag_id))
+            .exec(&txn)
+            .await?;
+        TagEntity::delete_by_id(tag_id).exec(&txn).await?;
+        txn.commit().await?;
+        Ok(())
+    }
 
-            let raw_genres: HashSet<GenreData> = metadatas
-                .values()
-                .into_iter()
-                .flat_map(|m| {
-                    m.genres.iter().cloned().filter(|gen| {
-                        !existing_genres_by_lib_id.contains_key(gen.library_id.trim_end())
-                    })
-                })
-                .collect();
-            log::info!("Genres to skip: {:?}", existing_genres_by_lib_id);
-            log::info!("Pushing genres: {:?}", raw_genres);
+    pub async fn add_tag_to_games(&self, tag_id: i32, game_ids: Vec<i32>) -> DbResult<()> {
+        if game_ids.is_empty() {
+            return Ok(());
+        }
+        let conn = &self.conn;
+        GameTagsEntity::insert_many(game_ids.into_iter().map(|game_id| game_tags::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            tag_id: ActiveValue::Set(tag_id),
+        }))
+        .on_conflict(
+            OnConflict::columns([game_tags::Column::GameId, game_tags::Column::TagId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(conn)
+        .await?;
+        Ok(())
+    }
 
-            let mut txn = conn.begin().await.unwrap();
-            for g in raw_genres {
-                let res = GenreEntity::insert(genre::ActiveModel {
-                    id: ActiveValue::NotSet,
-                    name: ActiveValue::Set(g.name.clone().into()),
-                    metadata_id: ActiveValue::Set(g.library_id.clone().into()),
-                    metadata_source: ActiveValue::Set(key.into()),
-                })
-                .exec(&mut txn)
-                .await
-                .unwrap();
-                existing_genres_by_lib_id.insert(g.library_id.into(), res.last_insert_id);
-            }
-            for mut item in items.iter().cloned() {
-                let metadata: GameMetadata = metadatas
-                    .get(&GameCommon::get_owned_ref(&item))
-                    .cloned()
-                    .unwrap_or_default();
-                item.extend(metadata);
-                let res = GameEntity::insert(game::ActiveModel {
-                    library_type: ActiveValue::Set(item.library_type),
-                    library_id: ActiveValue::Set(item.library_id),
-                    name: ActiveValue::Set(item.name),
-                    description: ActiveValue::Set(item.description),
-                    install_status: ActiveValue::Set(item.install_status.into()),
-                    play_time_secs: ActiveValue::Set(item.play_time.num_seconds()),
-                    last_played: ActiveValue::Set(item.last_played),
-                    icon_url: ActiveValue::Set(item.icon_url),
-                    release_date: ActiveValue::Set(item.release_date),
-                    ..Default::default()
-                })
-                .exec(&mut txn)
-                .await
-                .unwrap();
-                log::info!("Got game metadata genres: {:?}", item.genres);
-                let to_insert = item
-                    .genres
+    pub async fn remove_tag_from_games(&self, tag_id: i32, game_ids: Vec<i32>) -> DbResult<()> {
+        let conn = &self.conn;
+        GameTagsEntity::delete_many()
+            .filter(game_tags::Column::TagId.eq(tag_id))
+            .filter(game_tags::Column::GameId.is_in(game_ids))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_favourite(&self, game_id: i32, favourite: bool) -> DbResult<()> {
+        let conn = &self.conn;
+        GameEntity::update_many()
+            .col_expr(Column::Favourite, Expr::value(favourite))
+            .filter(Column::Id.eq(game_id))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_hidden(&self, game_id: i32, hidden: bool) -> DbResult<()> {
+        let conn = &self.conn;
+        GameEntity::update_many()
+            .col_expr(Column::Hidden, Expr::value(hidden))
+            .filter(Column::Id.eq(game_id))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_collections(&self) -> DbResult<Vec<Collection>> {
+        let conn = &self.conn;
+        Ok(CollectionEntity::find()
+            .order_by_asc(collection::Column::Name)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn create_collection(
+        &self,
+        name: String,
+        filter: FilterExpr,
+    ) -> DbResult<Collection> {
+        let conn = &self.conn;
+        let res = CollectionEntity::insert(collection::ActiveModel {
+            id: ActiveValue::NotSet,
+            name: ActiveValue::Set(name.clone()),
+            filter: ActiveValue::Set(
+                serde_json::to_string(&filter).map_err(|err| DbErr::Json(err.to_string()))?,
+            ),
+        })
+        .exec(conn)
+        .await?;
+        Ok(Collection {
+            id: res.last_insert_id,
+            name,
+            filter,
+        })
+    }
+
+    pub async fn update_collection(&self, collection: Collection) -> DbResult<()> {
+        let conn = &self.conn;
+        CollectionEntity::update(collection::ActiveModel {
+            id: ActiveValue::Unchanged(collection.id),
+            name: ActiveValue::Set(collection.name),
+            filter: ActiveValue::Set(
+                serde_json::to_string(&collection.filter)
+                    .map_err(|err| DbErr::Json(err.to_string()))?,
+            ),
+        })
+        .exec(conn)
+        .await?;
+        Ok(())
+    }
+
+    pub async fn delete_collection(&self, collection_id: i32) -> DbResult<()> {
+        let conn = &self.conn;
+        CollectionEntity::delete_by_id(collection_id)
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn get_accounts(&self) -> DbResult<Vec<Account>> {
+        let conn = &self.conn;
+        AccountEntity::find()
+            .order_by_asc(account::Column::LibraryType)
+            .order_by_asc(account::Column::Name)
+            .all(conn)
+            .await
+    }
+
+    /// The account with an ID, or failing that a name ignoring case
+    pub async fn find_account(&self, query: &str) -> DbResult<Option<Account>> {
+        let accounts = self.get_accounts().await?;
+        let by_id = accounts
+            .iter()
+            .position(|account| account.account_id == query);
+        Ok(by_id
+            .or_else(|| {
+                accounts
                     .iter()
-                    .map(|gg| game_genres::ActiveModel {
-                        game_id: ActiveValue::Set(res.last_insert_id),
-                        genre_id: ActiveValue::Set(
-                            existing_genres_by_lib_id[gg.library_id.trim_end()],
-                        ),
-                    })
-                    .collect::<Vec<game_genres::ActiveModel>>();
-                log::info!(
-                    "Inserted game metadata: {:?}; GameGenres: {:?}",
-                    res.last_insert_id,
-                    to_insert
-                );
-                if !to_insert.is_empty() {
-                    GameGenresEntity::insert_many(to_insert)
-                        .exec(&mut txn)
-                        .await
-                        .unwrap();
-                }
+                    .position(|account| account.name.eq_ignore_ascii_case(query))
+            })
+            .map(|index| accounts[index].clone()))
+    }
+}
+
+async fn sync_accounts(
+    conn: &DatabaseConnection,
+    key: &str,
+    accounts: &[LibraryAccount],
+) -> DbResult<()> {
+    for acc in accounts {
+        AccountEntity::insert(account::ActiveModel {
+            id: ActiveValue::NotSet,
+            library_type: ActiveValue::Set(key.into()),
+            account_id: ActiveValue::Set(acc.account_id.to_string()),
+            name: ActiveValue::Set(acc.name.to_string()),
+        })
+        .on_conflict(
+            OnConflict::columns([account::Column::LibraryType, account::Column::AccountId])
+                .update_column(account::Column::Name)
+                .to_owned(),
+        )
+        .exec(conn)
+        .await?;
+    }
+    // games synced before accounts existed are adopted by the first account
+    if let Some(first) = accounts.first() {
+        GameEntity::update_many()
+            .col_expr(Column::AccountId, Expr::value(first.account_id.to_string()))
+            .filter(Column::LibraryType.eq(key))
+            .filter(Column::AccountId.eq(""))
+            .exec(conn)
+            .await?;
+    }
+    Ok(())
+}
+
+impl LibraryStore {
+    /// Adds the games the libraries of `addons` report that aren't stored yet
+    pub async fn sync_library(&self, addons: &ExternalAddons) -> DbResult<()> {
+        for key in addons.get_keys() {
+            self.sync_library_type(addons, key).await?;
+        }
+        Ok(())
+    }
+
+    /// Like [`Self::sync_library`], for the library registered as `key` only
+    pub async fn sync_library_type(&self, addons: &ExternalAddons, key: &str) -> DbResult<()> {
+        let conn = &self.conn;
+        let Some(lib) = addons.get_game_library(key) else {
+            return Err(DbErr::Custom(format!("no library named {}", key)));
+        };
+        let accounts = lib.get_accounts();
+        sync_accounts(conn, key, &accounts).await?;
+        if accounts.is_empty() {
+            sync_library_account(conn, addons, key, lib, "").await?;
+        }
+        for account in accounts {
+            sync_library_account(conn, addons, key, lib, &account.account_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Number of games in the library, counting merged games once
+    pub async fn count_games(&self) -> DbResult<u64> {
+        GameEntity::find()
+            .filter(Column::MergedInto.is_null())
+            .count(&self.conn)
+            .await
+    }
+
+    /// Asks the libraries of `addons` for the install status of their games
+    ///
+    /// Returns the games whose stored status changed, by id of their own library entry.
+    pub async fn refresh_install_statuses(
+        &self,
+        addons: &ExternalAddons,
+    ) -> DbResult<Vec<(i32, GameInstallStatus)>> {
+        let conn = &self.conn;
+        let mut changes = Vec::new();
+        for model in GameEntity::find().all(conn).await? {
+            let Some(lib) = addons.get_game_library(&model.library_type) else {
+                continue;
+            };
+            let game: GameData = model.into();
+            let status = lib.check_install_status(game.get_ref());
+            if status == game.install_status {
+                continue;
             }
-            txn.commit().await.unwrap();
-            log::info!("Pushed games to DB");
+            GameEntity::update(game::ActiveModel {
+                id: ActiveValue::Unchanged(game.id),
+                install_status: ActiveValue::Set(status.into()),
+                ..Default::default()
+            })
+            .exec(conn)
+            .await?;
+            changes.push((game.id, status));
+        }
+        Ok(changes)
+    }
+}
+
+async fn sync_library_account(
+    conn: &DatabaseConnection,
+    addons: &ExternalAddons,
+    key: &str,
+    lib: &GameLibraryProxy,
+    account_id: &str,
+) -> DbResult<()> {
+    let mut items: Vec<GameData> = lib
+        .scan(account_id)
+        .into_iter()
+        .map(|v| GameData {
+            account_id: account_id.into(),
+            ..v.into()
+        })
+        .collect();
+
+    let existing_query = GameEntity::find()
+        .select_column(Column::LibraryId)
+        .filter(Column::LibraryType.eq(key))
+        .filter(Column::AccountId.eq(account_id))
+        .filter(Column::LibraryId.is_in(items.iter().map(|v| v.library_id.as_str())));
+    let existing_items: HashMap<String, game::Model> = existing_query
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|v| (v.library_id.clone(), v))
+        .collect();
+    log::info!("Pushing {} games to DB", items.len());
+
+    let (known, new_items): (Vec<GameData>, Vec<GameData>) = items
+        .into_iter()
+        .partition(|v| existing_items.contains_key(&v.library_id));
+    items = new_items;
+    if lib.reports_play_stats() {
+        update_play_stats(conn, key, &known, &existing_items).await?;
+    }
+    log::info!("Scanning {} games metadata ", items.len());
+    let priority = load_metadata_priority(conn).await?;
+    let mut metadatas = fetch_metadatas(addons, &items, &priority, &[]);
+    log::debug!("Got metadatas: {:?}", metadatas);
+
+    let txn = conn.begin().await?;
+    for mut item in items {
+        let merged = metadatas
+            .remove(&GameCommon::get_owned_ref(&item))
+            .unwrap_or_default();
+        let external_ids = merged.metadata.external_ids.clone();
+        let genre_source = merged.sources.get(&GameField::Genres).cloned();
+        let mut own_fields = vec![GameField::Name, GameField::PlayTime];
+        if item.last_played.is_some() {
+            own_fields.push(GameField::LastPlayed);
+        }
+        if item.icon_url.is_some() {
+            own_fields.push(GameField::IconUrl);
+        }
+        item.extend(merged.metadata);
+        let res = GameEntity::insert(game::ActiveModel {
+            library_type: ActiveValue::Set(item.library_type),
+            library_id: ActiveValue::Set(item.library_id),
+            account_id: ActiveValue::Set(item.account_id),
+            name: ActiveValue::Set(item.name),
+            description: ActiveValue::Set(item.description),
+            install_status: ActiveValue::Set(item.install_status.into()),
+            play_time_secs: ActiveValue::Set(item.play_time.num_seconds()),
+            last_played: ActiveValue::Set(item.last_played),
+            icon_url: ActiveValue::Set(item.icon_url),
+            header_url: ActiveValue::Set(item.header_url),
+            cover_url: ActiveValue::Set(item.cover_url),
+            release_date: ActiveValue::Set(item.release_date),
+            developers: ActiveValue::Set(item.developers.join("\n")),
+            publishers: ActiveValue::Set(item.publishers.join("\n")),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+        record_field_sources(&txn, res.last_insert_id, own_fields, key).await?;
+        record_merged_sources(&txn, res.last_insert_id, &merged.sources).await?;
+        if let Some(genre_source) = genre_source {
+            log::info!("Got game metadata genres: {:?}", item.genres);
+            let genre_ids = ensure_genres(&txn, &genre_source, &item.genres).await?;
+            insert_game_genres(&txn, res.last_insert_id, &item.genres, &genre_ids).await?;
+        }
+        insert_external_ids(&txn, res.last_insert_id, &external_ids).await?;
+    }
+    txn.commit().await?;
+    log::info!("Pushed games to DB");
+    Ok(())
+}
+/// Raises the play time and moves the last played date of already synced games forward
+///
+/// Only done for libraries that [report play stats](GameLibrary::reports_play_stats), those only
+/// ever report growing totals, so lower values are kept. User and locked fields
+/// are left alone.
+async fn update_play_stats(
+    conn: &DatabaseConnection,
+    key: &str,
+    scanned: &[GameData],
+    stored: &HashMap<String, game::Model>,
+) -> DbResult<()> {
+    let ids = scanned
+        .iter()
+        .filter_map(|game| stored.get(&game.library_id))
+        .map(|row| row.id)
+        .collect();
+    let field_sources = get_field_sources_of(conn, ids).await?;
+    let txn = conn.begin().await?;
+    for game in scanned {
+        let Some(row) = stored.get(&game.library_id) else {
+            continue;
+        };
+        let may_update = |field| may_overwrite(field_sources.get(&(row.id, field)), key, &[]);
+        let mut model = game::ActiveModel {
+            id: ActiveValue::Unchanged(row.id),
+            ..Default::default()
+        };
+        let mut fields = Vec::new();
+        let play_time_secs = game.play_time.num_seconds();
+        if play_time_secs > row.play_time_secs && may_update(GameField::PlayTime) {
+            model.play_time_secs = ActiveValue::Set(play_time_secs);
+            fields.push(GameField::PlayTime);
+        }
+        if game.last_played > row.last_played && may_update(GameField::LastPlayed) {
+            model.last_played = ActiveValue::Set(game.last_played);
+            fields.push(GameField::LastPlayed);
+        }
+        if model.is_changed() {
+            GameEntity::update(model).exec(&txn).await?;
+            record_field_sources(&txn, row.id, fields, key).await?;
         }
     }
+    txn.commit().await?;
+    Ok(())
 }
-#[derive(Debug, Default, Copy, Clone, PartialEq)]
+
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SortOrder {
     #[default]
     Ascending,
     Descending,
 }
-impl Into<Order> for SortOrder {
-    fn into(self) -> Order {
-        match self {
+impl From<SortOrder> for Order {
+    fn from(order: SortOrder) -> Self {
+        match order {
             SortOrder::Ascending => Order::Asc,
             SortOrder::Descending => Order::Desc,
         }
     }
 }
-#[derive(Debug, Default, Copy, Clone, PartialEq)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
 pub enum SortField {
     #[default]
     Name,
@@ -191,6 +514,8 @@ pub enum SortField {
     Playtime,
     ReleaseDate,
     CompletionStatus,
+    InstallStatus,
+    Library,
 }
 impl fmt::Display for SortField {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
@@ -200,88 +525,2265 @@ impl fmt::Display for SortField {
             Self::Playtime => "Playtime",
             Self::ReleaseDate => "Release date",
             Self::CompletionStatus => "Completion status",
+            Self::InstallStatus => "Install status",
+            Self::Library => "Library",
         })
     }
 }
 impl SortField {
-    pub const ALL: [SortField; 5] = [
+    pub const ALL: [SortField; 7] = [
         SortField::Name,
         SortField::LastPlayed,
         SortField::Playtime,
         SortField::ReleaseDate,
         SortField::CompletionStatus,
+        SortField::InstallStatus,
+        SortField::Library,
     ];
 }
-impl Into<Column> for SortField {
-    fn into(self) -> Column {
-        match self {
-            Self::Name => Column::Name,
-            Self::LastPlayed => Column::LastPlayed,
-            Self::Playtime => Column::PlayTimeSecs,
-            Self::ReleaseDate => Column::ReleaseDate,
-            Self::CompletionStatus => Column::CompletionStatus,
+impl From<SortField> for Column {
+    fn from(field: SortField) -> Self {
+        match field {
+            SortField::Name => Column::Name,
+            SortField::LastPlayed => Column::LastPlayed,
+            SortField::Playtime => Column::PlayTimeSecs,
+            SortField::ReleaseDate => Column::ReleaseDate,
+            SortField::CompletionStatus => Column::CompletionStatus,
+            SortField::InstallStatus => Column::InstallStatus,
+            SortField::Library => Column::LibraryType,
         }
     }
 }
-#[derive(Debug, Default, Copy, Clone)]
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
 pub struct Sort {
     pub field: SortField,
     pub order: SortOrder,
 }
+#[derive(Debug, Default, Copy, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HiddenFilter {
+    #[default]
+    Exclude,
+    Include,
+    Only,
+}
+impl HiddenFilter {
+    pub const ALL: [HiddenFilter; 3] = [
+        HiddenFilter::Exclude,
+        HiddenFilter::Include,
+        HiddenFilter::Only,
+    ];
+}
+impl fmt::Display for HiddenFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::Exclude => "Hide hidden",
+            Self::Include => "Show hidden",
+            Self::Only => "Only hidden",
+        })
+    }
+}
 #[derive(Debug, Default, Clone)]
 pub struct GamesFilters {
     pub search: String,
-    pub sort: Sort,
-}
-pub async fn get_games(filters: GamesFilters) -> Vec<GameData> {
-    let conn = db::connect().await;
-    let mut query = GameEntity::find().find_with_related(GenreEntity);
-    if !filters.search.is_empty() {
-        query = query.filter(Column::Name.contains(&filters.search));
-    }
-    let sort_field: Column = filters.sort.field.into();
-    let sort_ord: Order = filters.sort.order.into();
-    query = query.order_by(sort_field, sort_ord);
-    let raw = query.all(&conn).await.unwrap();
-    raw.into_iter()
-        .map(|(game, genres)| GameData {
-            genres: genres.into_iter().map(|v| v.into()).collect(),
-            ..game.into()
+    /// Sort keys by priority, sorted by name when empty
+    pub sort: Vec<Sort>,
+    pub account: Option<Account>,
+    /// Only include games with any of these tags
+    pub tags: Vec<i32>,
+    pub favourites_only: bool,
+    pub hidden: HiddenFilter,
+    /// Filter expression of the selected smart collection
+    pub expr: Option<FilterExpr>,
+    pub facets: FacetFilters,
+}
+fn games_condition(filters: &GamesFilters) -> Condition {
+    let mut cond = Condition::all().add(Column::MergedInto.is_null());
+    if let Some(fts_query) = search::to_fts_query(&filters.search) {
+        cond = cond.add(
+            Column::Id.in_subquery(
+                Query::select()
+                    .expr(Expr::cust("rowid"))
+                    .from(Alias::new("games_fts"))
+                    .and_where(Expr::cust_with_values("games_fts MATCH ?", [fts_query]))
+                    .to_owned(),
+            ),
+        );
+    }
+    if let Some(account) = filters.account.as_ref() {
+        cond = cond
+            .add(Column::LibraryType.eq(account.library_type.as_str()))
+            .add(Column::AccountId.eq(account.account_id.as_str()));
+    }
+    if !filters.tags.is_empty() {
+        cond = cond.add(
+            Column::Id.in_subquery(
+                Query::select()
+                    .column(game_tags::Column::GameId)
+                    .from(GameTagsEntity)
+                    .and_where(game_tags::Column::TagId.is_in(filters.tags.iter().copied()))
+                    .to_owned(),
+            ),
+        );
+    }
+    if filters.favourites_only {
+        cond = cond.add(Column::Favourite.eq(true));
+    }
+    if let Some(expr) = filters.expr.as_ref() {
+        cond = cond.add(expr.to_condition());
+    }
+    if !filters.facets.is_empty() {
+        cond = cond.add(filters.facets.to_expr().to_condition());
+    }
+    match filters.hidden {
+        HiddenFilter::Exclude => cond.add(Column::Hidden.eq(false)),
+        HiddenFilter::Include => cond,
+        HiddenFilter::Only => cond.add(Column::Hidden.eq(true)),
+    }
+}
+/// IDs of the games matching `filters`, to load their related rows without loading the rest
+fn matching_game_ids(filters: &GamesFilters) -> SelectStatement {
+    Query::select()
+        .column(Column::Id)
+        .from(GameEntity)
+        .cond_where(games_condition(filters))
+        .to_owned()
+}
+impl LibraryStore {
+    pub async fn get_games(&self, filters: GamesFilters) -> DbResult<Vec<GameData>> {
+        let conn = &self.conn;
+        let fts_query = search::to_fts_query(&filters.search);
+        let mut query = GameEntity::find()
+            .find_with_related(GenreEntity)
+            .filter(games_condition(&filters));
+        // joining genres orders by id first, which would leave the chosen sort to break ties only
+        QueryOrder::query(&mut query).clear_order_by();
+        if filters.sort.is_empty() {
+            query = query.order_by_asc(Column::Name);
+        }
+        for sort in &filters.sort {
+            let sort_field: Column = sort.field.into();
+            let sort_ord: Order = sort.order.into();
+            query = query.order_by(sort_field, sort_ord);
+        }
+        query = query.order_by_asc(Column::Id);
+        let mut raw = query.all(conn).await?;
+        // while searching, the best matches come first and the chosen sort only breaks ties
+        if let Some(fts_query) = fts_query {
+            let ranks: HashMap<i32, f64> = conn
+                .query_all(Statement::from_sql_and_values(
+                    DbBackend::Sqlite,
+                    search::RANK_QUERY,
+                    [fts_query.into()],
+                ))
+                .await?
+                .into_iter()
+                .map(|row| Ok((row.try_get_by_index(0)?, row.try_get_by_index(1)?)))
+                .collect::<DbResult<_>>()?;
+            raw.sort_by(|(a, _), (b, _)| {
+                let rank = |id| ranks.get(id).copied().unwrap_or(f64::MAX);
+                rank(&a.id).total_cmp(&rank(&b.id))
+            });
+        }
+
+        let mut merged_by_parent: HashMap<i32, Vec<game::Model>> = HashMap::new();
+        for merged in GameEntity::find()
+            .filter(Column::MergedInto.in_subquery(matching_game_ids(&filters)))
+            .all(conn)
+            .await?
+        {
+            if let Some(parent) = merged.merged_into {
+                merged_by_parent.entry(parent).or_default().push(merged);
+            }
+        }
+        let mut tags_by_game: HashMap<i32, Vec<TagData>> = HashMap::new();
+        for (game_tag, tag) in GameTagsEntity::find()
+            .find_also_related(TagEntity)
+            .filter(game_tags::Column::GameId.in_subquery(matching_game_ids(&filters)))
+            .all(conn)
+            .await?
+        {
+            if let Some(tag) = tag {
+                tags_by_game
+                    .entry(game_tag.game_id)
+                    .or_default()
+                    .push(tag.into());
+            }
+        }
+        Ok(raw
+            .into_iter()
+            .map(|(game, genres)| {
+                let merged = merged_by_parent.remove(&game.id).unwrap_or_default();
+                let tags = tags_by_game.remove(&game.id).unwrap_or_default();
+                let sources = std::iter::once(&game)
+                    .chain(merged.iter())
+                    .map(game::Model::to_source)
+                    .collect::<Vec<_>>();
+                let mut data = GameData {
+                    genres: genres.into_iter().map(|v| v.into()).collect(),
+                    ..game.into()
+                };
+                if sources.len() > 1 {
+                    data.play_time = sources.iter().map(|s| s.play_time).sum();
+                    data.last_played = sources.iter().filter_map(|s| s.last_played).max();
+                    data.install_status = sources
+                        .iter()
+                        .map(|s| s.install_status)
+                        .min_by_key(|status| install_status_rank(*status))
+                        .unwrap_or_default();
+                }
+                data.sources = sources;
+                data.tags = tags;
+                data
+            })
+            .collect())
+    }
+}
+/// Metadata source of genres users typed in the game editor
+pub const USER_GENRE_SOURCE: &str = "user";
+
+/// "Last played within" choices offered in the facet sidebar, in days
+pub const PLAYED_WITHIN_PRESETS: [i64; 4] = [7, 30, 90, 365];
+
+#[derive(Debug, Default, Clone)]
+pub struct FacetCounts {
+    pub install_status: Vec<(GameInstallStatus, u64)>,
+    pub completion_status: Vec<(CompletionStatus, u64)>,
+    pub library_types: Vec<(String, u64)>,
+    pub genres: Vec<(String, u64)>,
+    /// Counts for each of [`PLAYED_WITHIN_PRESETS`]
+    pub played_within: Vec<(i64, u64)>,
+}
+
+impl LibraryStore {
+    /// Counts matching games per facet value
+    ///
+    /// Each facet ignores its own selection, so picking a value doesn't hide its alternatives.
+    pub async fn get_facet_counts(&self, filters: GamesFilters) -> DbResult<FacetCounts> {
+        let conn = &self.conn;
+        let without = |clear: fn(&mut FacetFilters)| {
+            let mut filters = filters.clone();
+            clear(&mut filters.facets);
+            games_condition(&filters)
+        };
+        let install_status = GameEntity::find()
+            .select_only()
+            .column(Column::InstallStatus)
+            .column_as(Expr::col(Column::Id).count(), "count")
+            .filter(without(|f| f.install_status.clear()))
+            .group_by(Column::InstallStatus)
+            .into_tuple::<(DbGameInstallStatus, i64)>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|(status, count)| (status.into(), count as u64))
+            .collect();
+        let completion_status = GameEntity::find()
+            .select_only()
+            .column(Column::CompletionStatus)
+            .column_as(Expr::col(Column::Id).count(), "count")
+            .filter(without(|f| f.completion_status.clear()))
+            .group_by(Column::CompletionStatus)
+            .into_tuple::<(DbGameCompletionStatus, i64)>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|(status, count)| (status.into(), count as u64))
+            .collect();
+        let library_types = GameEntity::find()
+            .select_only()
+            .column(Column::LibraryType)
+            .column_as(Expr::col(Column::Id).count(), "count")
+            .filter(without(|f| f.library_types.clear()))
+            .group_by(Column::LibraryType)
+            .order_by_asc(Column::LibraryType)
+            .into_tuple::<(String, i64)>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|(library_type, count)| (library_type, count as u64))
+            .collect();
+        let genres = GenreEntity::find()
+            .select_only()
+            .column(genre::Column::Name)
+            .column_as(Expr::col(game_genres::Column::GameId).count(), "count")
+            .join(JoinType::InnerJoin, genre::Relation::GameGenres.def())
+            .filter(
+                game_genres::Column::GameId.in_subquery(
+                    Query::select()
+                        .column(Column::Id)
+                        .from(GameEntity)
+                        .cond_where(without(|f| f.genres.clear()))
+                        .to_owned(),
+                ),
+            )
+            .group_by(genre::Column::Name)
+            .order_by_asc(genre::Column::Name)
+            .into_tuple::<(String, i64)>()
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|(name, count)| (name, count as u64))
+            .collect();
+        let mut played_within = Vec::new();
+        for days in PLAYED_WITHIN_PRESETS {
+            let count = GameEntity::find()
+                .filter(without(|f| f.played_within_days = None))
+                .filter(FilterExpr::PlayedWithinDays(days).to_condition())
+                .count(conn)
+                .await?;
+            played_within.push((days, count));
+        }
+        Ok(FacetCounts {
+            install_status,
+            completion_status,
+            library_types,
+            genres,
+            played_within,
+        })
+    }
+}
+fn install_status_rank(status: GameInstallStatus) -> u8 {
+    match status {
+        GameInstallStatus::Installed => 0,
+        GameInstallStatus::Installing => 1,
+        GameInstallStatus::Queued => 2,
+        GameInstallStatus::InLibrary => 3,
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct DuplicateGroup {
+    pub games: Vec<GameData>,
+    pub reason: DuplicateReason,
+}
+
+impl LibraryStore {
+    /// Finds games in different libraries which are likely the same title
+    pub async fn find_duplicates(&self) -> DbResult<Vec<DuplicateGroup>> {
+        let conn = &self.conn;
+        let games = GameEntity::find()
+            .filter(Column::MergedInto.is_null())
+            .all(conn)
+            .await?;
+        let mut external_ids: HashMap<i32, Vec<(String, String)>> = HashMap::new();
+        for ext in GameExternalIdsEntity::find().all(conn).await? {
+            external_ids
+                .entry(ext.game_id)
+                .or_default()
+                .push((ext.source, ext.external_id));
+        }
+        let candidates: Vec<DedupeCandidate> = games
+            .iter()
+            .map(|game| DedupeCandidate {
+                id: game.id,
+                name: game.name.clone(),
+                external_ids: std::iter::once((game.library_type.clone(), game.library_id.clone()))
+                    .chain(external_ids.remove(&game.id).unwrap_or_default())
+                    .collect(),
+            })
+            .collect();
+        let mut by_id: HashMap<i32, game::Model> = games.into_iter().map(|g| (g.id, g)).collect();
+        Ok(find_duplicate_groups(&candidates)
+            .into_iter()
+            .map(|group| DuplicateGroup {
+                games: group
+                    .ids
+                    .iter()
+                    .filter_map(|id| by_id.remove(id))
+                    .map(|game| {
+                        let source = game.to_source();
+                        GameData {
+                            sources: vec![source],
+                            ..game.into()
+                        }
+                    })
+                    .collect(),
+                reason: group.reason,
+            })
+            .collect())
+    }
+
+    /// Merges games into `primary_id`, which keeps its own details and becomes the preferred launcher
+    pub async fn merge_games(&self, primary_id: i32, other_ids: Vec<i32>) -> DbResult<()> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        GameEntity::update_many()
+            .col_expr(Column::MergedInto, Expr::value(primary_id))
+            .filter(
+                Condition::any()
+                    .add(Column::Id.is_in(other_ids.clone()))
+                    .add(Column::MergedInto.is_in(other_ids)),
+            )
+            .filter(Column::Id.ne(primary_id))
+            .exec(&txn)
+            .await?;
+        GameEntity::update_many()
+            .col_expr(Column::PreferredSourceId, Expr::value(primary_id))
+            .filter(Column::Id.eq(primary_id))
+            .filter(Column::PreferredSourceId.is_null())
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    /// Splits a source back out of the game it was merged into
+    pub async fn unmerge_game(&self, source_id: i32) -> DbResult<()> {
+        let conn = &self.conn;
+        GameEntity::update_many()
+            .col_expr(Column::MergedInto, Expr::value(Option::<i32>::None))
+            .filter(Column::Id.eq(source_id))
+            .exec(conn)
+            .await?;
+        GameEntity::update_many()
+            .col_expr(Column::PreferredSourceId, Expr::value(Option::<i32>::None))
+            .filter(Column::PreferredSourceId.eq(source_id))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+
+    pub async fn set_preferred_source(&self, game_id: i32, source_id: i32) -> DbResult<()> {
+        let conn = &self.conn;
+        GameEntity::update_many()
+            .col_expr(Column::PreferredSourceId, Expr::value(source_id))
+            .filter(Column::Id.eq(game_id))
+            .exec(conn)
+            .await?;
+        Ok(())
+    }
+}
+
+/// Previous state of the games touched by a bulk edit, enough to revert it
+#[derive(Debug, Clone)]
+pub enum BulkUndo {
+    CompletionStatus(Vec<(i32, CompletionStatus)>),
+    Hidden(Vec<(i32, bool)>),
+    /// Games that got the tag, excluding those that already had it
+    TagAdded {
+        tag_id: i32,
+        game_ids: Vec<i32>,
+    },
+    TagRemoved {
+        tag_id: i32,
+        game_ids: Vec<i32>,
+    },
+    Deleted(DeletedGames),
+}
+
+/// Rows removed by [`LibraryStore::delete_games`], re-inserted as they were on undo
+#[derive(Debug, Clone, Default)]
+pub struct DeletedGames {
+    games: Vec<game::Model>,
+    genres: Vec<game_genres::Model>,
+    tags: Vec<game_tags::Model>,
+    external_ids: Vec<game_external_ids::Model>,
+    field_sources: Vec<game_field_sources::Model>,
+    launch_profiles: Vec<launch_profile::Model>,
+    launch_log: Vec<launch_log::Model>,
+    play_sessions: Vec<play_session::Model>,
+}
+
+async fn games_with_tag(
+    txn: &DatabaseTransaction,
+    tag_id: i32,
+    game_ids: Vec<i32>,
+) -> DbResult<Vec<i32>> {
+    Ok(GameTagsEntity::find()
+        .filter(game_tags::Column::TagId.eq(tag_id))
+        .filter(game_tags::Column::GameId.is_in(game_ids))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|game_tag| game_tag.game_id)
+        .collect())
+}
+
+impl LibraryStore {
+    pub async fn set_completion_status_bulk(
+        &self,
+        game_ids: Vec<i32>,
+        status: CompletionStatus,
+    ) -> DbResult<BulkUndo> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        let previous = GameEntity::find()
+            .filter(Column::Id.is_in(game_ids.clone()))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|game| (game.id, game.completion_status.into()))
+            .collect();
+        GameEntity::update_many()
+            .col_expr(
+                Column::CompletionStatus,
+                Expr::value(DbGameCompletionStatus::from(status)),
+            )
+            .filter(Column::Id.is_in(game_ids))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(BulkUndo::CompletionStatus(previous))
+    }
+
+    pub async fn set_hidden_bulk(&self, game_ids: Vec<i32>, hidden: bool) -> DbResult<BulkUndo> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        let previous = GameEntity::find()
+            .filter(Column::Id.is_in(game_ids.clone()))
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|game| (game.id, game.hidden))
+            .collect();
+        GameEntity::update_many()
+            .col_expr(Column::Hidden, Expr::value(hidden))
+            .filter(Column::Id.is_in(game_ids))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(BulkUndo::Hidden(previous))
+    }
+
+    pub async fn add_tag_bulk(&self, tag_id: i32, game_ids: Vec<i32>) -> DbResult<BulkUndo> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        let tagged = games_with_tag(&txn, tag_id, game_ids.clone()).await?;
+        let added: Vec<i32> = game_ids
+            .into_iter()
+            .filter(|id| !tagged.contains(id))
+            .collect();
+        if !added.is_empty() {
+            GameTagsEntity::insert_many(added.iter().map(|game_id| game_tags::ActiveModel {
+                game_id: ActiveValue::Set(*game_id),
+                tag_id: ActiveValue::Set(tag_id),
+            }))
+            .exec(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(BulkUndo::TagAdded {
+            tag_id,
+            game_ids: added,
+        })
+    }
+
+    pub async fn remove_tag_bulk(&self, tag_id: i32, game_ids: Vec<i32>) -> DbResult<BulkUndo> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        let removed = games_with_tag(&txn, tag_id, game_ids).await?;
+        GameTagsEntity::delete_many()
+            .filter(game_tags::Column::TagId.eq(tag_id))
+            .filter(game_tags::Column::GameId.is_in(removed.clone()))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(BulkUndo::TagRemoved {
+            tag_id,
+            game_ids: removed,
+        })
+    }
+
+    /// Deletes games, the sources merged into them and every row linked to them
+    ///
+    /// The library is snapshotted first.
+    pub async fn delete_games(&self, game_ids: Vec<i32>) -> DbResult<BulkUndo> {
+        self.snapshot_before("delete").await?;
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        let games = GameEntity::find()
+            .filter(
+                Condition::any()
+                    .add(Column::Id.is_in(game_ids.clone()))
+                    .add(Column::MergedInto.is_in(game_ids)),
+            )
+            .all(&txn)
+            .await?;
+        let ids: Vec<i32> = games.iter().map(|game| game.id).collect();
+        let deleted = DeletedGames {
+            genres: GameGenresEntity::find()
+                .filter(game_genres::Column::GameId.is_in(ids.clone()))
+                .all(&txn)
+                .await?,
+            tags: GameTagsEntity::find()
+                .filter(game_tags::Column::GameId.is_in(ids.clone()))
+                .all(&txn)
+                .await?,
+            external_ids: GameExternalIdsEntity::find()
+                .filter(game_external_ids::Column::GameId.is_in(ids.clone()))
+                .all(&txn)
+                .await?,
+            field_sources: GameFieldSourcesEntity::find()
+                .filter(game_field_sources::Column::GameId.is_in(ids.clone()))
+                .all(&txn)
+                .await?,
+            launch_profiles: LaunchProfileEntity::find()
+                .filter(launch_profile::Column::GameId.is_in(ids.clone()))
+                .all(&txn)
+                .await?,
+            launch_log: LaunchLogEntity::find()
+                .filter(launch_log::Column::GameId.is_in(ids.clone()))
+                .all(&txn)
+                .await?,
+            play_sessions: PlaySessionEntity::find()
+                .filter(play_session::Column::GameId.is_in(ids.clone()))
+                .all(&txn)
+                .await?,
+            games,
+        };
+        GameGenresEntity::delete_many()
+            .filter(game_genres::Column::GameId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        GameTagsEntity::delete_many()
+            .filter(game_tags::Column::GameId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        GameExternalIdsEntity::delete_many()
+            .filter(game_external_ids::Column::GameId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        GameFieldSourcesEntity::delete_many()
+            .filter(game_field_sources::Column::GameId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        LaunchProfileEntity::delete_many()
+            .filter(launch_profile::Column::GameId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        LaunchLogEntity::delete_many()
+            .filter(launch_log::Column::GameId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        PlaySessionEntity::delete_many()
+            .filter(play_session::Column::GameId.is_in(ids.clone()))
+            .exec(&txn)
+            .await?;
+        GameEntity::delete_many()
+            .filter(Column::Id.is_in(ids))
+            .exec(&txn)
+            .await?;
+        txn.commit().await?;
+        Ok(BulkUndo::Deleted(deleted))
+    }
+
+    pub async fn undo_bulk(&self, undo: BulkUndo) -> DbResult<()> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        match undo {
+            BulkUndo::CompletionStatus(previous) => {
+                for (game_id, status) in previous {
+                    GameEntity::update_many()
+                        .col_expr(
+                            Column::CompletionStatus,
+                            Expr::value(DbGameCompletionStatus::from(status)),
+                        )
+                        .filter(Column::Id.eq(game_id))
+                        .exec(&txn)
+                        .await?;
+                }
+            }
+            BulkUndo::Hidden(previous) => {
+                for (game_id, hidden) in previous {
+                    GameEntity::update_many()
+                        .col_expr(Column::Hidden, Expr::value(hidden))
+                        .filter(Column::Id.eq(game_id))
+                        .exec(&txn)
+                        .await?;
+                }
+            }
+            BulkUndo::TagAdded { tag_id, game_ids } => {
+                GameTagsEntity::delete_many()
+                    .filter(game_tags::Column::TagId.eq(tag_id))
+                    .filter(game_tags::Column::GameId.is_in(game_ids))
+                    .exec(&txn)
+                    .await?;
+            }
+            BulkUndo::TagRemoved { tag_id, game_ids } => {
+                if !game_ids.is_empty() {
+                    GameTagsEntity::insert_many(game_ids.into_iter().map(|game_id| {
+                        game_tags::ActiveModel {
+                            game_id: ActiveValue::Set(game_id),
+                            tag_id: ActiveValue::Set(tag_id),
+                        }
+                    }))
+                    .exec(&txn)
+                    .await?;
+                }
+            }
+            BulkUndo::Deleted(deleted) => {
+                // primaries first, so merged sources point at an existing row again
+                let (primaries, merged): (Vec<_>, Vec<_>) = deleted
+                    .games
+                    .into_iter()
+                    .partition(|game| game.merged_into.is_none());
+                for game in primaries.into_iter().chain(merged) {
+                    let model: game::ActiveModel = game.into();
+                    GameEntity::insert(model.reset_all()).exec(&txn).await?;
+                }
+                if !deleted.genres.is_empty() {
+                    GameGenresEntity::insert_many(deleted.genres.into_iter().map(|row| {
+                        let model: game_genres::ActiveModel = row.into();
+                        model.reset_all()
+                    }))
+                    .exec(&txn)
+                    .await?;
+                }
+                if !deleted.tags.is_empty() {
+                    GameTagsEntity::insert_many(deleted.tags.into_iter().map(|row| {
+                        let model: game_tags::ActiveModel = row.into();
+                        model.reset_all()
+                    }))
+                    .exec(&txn)
+                    .await?;
+                }
+                if !deleted.external_ids.is_empty() {
+                    GameExternalIdsEntity::insert_many(deleted.external_ids.into_iter().map(
+                        |row| {
+                            let model: game_external_ids::ActiveModel = row.into();
+                            model.reset_all()
+                        },
+                    ))
+                    .exec(&txn)
+                    .await?;
+                }
+                if !deleted.field_sources.is_empty() {
+                    GameFieldSourcesEntity::insert_many(deleted.field_sources.into_iter().map(
+                        |row| {
+                            let model: game_field_sources::ActiveModel = row.into();
+                            model.reset_all()
+                        },
+                    ))
+                    .exec(&txn)
+                    .await?;
+                }
+                if !deleted.launch_profiles.is_empty() {
+                    LaunchProfileEntity::insert_many(deleted.launch_profiles.into_iter().map(
+                        |row| {
+                            let model: launch_profile::ActiveModel = row.into();
+                            model.reset_all()
+                        },
+                    ))
+                    .exec(&txn)
+                    .await?;
+                }
+                if !deleted.launch_log.is_empty() {
+                    LaunchLogEntity::insert_many(deleted.launch_log.into_iter().map(|row| {
+                        let model: launch_log::ActiveModel = row.into();
+                        model.reset_all()
+                    }))
+                    .exec(&txn)
+                    .await?;
+                }
+                if !deleted.play_sessions.is_empty() {
+                    PlaySessionEntity::insert_many(deleted.play_sessions.into_iter().map(|row| {
+                        let model: play_session::ActiveModel = row.into();
+                        model.reset_all()
+                    }))
+                    .exec(&txn)
+                    .await?;
+                }
+            }
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+/// Finds or creates the genres reported by a metadata scanner, keyed by their metadata ID
+async fn ensure_genres(
+    txn: &DatabaseTransaction,
+    key: &str,
+    genres: &[GenreData],
+) -> DbResult<HashMap<String, i32>> {
+    let mut by_metadata_id: HashMap<String, i32> = GenreEntity::find()
+        .filter(genre::Column::MetadataSource.eq(key))
+        .filter(genre::Column::MetadataId.is_in(genres.iter().map(|g| g.library_id.trim_end())))
+        .all(txn)
+        .await?
+        .into_iter()
+        .map(|v| (v.metadata_id, v.id))
+        .collect();
+    for g in genres {
+        let metadata_id = g.library_id.trim_end().to_string();
+        if by_metadata_id.contains_key(&metadata_id) {
+            continue;
+        }
+        let res = GenreEntity::insert(genre::ActiveModel {
+            id: ActiveValue::NotSet,
+            name: ActiveValue::Set(g.name.trim_end().into()),
+            metadata_id: ActiveValue::Set(metadata_id.clone()),
+            metadata_source: ActiveValue::Set(key.into()),
+        })
+        .exec(txn)
+        .await?;
+        by_metadata_id.insert(metadata_id, res.last_insert_id);
+    }
+    Ok(by_metadata_id)
+}
+
+async fn insert_game_genres(
+    txn: &DatabaseTransaction,
+    game_id: i32,
+    genres: &[GenreData],
+    genre_ids: &HashMap<String, i32>,
+) -> DbResult<()> {
+    let to_insert: Vec<game_genres::ActiveModel> = genres
+        .iter()
+        .map(|g| game_genres::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            genre_id: ActiveValue::Set(genre_ids[g.library_id.trim_end()]),
         })
+        .collect();
+    if to_insert.is_empty() {
+        return Ok(());
+    }
+    GameGenresEntity::insert_many(to_insert)
+        .on_conflict(
+            OnConflict::columns([game_genres::Column::GameId, game_genres::Column::GenreId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(txn)
+        .await?;
+    Ok(())
+}
+
+async fn insert_external_ids(
+    txn: &DatabaseTransaction,
+    game_id: i32,
+    external_ids: &[ExternalId],
+) -> DbResult<()> {
+    if external_ids.is_empty() {
+        return Ok(());
+    }
+    GameExternalIdsEntity::insert_many(external_ids.iter().map(|ext| {
+        game_external_ids::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            source: ActiveValue::Set(ext.source.to_string()),
+            external_id: ActiveValue::Set(ext.id.to_string()),
+        }
+    }))
+    .on_conflict(
+        OnConflict::columns([
+            game_external_ids::Column::GameId,
+            game_external_ids::Column::Source,
+        ])
+        .do_nothing()
+        .to_owned(),
+    )
+    .do_nothing()
+    .exec(txn)
+    .await?;
+    Ok(())
+}
+
+/// Asks every scanner supporting a game's library for metadata and merges their answers
+///
+/// Only scanners listed in `sources` are asked, unless it is empty.
+fn fetch_metadatas(
+    addons: &ExternalAddons,
+    games: &[GameData],
+    priority: &MetadataPriority,
+    sources: &[String],
+) -> HashMap<GameLibraryRefOwned, MergedMetadata> {
+    let mut by_library: HashMap<&str, Vec<&GameData>> = HashMap::new();
+    for game in games {
+        by_library
+            .entry(game.library_type.as_str())
+            .or_default()
+            .push(game);
+    }
+    let mut fetched = HashMap::new();
+    for (library_type, games) in by_library {
+        let refs: Vec<GameLibraryRef> = games.iter().map(|game| game.get_ref()).collect();
+        let mut results: HashMap<GameLibraryRefOwned, Vec<(String, GameMetadata)>> = HashMap::new();
+        for (key, scanner) in addons
+            .get_metadata_scanners_for(library_type)
+            .into_iter()
+            .filter(|(key, _)| sources.is_empty() || sources.iter().any(|s| s == key))
+        {
+            for (game, metadata) in scanner.get_metadatas(&refs) {
+                results
+                    .entry(game)
+                    .or_default()
+                    .push((key.to_string(), metadata));
+            }
+        }
+        if results.is_empty() {
+            log::warn!("No metadata found for {} games", library_type);
+        }
+        for (game, results) in results {
+            fetched.insert(game, merge_metadata(library_type, &results, priority));
+        }
+    }
+    fetched
+}
+
+/// Keys of the scanners able to describe games of `library_type`
+fn metadata_scanner_keys(addons: &ExternalAddons, library_type: &str) -> Vec<String> {
+    addons
+        .get_metadata_scanners_for(library_type)
+        .into_iter()
+        .map(|(key, _)| key.to_string())
         .collect()
 }
 
-pub async fn update_game_played(id: i32) -> DateTime<Utc> {
-    let mut conn = db::connect().await;
-    let curr = Local::now().into();
-    GameEntity::update(game::ActiveModel {
-        id: ActiveValue::Unchanged(id),
-        last_played: ActiveValue::Set(Some(curr)),
-        ..Default::default()
-    })
-    .exec(&mut conn)
-    .await
-    .unwrap();
-    curr
-}
-pub async fn update_game(game: GameData) {
-    let mut conn = db::connect().await;
-    GameEntity::update(game::ActiveModel {
-        id: ActiveValue::Set(game.id),
-        icon_url: ActiveValue::Set(game.icon_url),
-        name: ActiveValue::Set(game.name),
-        header_url: ActiveValue::Set(game.header_url),
-        library_type: ActiveValue::Set(game.library_type),
-        library_id: ActiveValue::Set(game.library_id),
-        install_status: ActiveValue::Set(game.install_status.into()),
-        description: ActiveValue::Set(game.description),
-        cover_url: ActiveValue::Set(game.cover_url),
-        last_played: ActiveValue::Set(game.last_played),
-        play_time_secs: ActiveValue::Set(game.play_time.num_seconds()),
-        release_date: ActiveValue::Set(game.release_date),
-        completion_status: ActiveValue::Set(game.completion_status.into()),
-    })
-    .exec(&mut conn)
-    .await
-    .unwrap();
+/// Records the scanner each field was taken from
+async fn record_merged_sources(
+    txn: &DatabaseTransaction,
+    game_id: i32,
+    sources: &HashMap<GameField, String>,
+) -> DbResult<()> {
+    let mut by_source: HashMap<&str, Vec<GameField>> = HashMap::new();
+    for (field, source) in sources {
+        by_source.entry(source).or_default().push(*field);
+    }
+    for (source, fields) in by_source {
+        record_field_sources(txn, game_id, fields, source).await?;
+    }
+    Ok(())
+}
+
+async fn load_metadata_priority(conn: &impl ConnectionTrait) -> DbResult<MetadataPriority> {
+    let mut priority = MetadataPriority::default();
+    for model in MetadataPriorityEntity::find()
+        .order_by_asc(metadata_priority::Column::Position)
+        .all(conn)
+        .await?
+    {
+        if let Some(field) = GameField::from_key(&model.field) {
+            priority.fields.entry(field).or_default().push(model.source);
+        }
+    }
+    Ok(priority)
+}
+
+impl LibraryStore {
+    pub async fn get_metadata_priority(&self) -> DbResult<MetadataPriority> {
+        load_metadata_priority(&self.conn).await
+    }
+
+    /// Replaces the user's ranking of metadata scanners
+    pub async fn set_metadata_priority(&self, priority: MetadataPriority) -> DbResult<()> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        MetadataPriorityEntity::delete_many().exec(&txn).await?;
+        let models: Vec<metadata_priority::ActiveModel> = priority
+            .fields
+            .iter()
+            .flat_map(|(field, sources)| {
+                sources.iter().enumerate().map(|(position, source)| {
+                    metadata_priority::ActiveModel {
+                        field: ActiveValue::Set(field.key().into()),
+                        position: ActiveValue::Set(position as i32),
+                        source: ActiveValue::Set(source.clone()),
+                    }
+                })
+            })
+            .collect();
+        if !models.is_empty() {
+            MetadataPriorityEntity::insert_many(models)
+                .exec(&txn)
+                .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+/// Metadata scanner keys the user can rank
+pub fn get_metadata_sources(addons: &ExternalAddons) -> Vec<String> {
+    let mut keys: Vec<String> = addons
+        .get_metadata_scanner_keys()
+        .into_iter()
+        .map(String::from)
+        .collect();
+    keys.sort();
+    keys
+}
+
+/// Records `source` as the origin of fields of a game, keeping their lock flags
+async fn record_field_sources(
+    txn: &DatabaseTransaction,
+    game_id: i32,
+    fields: impl IntoIterator<Item = GameField>,
+    source: &str,
+) -> DbResult<()> {
+    let now = Utc::now();
+    let models: Vec<game_field_sources::ActiveModel> = fields
+        .into_iter()
+        .map(|field| game_field_sources::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            field: ActiveValue::Set(field.key().into()),
+            source: ActiveValue::Set(source.into()),
+            updated_at: ActiveValue::Set(now),
+            locked: ActiveValue::NotSet,
+        })
+        .collect();
+    if models.is_empty() {
+        return Ok(());
+    }
+    GameFieldSourcesEntity::insert_many(models)
+        .on_conflict(
+            OnConflict::columns([
+                game_field_sources::Column::GameId,
+                game_field_sources::Column::Field,
+            ])
+            .update_columns([
+                game_field_sources::Column::Source,
+                game_field_sources::Column::UpdatedAt,
+            ])
+            .to_owned(),
+        )
+        .exec(txn)
+        .await?;
+    Ok(())
+}
+
+async fn get_field_sources_of(
+    conn: &impl ConnectionTrait,
+    game_ids: Vec<i32>,
+) -> DbResult<HashMap<(i32, GameField), FieldSource>> {
+    Ok(GameFieldSourcesEntity::find()
+        .filter(game_field_sources::Column::GameId.is_in(game_ids))
+        .all(conn)
+        .await?
+        .into_iter()
+        .filter_map(|model| {
+            let game_id = model.game_id;
+            FieldSource::from_model(model).map(|source| ((game_id, source.field), source))
+        })
+        .collect())
+}
+
+/// Sets a column only for fields the refresh may change
+fn set_if<V: Into<sea_orm::Value>>(
+    allowed: &[GameField],
+    field: GameField,
+    value: V,
+) -> ActiveValue<V> {
+    if allowed.contains(&field) {
+        ActiveValue::Set(value)
+    } else {
+        ActiveValue::NotSet
+    }
+}
+
+/// Fetched metadata of a game awaiting the user's review
+#[derive(Debug, Clone)]
+pub struct GameMetadataDiff {
+    pub game_id: i32,
+    pub name: String,
+    pub changes: Vec<MetadataChange>,
+    /// The game with every change applied
+    proposed: GameData,
+    external_ids: Vec<ExternalId>,
+}
+
+impl LibraryStore {
+    /// Re-fetches metadata for games and lists how it differs from the stored values
+    ///
+    /// Only `sources` are asked, or every supporting scanner if empty. Locked fields are left out,
+    /// changes to user values or values from higher priority sources start out rejected.
+    pub async fn refresh_metadata(
+        &self,
+        addons: &ExternalAddons,
+        game_ids: Vec<i32>,
+        sources: Vec<String>,
+    ) -> DbResult<Vec<GameMetadataDiff>> {
+        let conn = &self.conn;
+        let games: Vec<GameData> = GameEntity::find()
+            .filter(Column::Id.is_in(game_ids))
+            .find_with_related(GenreEntity)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|(game, genres)| GameData {
+                genres: genres.into_iter().map(|v| v.into()).collect(),
+                ..game.into()
+            })
+            .collect();
+        let field_sources =
+            get_field_sources_of(conn, games.iter().map(|game| game.id).collect()).await?;
+        let priority = load_metadata_priority(conn).await?;
+        let mut metadatas = fetch_metadatas(addons, &games, &priority, &sources);
+
+        let mut diffs = Vec::new();
+        for game in games {
+            let Some(merged) = metadatas.remove(&game.get_owned_ref()) else {
+                continue;
+            };
+            let available = metadata_scanner_keys(addons, &game.library_type);
+            let available: Vec<&str> = available.iter().map(String::as_str).collect();
+            let field_sources: HashMap<GameField, (String, bool)> = merged
+                .sources
+                .into_iter()
+                .filter_map(|(field, source)| {
+                    let current = field_sources.get(&(game.id, field));
+                    if current.is_some_and(|current| current.locked) {
+                        return None;
+                    }
+                    let ranking = priority.ranked(field, &game.library_type, &available);
+                    let allowed = may_overwrite(current, &source, &ranking);
+                    Some((field, (source, allowed)))
+                })
+                .collect();
+            let external_ids: Vec<ExternalId> = merged.metadata.external_ids.clone().into();
+            let mut proposed = game.clone();
+            proposed.extend(merged.metadata);
+            let changes = diff_metadata(&game, &proposed, &field_sources);
+            if changes.is_empty() && external_ids.is_empty() {
+                continue;
+            }
+            diffs.push(GameMetadataDiff {
+                game_id: game.id,
+                name: game.name,
+                changes,
+                proposed,
+                external_ids,
+            });
+        }
+        Ok(diffs)
+    }
+
+    /// Writes the accepted changes of refreshed metadata in one transaction
+    pub async fn apply_metadata_diffs(&self, diffs: Vec<GameMetadataDiff>) -> DbResult<()> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        for diff in diffs {
+            let accepted: HashMap<GameField, String> = diff
+                .changes
+                .iter()
+                .filter(|change| change.accepted)
+                .map(|change| (change.field, change.source.clone()))
+                .collect();
+            let fields: Vec<GameField> = accepted.keys().copied().collect();
+            let game = diff.proposed;
+            let model = game::ActiveModel {
+                id: ActiveValue::Unchanged(diff.game_id),
+                description: set_if(&fields, GameField::Description, game.description),
+                icon_url: set_if(&fields, GameField::IconUrl, game.icon_url),
+                header_url: set_if(&fields, GameField::HeaderUrl, game.header_url),
+                cover_url: set_if(&fields, GameField::CoverUrl, game.cover_url),
+                release_date: set_if(&fields, GameField::ReleaseDate, game.release_date),
+                developers: set_if(&fields, GameField::Developers, game.developers.join("\n")),
+                publishers: set_if(&fields, GameField::Publishers, game.publishers.join("\n")),
+                ..Default::default()
+            };
+            if model.is_changed() {
+                GameEntity::update(model).exec(&txn).await?;
+            }
+            record_merged_sources(&txn, diff.game_id, &accepted).await?;
+            if let Some(genre_source) = accepted.get(&GameField::Genres) {
+                let genre_ids = ensure_genres(&txn, genre_source, &game.genres).await?;
+                GameGenresEntity::delete_many()
+                    .filter(game_genres::Column::GameId.eq(diff.game_id))
+                    .exec(&txn)
+                    .await?;
+                insert_game_genres(&txn, diff.game_id, &game.genres, &genre_ids).await?;
+            }
+            insert_external_ids(&txn, diff.game_id, &diff.external_ids).await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+/// Links a game to genres by name, creating user genres for names no scanner reported
+async fn set_genres_by_name(
+    txn: &DatabaseTransaction,
+    game_id: i32,
+    names: &[&str],
+) -> DbResult<()> {
+    let mut by_name: HashMap<String, i32> = HashMap::new();
+    for genre in GenreEntity::find()
+        .filter(genre::Column::Name.is_in(names.iter().copied()))
+        .order_by_asc(genre::Column::Id)
+        .all(txn)
+        .await?
+    {
+        by_name.entry(genre.name).or_insert(genre.id);
+    }
+    GameGenresEntity::delete_many()
+        .filter(game_genres::Column::GameId.eq(game_id))
+        .exec(txn)
+        .await?;
+    for name in names {
+        let genre_id = match by_name.get(*name) {
+            Some(id) => *id,
+            None => {
+                let res = GenreEntity::insert(genre::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    name: ActiveValue::Set(name.to_string()),
+                    metadata_id: ActiveValue::Set(name.to_string()),
+                    metadata_source: ActiveValue::Set(USER_GENRE_SOURCE.into()),
+                })
+                .exec(txn)
+                .await?;
+                by_name.insert(name.to_string(), res.last_insert_id);
+                res.last_insert_id
+            }
+        };
+        GameGenresEntity::insert(game_genres::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            genre_id: ActiveValue::Set(genre_id),
+        })
+        .on_conflict(
+            OnConflict::columns([game_genres::Column::GameId, game_genres::Column::GenreId])
+                .do_nothing()
+                .to_owned(),
+        )
+        .do_nothing()
+        .exec(txn)
+        .await?;
+    }
+    Ok(())
+}
+
+async fn find_launch_profile(
+    conn: &impl ConnectionTrait,
+    game_id: Option<i32>,
+) -> DbResult<Option<launch_profile::Model>> {
+    let column = launch_profile::Column::GameId;
+    LaunchProfileEntity::find()
+        .filter(match game_id {
+            Some(game_id) => column.eq(game_id),
+            None => column.is_null(),
+        })
+        .one(conn)
+        .await
+}
+
+/// Writes the global launch profile, or a game's own one; an empty profile is removed
+async fn save_launch_profile(
+    conn: &impl ConnectionTrait,
+    game_id: Option<i32>,
+    profile: LaunchProfile,
+) -> DbResult<()> {
+    let existing = find_launch_profile(conn, game_id).await?;
+    if profile.is_empty() {
+        if let Some(existing) = existing {
+            LaunchProfileEntity::delete_by_id(existing.id)
+                .exec(conn)
+                .await?;
+        }
+        return Ok(());
+    }
+    let model = launch_profile::ActiveModel {
+        id: existing.map_or(ActiveValue::NotSet, |e| ActiveValue::Unchanged(e.id)),
+        game_id: ActiveValue::Set(game_id),
+        pre_launch: ActiveValue::Set(profile.pre_launch),
+        post_exit: ActiveValue::Set(profile.post_exit),
+        wrappers: ActiveValue::Set(profile.wrappers.join("\n")),
+        env: ActiveValue::Set(
+            profile
+                .env
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join("\n"),
+        ),
+        working_dir: ActiveValue::Set(profile.working_dir),
+        post_launch_action: ActiveValue::Set(profile.post_launch_action.map(Into::into)),
+    };
+    model.save(conn).await?;
+    Ok(())
+}
+
+/// What the game editor needs besides the game itself
+#[derive(Debug, Clone, Default)]
+pub struct GameEditState {
+    pub manual_fields: HashSet<GameField>,
+    /// The game as its metadata scanners currently describe it, used to reset edited fields
+    pub metadata: Option<GameData>,
+    /// The game's own launch profile, without the global one
+    pub launch_profile: LaunchProfile,
+    /// Most recent first
+    pub launch_log: Vec<LaunchLogEntry>,
+}
+
+impl LibraryStore {
+    pub async fn get_game_edit_state(
+        &self,
+        addons: &ExternalAddons,
+        game_id: i32,
+    ) -> DbResult<GameEditState> {
+        let conn = &self.conn;
+        let Some(stored) = GameEntity::find_by_id(game_id).one(conn).await? else {
+            return Ok(GameEditState::default());
+        };
+        let manual_fields = get_field_sources_of(conn, vec![game_id])
+            .await?
+            .into_values()
+            .filter(FieldSource::is_user)
+            .map(|source| source.field)
+            .collect();
+        let game: GameData = stored.into();
+        let priority = load_metadata_priority(conn).await?;
+        let metadata = fetch_metadatas(addons, std::slice::from_ref(&game), &priority, &[])
+            .remove(&game.get_owned_ref())
+            .map(|merged| {
+                let mut game = game;
+                game.extend(merged.metadata);
+                game
+            });
+        Ok(GameEditState {
+            manual_fields,
+            metadata,
+            launch_profile: self.get_launch_profile(Some(game_id)).await?,
+            launch_log: self.get_launch_log(game_id).await?,
+        })
+    }
+
+    /// The global launch profile, or a game's own one
+    pub async fn get_launch_profile(&self, game_id: Option<i32>) -> DbResult<LaunchProfile> {
+        Ok(find_launch_profile(&self.conn, game_id)
+            .await?
+            .map(Into::into)
+            .unwrap_or_default())
+    }
+
+    /// The profile a game is launched with, see [`LaunchProfile::merged`]
+    pub async fn get_effective_launch_profile(&self, game_id: i32) -> DbResult<LaunchProfile> {
+        Ok(LaunchProfile::merged(
+            &self.get_launch_profile(None).await?,
+            &self.get_launch_profile(Some(game_id)).await?,
+        ))
+    }
+
+    /// Replaces the global launch profile, or a game's own one; an empty profile is removed
+    pub async fn set_launch_profile(
+        &self,
+        game_id: Option<i32>,
+        profile: LaunchProfile,
+    ) -> DbResult<()> {
+        let txn = self.conn.begin().await?;
+        save_launch_profile(&txn, game_id, profile).await?;
+        txn.commit().await
+    }
+
+    /// Records a step of launching a game, pruning entries past [`LOG_ENTRIES_KEPT`]
+    pub async fn add_launch_log(
+        &self,
+        game_id: i32,
+        stage: LaunchStage,
+        success: bool,
+        output: String,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+        LaunchLogEntity::insert(launch_log::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            at: ActiveValue::Set(Utc::now()),
+            stage: ActiveValue::Set(stage.into()),
+            success: ActiveValue::Set(success),
+            output: ActiveValue::Set(output),
+            ..Default::default()
+        })
+        .exec(conn)
+        .await?;
+        let stale: Vec<i32> = LaunchLogEntity::find()
+            .filter(launch_log::Column::GameId.eq(game_id))
+            .order_by_desc(launch_log::Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .skip(LOG_ENTRIES_KEPT)
+            .map(|entry| entry.id)
+            .collect();
+        if !stale.is_empty() {
+            LaunchLogEntity::delete_many()
+                .filter(launch_log::Column::Id.is_in(stale))
+                .exec(conn)
+                .await?;
+        }
+        Ok(())
+    }
+
+    /// Records a run of a game's process
+    pub async fn add_play_session(
+        &self,
+        game_id: i32,
+        started_at: DateTime<Utc>,
+        ended_at: DateTime<Utc>,
+    ) -> DbResult<()> {
+        PlaySessionEntity::insert(play_session::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            started_at: ActiveValue::Set(started_at),
+            ended_at: ActiveValue::Set(ended_at),
+            ..Default::default()
+        })
+        .exec(&self.conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Play sessions of a game, most recent first
+    pub async fn get_play_sessions(&self, game_id: i32) -> DbResult<Vec<PlaySession>> {
+        PlaySessionEntity::find()
+            .filter(play_session::Column::GameId.eq(game_id))
+            .order_by_desc(play_session::Column::StartedAt)
+            .all(&self.conn)
+            .await
+    }
+
+    /// Launch log of a game, most recent first
+    pub async fn get_launch_log(&self, game_id: i32) -> DbResult<Vec<LaunchLogEntry>> {
+        Ok(LaunchLogEntity::find()
+            .filter(launch_log::Column::GameId.eq(game_id))
+            .order_by_desc(launch_log::Column::Id)
+            .all(&self.conn)
+            .await?
+            .into_iter()
+            .map(Into::into)
+            .collect())
+    }
+
+    pub async fn update_game_played(&self, id: i32) -> DbResult<DateTime<Utc>> {
+        let conn = &self.conn;
+        let curr = Local::now().into();
+        GameEntity::update(game::ActiveModel {
+            id: ActiveValue::Unchanged(id),
+            last_played: ActiveValue::Set(Some(curr)),
+            ..Default::default()
+        })
+        .exec(conn)
+        .await?;
+        Ok(curr)
+    }
+    /// Saves a game from the editor, recording the user as the source of the fields edited by hand
+    ///
+    /// User values missing from `manual_fields` were reset to the library's metadata.
+    pub async fn update_game(
+        &self,
+        game: GameData,
+        manual_fields: HashSet<GameField>,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+        // merged games show the sum of all sources, only the primary's own share is stored on it
+        let merged_play_time = game
+            .sources
+            .iter()
+            .filter(|s| s.id != game.id)
+            .map(|s| s.play_time)
+            .sum::<chrono::Duration>();
+        let own_source = game.sources.iter().find(|s| s.id == game.id);
+        let install_status = own_source.map_or(game.install_status, |s| s.install_status);
+        let last_played = if manual_fields.contains(&GameField::LastPlayed) {
+            game.last_played
+        } else {
+            own_source.map_or(game.last_played, |s| s.last_played)
+        };
+        let txn = conn.begin().await?;
+        let reset_fields: Vec<GameField> = get_field_sources_of(&txn, vec![game.id])
+            .await?
+            .into_values()
+            .filter(|source| source.is_user() && !manual_fields.contains(&source.field))
+            .map(|source| source.field)
+            .collect();
+        record_field_sources(&txn, game.id, manual_fields, USER_SOURCE).await?;
+        record_field_sources(&txn, game.id, reset_fields, &game.library_type).await?;
+        let genres: Vec<&str> = game.genres.iter().map(|g| g.name.trim_end()).collect();
+        set_genres_by_name(&txn, game.id, &genres).await?;
+        GameEntity::update(game::ActiveModel {
+            id: ActiveValue::Set(game.id),
+            icon_url: ActiveValue::Set(game.icon_url),
+            name: ActiveValue::Set(game.name),
+            header_url: ActiveValue::Set(game.header_url),
+            library_type: ActiveValue::Set(game.library_type),
+            library_id: ActiveValue::Set(game.library_id),
+            account_id: ActiveValue::Set(game.account_id),
+            install_status: ActiveValue::Set(install_status.into()),
+            description: ActiveValue::Set(game.description),
+            cover_url: ActiveValue::Set(game.cover_url),
+            last_played: ActiveValue::Set(last_played),
+            play_time_secs: ActiveValue::Set((game.play_time - merged_play_time).num_seconds()),
+            release_date: ActiveValue::Set(game.release_date),
+            completion_status: ActiveValue::Set(game.completion_status.into()),
+            developers: ActiveValue::Set(game.developers.join("\n")),
+            publishers: ActiveValue::Set(game.publishers.join("\n")),
+            install_path: ActiveValue::Set(game.install_path),
+            notes: ActiveValue::Set(game.notes),
+            ..Default::default()
+        })
+        .exec(&txn)
+        .await?;
+        txn.commit().await?;
+        Ok(())
+    }
+
+    pub async fn get_field_sources(&self, game_id: i32) -> DbResult<Vec<FieldSource>> {
+        let conn = &self.conn;
+        let mut sources: Vec<FieldSource> = get_field_sources_of(conn, vec![game_id])
+            .await?
+            .into_values()
+            .collect();
+        sources.sort_by_key(|source| GameField::ALL.iter().position(|f| *f == source.field));
+        Ok(sources)
+    }
+
+    /// Locks or unlocks a field against syncs and metadata refreshes
+    pub async fn set_field_locked(
+        &self,
+        game_id: i32,
+        field: GameField,
+        locked: bool,
+    ) -> DbResult<()> {
+        let conn = &self.conn;
+        GameFieldSourcesEntity::insert(game_field_sources::ActiveModel {
+            game_id: ActiveValue::Set(game_id),
+            field: ActiveValue::Set(field.key().into()),
+            // fields set before sources were tracked have no known origin
+            source: ActiveValue::Set(String::new()),
+            updated_at: ActiveValue::Set(Utc::now()),
+            locked: ActiveValue::Set(locked),
+        })
+        .on_conflict(
+            OnConflict::columns([
+                game_field_sources::Column::GameId,
+                game_field_sources::Column::Field,
+            ])
+            .update_column(game_field_sources::Column::Locked)
+            .to_owned(),
+        )
+        .exec(conn)
+        .await?;
+        Ok(())
+    }
+
+    /// Reads the whole library for an export
+    pub async fn export_library(&self) -> DbResult<LibraryExport> {
+        let conn = &self.conn;
+        let mut genre_ids: HashMap<i32, Vec<i32>> = HashMap::new();
+        for link in GameGenresEntity::find().all(conn).await? {
+            genre_ids
+                .entry(link.game_id)
+                .or_default()
+                .push(link.genre_id);
+        }
+        let mut tag_ids: HashMap<i32, Vec<i32>> = HashMap::new();
+        for link in GameTagsEntity::find().all(conn).await? {
+            tag_ids.entry(link.game_id).or_default().push(link.tag_id);
+        }
+        let mut external_ids: HashMap<i32, BTreeMap<String, String>> = HashMap::new();
+        for ext in GameExternalIdsEntity::find().all(conn).await? {
+            external_ids
+                .entry(ext.game_id)
+                .or_default()
+                .insert(ext.source, ext.external_id);
+        }
+        let mut field_sources: HashMap<i32, Vec<ExportedFieldSource>> = HashMap::new();
+        for source in GameFieldSourcesEntity::find()
+            .order_by_asc(game_field_sources::Column::Field)
+            .all(conn)
+            .await?
+        {
+            field_sources
+                .entry(source.game_id)
+                .or_default()
+                .push(ExportedFieldSource {
+                    field: source.field,
+                    source: source.source,
+                    updated_at: source.updated_at,
+                    locked: source.locked,
+                });
+        }
+        let mut play_sessions: HashMap<i32, Vec<ExportedPlaySession>> = HashMap::new();
+        for session in PlaySessionEntity::find()
+            .order_by_asc(play_session::Column::StartedAt)
+            .all(conn)
+            .await?
+        {
+            play_sessions
+                .entry(session.game_id)
+                .or_default()
+                .push(ExportedPlaySession {
+                    started_at: session.started_at,
+                    ended_at: session.ended_at,
+                });
+        }
+        let mut global_launch_profile = LaunchProfile::default();
+        let mut launch_profiles: HashMap<i32, LaunchProfile> = HashMap::new();
+        for profile in LaunchProfileEntity::find().all(conn).await? {
+            match profile.game_id {
+                Some(game_id) => {
+                    launch_profiles.insert(game_id, profile.into());
+                }
+                None => global_launch_profile = profile.into(),
+            }
+        }
+        let games = GameEntity::find()
+            .order_by_asc(Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|game| {
+                let mut genres = genre_ids.remove(&game.id).unwrap_or_default();
+                genres.sort();
+                let mut tags = tag_ids.remove(&game.id).unwrap_or_default();
+                tags.sort();
+                ExportedGame {
+                    id: game.id,
+                    genre_ids: genres,
+                    tag_ids: tags,
+                    external_ids: external_ids.remove(&game.id).unwrap_or_default(),
+                    field_sources: field_sources.remove(&game.id).unwrap_or_default(),
+                    play_sessions: play_sessions.remove(&game.id).unwrap_or_default(),
+                    launch_profile: launch_profiles.remove(&game.id).unwrap_or_default(),
+                    library_type: game.library_type,
+                    library_id: game.library_id,
+                    account_id: game.account_id,
+                    name: game.name,
+                    description: game.description,
+                    install_status: game.install_status.into(),
+                    completion_status: game.completion_status.into(),
+                    play_time_secs: game.play_time_secs,
+                    release_date: game.release_date,
+                    last_played: game.last_played,
+                    icon_url: game.icon_url,
+                    header_url: game.header_url,
+                    cover_url: game.cover_url,
+                    developers: game.developers.lines().map(String::from).collect(),
+                    publishers: game.publishers.lines().map(String::from).collect(),
+                    install_path: game.install_path,
+                    favourite: game.favourite,
+                    hidden: game.hidden,
+                    merged_into: game.merged_into,
+                    preferred_source_id: game.preferred_source_id,
+                    notes: game.notes,
+                }
+            })
+            .collect();
+        let genres = GenreEntity::find()
+            .order_by_asc(genre::Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|genre| ExportedGenre {
+                id: genre.id,
+                name: genre.name,
+                metadata_source: genre.metadata_source,
+                metadata_id: genre.metadata_id,
+            })
+            .collect();
+        let tags = TagEntity::find()
+            .order_by_asc(tag::Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|tag| ExportedTag {
+                id: tag.id,
+                name: tag.name,
+                color: tag.color,
+            })
+            .collect();
+        let collections = CollectionEntity::find()
+            .order_by_asc(collection::Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|collection| ExportedCollection {
+                name: collection.name,
+                filter: collection.filter,
+            })
+            .collect();
+        let accounts = AccountEntity::find()
+            .order_by_asc(account::Column::Id)
+            .all(conn)
+            .await?
+            .into_iter()
+            .map(|account| ExportedAccount {
+                library_type: account.library_type,
+                account_id: account.account_id,
+                name: account.name,
+            })
+            .collect();
+        let metadata_priority = load_metadata_priority(conn)
+            .await?
+            .fields
+            .into_iter()
+            .map(|(field, sources)| (field.key().to_string(), sources))
+            .collect();
+        Ok(LibraryExport {
+            version: EXPORT_VERSION,
+            games,
+            genres,
+            tags,
+            collections,
+            accounts,
+            metadata_priority,
+            global_launch_profile,
+        })
+    }
+
+    /// Writes the library to `path`, a `.gami` archive also gets settings and addon configs
+    pub async fn export_to_file(&self, path: PathBuf, format: ExportFormat) -> std::io::Result<()> {
+        let library = self.export_library().await.map_err(std::io::Error::other)?;
+        let bytes = match format {
+            ExportFormat::Json => export::to_json(&library).into_bytes(),
+            ExportFormat::Csv => export::to_csv(&library).into_bytes(),
+            ExportFormat::Gami => export::to_gami(&Backup {
+                library,
+                files: export::collect_backup_files()?,
+            }),
+        };
+        std::fs::write(path, bytes)
+    }
+
+    /// Imports the library of a backup and restores its files, overwriting them only when replacing
+    ///
+    /// The library is snapshotted first when it gets replaced.
+    pub async fn import_backup(&self, backup: Backup, mode: ImportMode) -> DbResult<()> {
+        if mode == ImportMode::Replace {
+            self.snapshot_before("import").await?;
+        }
+        self.import_library(backup.library, mode).await?;
+        export::restore_backup_files(&backup.files, mode == ImportMode::Replace)
+            .map_err(|err| DbErr::Custom(format!("could not restore the backup files: {}", err)))
+    }
+}
+
+/// What an import would change
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ImportPreview {
+    pub new_games: usize,
+    /// Games already in the library that the import overwrites
+    pub updated_games: usize,
+    /// Games deleted by a replacing import
+    pub removed_games: usize,
+    pub genres: usize,
+    pub tags: usize,
+    pub collections: usize,
+    /// Games with their own launch profile, plus the global one if the export has it
+    pub launch_profiles: usize,
+}
+
+/// IDs of current games by library entry
+async fn game_ids_by_ref(
+    conn: &impl ConnectionTrait,
+) -> DbResult<HashMap<(String, String, String), i32>> {
+    Ok(GameEntity::find()
+        .all(conn)
+        .await?
+        .into_iter()
+        .map(|game| {
+            (
+                (game.library_type, game.account_id, game.library_id),
+                game.id,
+            )
+        })
+        .collect())
+}
+
+impl LibraryStore {
+    pub async fn preview_import(
+        &self,
+        library: &LibraryExport,
+        mode: ImportMode,
+    ) -> DbResult<ImportPreview> {
+        let conn = &self.conn;
+        let existing = game_ids_by_ref(conn).await?;
+        let updated_games = match mode {
+            ImportMode::Merge => library
+                .games
+                .iter()
+                .filter(|game| {
+                    existing.contains_key(&(
+                        game.library_type.clone(),
+                        game.account_id.clone(),
+                        game.library_id.clone(),
+                    ))
+                })
+                .count(),
+            ImportMode::Replace => 0,
+        };
+        Ok(ImportPreview {
+            new_games: library.games.len() - updated_games,
+            updated_games,
+            removed_games: match mode {
+                ImportMode::Merge => 0,
+                ImportMode::Replace => existing.len(),
+            },
+            genres: library.genres.len(),
+            tags: library.tags.len(),
+            collections: library.collections.len(),
+            launch_profiles: library
+                .games
+                .iter()
+                .filter(|game| !game.launch_profile.is_empty())
+                .count()
+                + usize::from(!library.global_launch_profile.is_empty()),
+        })
+    }
+
+    /// Writes an export into the library in one transaction
+    ///
+    /// Merging matches games by library entry, genres by metadata ID, tags and collections by name
+    /// and play sessions by start time, a game's launch profile is only replaced by a non-empty one.
+    /// Replacing deletes the library first and keeps the exported IDs.
+    pub async fn import_library(&self, library: LibraryExport, mode: ImportMode) -> DbResult<()> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        let keep_ids = mode == ImportMode::Replace;
+        if keep_ids {
+            GameGenresEntity::delete_many().exec(&txn).await?;
+            GameTagsEntity::delete_many().exec(&txn).await?;
+            GameExternalIdsEntity::delete_many().exec(&txn).await?;
+            GameFieldSourcesEntity::delete_many().exec(&txn).await?;
+            PlaySessionEntity::delete_many().exec(&txn).await?;
+            // imported games reuse the IDs, they must not inherit the old games' scripts
+            LaunchProfileEntity::delete_many()
+                .filter(launch_profile::Column::GameId.is_not_null())
+                .exec(&txn)
+                .await?;
+            LaunchLogEntity::delete_many().exec(&txn).await?;
+            GameEntity::delete_many().exec(&txn).await?;
+            GenreEntity::delete_many().exec(&txn).await?;
+            TagEntity::delete_many().exec(&txn).await?;
+            CollectionEntity::delete_many().exec(&txn).await?;
+            AccountEntity::delete_many().exec(&txn).await?;
+            MetadataPriorityEntity::delete_many().exec(&txn).await?;
+        }
+        let id_value = |id: i32| {
+            if keep_ids {
+                ActiveValue::Set(id)
+            } else {
+                ActiveValue::NotSet
+            }
+        };
+
+        let mut genre_ids: HashMap<i32, i32> = HashMap::new();
+        for g in &library.genres {
+            let existing = GenreEntity::find()
+                .filter(genre::Column::MetadataSource.eq(&g.metadata_source))
+                .filter(genre::Column::MetadataId.eq(&g.metadata_id))
+                .one(&txn)
+                .await?;
+            let id = match existing {
+                Some(existing) => existing.id,
+                None => {
+                    GenreEntity::insert(genre::ActiveModel {
+                        id: id_value(g.id),
+                        name: ActiveValue::Set(g.name.clone()),
+                        metadata_source: ActiveValue::Set(g.metadata_source.clone()),
+                        metadata_id: ActiveValue::Set(g.metadata_id.clone()),
+                    })
+                    .exec(&txn)
+                    .await?
+                    .last_insert_id
+                }
+            };
+            genre_ids.insert(g.id, id);
+        }
+        let mut tag_ids: HashMap<i32, i32> = HashMap::new();
+        for t in &library.tags {
+            let existing = TagEntity::find()
+                .filter(tag::Column::Name.eq(&t.name))
+                .one(&txn)
+                .await?;
+            let id = match existing {
+                Some(existing) => existing.id,
+                None => {
+                    TagEntity::insert(tag::ActiveModel {
+                        id: id_value(t.id),
+                        name: ActiveValue::Set(t.name.clone()),
+                        color: ActiveValue::Set(t.color.clone()),
+                    })
+                    .exec(&txn)
+                    .await?
+                    .last_insert_id
+                }
+            };
+            tag_ids.insert(t.id, id);
+        }
+        for c in &library.collections {
+            let exists = CollectionEntity::find()
+                .filter(collection::Column::Name.eq(&c.name))
+                .one(&txn)
+                .await?
+                .is_some();
+            if !exists {
+                CollectionEntity::insert(collection::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    name: ActiveValue::Set(c.name.clone()),
+                    filter: ActiveValue::Set(c.filter.clone()),
+                })
+                .exec(&txn)
+                .await?;
+            }
+        }
+        if !library.accounts.is_empty() {
+            AccountEntity::insert_many(library.accounts.iter().map(|a| account::ActiveModel {
+                id: ActiveValue::NotSet,
+                library_type: ActiveValue::Set(a.library_type.clone()),
+                account_id: ActiveValue::Set(a.account_id.clone()),
+                name: ActiveValue::Set(a.name.clone()),
+            }))
+            .on_conflict(
+                OnConflict::columns([account::Column::LibraryType, account::Column::AccountId])
+                    .update_column(account::Column::Name)
+                    .to_owned(),
+            )
+            .exec(&txn)
+            .await?;
+        }
+
+        let existing = game_ids_by_ref(&txn).await?;
+        let mut game_ids: HashMap<i32, i32> = HashMap::new();
+        for g in &library.games {
+            let current = existing
+                .get(&(
+                    g.library_type.clone(),
+                    g.account_id.clone(),
+                    g.library_id.clone(),
+                ))
+                .copied();
+            let mut model = game::ActiveModel {
+                id: id_value(g.id),
+                name: ActiveValue::Set(g.name.clone()),
+                description: ActiveValue::Set(g.description.clone()),
+                play_time_secs: ActiveValue::Set(g.play_time_secs),
+                install_status: ActiveValue::Set(g.install_status.into()),
+                release_date: ActiveValue::Set(g.release_date),
+                last_played: ActiveValue::Set(g.last_played),
+                icon_url: ActiveValue::Set(g.icon_url.clone()),
+                header_url: ActiveValue::Set(g.header_url.clone()),
+                cover_url: ActiveValue::Set(g.cover_url.clone()),
+                library_type: ActiveValue::Set(g.library_type.clone()),
+                library_id: ActiveValue::Set(g.library_id.clone()),
+                account_id: ActiveValue::Set(g.account_id.clone()),
+                completion_status: ActiveValue::Set(g.completion_status.into()),
+                merged_into: ActiveValue::NotSet,
+                preferred_source_id: ActiveValue::NotSet,
+                favourite: ActiveValue::Set(g.favourite),
+                hidden: ActiveValue::Set(g.hidden),
+                developers: ActiveValue::Set(g.developers.join("\n")),
+                publishers: ActiveValue::Set(g.publishers.join("\n")),
+                install_path: ActiveValue::Set(g.install_path.clone()),
+                // exports without notes, like spreadsheets, leave the user's own ones alone
+                notes: if g.notes.is_empty() {
+                    ActiveValue::NotSet
+                } else {
+                    ActiveValue::Set(g.notes.clone())
+                },
+            };
+            let id = match current {
+                Some(id) => {
+                    model.id = ActiveValue::Unchanged(id);
+                    GameEntity::update(model).exec(&txn).await?;
+                    id
+                }
+                None => GameEntity::insert(model).exec(&txn).await?.last_insert_id,
+            };
+            game_ids.insert(g.id, id);
+        }
+        for g in &library.games {
+            let id = game_ids[&g.id];
+            GameEntity::update(game::ActiveModel {
+                id: ActiveValue::Unchanged(id),
+                merged_into: ActiveValue::Set(g.merged_into.map(|other| game_ids[&other])),
+                preferred_source_id: ActiveValue::Set(
+                    g.preferred_source_id.map(|other| game_ids[&other]),
+                ),
+                ..Default::default()
+            })
+            .exec(&txn)
+            .await?;
+            let genres: Vec<game_genres::ActiveModel> = g
+                .genre_ids
+                .iter()
+                .map(|genre_id| game_genres::ActiveModel {
+                    game_id: ActiveValue::Set(id),
+                    genre_id: ActiveValue::Set(genre_ids[genre_id]),
+                })
+                .collect();
+            if !genres.is_empty() {
+                GameGenresEntity::insert_many(genres)
+                    .on_conflict(
+                        OnConflict::columns([
+                            game_genres::Column::GameId,
+                            game_genres::Column::GenreId,
+                        ])
+                        .do_nothing()
+                        .to_owned(),
+                    )
+                    .do_nothing()
+                    .exec(&txn)
+                    .await?;
+            }
+            let tags: Vec<game_tags::ActiveModel> = g
+                .tag_ids
+                .iter()
+                .map(|tag_id| game_tags::ActiveModel {
+                    game_id: ActiveValue::Set(id),
+                    tag_id: ActiveValue::Set(tag_ids[tag_id]),
+                })
+                .collect();
+            if !tags.is_empty() {
+                GameTagsEntity::insert_many(tags)
+                    .on_conflict(
+                        OnConflict::columns([game_tags::Column::GameId, game_tags::Column::TagId])
+                            .do_nothing()
+                            .to_owned(),
+                    )
+                    .do_nothing()
+                    .exec(&txn)
+                    .await?;
+            }
+            let external_ids: Vec<ExternalId> = g
+                .external_ids
+                .iter()
+                .map(|(source, id)| ExternalId {
+                    source: source.clone().into(),
+                    id: id.clone().into(),
+                })
+                .collect();
+            insert_external_ids(&txn, id, &external_ids).await?;
+            if !g.field_sources.is_empty() {
+                GameFieldSourcesEntity::insert_many(g.field_sources.iter().map(|source| {
+                    game_field_sources::ActiveModel {
+                        game_id: ActiveValue::Set(id),
+                        field: ActiveValue::Set(source.field.clone()),
+                        source: ActiveValue::Set(source.source.clone()),
+                        updated_at: ActiveValue::Set(source.updated_at),
+                        locked: ActiveValue::Set(source.locked),
+                    }
+                }))
+                .on_conflict(
+                    OnConflict::columns([
+                        game_field_sources::Column::GameId,
+                        game_field_sources::Column::Field,
+                    ])
+                    .update_columns([
+                        game_field_sources::Column::Source,
+                        game_field_sources::Column::UpdatedAt,
+                        game_field_sources::Column::Locked,
+                    ])
+                    .to_owned(),
+                )
+                .exec(&txn)
+                .await?;
+            }
+            let known: HashSet<DateTime<Utc>> = PlaySessionEntity::find()
+                .filter(play_session::Column::GameId.eq(id))
+                .all(&txn)
+                .await?
+                .into_iter()
+                .map(|session| session.started_at)
+                .collect();
+            let sessions: Vec<play_session::ActiveModel> = g
+                .play_sessions
+                .iter()
+                .filter(|session| !known.contains(&session.started_at))
+                .map(|session| play_session::ActiveModel {
+                    id: ActiveValue::NotSet,
+                    game_id: ActiveValue::Set(id),
+                    started_at: ActiveValue::Set(session.started_at),
+                    ended_at: ActiveValue::Set(session.ended_at),
+                })
+                .collect();
+            if !sessions.is_empty() {
+                PlaySessionEntity::insert_many(sessions).exec(&txn).await?;
+            }
+            if !g.launch_profile.is_empty() {
+                save_launch_profile(&txn, Some(id), g.launch_profile.clone()).await?;
+            }
+        }
+        if keep_ids || !library.global_launch_profile.is_empty() {
+            save_launch_profile(&txn, None, library.global_launch_profile).await?;
+        }
+
+        for (field, sources) in &library.metadata_priority {
+            MetadataPriorityEntity::delete_many()
+                .filter(metadata_priority::Column::Field.eq(field))
+                .exec(&txn)
+                .await?;
+            if sources.is_empty() {
+                continue;
+            }
+            MetadataPriorityEntity::insert_many(sources.iter().enumerate().map(
+                |(position, source)| metadata_priority::ActiveModel {
+                    field: ActiveValue::Set(field.clone()),
+                    position: ActiveValue::Set(position as i32),
+                    source: ActiveValue::Set(source.clone()),
+                },
+            ))
+            .exec(&txn)
+            .await?;
+        }
+        txn.commit().await?;
+        Ok(())
+    }
+}
+
+/// What importing from another launcher changed
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct LauncherImportSummary {
+    pub updated: usize,
+    pub added: usize,
+}
+
+/// IDs of the rows games read from another launcher match, by library type and ID
+///
+/// Other launchers don't know Gami's accounts, so the oldest row of any account matches.
+async fn rows_by_library_id(
+    conn: &impl ConnectionTrait,
+) -> DbResult<HashMap<(String, String), game::Model>> {
+    let mut rows = HashMap::new();
+    for game in GameEntity::find()
+        .order_by_asc(Column::Id)
+        .all(conn)
+        .await?
+    {
+        rows.entry((game.library_type.clone(), game.library_id.clone()))
+            .or_insert(game);
+    }
+    Ok(rows)
+}
+
+impl LibraryStore {
+    /// The library game each game read from another launcher matches, if any
+    pub async fn match_launcher_games(&self, games: &[ImportedGame]) -> DbResult<Vec<Option<i32>>> {
+        let conn = &self.conn;
+        let rows = rows_by_library_id(conn).await?;
+        Ok(games
+            .iter()
+            .map(|imported| {
+                rows.get(&(
+                    imported.game.library_type.clone(),
+                    imported.game.library_id.clone(),
+                ))
+                .map(|row| row.merged_into.unwrap_or(row.id))
+            })
+            .collect())
+    }
+
+    /// Takes completion statuses, play time and tags of games read from another launcher
+    ///
+    /// Play time and last played only grow, favourite and hidden flags are only set.
+    /// Games without a match are added when `add_missing` is set.
+    pub async fn import_launcher_games(
+        &self,
+        games: Vec<ImportedGame>,
+        add_missing: bool,
+    ) -> DbResult<LauncherImportSummary> {
+        let conn = &self.conn;
+        let txn = conn.begin().await?;
+        let rows = rows_by_library_id(&txn).await?;
+        let mut tag_ids: HashMap<String, i32> = TagEntity::find()
+            .all(&txn)
+            .await?
+            .into_iter()
+            .map(|tag| (tag.name, tag.id))
+            .collect();
+        let mut summary = LauncherImportSummary::default();
+        for imported in games {
+            let game = imported.game;
+            let play_time_secs = game.play_time.num_seconds();
+            let game_id = match rows.get(&(game.library_type.clone(), game.library_id.clone())) {
+                Some(row) => {
+                    // merged games show the sum of their sources, so play time stays on the source
+                    let mut source = game::ActiveModel {
+                        id: ActiveValue::Unchanged(row.id),
+                        ..Default::default()
+                    };
+                    if play_time_secs > row.play_time_secs {
+                        source.play_time_secs = ActiveValue::Set(play_time_secs);
+                    }
+                    if game.last_played > row.last_played {
+                        source.last_played = ActiveValue::Set(game.last_played);
+                    }
+                    if source.is_changed() {
+                        GameEntity::update(source).exec(&txn).await?;
+                    }
+                    let primary_id = row.merged_into.unwrap_or(row.id);
+                    let mut primary = game::ActiveModel {
+                        id: ActiveValue::Unchanged(primary_id),
+                        ..Default::default()
+                    };
+                    if let Some(status) = imported.completion_status {
+                        primary.completion_status = ActiveValue::Set(status.into());
+                    }
+                    if game.favourite {
+                        primary.favourite = ActiveValue::Set(true);
+                    }
+                    if game.hidden {
+                        primary.hidden = ActiveValue::Set(true);
+                    }
+                    if primary.is_changed() {
+                        GameEntity::update(primary).exec(&txn).await?;
+                    }
+                    summary.updated += 1;
+                    primary_id
+                }
+                None if add_missing => {
+                    let res = GameEntity::insert(game::ActiveModel {
+                        library_type: ActiveValue::Set(game.library_type),
+                        library_id: ActiveValue::Set(game.library_id),
+                        account_id: ActiveValue::Set(game.account_id),
+                        name: ActiveValue::Set(game.name),
+                        description: ActiveValue::Set(game.description),
+                        install_status: ActiveValue::Set(GameInstallStatus::InLibrary.into()),
+                        completion_status: ActiveValue::Set(
+                            imported.completion_status.unwrap_or_default().into(),
+                        ),
+                        play_time_secs: ActiveValue::Set(play_time_secs),
+                        last_played: ActiveValue::Set(game.last_played),
+                        header_url: ActiveValue::Set(game.header_url),
+                        cover_url: ActiveValue::Set(game.cover_url),
+                        release_date: ActiveValue::Set(game.release_date),
+                        developers: ActiveValue::Set(game.developers.join("\n")),
+                        publishers: ActiveValue::Set(game.publishers.join("\n")),
+                        favourite: ActiveValue::Set(game.favourite),
+                        hidden: ActiveValue::Set(game.hidden),
+                        ..Default::default()
+                    })
+                    .exec(&txn)
+                    .await?;
+                    summary.added += 1;
+                    res.last_insert_id
+                }
+                None => continue,
+            };
+            for name in imported.tags {
+                let tag_id = match tag_ids.get(&name) {
+                    Some(id) => *id,
+                    None => {
+                        let id = TagEntity::insert(tag::ActiveModel {
+                            id: ActiveValue::NotSet,
+                            name: ActiveValue::Set(name.clone()),
+                            color: ActiveValue::Set("#808080".into()),
+                        })
+                        .exec(&txn)
+                        .await?
+                        .last_insert_id;
+                        tag_ids.insert(name, id);
+                        id
+                    }
+                };
+                GameTagsEntity::insert(game_tags::ActiveModel {
+                    game_id: ActiveValue::Set(game_id),
+                    tag_id: ActiveValue::Set(tag_id),
+                })
+                .on_conflict(
+                    OnConflict::columns([game_tags::Column::GameId, game_tags::Column::TagId])
+                        .do_nothing()
+                        .to_owned(),
+                )
+                .do_nothing()
+                .exec(&txn)
+                .await?;
+            }
+        }
+        txn.commit().await?;
+        log::info!(
+            "Imported launcher library: {} games updated, {} added",
+            summary.updated,
+            summary.added
+        );
+        Ok(summary)
+    }
 }
@@ -0,0 +1,97 @@
+/// FTS5 columns of `games_fts` and the qualifiers that select them
+const QUALIFIERS: &[(&str, &str)] = &[
+    ("name", "name"),
+    ("desc", "description"),
+    ("description", "description"),
+    ("genre", "genres"),
+    ("genres", "genres"),
+    ("dev", "developers"),
+    ("developer", "developers"),
+    ("developers", "developers"),
+];
+
+/// Ranks matches with bm25, weighting names above genres and developers above descriptions
+pub(crate) const RANK_QUERY: &str =
+    "SELECT rowid, bm25(games_fts, 10.0, 1.0, 4.0, 4.0) FROM games_fts WHERE games_fts MATCH ?";
+
+fn split_terms(search: &str) -> Vec<String> {
+    let mut terms = Vec::new();
+    let mut curr = String::new();
+    let mut quoted = false;
+    for c in search.chars() {
+        if c == '"' {
+            quoted = !quoted;
+            curr.push(c);
+        } else if c.is_whitespace() && !quoted {
+            if !curr.is_empty() {
+                terms.push(std::mem::take(&mut curr));
+            }
+        } else {
+            curr.push(c);
+        }
+    }
+    if !curr.is_empty() {
+        terms.push(curr);
+    }
+    terms
+}
+
+/// Turns user search input into an FTS5 `MATCH` expression
+///
+/// Every term is a prefix match and all terms must match. Terms may be quoted phrases and may be
+/// qualified with a column, e.g. `genre:rpg dev:valve "half life"`.
+pub fn to_fts_query(search: &str) -> Option<String> {
+    let terms: Vec<String> = split_terms(search)
+        .into_iter()
+        .filter_map(|term| {
+            let (column, text) = match term.split_once(':') {
+                Some((qualifier, rest)) if !qualifier.contains('"') => QUALIFIERS
+                    .iter()
+                    .find(|(name, _)| name.eq_ignore_ascii_case(qualifier))
+                    .map(|(_, column)| (Some(*column), rest))
+                    .unwrap_or((None, term.as_str())),
+                _ => (None, term.as_str()),
+            };
+            let text = text.replace('"', " ");
+            let text = text.trim();
+            if !text.chars().any(char::is_alphanumeric) {
+                return None;
+            }
+            Some(match column {
+                Some(column) => format!("{} : \"{}\"*", column, text),
+                None => format!("\"{}\"*", text),
+            })
+        })
+        .collect();
+    if terms.is_empty() {
+        None
+    } else {
+        Some(terms.join(" "))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_plain_terms() {
+        assert_eq!(
+            to_fts_query("witcher  3").as_deref(),
+            Some("\"witcher\"* \"3\"*")
+        );
+        assert_eq!(to_fts_query(" - ").as_deref(), None);
+    }
+
+    #[test]
+    fn test_qualifiers_and_phrases() {
+        assert_eq!(
+            to_fts_query("genre:rpg DEV:\"cd projekt\" \"half life\"").as_deref(),
+            Some("genres : \"rpg\"* developers : \"cd projekt\"* \"half life\"*")
+        );
+        assert_eq!(
+            to_fts_query("Half-Life: Alyx").as_deref(),
+            Some("\"Half-Life:\"* \"Alyx\"*")
+        );
+    }
+}
@@ -0,0 +1,35 @@
+use gami_sdk::TagData;
+use sea_orm::entity::prelude::*;
+
+#[derive(Clone, Debug, PartialEq, Eq, DeriveEntityModel)]
+#[sea_orm(table_name = "tags")]
+pub struct Model {
+    #[sea_orm(primary_key)]
+    pub id: i32,
+    pub name: String,
+    pub color: String,
+}
+#[derive(Copy, Clone, Debug, EnumIter, DeriveRelation)]
+pub enum Relation {
+    #[sea_orm(has_many = "super::game_tags::Entity")]
+    GameTags,
+}
+impl Related<super::game::Entity> for Entity {
+    fn to() -> RelationDef {
+        super::game_tags::Relation::Game.def()
+    }
+    fn via() -> Option<RelationDef> {
+        Some(super::game_tags::Relation::Tag.def().rev())
+    }
+}
+impl ActiveModelBehavior for ActiveModel {}
+
+impl From<Model> for TagData {
+    fn from(tag: Model) -> Self {
+        TagData {
+            id: tag.id,
+            name: tag.name,
+            color: tag.color,
+        }
+    }
+}
@@ -0,0 +1,286 @@
+use std::collections::{BTreeMap, HashMap};
+
+/// Minimum normalized name similarity for two games to be considered duplicates
+pub const NAME_SIMILARITY_THRESHOLD: f32 = 0.9;
+
+/// What a duplicate check knows about a game
+#[derive(Debug, Clone)]
+pub struct DedupeCandidate {
+    pub id: i32,
+    pub name: String,
+    /// `(source, id)` pairs identifying the game, including its own library type and ID
+    pub external_ids: Vec<(String, String)>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq)]
+pub enum DuplicateReason {
+    ExternalId,
+    Name { similarity: f32 },
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct CandidateGroup {
+    pub ids: Vec<i32>,
+    pub reason: DuplicateReason,
+}
+
+/// Lowercases a name and strips symbols, articles and common edition suffixes
+pub fn normalize_name(name: &str) -> String {
+    let cleaned: String = name
+        .chars()
+        .map(|c| {
+            if c.is_alphanumeric() {
+                c.to_ascii_lowercase()
+            } else {
+                ' '
+            }
+        })
+        .collect();
+    let mut words: Vec<&str> = cleaned.split_whitespace().collect();
+    if words.first() == Some(&"the") {
+        words.remove(0);
+    }
+    while words.len() > 1
+        && matches!(
+            words.last(),
+            Some(&"edition" | &"goty" | &"remastered" | &"definitive" | &"complete")
+        )
+    {
+        words.pop();
+    }
+    words.join(" ")
+}
+
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut prev: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut curr = vec![i + 1; b.len() + 1];
+        for (j, cb) in b.iter().enumerate() {
+            let cost = if ca == *cb { 0 } else { 1 };
+            curr[j + 1] = (prev[j] + cost).min(prev[j + 1] + 1).min(curr[j] + 1);
+        }
+        prev = curr;
+    }
+    prev[b.len()]
+}
+
+/// Whether a normalized name token is a number or a roman numeral up to 39, e.g. a sequel number
+fn is_number_token(token: &str) -> bool {
+    if token.chars().all(|c| c.is_ascii_digit()) {
+        return !token.is_empty();
+    }
+    let units = token.trim_start_matches('x');
+    let tens = token.len() - units.len();
+    tens <= 3
+        && match units {
+            "" => tens > 0,
+            _ => matches!(
+                units,
+                "i" | "ii" | "iii" | "iv" | "v" | "vi" | "vii" | "viii" | "ix"
+            ),
+        }
+}
+
+fn number_tokens(name: &str) -> Vec<&str> {
+    name.split_whitespace()
+        .filter(|token| is_number_token(token))
+        .collect()
+}
+
+/// Similarity of two normalized names between 0 (different) and 1 (equal)
+///
+/// Names whose number or roman numeral tokens differ are never similar, so sequels like
+/// "Portal" and "Portal 2" don't match.
+pub fn name_similarity(a: &str, b: &str) -> f32 {
+    if number_tokens(a) != number_tokens(b) {
+        return 0.0;
+    }
+    let len = a.chars().count().max(b.chars().count());
+    if len == 0 {
+        return 1.0;
+    }
+    1.0 - levenshtein(a, b) as f32 / len as f32
+}
+
+struct DisjointSet {
+    parents: Vec<usize>,
+}
+impl DisjointSet {
+    fn new(len: usize) -> Self {
+        Self {
+            parents: (0..len).collect(),
+        }
+    }
+    fn find(&mut self, index: usize) -> usize {
+        let parent = self.parents[index];
+        if parent == index {
+            return index;
+        }
+        let root = self.find(parent);
+        self.parents[index] = root;
+        root
+    }
+    fn union(&mut self, a: usize, b: usize) {
+        let (a, b) = (self.find(a), self.find(b));
+        if a != b {
+            self.parents[b] = a;
+        }
+    }
+}
+
+/// Groups games that share an external ID or have near-identical names
+///
+/// Fuzzy name matching only compares names starting with the same few characters to stay fast
+/// on large libraries.
+pub fn find_duplicate_groups(candidates: &[DedupeCandidate]) -> Vec<CandidateGroup> {
+    let mut set = DisjointSet::new(candidates.len());
+    let mut linked_by_id = vec![false; candidates.len()];
+    let mut best_similarity = vec![1.0f32; candidates.len()];
+
+    let mut by_external_id: HashMap<&(String, String), usize> = HashMap::new();
+    for (index, candidate) in candidates.iter().enumerate() {
+        for external_id in &candidate.external_ids {
+            if let Some(&other) = by_external_id.get(external_id) {
+                set.union(other, index);
+                linked_by_id[index] = true;
+                linked_by_id[other] = true;
+            } else {
+                by_external_id.insert(external_id, index);
+            }
+        }
+    }
+
+    let names: Vec<String> = candidates.iter().map(|c| normalize_name(&c.name)).collect();
+    let mut buckets: BTreeMap<String, Vec<usize>> = BTreeMap::new();
+    for (index, name) in names.iter().enumerate() {
+        if !name.is_empty() {
+            buckets
+                .entry(name.chars().take(3).collect())
+                .or_default()
+                .push(index);
+        }
+    }
+    for bucket in buckets.values() {
+        for (i, &a) in bucket.iter().enumerate() {
+            for &b in &bucket[i + 1..] {
+                let similarity = name_similarity(&names[a], &names[b]);
+                if similarity >= NAME_SIMILARITY_THRESHOLD {
+                    set.union(a, b);
+                    best_similarity[a] = best_similarity[a].min(similarity);
+                    best_similarity[b] = best_similarity[b].min(similarity);
+                }
+            }
+        }
+    }
+
+    let mut groups: BTreeMap<usize, Vec<usize>> = BTreeMap::new();
+    for index in 0..candidates.len() {
+        groups.entry(set.find(index)).or_default().push(index);
+    }
+    groups
+        .into_values()
+        .filter(|members| members.len() > 1)
+        .map(|members| CandidateGroup {
+            reason: if members.iter().any(|&m| linked_by_id[m]) {
+                DuplicateReason::ExternalId
+            } else {
+                DuplicateReason::Name {
+                    similarity: members
+                        .iter()
+                        .map(|&m| best_similarity[m])
+                        .fold(1.0, f32::min),
+                }
+            },
+            ids: members.into_iter().map(|m| candidates[m].id).collect(),
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn candidate(id: i32, name: &str, external_ids: &[(&str, &str)]) -> DedupeCandidate {
+        DedupeCandidate {
+            id,
+            name: name.into(),
+            external_ids: external_ids
+                .iter()
+                .map(|(source, id)| (source.to_string(), id.to_string()))
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn test_normalize_name() {
+        assert_eq!(
+            normalize_name("The Witcher® 3: Wild Hunt"),
+            "witcher 3 wild hunt"
+        );
+        assert_eq!(normalize_name("Portal 2 - GOTY Edition"), "portal 2");
+        assert_eq!(normalize_name("Edition"), "edition");
+    }
+
+    #[test]
+    fn test_name_similarity() {
+        assert_eq!(name_similarity("portal", "portal"), 1.0);
+        assert!(name_similarity("half life 2", "half life 3") < NAME_SIMILARITY_THRESHOLD);
+        assert!(name_similarity("portal", "portal 2") < NAME_SIMILARITY_THRESHOLD);
+        assert!(
+            name_similarity("final fantasy vii", "final fantasy viii") < NAME_SIMILARITY_THRESHOLD
+        );
+        assert!(name_similarity("hollow knight", "hollow knights") >= NAME_SIMILARITY_THRESHOLD);
+        assert!(name_similarity("portal", "doom") < NAME_SIMILARITY_THRESHOLD);
+    }
+
+    #[test]
+    fn test_groups_by_external_id() {
+        let groups = find_duplicate_groups(&[
+            candidate(1, "Team Fortress 2", &[("steam", "440")]),
+            candidate(2, "TF2", &[("local", "/games/tf2"), ("steam", "440")]),
+            candidate(3, "Portal", &[("steam", "400")]),
+        ]);
+        assert_eq!(
+            groups,
+            vec![CandidateGroup {
+                ids: vec![1, 2],
+                reason: DuplicateReason::ExternalId,
+            }]
+        );
+    }
+
+    #[test]
+    fn test_number_tokens() {
+        assert_eq!(number_tokens("witcher 3 wild hunt"), vec!["3"]);
+        assert_eq!(number_tokens("final fantasy xiv"), vec!["xiv"]);
+        assert!(number_tokens("mix and match").is_empty());
+    }
+
+    #[test]
+    fn test_sequels_are_not_grouped() {
+        let groups = find_duplicate_groups(&[
+            candidate(1, "Portal", &[("steam", "400")]),
+            candidate(2, "Portal 2", &[("steam", "620")]),
+            candidate(3, "Half-Life 2", &[("steam", "220")]),
+            candidate(4, "Half-Life 3", &[("local", "/games/hl3")]),
+        ]);
+        assert!(groups.is_empty());
+    }
+
+    #[test]
+    fn test_groups_by_name() {
+        let groups = find_duplicate_groups(&[
+            candidate(1, "Hollow Knight", &[("steam", "367520")]),
+            candidate(2, "Hollow Knight™", &[("local", "/games/hk")]),
+            candidate(
+                3,
+                "Hollow Knight: Silksong",
+                &[("local", "/games/silksong")],
+            ),
+        ]);
+        assert_eq!(groups.len(), 1);
+        assert_eq!(groups[0].ids, vec![1, 2]);
+        assert_eq!(groups[0].reason, DuplicateReason::Name { similarity: 1.0 });
+    }
+}
@@ -0,0 +1,780 @@
+use crate::db::ops::USER_GENRE_SOURCE;
+use crate::launch::LaunchProfile;
+use chrono::{DateTime, NaiveDate, Utc};
+use gami_sdk::{CompletionStatus, GameInstallStatus, ADDONS_DIR, BASE_DATA_DIR};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fmt;
+use std::io::{self, Cursor, Read, Write};
+use std::path::{Component, Path};
+
+/// Version written to exports, files from newer versions are refused
+pub const EXPORT_VERSION: u32 = 1;
+const CSV_HEADER: &str = "# gami library export v";
+const GAMI_LIBRARY_ENTRY: &str = "library.json";
+/// Archive folder holding files restored relative to the data directory
+const GAMI_FILES_DIR: &str = "files/";
+
+/// Everything stored in the library database
+#[derive(Debug, Clone, PartialEq, Default, Serialize, Deserialize)]
+pub struct LibraryExport {
+    pub version: u32,
+    pub games: Vec<ExportedGame>,
+    #[serde(default)]
+    pub genres: Vec<ExportedGenre>,
+    #[serde(default)]
+    pub tags: Vec<ExportedTag>,
+    #[serde(default)]
+    pub collections: Vec<ExportedCollection>,
+    #[serde(default)]
+    pub accounts: Vec<ExportedAccount>,
+    /// Metadata scanner ranking by field key, best first
+    #[serde(default)]
+    pub metadata_priority: BTreeMap<String, Vec<String>>,
+    #[serde(default)]
+    pub global_launch_profile: LaunchProfile,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedGame {
+    pub id: i32,
+    pub library_type: String,
+    pub library_id: String,
+    pub account_id: String,
+    pub name: String,
+    pub description: String,
+    pub install_status: GameInstallStatus,
+    pub completion_status: CompletionStatus,
+    pub play_time_secs: i64,
+    pub release_date: Option<NaiveDate>,
+    pub last_played: Option<DateTime<Utc>>,
+    pub icon_url: Option<String>,
+    pub header_url: Option<String>,
+    pub cover_url: Option<String>,
+    pub developers: Vec<String>,
+    pub publishers: Vec<String>,
+    pub install_path: Option<String>,
+    pub favourite: bool,
+    pub hidden: bool,
+    pub merged_into: Option<i32>,
+    pub preferred_source_id: Option<i32>,
+    #[serde(default)]
+    pub genre_ids: Vec<i32>,
+    #[serde(default)]
+    pub tag_ids: Vec<i32>,
+    /// IDs of the game in other stores, by source
+    #[serde(default)]
+    pub external_ids: BTreeMap<String, String>,
+    #[serde(default)]
+    pub field_sources: Vec<ExportedFieldSource>,
+    #[serde(default)]
+    pub notes: String,
+    /// Oldest first
+    #[serde(default)]
+    pub play_sessions: Vec<ExportedPlaySession>,
+    /// The game's own launch profile, without the global one
+    #[serde(default)]
+    pub launch_profile: LaunchProfile,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedGenre {
+    pub id: i32,
+    pub name: String,
+    pub metadata_source: String,
+    pub metadata_id: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedTag {
+    pub id: i32,
+    pub name: String,
+    pub color: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedCollection {
+    pub name: String,
+    /// JSON serialized [`crate::db::filter::FilterExpr`]
+    pub filter: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedAccount {
+    pub library_type: String,
+    pub account_id: String,
+    pub name: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedFieldSource {
+    pub field: String,
+    pub source: String,
+    pub updated_at: DateTime<Utc>,
+    pub locked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ExportedPlaySession {
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+}
+
+/// A `.gami` archive: the library plus settings and addon configs
+#[derive(Debug, Clone, PartialEq, Default)]
+pub struct Backup {
+    pub library: LibraryExport,
+    /// Contents by path relative to the data directory
+    pub files: BTreeMap<String, Vec<u8>>,
+}
+
+#[derive(Debug, Copy, Clone, PartialEq, Eq)]
+pub enum ExportFormat {
+    Json,
+    /// Games only, with genres and tags by name
+    Csv,
+    Gami,
+}
+impl ExportFormat {
+    pub const ALL: [ExportFormat; 3] = [ExportFormat::Json, ExportFormat::Csv, ExportFormat::Gami];
+    pub fn extension(self) -> &'static str {
+        match self {
+            ExportFormat::Json => "json",
+            ExportFormat::Csv => "csv",
+            ExportFormat::Gami => "gami",
+        }
+    }
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_lowercase();
+        Self::ALL
+            .into_iter()
+            .find(|format| format.extension() == extension)
+    }
+}
+impl fmt::Display for ExportFormat {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ExportFormat::Json => "JSON",
+            ExportFormat::Csv => "CSV",
+            ExportFormat::Gami => "Gami backup",
+        })
+    }
+}
+
+/// How an import treats the current library
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum ImportMode {
+    /// Adds new games and overwrites matching ones, keeping everything else
+    #[default]
+    Merge,
+    /// Deletes the current library first
+    Replace,
+}
+impl ImportMode {
+    pub const ALL: [ImportMode; 2] = [ImportMode::Merge, ImportMode::Replace];
+}
+impl fmt::Display for ImportMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            ImportMode::Merge => "Merge",
+            ImportMode::Replace => "Replace",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ImportError {
+    Io(String),
+    Parse(String),
+    UnsupportedVersion(u32),
+    /// Every problem found in the imported data
+    Invalid(Vec<String>),
+    /// The file is of a kind that can't be read, with what to use instead
+    Unsupported(String),
+}
+impl fmt::Display for ImportError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ImportError::Io(err) => write!(f, "Could not read the file: {}", err),
+            ImportError::Parse(err) => write!(f, "Could not parse the file: {}", err),
+            ImportError::UnsupportedVersion(version) => write!(
+                f,
+                "Export version {} is not supported, expected at most {}",
+                version, EXPORT_VERSION
+            ),
+            ImportError::Invalid(problems) => write!(f, "Invalid data: {}", problems.join("; ")),
+            ImportError::Unsupported(hint) => write!(f, "Unsupported file: {}", hint),
+        }
+    }
+}
+impl From<io::Error> for ImportError {
+    fn from(value: io::Error) -> Self {
+        ImportError::Io(value.to_string())
+    }
+}
+
+impl LibraryExport {
+    /// Checks the version and that every reference points at an exported row
+    pub fn validate(&self) -> Result<(), ImportError> {
+        if self.version == 0 || self.version > EXPORT_VERSION {
+            return Err(ImportError::UnsupportedVersion(self.version));
+        }
+        let mut problems = Vec::new();
+        let mut game_ids = HashSet::new();
+        let mut library_refs = HashSet::new();
+        for game in &self.games {
+            if !game_ids.insert(game.id) {
+                problems.push(format!("game ID {} is used twice", game.id));
+            }
+            if !library_refs.insert((&game.library_type, &game.account_id, &game.library_id)) {
+                problems.push(format!("game {} is listed twice", game.name));
+            }
+            if game.name.trim().is_empty() || game.library_type.is_empty() {
+                problems.push(format!("game {} has no name or library", game.id));
+            }
+        }
+        let genre_ids: HashSet<i32> = self.genres.iter().map(|g| g.id).collect();
+        let tag_ids: HashSet<i32> = self.tags.iter().map(|t| t.id).collect();
+        if genre_ids.len() != self.genres.len() {
+            problems.push("a genre ID is used twice".into());
+        }
+        if tag_ids.len() != self.tags.len() {
+            problems.push("a tag ID is used twice".into());
+        }
+        for game in &self.games {
+            for genre_id in game.genre_ids.iter().filter(|id| !genre_ids.contains(id)) {
+                problems.push(format!("game {} has unknown genre {}", game.id, genre_id));
+            }
+            for tag_id in game.tag_ids.iter().filter(|id| !tag_ids.contains(id)) {
+                problems.push(format!("game {} has unknown tag {}", game.id, tag_id));
+            }
+            for other in [game.merged_into, game.preferred_source_id]
+                .into_iter()
+                .flatten()
+                .filter(|id| !game_ids.contains(id))
+            {
+                problems.push(format!("game {} refers to unknown game {}", game.id, other));
+            }
+        }
+        if problems.is_empty() {
+            Ok(())
+        } else {
+            Err(ImportError::Invalid(problems))
+        }
+    }
+}
+
+pub fn to_json(library: &LibraryExport) -> String {
+    serde_json::to_string_pretty(library).unwrap()
+}
+
+pub fn from_json(json: &str) -> Result<LibraryExport, ImportError> {
+    let library: LibraryExport =
+        serde_json::from_str(json).map_err(|err| ImportError::Parse(err.to_string()))?;
+    library.validate()?;
+    Ok(library)
+}
+
+/// One game per row, lists are newline separated
+#[derive(Debug, Serialize, Deserialize)]
+struct CsvGame {
+    id: i32,
+    library_type: String,
+    library_id: String,
+    account_id: String,
+    name: String,
+    description: String,
+    install_status: GameInstallStatus,
+    completion_status: CompletionStatus,
+    play_time_secs: i64,
+    release_date: Option<NaiveDate>,
+    last_played: Option<DateTime<Utc>>,
+    icon_url: Option<String>,
+    header_url: Option<String>,
+    cover_url: Option<String>,
+    developers: String,
+    publishers: String,
+    install_path: Option<String>,
+    favourite: bool,
+    hidden: bool,
+    merged_into: Option<i32>,
+    preferred_source_id: Option<i32>,
+    genres: String,
+    tags: String,
+    /// `source=id` lines
+    external_ids: String,
+    #[serde(default)]
+    notes: String,
+}
+
+fn split_lines(value: &str) -> Vec<String> {
+    value
+        .lines()
+        .filter(|line| !line.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Games as a spreadsheet; tag colors, collections, accounts, field sources, play sessions and
+/// launch profiles are left out
+pub fn to_csv(library: &LibraryExport) -> String {
+    let genres: HashMap<i32, &str> = library
+        .genres
+        .iter()
+        .map(|g| (g.id, g.name.as_str()))
+        .collect();
+    let tags: HashMap<i32, &str> = library
+        .tags
+        .iter()
+        .map(|t| (t.id, t.name.as_str()))
+        .collect();
+    let mut writer = csv::Writer::from_writer(Vec::new());
+    for game in &library.games {
+        writer
+            .serialize(CsvGame {
+                id: game.id,
+                library_type: game.library_type.clone(),
+                library_id: game.library_id.clone(),
+                account_id: game.account_id.clone(),
+                name: game.name.clone(),
+                description: game.description.clone(),
+                install_status: game.install_status,
+                completion_status: game.completion_status,
+                play_time_secs: game.play_time_secs,
+                release_date: game.release_date,
+                last_played: game.last_played,
+                icon_url: game.icon_url.clone(),
+                header_url: game.header_url.clone(),
+                cover_url: game.cover_url.clone(),
+                developers: game.developers.join("\n"),
+                publishers: game.publishers.join("\n"),
+                install_path: game.install_path.clone(),
+                favourite: game.favourite,
+                hidden: game.hidden,
+                merged_into: game.merged_into,
+                preferred_source_id: game.preferred_source_id,
+                genres: game
+                    .genre_ids
+                    .iter()
+                    .filter_map(|id| genres.get(id).copied())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                tags: game
+                    .tag_ids
+                    .iter()
+                    .filter_map(|id| tags.get(id).copied())
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                external_ids: game
+                    .external_ids
+                    .iter()
+                    .map(|(source, id)| format!("{}={}", source, id))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                notes: game.notes.clone(),
+            })
+            .unwrap();
+    }
+    let body = String::from_utf8(writer.into_inner().unwrap()).unwrap();
+    format!("{}{}\n{}", CSV_HEADER, EXPORT_VERSION, body)
+}
+
+/// Reads games exported by [`to_csv`], genres become user genres
+pub fn from_csv(csv: &str) -> Result<LibraryExport, ImportError> {
+    let (header, body) = csv.split_once('\n').unwrap_or((csv, ""));
+    let version = header
+        .trim_end()
+        .strip_prefix(CSV_HEADER)
+        .and_then(|version| version.parse().ok())
+        .ok_or_else(|| ImportError::Parse("missing export version line".into()))?;
+    let mut library = LibraryExport {
+        version,
+        ..Default::default()
+    };
+    let mut genre_ids: HashMap<String, i32> = HashMap::new();
+    let mut tag_ids: HashMap<String, i32> = HashMap::new();
+    let mut reader = csv::Reader::from_reader(body.as_bytes());
+    for row in reader.deserialize::<CsvGame>() {
+        let row = row.map_err(|err| ImportError::Parse(err.to_string()))?;
+        let genres = split_lines(&row.genres)
+            .into_iter()
+            .map(|name| {
+                let next_id = genre_ids.len() as i32 + 1;
+                *genre_ids.entry(name).or_insert(next_id)
+            })
+            .collect();
+        let tags = split_lines(&row.tags)
+            .into_iter()
+            .map(|name| {
+                let next_id = tag_ids.len() as i32 + 1;
+                *tag_ids.entry(name).or_insert(next_id)
+            })
+            .collect();
+        let external_ids = split_lines(&row.external_ids)
+            .iter()
+            .filter_map(|line| line.split_once('='))
+            .map(|(source, id)| (source.to_string(), id.to_string()))
+            .collect();
+        library.games.push(ExportedGame {
+            id: row.id,
+            library_type: row.library_type,
+            library_id: row.library_id,
+            account_id: row.account_id,
+            name: row.name,
+            description: row.description,
+            install_status: row.install_status,
+            completion_status: row.completion_status,
+            play_time_secs: row.play_time_secs,
+            release_date: row.release_date,
+            last_played: row.last_played,
+            icon_url: row.icon_url,
+            header_url: row.header_url,
+            cover_url: row.cover_url,
+            developers: split_lines(&row.developers),
+            publishers: split_lines(&row.publishers),
+            install_path: row.install_path,
+            favourite: row.favourite,
+            hidden: row.hidden,
+            merged_into: row.merged_into,
+            preferred_source_id: row.preferred_source_id,
+            genre_ids: genres,
+            tag_ids: tags,
+            external_ids,
+            field_sources: Vec::new(),
+            notes: row.notes,
+            play_sessions: Vec::new(),
+            launch_profile: LaunchProfile::default(),
+        });
+    }
+    library.genres = genre_ids
+        .into_iter()
+        .map(|(name, id)| ExportedGenre {
+            id,
+            metadata_source: USER_GENRE_SOURCE.into(),
+            metadata_id: name.clone(),
+            name,
+        })
+        .collect();
+    library.genres.sort_by_key(|genre| genre.id);
+    library.tags = tag_ids
+        .into_iter()
+        .map(|(name, id)| ExportedTag {
+            id,
+            name,
+            color: "#808080".into(),
+        })
+        .collect();
+    library.tags.sort_by_key(|tag| tag.id);
+    library.validate()?;
+    Ok(library)
+}
+
+/// Reads an export or backup, picking the format from the file extension
+pub fn read_file(path: &Path) -> Result<Backup, ImportError> {
+    let format = ExportFormat::from_path(path)
+        .ok_or_else(|| ImportError::Parse("unknown file extension".into()))?;
+    let library = match format {
+        ExportFormat::Json => from_json(&std::fs::read_to_string(path)?)?,
+        ExportFormat::Csv => from_csv(&std::fs::read_to_string(path)?)?,
+        ExportFormat::Gami => return from_gami(&std::fs::read(path)?),
+    };
+    Ok(Backup {
+        library,
+        files: BTreeMap::new(),
+    })
+}
+
+/// Whether an archive path stays inside the data directory
+fn is_safe_path(path: &str) -> bool {
+    Path::new(path)
+        .components()
+        .all(|component| matches!(component, Component::Normal(_)))
+}
+
+pub fn to_gami(backup: &Backup) -> Vec<u8> {
+    let mut zip = zip::ZipWriter::new(Cursor::new(Vec::new()));
+    let options = zip::write::SimpleFileOptions::default();
+    zip.start_file(GAMI_LIBRARY_ENTRY, options).unwrap();
+    zip.write_all(to_json(&backup.library).as_bytes()).unwrap();
+    for (path, bytes) in &backup.files {
+        zip.start_file(format!("{}{}", GAMI_FILES_DIR, path), options)
+            .unwrap();
+        zip.write_all(bytes).unwrap();
+    }
+    zip.finish().unwrap().into_inner()
+}
+
+pub fn from_gami(bytes: &[u8]) -> Result<Backup, ImportError> {
+    let parse_err = |err: zip::result::ZipError| ImportError::Parse(err.to_string());
+    let mut archive = zip::ZipArchive::new(Cursor::new(bytes)).map_err(parse_err)?;
+    let mut library = None;
+    let mut files = BTreeMap::new();
+    for index in 0..archive.len() {
+        let mut entry = archive.by_index(index).map_err(parse_err)?;
+        if entry.is_dir() {
+            continue;
+        }
+        let name = entry.name().to_string();
+        let mut contents = Vec::new();
+        entry.read_to_end(&mut contents)?;
+        if name == GAMI_LIBRARY_ENTRY {
+            let json =
+                String::from_utf8(contents).map_err(|err| ImportError::Parse(err.to_string()))?;
+            library = Some(from_json(&json)?);
+        } else if let Some(path) = name.strip_prefix(GAMI_FILES_DIR) {
+            if !is_safe_path(path) {
+                return Err(ImportError::Invalid(vec![format!(
+                    "file {} leaves the data directory",
+                    name
+                )]));
+            }
+            files.insert(path.to_string(), contents);
+        }
+    }
+    let library =
+        library.ok_or_else(|| ImportError::Parse(format!("{} is missing", GAMI_LIBRARY_ENTRY)))?;
+    Ok(Backup { library, files })
+}
+
+fn collect_json_files(dir: &Path, files: &mut BTreeMap<String, Vec<u8>>) -> io::Result<()> {
+    let entries = match std::fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(()),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let path = entry?.path();
+        if path.is_dir() {
+            collect_json_files(&path, files)?;
+        } else if path.extension().is_some_and(|ext| ext == "json")
+            && path.file_name().is_some_and(|name| name != "schema.json")
+        {
+            let relative = path
+                .strip_prefix(&*BASE_DATA_DIR)
+                .map_err(io::Error::other)?;
+            files.insert(
+                relative.to_string_lossy().replace('\\', "/"),
+                std::fs::read(&path)?,
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Settings and addon configs to put in a `.gami` archive, addon schemas excluded
+pub fn collect_backup_files() -> io::Result<BTreeMap<String, Vec<u8>>> {
+    let mut files = BTreeMap::new();
+    let entries = match std::fs::read_dir(&*BASE_DATA_DIR) {
+        Ok(entries) => entries,
+        Err(err) if err.kind() == io::ErrorKind::NotFound => return Ok(files),
+        Err(err) => return Err(err),
+    };
+    for entry in entries {
+        let entry = entry?;
+        let path = entry.path();
+        if path.extension().is_some_and(|ext| ext == "bin") {
+            files.insert(
+                entry.file_name().to_string_lossy().into_owned(),
+                std::fs::read(&path)?,
+            );
+        }
+    }
+    collect_json_files(&ADDONS_DIR, &mut files)?;
+    Ok(files)
+}
+
+/// Writes backed up files into the data directory, keeping existing ones unless `overwrite`
+pub fn restore_backup_files(files: &BTreeMap<String, Vec<u8>>, overwrite: bool) -> io::Result<()> {
+    for (relative, bytes) in files {
+        if !is_safe_path(relative) {
+            log::warn!("skipping unsafe backup path {}", relative);
+            continue;
+        }
+        let path = BASE_DATA_DIR.join(relative);
+        if path.exists() && !overwrite {
+            continue;
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, bytes)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library() -> LibraryExport {
+        LibraryExport {
+            version: EXPORT_VERSION,
+            games: vec![
+                ExportedGame {
+                    id: 1,
+                    library_type: "steam".into(),
+                    library_id: "620".into(),
+                    account_id: "76561".into(),
+                    name: "Portal 2".into(),
+                    description: "Think with portals,\n\"together\"".into(),
+                    install_status: GameInstallStatus::Installed,
+                    completion_status: CompletionStatus::Played,
+                    play_time_secs: 7260,
+                    release_date: NaiveDate::from_ymd_opt(2011, 4, 18),
+                    last_played: DateTime::from_timestamp(1_700_000_000, 0),
+                    icon_url: Some("https://example.com/icon.png".into()),
+                    header_url: None,
+                    cover_url: None,
+                    developers: vec!["Valve".into()],
+                    publishers: vec!["Valve".into(), "EA".into()],
+                    install_path: Some("/games/portal 2".into()),
+                    favourite: true,
+                    hidden: false,
+                    merged_into: None,
+                    preferred_source_id: Some(2),
+                    genre_ids: vec![1, 2],
+                    tag_ids: vec![1],
+                    external_ids: BTreeMap::from([("igdb".into(), "72".into())]),
+                    field_sources: vec![ExportedFieldSource {
+                        field: "description".into(),
+                        source: "user".into(),
+                        updated_at: DateTime::from_timestamp(1_700_000_100, 0).unwrap(),
+                        locked: true,
+                    }],
+                    notes: "Finish the co-op campaign".into(),
+                    play_sessions: vec![ExportedPlaySession {
+                        started_at: DateTime::from_timestamp(1_699_990_000, 0).unwrap(),
+                        ended_at: DateTime::from_timestamp(1_700_000_000, 0).unwrap(),
+                    }],
+                    launch_profile: LaunchProfile {
+                        wrappers: vec!["taskset -c 0-3".into()],
+                        ..LaunchProfile::default()
+                    },
+                },
+                ExportedGame {
+                    id: 2,
+                    library_type: "local".into(),
+                    library_id: "portal2".into(),
+                    account_id: "".into(),
+                    name: "Portal 2".into(),
+                    description: "".into(),
+                    install_status: GameInstallStatus::InLibrary,
+                    completion_status: CompletionStatus::Backlog,
+                    play_time_secs: 0,
+                    release_date: None,
+                    last_played: None,
+                    icon_url: None,
+                    header_url: None,
+                    cover_url: None,
+                    developers: vec![],
+                    publishers: vec![],
+                    install_path: None,
+                    favourite: false,
+                    hidden: true,
+                    merged_into: Some(1),
+                    preferred_source_id: None,
+                    genre_ids: vec![],
+                    tag_ids: vec![],
+                    external_ids: BTreeMap::new(),
+                    field_sources: vec![],
+                    notes: "".into(),
+                    play_sessions: vec![],
+                    launch_profile: LaunchProfile::default(),
+                },
+            ],
+            genres: vec![
+                ExportedGenre {
+                    id: 1,
+                    name: "Puzzle".into(),
+                    metadata_source: USER_GENRE_SOURCE.into(),
+                    metadata_id: "Puzzle".into(),
+                },
+                ExportedGenre {
+                    id: 2,
+                    name: "Co-op".into(),
+                    metadata_source: USER_GENRE_SOURCE.into(),
+                    metadata_id: "Co-op".into(),
+                },
+            ],
+            tags: vec![ExportedTag {
+                id: 1,
+                name: "Favourites".into(),
+                color: "#808080".into(),
+            }],
+            collections: vec![ExportedCollection {
+                name: "Short".into(),
+                filter: r#"{"playtime":{"op":"lt","minutes":120}}"#.into(),
+            }],
+            accounts: vec![ExportedAccount {
+                library_type: "steam".into(),
+                account_id: "76561".into(),
+                name: "me".into(),
+            }],
+            metadata_priority: BTreeMap::from([(
+                "description".into(),
+                vec!["igdb".into(), "steam".into()],
+            )]),
+            global_launch_profile: LaunchProfile {
+                env: vec![("DXVK_HUD".into(), "fps".into())],
+                ..LaunchProfile::default()
+            },
+        }
+    }
+
+    #[test]
+    fn test_json_round_trip() {
+        let library = library();
+        assert_eq!(from_json(&to_json(&library)).unwrap(), library);
+    }
+
+    #[test]
+    fn test_csv_round_trip_keeps_games() {
+        let library = library();
+        let csv = to_csv(&library);
+        let imported = from_csv(&csv).unwrap();
+        assert_eq!(to_csv(&imported), csv);
+        for (imported, game) in imported.games.iter().zip(&library.games) {
+            assert_eq!(
+                ExportedGame {
+                    field_sources: game.field_sources.clone(),
+                    play_sessions: game.play_sessions.clone(),
+                    launch_profile: game.launch_profile.clone(),
+                    ..imported.clone()
+                },
+                *game
+            );
+        }
+        assert_eq!(imported.genres, library.genres);
+    }
+
+    #[test]
+    fn test_gami_round_trip() {
+        let backup = Backup {
+            library: library(),
+            files: BTreeMap::from([
+                ("desktop_settings.bin".into(), vec![1, 2, 3]),
+                (
+                    "addons/steam/config.json".into(),
+                    b"{\"apiKey\":1}".to_vec(),
+                ),
+            ]),
+        };
+        assert_eq!(from_gami(&to_gami(&backup)).unwrap(), backup);
+    }
+
+    #[test]
+    fn test_validate_rejects_broken_references() {
+        let mut library = library();
+        library.games[0].tag_ids.push(9);
+        library.games[1].merged_into = Some(5);
+        let Err(ImportError::Invalid(problems)) = library.validate() else {
+            panic!("expected invalid data");
+        };
+        assert_eq!(problems.len(), 2);
+        library.version = EXPORT_VERSION + 1;
+        assert_eq!(
+            library.validate(),
+            Err(ImportError::UnsupportedVersion(EXPORT_VERSION + 1))
+        );
+    }
+}
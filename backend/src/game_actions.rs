@@ -4,10 +4,12 @@ use gami_sdk::GameInstallStatus;
 #[derive(Debug, Clone, Copy)]
 pub enum GameAction {
     Play,
+    Stop,
     Install,
     Uninstall,
     Delete,
     Edit,
+    RefreshMetadata,
 }
 #[derive(Debug, Clone, Copy)]
 pub struct GameActionData {
@@ -22,6 +24,12 @@ const PLAY_ACTION: GameActionData = GameActionData {
     kind: GameAction::Play,
     color: StyleVariant::Success,
 };
+const STOP_ACTION: GameActionData = GameActionData {
+    name: "Stop game",
+    icon: include_bytes!("../../desktop/src/icons/tabler--player-stop.svg"),
+    kind: GameAction::Stop,
+    color: StyleVariant::Danger,
+};
 const INSTALL_ACTION: GameActionData = GameActionData {
     name: "Install",
     icon: include_bytes!("../../desktop/src/icons/tabler--plus.svg"),
@@ -46,11 +54,30 @@ const EDIT_ACTION: GameActionData = GameActionData {
     kind: GameAction::Edit,
     color: StyleVariant::Primary,
 };
-pub const fn get_actions(status: GameInstallStatus) -> &'static [GameActionData] {
+const REFRESH_METADATA_ACTION: GameActionData = GameActionData {
+    name: "Refresh metadata",
+    icon: include_bytes!("../../desktop/src/icons/tabler--refresh.svg"),
+    kind: GameAction::RefreshMetadata,
+    color: StyleVariant::Secondary,
+};
+/// Actions offered for a game, a running game can only be stopped, edited and refreshed
+pub const fn get_actions(status: GameInstallStatus, running: bool) -> &'static [GameActionData] {
+    if running {
+        return &[STOP_ACTION, EDIT_ACTION, REFRESH_METADATA_ACTION];
+    }
     match status {
-        GameInstallStatus::Installed => {
-            &[PLAY_ACTION, UNINSTALL_ACTION, EDIT_ACTION, DELETE_ACTION]
-        }
-        _ => &[INSTALL_ACTION, EDIT_ACTION, DELETE_ACTION],
+        GameInstallStatus::Installed => &[
+            PLAY_ACTION,
+            UNINSTALL_ACTION,
+            EDIT_ACTION,
+            REFRESH_METADATA_ACTION,
+            DELETE_ACTION,
+        ],
+        _ => &[
+            INSTALL_ACTION,
+            EDIT_ACTION,
+            REFRESH_METADATA_ACTION,
+            DELETE_ACTION,
+        ],
     }
 }
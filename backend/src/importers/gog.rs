@@ -0,0 +1,192 @@
+use super::{library_type_for_store, parse_timestamp, query_error, status_and_tags, ImportedGame};
+use crate::export::ImportError;
+use chrono::{DateTime, Duration};
+use gami_sdk::GameData;
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use serde::Deserialize;
+use std::collections::HashMap;
+
+/// Game pieces of the meta type, with details from the game's store
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Meta {
+    #[serde(default)]
+    developers: Vec<String>,
+    #[serde(default)]
+    publishers: Vec<String>,
+    /// Unix timestamp
+    #[serde(default)]
+    release_date: Option<i64>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Title {
+    title: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct Summary {
+    summary: Option<String>,
+}
+
+/// Splits a Galaxy release key like `steam_620` into library type and ID
+fn split_release_key(key: &str) -> (String, String) {
+    match key.split_once('_') {
+        Some((platform, id)) => (library_type_for_store(platform), id.to_string()),
+        None => ("gog".to_string(), key.to_string()),
+    }
+}
+
+/// Values of a game piece type by release key, user edited pieces first
+async fn game_pieces(
+    conn: &DatabaseConnection,
+    types: &[&str],
+) -> Result<HashMap<String, String>, ImportError> {
+    let mut pieces = HashMap::new();
+    for piece_type in types.iter().rev() {
+        for row in conn
+            .query_all(Statement::from_sql_and_values(
+                DbBackend::Sqlite,
+                "SELECT gp.releaseKey, gp.value FROM GamePieces gp \
+                 JOIN GamePieceTypes t ON t.id = gp.gamePieceTypeId WHERE t.type = ?",
+                [(*piece_type).into()],
+            ))
+            .await
+            .map_err(query_error)?
+        {
+            pieces.insert(
+                row.try_get_by_index(0).map_err(query_error)?,
+                row.try_get_by_index(1).map_err(query_error)?,
+            );
+        }
+    }
+    Ok(pieces)
+}
+
+/// Pairs of a key and a value from a query selecting two columns
+async fn pairs<V: sea_orm::TryGetable>(
+    conn: &DatabaseConnection,
+    sql: &str,
+) -> Result<Vec<(String, V)>, ImportError> {
+    conn.query_all(Statement::from_string(DbBackend::Sqlite, sql))
+        .await
+        .map_err(query_error)?
+        .into_iter()
+        .map(|row| {
+            Ok((
+                row.try_get_by_index(0).map_err(query_error)?,
+                row.try_get_by_index(1).map_err(query_error)?,
+            ))
+        })
+        .collect()
+}
+
+fn parse_piece<T: for<'de> Deserialize<'de>>(value: Option<&String>) -> Option<T> {
+    value.and_then(|value| serde_json::from_str(value).ok())
+}
+
+/// Reads the owned games, play times and tags of a GOG Galaxy `galaxy-2.0.db`
+pub async fn read_from(conn: &DatabaseConnection) -> Result<Vec<ImportedGame>, ImportError> {
+    let titles = game_pieces(conn, &["title", "originalTitle"]).await?;
+    let metas = game_pieces(conn, &["meta", "originalMeta"]).await?;
+    let summaries = game_pieces(conn, &["summary", "originalSummary"]).await?;
+    let play_times: HashMap<String, i64> =
+        pairs(conn, "SELECT releaseKey, minutesInGame FROM GameTimes")
+            .await?
+            .into_iter()
+            .collect();
+    let last_played: HashMap<String, String> = pairs(
+        conn,
+        "SELECT gameReleaseKey, lastPlayedDate FROM LastPlayedDates",
+    )
+    .await?
+    .into_iter()
+    .collect();
+    let hidden: HashMap<String, bool> = pairs(
+        conn,
+        "SELECT releaseKey, isHidden FROM UserReleaseProperties",
+    )
+    .await?
+    .into_iter()
+    .collect();
+    let mut tags: HashMap<String, Vec<String>> = HashMap::new();
+    for (key, tag) in pairs::<String>(conn, "SELECT releaseKey, tag FROM UserReleaseTags").await? {
+        tags.entry(key).or_default().push(tag);
+    }
+    let release_keys: Vec<(String, i64)> = pairs(
+        conn,
+        "SELECT releaseKey, MIN(userId) FROM LibraryReleases GROUP BY releaseKey ORDER BY releaseKey",
+    )
+    .await?;
+
+    let mut games = Vec::new();
+    for (key, _) in release_keys {
+        let (library_type, library_id) = split_release_key(&key);
+        let meta: Meta = parse_piece(metas.get(&key)).unwrap_or_default();
+        let (completion_status, tags) = status_and_tags(tags.remove(&key).unwrap_or_default());
+        games.push(ImportedGame {
+            game: GameData {
+                name: parse_piece::<Title>(titles.get(&key))
+                    .and_then(|title| title.title)
+                    .unwrap_or_else(|| key.clone()),
+                description: parse_piece::<Summary>(summaries.get(&key))
+                    .and_then(|summary| summary.summary)
+                    .unwrap_or_default(),
+                play_time: Duration::minutes(play_times.get(&key).copied().unwrap_or_default()),
+                last_played: last_played.get(&key).and_then(|date| parse_timestamp(date)),
+                release_date: meta
+                    .release_date
+                    .and_then(|time| DateTime::from_timestamp(time, 0))
+                    .map(|date| date.date_naive()),
+                hidden: hidden.get(&key).copied().unwrap_or_default(),
+                developers: meta.developers,
+                publishers: meta.publishers,
+                library_type,
+                library_id,
+                ..Default::default()
+            },
+            completion_status,
+            tags,
+        });
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::fixture_db;
+    use chrono::NaiveDate;
+    use gami_sdk::CompletionStatus;
+
+    #[tokio::test]
+    async fn test_read_galaxy_db() {
+        let conn = fixture_db(include_str!(
+            "../../tests/fixtures/importers/gog_galaxy.sql"
+        ))
+        .await;
+        let games = read_from(&conn).await.unwrap();
+        assert_eq!(games.len(), 2);
+
+        let witcher = &games[0];
+        assert_eq!(witcher.game.library_type, "gog");
+        assert_eq!(witcher.game.library_id, "1207664663");
+        assert_eq!(witcher.game.name, "The Witcher 3: Wild Hunt - GOTY");
+        assert_eq!(witcher.game.play_time, Duration::minutes(6120));
+        assert_eq!(witcher.completion_status, Some(CompletionStatus::Played));
+        assert_eq!(witcher.tags, vec!["RPG".to_string()]);
+        assert_eq!(witcher.game.developers, vec!["CD PROJEKT RED".to_string()]);
+        assert_eq!(
+            witcher.game.release_date,
+            NaiveDate::from_ymd_opt(2015, 5, 19)
+        );
+        assert!(witcher.game.last_played.is_some());
+
+        let portal = &games[1];
+        assert_eq!(portal.game.library_type, "steam");
+        assert_eq!(portal.game.library_id, "620");
+        assert_eq!(portal.game.name, "Portal 2");
+        assert!(portal.game.hidden);
+        assert_eq!(portal.completion_status, None);
+    }
+}
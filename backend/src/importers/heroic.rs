@@ -0,0 +1,204 @@
+use super::{library_type_for_store, parse_timestamp, status_and_tags, ImportedGame};
+use crate::export::ImportError;
+use chrono::Duration;
+use gami_sdk::{GameData, GameInstallStatus};
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Library caches of the stores Heroic supports, relative to its configuration folder
+const LIBRARY_FILES: &[(&str, &str)] = &[
+    ("store_cache/legendary_library.json", "legendary"),
+    ("store_cache/gog_library.json", "gog"),
+    ("store_cache/nile_library.json", "nile"),
+];
+const TIMESTAMPS_FILE: &str = "store/timestamp.json";
+const CONFIG_FILE: &str = "store/config.json";
+
+#[derive(Debug, Default, Deserialize)]
+struct InstallInfo {
+    #[serde(default)]
+    install_path: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+struct HeroicGame {
+    app_name: String,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(default)]
+    developer: Option<String>,
+    #[serde(default)]
+    is_installed: bool,
+    #[serde(default)]
+    install: Option<InstallInfo>,
+    #[serde(default)]
+    art_square: Option<String>,
+    #[serde(default)]
+    art_cover: Option<String>,
+}
+
+/// Library caches keep games under `library`, or `games` for GOG
+#[derive(Debug, Deserialize)]
+struct LibraryCache {
+    #[serde(default, alias = "games")]
+    library: Vec<HeroicGame>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct Timestamp {
+    #[serde(default)]
+    last_played: Option<String>,
+    /// Minutes
+    #[serde(default)]
+    total_played: Option<f64>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GameRef {
+    app_name: String,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GamesConfig {
+    #[serde(default)]
+    favourites: Vec<GameRef>,
+    #[serde(default)]
+    hidden: Vec<GameRef>,
+    /// Category names to `<app name>_<runner>` entries
+    #[serde(default)]
+    custom_categories: HashMap<String, Vec<String>>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct Config {
+    #[serde(default)]
+    games: GamesConfig,
+}
+
+/// Reads an optional JSON file of the configuration folder
+fn read_json<T: DeserializeOwned + Default>(dir: &Path, file: &str) -> Result<T, ImportError> {
+    let path = dir.join(file);
+    if !path.exists() {
+        return Ok(T::default());
+    }
+    serde_json::from_slice(&std::fs::read(path)?)
+        .map_err(|err| ImportError::Parse(format!("{}: {}", file, err)))
+}
+
+/// Reads the library caches, play times and categories of a Heroic configuration folder
+pub fn read(dir: &Path) -> Result<Vec<ImportedGame>, ImportError> {
+    let timestamps: HashMap<String, Timestamp> = read_json(dir, TIMESTAMPS_FILE)?;
+    let config: Config = read_json(dir, CONFIG_FILE)?;
+    let mut games = Vec::new();
+    let mut found_library = false;
+    for (file, runner) in LIBRARY_FILES {
+        let cache: Option<LibraryCache> = read_json(dir, file)?;
+        let Some(cache) = cache else {
+            continue;
+        };
+        found_library = true;
+        for game in cache.library {
+            let entry = format!("{}_{}", game.app_name, runner);
+            let categories = config
+                .games
+                .custom_categories
+                .iter()
+                .filter(|(_, entries)| entries.iter().any(|e| *e == entry || *e == game.app_name))
+                .map(|(name, _)| name.clone())
+                .collect();
+            let (completion_status, mut tags) = status_and_tags(categories);
+            tags.sort();
+            let timestamp = timestamps.get(&game.app_name);
+            games.push(ImportedGame {
+                game: GameData {
+                    name: game.title,
+                    description: game.description.unwrap_or_default(),
+                    play_time: Duration::seconds(
+                        timestamp
+                            .and_then(|t| t.total_played)
+                            .map(|minutes| (minutes * 60.0) as i64)
+                            .unwrap_or_default(),
+                    ),
+                    install_status: if game.is_installed {
+                        GameInstallStatus::Installed
+                    } else {
+                        GameInstallStatus::InLibrary
+                    },
+                    last_played: timestamp
+                        .and_then(|t| t.last_played.as_deref())
+                        .and_then(parse_timestamp),
+                    cover_url: game.art_square.filter(|url| !url.is_empty()),
+                    header_url: game.art_cover.filter(|url| !url.is_empty()),
+                    library_type: library_type_for_store(runner),
+                    favourite: config
+                        .games
+                        .favourites
+                        .iter()
+                        .any(|f| f.app_name == game.app_name),
+                    hidden: config
+                        .games
+                        .hidden
+                        .iter()
+                        .any(|h| h.app_name == game.app_name),
+                    developers: game
+                        .developer
+                        .into_iter()
+                        .filter(|d| !d.is_empty())
+                        .collect(),
+                    install_path: game
+                        .install
+                        .and_then(|install| install.install_path)
+                        .filter(|path| !path.is_empty()),
+                    library_id: game.app_name,
+                    ..Default::default()
+                },
+                completion_status,
+                tags,
+            });
+        }
+    }
+    if !found_library {
+        return Err(ImportError::Invalid(vec![format!(
+            "{} has no Heroic library caches",
+            dir.display()
+        )]));
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gami_sdk::CompletionStatus;
+    use std::path::PathBuf;
+
+    #[test]
+    fn test_read_heroic_config() {
+        let dir = PathBuf::from(env!("CARGO_MANIFEST_DIR")).join("tests/fixtures/importers/heroic");
+        let games = read(&dir).unwrap();
+        assert_eq!(games.len(), 3);
+
+        let control = &games[0];
+        assert_eq!(control.game.library_type, "epic");
+        assert_eq!(control.game.library_id, "calluna");
+        assert_eq!(control.game.play_time, Duration::minutes(754));
+        assert!(control.game.last_played.is_some());
+        assert!(control.game.favourite);
+        assert_eq!(control.completion_status, Some(CompletionStatus::Playing));
+        assert_eq!(control.tags, vec!["Remedy".to_string()]);
+
+        let witcher = &games[1];
+        assert_eq!(witcher.game.library_type, "gog");
+        assert!(witcher.game.hidden);
+        assert_eq!(witcher.game.play_time, Duration::zero());
+        assert_eq!(witcher.completion_status, None);
+
+        assert_eq!(games[2].game.library_type, "amazon");
+    }
+}
@@ -0,0 +1,119 @@
+use super::{library_type_for_store, query_error, status_and_tags, ImportedGame};
+use crate::export::ImportError;
+use chrono::{DateTime, Duration};
+use gami_sdk::{GameData, GameInstallStatus};
+use sea_orm::{ConnectionTrait, DatabaseConnection, DbBackend, Statement};
+use std::collections::HashMap;
+
+/// Library type of games installed through Lutris installers or added by hand
+const LUTRIS_LIBRARY_TYPE: &str = "lutris";
+/// Lutris keeps favourites and hidden games as categories
+const FAVOURITE_CATEGORY: &str = "favorite";
+const HIDDEN_CATEGORY: &str = ".hidden";
+
+/// Reads the games and categories of a Lutris `pga.db`
+pub async fn read_from(conn: &DatabaseConnection) -> Result<Vec<ImportedGame>, ImportError> {
+    let mut categories: HashMap<i64, Vec<String>> = HashMap::new();
+    for row in conn
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT gc.game_id, c.name FROM games_categories gc \
+             JOIN categories c ON c.id = gc.category_id",
+        ))
+        .await
+        .map_err(query_error)?
+    {
+        categories
+            .entry(row.try_get_by_index(0).map_err(query_error)?)
+            .or_default()
+            .push(row.try_get_by_index(1).map_err(query_error)?);
+    }
+
+    let rows = conn
+        .query_all(Statement::from_string(
+            DbBackend::Sqlite,
+            "SELECT id, name, slug, service, service_id, directory, installed, lastplayed, \
+             playtime FROM games ORDER BY id",
+        ))
+        .await
+        .map_err(query_error)?;
+    let mut games = Vec::new();
+    for row in rows {
+        let id: i64 = row.try_get("", "id").map_err(query_error)?;
+        let slug: String = row.try_get("", "slug").map_err(query_error)?;
+        let service: Option<String> = row.try_get("", "service").map_err(query_error)?;
+        let service_id: Option<String> = row.try_get("", "service_id").map_err(query_error)?;
+        let (library_type, library_id) = match (service, service_id) {
+            (Some(service), Some(service_id)) if !service.is_empty() && !service_id.is_empty() => {
+                (library_type_for_store(&service), service_id)
+            }
+            _ => (LUTRIS_LIBRARY_TYPE.to_string(), slug),
+        };
+        let installed: Option<i64> = row.try_get("", "installed").map_err(query_error)?;
+        let last_played: Option<i64> = row.try_get("", "lastplayed").map_err(query_error)?;
+        // hours
+        let playtime: Option<f64> = row.try_get("", "playtime").map_err(query_error)?;
+        let directory: Option<String> = row.try_get("", "directory").map_err(query_error)?;
+
+        let mut names = categories.remove(&id).unwrap_or_default();
+        let favourite = names.iter().any(|name| name == FAVOURITE_CATEGORY);
+        let hidden = names.iter().any(|name| name == HIDDEN_CATEGORY);
+        names.retain(|name| name != FAVOURITE_CATEGORY && name != HIDDEN_CATEGORY);
+        let (completion_status, tags) = status_and_tags(names);
+        games.push(ImportedGame {
+            game: GameData {
+                name: row.try_get("", "name").map_err(query_error)?,
+                play_time: Duration::seconds((playtime.unwrap_or_default() * 3600.0) as i64),
+                install_status: if installed.unwrap_or_default() != 0 {
+                    GameInstallStatus::Installed
+                } else {
+                    GameInstallStatus::InLibrary
+                },
+                last_played: last_played
+                    .filter(|time| *time > 0)
+                    .and_then(|time| DateTime::from_timestamp(time, 0)),
+                library_type,
+                library_id,
+                favourite,
+                hidden,
+                install_path: directory.filter(|dir| !dir.is_empty()),
+                ..Default::default()
+            },
+            completion_status,
+            tags,
+        });
+    }
+    Ok(games)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::importers::tests::fixture_db;
+    use gami_sdk::CompletionStatus;
+
+    #[tokio::test]
+    async fn test_read_lutris_db() {
+        let conn = fixture_db(include_str!("../../tests/fixtures/importers/lutris.sql")).await;
+        let games = read_from(&conn).await.unwrap();
+        assert_eq!(games.len(), 3);
+
+        let hades = &games[0];
+        assert_eq!(hades.game.library_type, "gog");
+        assert_eq!(hades.game.library_id, "1207658924");
+        assert_eq!(hades.game.play_time, Duration::minutes(90));
+        assert!(hades.game.favourite);
+        assert_eq!(hades.completion_status, Some(CompletionStatus::Played));
+        assert_eq!(hades.tags, vec!["Roguelike".to_string()]);
+        assert_eq!(hades.game.install_status, GameInstallStatus::Installed);
+
+        let doom = &games[1];
+        assert_eq!(doom.game.library_type, LUTRIS_LIBRARY_TYPE);
+        assert_eq!(doom.game.library_id, "gzdoom");
+        assert!(doom.game.hidden);
+        assert!(doom.tags.is_empty());
+        assert!(doom.game.last_played.is_none());
+
+        assert_eq!(games[2].game.library_type, "epic");
+    }
+}
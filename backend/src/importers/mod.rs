@@ -0,0 +1,195 @@
+//! Readers for the libraries of other launchers
+
+mod gog;
+mod heroic;
+mod lutris;
+mod playnite;
+
+use crate::export::ImportError;
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+use gami_sdk::{CompletionStatus, GameData};
+use sea_orm::{Database, DatabaseConnection};
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+/// A game read from another launcher
+#[derive(Debug, Clone, Default)]
+pub struct ImportedGame {
+    /// Library type and ID are mapped to the ones store addons use, so games match
+    pub game: GameData,
+    pub completion_status: Option<CompletionStatus>,
+    /// Tag and category names
+    pub tags: Vec<String>,
+}
+
+/// A launcher whose library can be imported
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Default)]
+pub enum LauncherSource {
+    #[default]
+    Playnite,
+    Lutris,
+    Heroic,
+    GogGalaxy,
+}
+impl LauncherSource {
+    pub const ALL: [LauncherSource; 4] = [
+        LauncherSource::Playnite,
+        LauncherSource::Lutris,
+        LauncherSource::Heroic,
+        LauncherSource::GogGalaxy,
+    ];
+    /// What the path given to [`LauncherSource::read`] points at
+    pub fn expected_path(self) -> &'static str {
+        match self {
+            LauncherSource::Playnite => "JSON export of the Playnite library",
+            LauncherSource::Lutris => "Lutris pga.db",
+            LauncherSource::Heroic => "Heroic configuration folder",
+            LauncherSource::GogGalaxy => "GOG Galaxy galaxy-2.0.db",
+        }
+    }
+    /// Where the launcher keeps its library when installed with default settings
+    pub fn default_path(self) -> Option<PathBuf> {
+        match self {
+            LauncherSource::Playnite => None,
+            LauncherSource::Lutris => dirs::data_dir().map(|dir| dir.join("lutris").join("pga.db")),
+            LauncherSource::Heroic => dirs::config_dir().map(|dir| dir.join("heroic")),
+            LauncherSource::GogGalaxy => std::env::var_os("PROGRAMDATA").map(|dir| {
+                PathBuf::from(dir)
+                    .join("GOG.com")
+                    .join("Galaxy")
+                    .join("storage")
+                    .join("galaxy-2.0.db")
+            }),
+        }
+    }
+    pub async fn read(self, path: &Path) -> Result<Vec<ImportedGame>, ImportError> {
+        if !path.exists() {
+            return Err(ImportError::Io(format!(
+                "{} does not exist",
+                path.display()
+            )));
+        }
+        match self {
+            LauncherSource::Playnite => playnite::read(path),
+            LauncherSource::Lutris => lutris::read_from(&open_sqlite(path).await?).await,
+            LauncherSource::Heroic => heroic::read(path),
+            LauncherSource::GogGalaxy => gog::read_from(&open_sqlite(path).await?).await,
+        }
+    }
+}
+impl fmt::Display for LauncherSource {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LauncherSource::Playnite => "Playnite",
+            LauncherSource::Lutris => "Lutris",
+            LauncherSource::Heroic => "Heroic",
+            LauncherSource::GogGalaxy => "GOG Galaxy",
+        })
+    }
+}
+
+/// Opens another launcher's database without writing to it
+async fn open_sqlite(path: &Path) -> Result<DatabaseConnection, ImportError> {
+    Database::connect(format!("sqlite:{}?mode=ro", path.to_string_lossy()))
+        .await
+        .map_err(|err| ImportError::Io(err.to_string()))
+}
+
+fn query_error(err: sea_orm::DbErr) -> ImportError {
+    ImportError::Parse(err.to_string())
+}
+
+/// Maps a store name used by another launcher to the library type of Gami's store addon
+fn library_type_for_store(store: &str) -> String {
+    match store.to_lowercase().as_str() {
+        "steam" => "steam",
+        "gog" => "gog",
+        "egs" | "epic" | "legendary" => "epic",
+        "nile" | "amazon" => "amazon",
+        "humblebundle" | "humble" => "humble",
+        "itchio" | "itch" => "itch",
+        "origin" | "ea" => "origin",
+        "ubisoft" | "uplay" => "ubisoft",
+        "battlenet" => "battlenet",
+        other => return other.to_string(),
+    }
+    .to_string()
+}
+
+/// Completion status for a status, category or tag name used by another launcher
+pub fn completion_status_from_name(name: &str) -> Option<CompletionStatus> {
+    match name.trim().to_lowercase().replace(['-', '_'], " ").as_str() {
+        "backlog" | "not played" | "plan to play" | "unplayed" => Some(CompletionStatus::Backlog),
+        "playing" | "currently playing" => Some(CompletionStatus::Playing),
+        "played" | "beaten" | "completed" | "finished" => Some(CompletionStatus::Played),
+        "on hold" | "paused" | "abandoned" => Some(CompletionStatus::OnHold),
+        _ => None,
+    }
+}
+
+/// Splits category names into a completion status and the remaining tags
+fn status_and_tags(names: Vec<String>) -> (Option<CompletionStatus>, Vec<String>) {
+    let mut status = None;
+    let mut tags = Vec::new();
+    for name in names {
+        match completion_status_from_name(&name) {
+            Some(found) if status.is_none() => status = Some(found),
+            Some(_) => {}
+            None if !name.trim().is_empty() && !tags.contains(&name) => tags.push(name),
+            None => {}
+        }
+    }
+    (status, tags)
+}
+
+/// Reads RFC 3339 timestamps and timestamps without offset, taken as UTC
+fn parse_timestamp(value: &str) -> Option<DateTime<Utc>> {
+    let value = value.trim();
+    DateTime::parse_from_rfc3339(value)
+        .map(|date| date.to_utc())
+        .ok()
+        .or_else(|| {
+            ["%Y-%m-%dT%H:%M:%S%.f", "%Y-%m-%d %H:%M:%S%.f"]
+                .iter()
+                .find_map(|format| NaiveDateTime::parse_from_str(value, format).ok())
+                .map(|date| date.and_utc())
+        })
+}
+
+/// Reads the date part of a date or timestamp
+fn parse_date(value: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(value.get(..10)?, "%Y-%m-%d").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sea_orm::ConnectionTrait;
+
+    /// In-memory database set up by a fixture script
+    pub async fn fixture_db(sql: &str) -> DatabaseConnection {
+        let conn = Database::connect("sqlite::memory:").await.unwrap();
+        conn.execute_unprepared(sql).await.unwrap();
+        conn
+    }
+
+    #[test]
+    fn test_status_and_tags() {
+        let (status, tags) = status_and_tags(vec![
+            "Co-op".into(),
+            "Completed".into(),
+            "On hold".into(),
+            "Co-op".into(),
+        ]);
+        assert_eq!(status, Some(CompletionStatus::Played));
+        assert_eq!(tags, vec!["Co-op".to_string()]);
+    }
+
+    #[test]
+    fn test_parse_timestamp() {
+        let expected = Some("2024-03-01T19:15:00Z".parse::<DateTime<Utc>>().unwrap());
+        assert_eq!(parse_timestamp("2024-03-01T20:15:00+01:00"), expected);
+        assert_eq!(parse_timestamp("2024-03-01 19:15:00"), expected);
+        assert_eq!(parse_timestamp("yesterday"), None);
+    }
+}
@@ -0,0 +1,214 @@
+use super::{library_type_for_store, parse_date, parse_timestamp, status_and_tags, ImportedGame};
+use crate::export::ImportError;
+use chrono::Duration;
+use gami_sdk::{GameData, GameInstallStatus};
+use serde::Deserialize;
+use std::path::Path;
+
+/// Library plugins of Playnite and the stores they import from
+const PLUGIN_STORES: &[(&str, &str)] = &[
+    ("cb91dfc9-b977-43bf-8e70-55f46e410fab", "steam"),
+    ("aebe8b7c-6dc3-4a66-af31-e7375c6b5e9e", "gog"),
+    ("00000002-dbd1-46c6-b5d0-b1ba559d10e4", "epic"),
+    ("00000001-ebb2-4eec-abcb-7c89937a42bb", "itch"),
+    ("96e8c4bc-ec5c-4c8b-87e7-18ee5a690626", "humble"),
+    ("85dd7072-2f20-4e76-a007-41035e390724", "origin"),
+    ("c2f038e5-8b92-4877-91f1-da9094155fc5", "ubisoft"),
+    ("e3c26a3d-d695-4cb7-a769-5ff7612c7edd", "battlenet"),
+    ("402674cd-4af6-4886-b6ec-0e695bfa0688", "amazon"),
+];
+/// Library type of games added to Playnite by hand
+const PLAYNITE_LIBRARY_TYPE: &str = "playnite";
+
+/// Names of Playnite database items, either expanded or as plain strings
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum Named {
+    Object {
+        #[serde(rename = "Name")]
+        name: String,
+    },
+    Name(String),
+}
+impl Named {
+    fn into_name(self) -> String {
+        match self {
+            Named::Object { name } | Named::Name(name) => name,
+        }
+    }
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum ReleaseDate {
+    Object {
+        #[serde(rename = "ReleaseDate")]
+        date: String,
+    },
+    Date(String),
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "PascalCase")]
+struct PlayniteGame {
+    id: String,
+    name: String,
+    #[serde(default)]
+    game_id: Option<String>,
+    #[serde(default)]
+    plugin_id: Option<String>,
+    #[serde(default)]
+    description: Option<String>,
+    /// Seconds
+    #[serde(default)]
+    playtime: u64,
+    #[serde(default)]
+    last_activity: Option<String>,
+    #[serde(default)]
+    release_date: Option<ReleaseDate>,
+    #[serde(default)]
+    completion_status: Option<Named>,
+    #[serde(default)]
+    tags: Option<Vec<Named>>,
+    #[serde(default)]
+    categories: Option<Vec<Named>>,
+    #[serde(default)]
+    developers: Option<Vec<Named>>,
+    #[serde(default)]
+    publishers: Option<Vec<Named>>,
+    #[serde(default)]
+    is_installed: bool,
+    #[serde(default)]
+    install_directory: Option<String>,
+    #[serde(default)]
+    favorite: bool,
+    #[serde(default)]
+    hidden: bool,
+}
+
+/// Playnite exports either a plain list of games or an object holding them
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+enum PlayniteExport {
+    Games(Vec<PlayniteGame>),
+    Library {
+        #[serde(rename = "Games")]
+        games: Vec<PlayniteGame>,
+    },
+}
+
+fn names(items: Option<Vec<Named>>) -> Vec<String> {
+    items
+        .unwrap_or_default()
+        .into_iter()
+        .map(Named::into_name)
+        .collect()
+}
+
+fn to_imported(game: PlayniteGame) -> ImportedGame {
+    let plugin_id = game.plugin_id.unwrap_or_default().to_lowercase();
+    let store = PLUGIN_STORES
+        .iter()
+        .find(|(id, _)| *id == plugin_id)
+        .map(|(_, store)| library_type_for_store(store));
+    let (library_type, library_id) = match (store, game.game_id) {
+        (Some(store), Some(game_id)) if !game_id.is_empty() => (store, game_id),
+        _ => (PLAYNITE_LIBRARY_TYPE.to_string(), game.id),
+    };
+    let (category_status, mut tags) = status_and_tags(names(game.categories));
+    for tag in names(game.tags) {
+        if !tags.contains(&tag) {
+            tags.push(tag);
+        }
+    }
+    let completion_status = game
+        .completion_status
+        .and_then(|status| super::completion_status_from_name(&status.into_name()))
+        .or(category_status);
+    ImportedGame {
+        game: GameData {
+            name: game.name,
+            description: game.description.unwrap_or_default(),
+            play_time: Duration::seconds(game.playtime as i64),
+            install_status: if game.is_installed {
+                GameInstallStatus::Installed
+            } else {
+                GameInstallStatus::InLibrary
+            },
+            release_date: game.release_date.and_then(|date| match date {
+                ReleaseDate::Object { date } | ReleaseDate::Date(date) => parse_date(&date),
+            }),
+            last_played: game.last_activity.as_deref().and_then(parse_timestamp),
+            library_type,
+            library_id,
+            favourite: game.favorite,
+            hidden: game.hidden,
+            developers: names(game.developers),
+            publishers: names(game.publishers),
+            install_path: game.install_directory.filter(|dir| !dir.is_empty()),
+            ..Default::default()
+        },
+        completion_status,
+        tags,
+    }
+}
+
+/// Reads a JSON export of the Playnite library
+///
+/// Playnite's own LiteDB files can't be read, the library has to be exported first.
+pub fn read(path: &Path) -> Result<Vec<ImportedGame>, ImportError> {
+    if path.extension().is_some_and(|ext| ext == "db") {
+        return Err(ImportError::Unsupported(
+            "Playnite databases can't be read directly, export the library to JSON instead"
+                .to_string(),
+        ));
+    }
+    parse(&std::fs::read(path)?)
+}
+
+fn parse(data: &[u8]) -> Result<Vec<ImportedGame>, ImportError> {
+    let export: PlayniteExport =
+        serde_json::from_slice(data).map_err(|err| ImportError::Parse(err.to_string()))?;
+    let games = match export {
+        PlayniteExport::Games(games) | PlayniteExport::Library { games } => games,
+    };
+    Ok(games.into_iter().map(to_imported).collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::NaiveDate;
+    use gami_sdk::CompletionStatus;
+
+    #[test]
+    fn test_read_playnite_export() {
+        let games = parse(include_bytes!(
+            "../../tests/fixtures/importers/playnite.json"
+        ))
+        .unwrap();
+        assert_eq!(games.len(), 2);
+
+        let portal = &games[0];
+        assert_eq!(portal.game.library_type, "steam");
+        assert_eq!(portal.game.library_id, "620");
+        assert_eq!(portal.game.play_time, Duration::hours(10));
+        assert_eq!(portal.completion_status, Some(CompletionStatus::Played));
+        assert_eq!(portal.tags, vec!["Puzzle".to_string(), "Co-op".to_string()]);
+        assert!(portal.game.favourite);
+        assert_eq!(portal.game.developers, vec!["Valve".to_string()]);
+        assert_eq!(
+            portal.game.release_date,
+            NaiveDate::from_ymd_opt(2011, 4, 18)
+        );
+
+        let emulated = &games[1];
+        assert_eq!(emulated.game.library_type, PLAYNITE_LIBRARY_TYPE);
+        assert_eq!(
+            emulated.game.library_id,
+            "2b4b1a5c-95e1-4c43-9b7e-0f0d5d3f0c11"
+        );
+        assert_eq!(emulated.completion_status, Some(CompletionStatus::OnHold));
+        assert!(emulated.game.last_played.is_none());
+    }
+}
@@ -0,0 +1,485 @@
+//! Starting games with their launch profile: scripts, wrappers and environment overrides
+
+use crate::action_executor::{self, ActionFailure, LibraryAction};
+use crate::db::{DbErr, LibraryStore};
+use crate::plugin::ExternalAddons;
+use crate::process_monitor;
+use chrono::{DateTime, Utc};
+use gami_sdk::{GameCommon, GameData, GameLibrary, LaunchCommand};
+use serde::{Deserialize, Serialize};
+use std::fmt;
+use std::io;
+use std::time::Duration;
+use tokio::process::Command;
+use tokio::task::JoinHandle;
+
+/// Launch log entries kept for each game, older ones are pruned
+pub const LOG_ENTRIES_KEPT: usize = 50;
+/// How long a pre-launch or post-exit script may run before it is killed
+pub const SCRIPT_TIMEOUT: Duration = Duration::from_secs(120);
+
+/// Step of launching a game
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum LaunchStage {
+    PreLaunch,
+    Launch,
+    PostExit,
+}
+impl fmt::Display for LaunchStage {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            LaunchStage::PreLaunch => "Pre-launch script",
+            LaunchStage::Launch => "Launch",
+            LaunchStage::PostExit => "Post-exit script",
+        })
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchLogEntry {
+    pub at: DateTime<Utc>,
+    pub stage: LaunchStage,
+    pub success: bool,
+    /// Output of the script, or what went wrong
+    pub output: String,
+}
+
+/// What the launcher window does once a game started
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub enum PostLaunchAction {
+    #[default]
+    DoNothing,
+    /// Minimize, restoring the window once the game exits when its process is known
+    Minimize,
+    /// Quit the launcher, after the game exited when its process is known so the post-exit script runs
+    Exit,
+}
+impl PostLaunchAction {
+    pub const ALL: [PostLaunchAction; 3] = [
+        PostLaunchAction::DoNothing,
+        PostLaunchAction::Minimize,
+        PostLaunchAction::Exit,
+    ];
+}
+impl fmt::Display for PostLaunchAction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::DoNothing => "Do Nothing",
+            Self::Minimize => "Minimize",
+            Self::Exit => "Exit",
+        })
+    }
+}
+
+/// How a game is run, either the global profile or a game's own additions to it
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct LaunchProfile {
+    /// Shell script run before the game starts, a failure cancels the launch
+    pub pre_launch: Option<String>,
+    /// Shell script run once the game exited
+    pub post_exit: Option<String>,
+    /// Commands the game runs through, outermost first, e.g. `gamemoderun` or `taskset -c 0-3`
+    pub wrappers: Vec<String>,
+    pub env: Vec<(String, String)>,
+    pub working_dir: Option<String>,
+    /// Overrides the launcher's post-launch action for one game
+    pub post_launch_action: Option<PostLaunchAction>,
+}
+
+impl LaunchProfile {
+    pub fn is_empty(&self) -> bool {
+        *self == LaunchProfile::default()
+    }
+
+    /// The profile a game launches with, its own settings on top of the global ones
+    ///
+    /// Scripts and the working directory of the game replace the global ones, wrappers of both
+    /// apply and variables are merged by name.
+    pub fn merged(global: &LaunchProfile, game: &LaunchProfile) -> LaunchProfile {
+        let mut wrappers = global.wrappers.clone();
+        for wrapper in &game.wrappers {
+            if !wrappers.contains(wrapper) {
+                wrappers.push(wrapper.clone());
+            }
+        }
+        let mut env = global.env.clone();
+        for (key, value) in &game.env {
+            env.retain(|(k, _)| k != key);
+            env.push((key.clone(), value.clone()));
+        }
+        LaunchProfile {
+            pre_launch: game
+                .pre_launch
+                .clone()
+                .or_else(|| global.pre_launch.clone()),
+            post_exit: game.post_exit.clone().or_else(|| global.post_exit.clone()),
+            wrappers,
+            env,
+            working_dir: game
+                .working_dir
+                .clone()
+                .or_else(|| global.working_dir.clone()),
+            post_launch_action: game.post_launch_action.or(global.post_launch_action),
+        }
+    }
+
+    /// Settings a library's client ignores when it starts the game itself, only the pre-launch
+    /// script runs then
+    pub fn client_ignored(&self) -> Vec<&'static str> {
+        [
+            (!self.wrappers.is_empty(), "wrappers"),
+            (!self.env.is_empty(), "environment variables"),
+            (self.working_dir.is_some(), "the working directory"),
+            (self.post_exit.is_some(), "the post-exit script"),
+        ]
+        .into_iter()
+        .filter_map(|(set, name)| set.then_some(name))
+        .collect()
+    }
+
+    /// The library's command run through the wrappers, with the profile's environment
+    ///
+    /// Fails when a wrapper can't be split into words, see [`split_wrapper`].
+    fn command(&self, launch: LaunchCommand) -> Result<Command, String> {
+        let mut words = Vec::new();
+        for wrapper in &self.wrappers {
+            words.extend(split_wrapper(wrapper)?);
+        }
+        let mut words = words.into_iter().chain([launch.program]).chain(launch.args);
+        // the chain always yields at least the library's program
+        let mut command = Command::new(words.next().unwrap_or_default());
+        command.args(words).envs(self.env.iter().cloned());
+        match self.working_dir.as_deref() {
+            Some(dir) => {
+                command.current_dir(dir);
+            }
+            None => {
+                if let Some(dir) = launch.working_dir {
+                    command.current_dir(dir);
+                }
+            }
+        }
+        Ok(command)
+    }
+
+    /// Runs a script with the profile's environment and details of the game, capturing its output
+    ///
+    /// Scripts still running after [`SCRIPT_TIMEOUT`] are killed and count as failed.
+    async fn run_script(&self, script: &str, game: &GameData) -> (bool, String) {
+        let mut command = if cfg!(windows) {
+            let mut command = Command::new("cmd");
+            command.arg("/C");
+            command
+        } else {
+            let mut command = Command::new("sh");
+            command.arg("-c");
+            command
+        };
+        command
+            .arg(script)
+            .envs(self.env.iter().cloned())
+            .env("GAMI_GAME_ID", game.id.to_string())
+            .env("GAMI_GAME_NAME", &game.name)
+            .env("GAMI_LIBRARY", &game.library_type)
+            .kill_on_drop(true);
+        if let Some(path) = &game.install_path {
+            command.env("GAMI_INSTALL_PATH", path);
+        }
+        if let Some(dir) = &self.working_dir {
+            command.current_dir(dir);
+        }
+        // dropping the timed out future drops the child, which kill_on_drop kills
+        match tokio::time::timeout(SCRIPT_TIMEOUT, command.output()).await {
+            Err(_) => (
+                false,
+                format!(
+                    "Script killed after running for {} seconds",
+                    SCRIPT_TIMEOUT.as_secs()
+                ),
+            ),
+            Ok(Ok(output)) => {
+                let mut text = String::from_utf8_lossy(&output.stdout).into_owned();
+                text.push_str(&String::from_utf8_lossy(&output.stderr));
+                let mut text = text.trim_end().to_string();
+                if !output.status.success() {
+                    if !text.is_empty() {
+                        text.push('\n');
+                    }
+                    text.push_str(&format!("Script {}", output.status));
+                }
+                (output.status.success(), text)
+            }
+            Ok(Err(err)) => (false, format!("Could not run the script: {}", err)),
+        }
+    }
+}
+
+/// Splits a wrapper into words like a shell, so quoted arguments such as `taskset -c "0,2"` stay
+/// whole
+pub fn split_wrapper(wrapper: &str) -> Result<Vec<String>, String> {
+    shlex::split(wrapper).ok_or_else(|| format!("the wrapper `{}` has an unclosed quote", wrapper))
+}
+
+#[derive(Debug)]
+pub enum LaunchError {
+    /// No addon provides the library of the game
+    NoLibrary(String),
+    /// The pre-launch script failed, with its output
+    PreLaunchFailed(String),
+    /// A wrapper of the launch profile could not be split into words
+    InvalidWrapper(String),
+    Spawn(io::Error),
+    /// The library's client could not start the game
+    Library(ActionFailure),
+    Db(DbErr),
+}
+impl fmt::Display for LaunchError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            LaunchError::NoLibrary(library) => {
+                write!(f, "no addon provides the {} library", library)
+            }
+            LaunchError::PreLaunchFailed(output) => {
+                write!(f, "the pre-launch script failed: {}", output)
+            }
+            LaunchError::InvalidWrapper(err) => write!(f, "could not start the game: {}", err),
+            LaunchError::Spawn(err) => write!(f, "could not start the game: {}", err),
+            LaunchError::Library(err) => write!(f, "could not start the game: {}", err),
+            LaunchError::Db(err) => write!(f, "database error: {}", err),
+        }
+    }
+}
+impl std::error::Error for LaunchError {}
+impl From<DbErr> for LaunchError {
+    fn from(err: DbErr) -> Self {
+        LaunchError::Db(err)
+    }
+}
+
+/// A game that was started
+#[derive(Debug)]
+pub struct Launched {
+    pub last_played: DateTime<Utc>,
+    /// The game's own post-launch action, if it overrides the launcher's
+    pub post_launch_action: Option<PostLaunchAction>,
+    /// Finishes once the game exited and the post-exit script ran
+    ///
+    /// `None` when the library's client started the game, its exit can't be told then.
+    pub exit: Option<JoinHandle<()>>,
+    /// Profile settings that were not applied because the library's client started the game,
+    /// see [`LaunchProfile::client_ignored`]
+    pub ignored: Vec<&'static str>,
+}
+
+/// Runs the pre-launch script, starts the game through its library and, when the game process is
+/// known, waits for it in the background to run the post-exit script
+///
+/// Every step is written to the game's launch log. Must be called within a Tokio runtime.
+pub async fn launch_game(
+    store: &LibraryStore,
+    addons: &ExternalAddons,
+    game: &GameData,
+) -> Result<Launched, LaunchError> {
+    let source = game.preferred_source();
+    let library = addons
+        .get_game_library(&source.library_type)
+        .cloned()
+        .ok_or_else(|| LaunchError::NoLibrary(source.library_type.clone()))?;
+    let source_id = source.id;
+    let library_type = source.library_type.clone();
+    let profile = store.get_effective_launch_profile(game.id).await?;
+    let post_launch_action = profile.post_launch_action;
+
+    if let Some(script) = &profile.pre_launch {
+        let (success, output) = profile.run_script(script, game).await;
+        store
+            .add_launch_log(game.id, LaunchStage::PreLaunch, success, output.clone())
+            .await?;
+        if !success {
+            return Err(LaunchError::PreLaunchFailed(output));
+        }
+    }
+
+    let mut ignored = Vec::new();
+    let exit = match library.launch_command(source.get_ref()) {
+        Some(launch) => {
+            let mut command = match profile.command(launch) {
+                Ok(command) => command,
+                Err(err) => {
+                    store
+                        .add_launch_log(game.id, LaunchStage::Launch, false, err.clone())
+                        .await?;
+                    return Err(LaunchError::InvalidWrapper(err));
+                }
+            };
+            let started_at = Utc::now();
+            let child = command.spawn();
+            let line = format!("{:?}", command.as_std());
+            let mut child = match child {
+                Ok(child) => child,
+                Err(err) => {
+                    store
+                        .add_launch_log(
+                            game.id,
+                            LaunchStage::Launch,
+                            false,
+                            format!("{}\n{}", line, err),
+                        )
+                        .await?;
+                    return Err(LaunchError::Spawn(err));
+                }
+            };
+            store
+                .add_launch_log(game.id, LaunchStage::Launch, true, line)
+                .await?;
+            let pid = child.id();
+            if let Some(pid) = pid {
+                process_monitor::track(pid, game.id);
+            }
+            let store = store.clone();
+            let game = game.clone();
+            Some(tokio::spawn(async move {
+                let (success, output) = match child.wait().await {
+                    Ok(status) => (status.success(), format!("Game {}", status)),
+                    Err(err) => (false, format!("Lost track of the game: {}", err)),
+                };
+                if let Some(pid) = pid {
+                    process_monitor::untrack(pid);
+                }
+                if let Err(err) = store
+                    .add_play_session(game.id, started_at, Utc::now())
+                    .await
+                {
+                    log::warn!(
+                        "could not record the play session of game {}: {}",
+                        game.id,
+                        err
+                    );
+                }
+                log_or_warn(&store, game.id, LaunchStage::Launch, success, output).await;
+                if let Some(script) = &profile.post_exit {
+                    let (success, output) = profile.run_script(script, &game).await;
+                    log_or_warn(&store, game.id, LaunchStage::PostExit, success, output).await;
+                }
+            }))
+        }
+        None => {
+            if let Err(err) = action_executor::run_on(library, LibraryAction::Launch, source).await
+            {
+                let output = format!("The library failed to launch the game: {}", err);
+                store
+                    .add_launch_log(game.id, LaunchStage::Launch, false, output)
+                    .await?;
+                return Err(LaunchError::Library(err));
+            }
+            let mut output = format!("Started by the {} library", library_type);
+            ignored = profile.client_ignored();
+            if !ignored.is_empty() {
+                output.push_str(&format!(
+                    "\nIts client starts the game, so {} were not applied",
+                    ignored.join(", ")
+                ));
+            }
+            store
+                .add_launch_log(game.id, LaunchStage::Launch, true, output)
+                .await?;
+            None
+        }
+    };
+    let last_played = store.update_game_played(source_id).await?;
+    Ok(Launched {
+        last_played,
+        post_launch_action,
+        exit,
+        ignored,
+    })
+}
+
+/// Logs a step that happened after [`launch_game`] returned, there is nobody to report to then
+async fn log_or_warn(
+    store: &LibraryStore,
+    game_id: i32,
+    stage: LaunchStage,
+    success: bool,
+    output: String,
+) {
+    if let Err(err) = store.add_launch_log(game_id, stage, success, output).await {
+        log::warn!(
+            "could not write the launch log of game {}: {}",
+            game_id,
+            err
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_game_profile_extends_the_global_one() {
+        let global = LaunchProfile {
+            pre_launch: Some("notify-send start".into()),
+            wrappers: vec!["gamemoderun".into()],
+            env: vec![("DXVK_HUD".into(), "fps".into()), ("A".into(), "1".into())],
+            ..LaunchProfile::default()
+        };
+        let game = LaunchProfile {
+            post_exit: Some("sync-saves".into()),
+            wrappers: vec!["gamemoderun".into(), "mangohud".into()],
+            env: vec![("DXVK_HUD".into(), "full".into())],
+            ..LaunchProfile::default()
+        };
+        let merged = LaunchProfile::merged(&global, &game);
+        assert_eq!(merged.pre_launch.as_deref(), Some("notify-send start"));
+        assert_eq!(merged.post_exit.as_deref(), Some("sync-saves"));
+        assert_eq!(merged.wrappers, ["gamemoderun", "mangohud"]);
+        assert_eq!(
+            merged.env,
+            [
+                ("A".to_string(), "1".to_string()),
+                ("DXVK_HUD".to_string(), "full".to_string())
+            ]
+        );
+        assert_eq!(
+            merged.client_ignored(),
+            ["wrappers", "environment variables", "the post-exit script"]
+        );
+    }
+
+    #[test]
+    fn test_wrappers_split_like_a_shell() {
+        let profile = LaunchProfile {
+            wrappers: vec![
+                "taskset -c \"0,2\"".into(),
+                "'/opt/my tools/run.sh' --fast".into(),
+            ],
+            ..LaunchProfile::default()
+        };
+        let command = profile
+            .command(LaunchCommand {
+                program: "game".into(),
+                args: vec!["--windowed".into()],
+                working_dir: None,
+            })
+            .unwrap();
+        let command = command.as_std();
+        assert_eq!(command.get_program(), "taskset");
+        assert_eq!(
+            command.get_args().collect::<Vec<_>>(),
+            [
+                "-c",
+                "0,2",
+                "/opt/my tools/run.sh",
+                "--fast",
+                "game",
+                "--windowed"
+            ]
+        );
+
+        assert_eq!(
+            split_wrapper("mangohud \"--dlsym").unwrap_err(),
+            "the wrapper `mangohud \"--dlsym` has an unclosed quote"
+        );
+    }
+}
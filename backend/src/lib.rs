@@ -1,16 +1,25 @@
 use crate::plugin::ExternalAddons;
-use std::cell::LazyCell;
+use std::sync::LazyLock;
 
+pub mod action_executor;
+pub mod addon_config;
 pub mod db;
+pub mod dedupe;
+pub mod export;
 mod game_actions;
+pub mod importers;
+pub mod launch;
+pub mod metadata;
 pub use game_actions::*;
 mod action_colors;
 mod models;
 pub use models::*;
 pub mod plugin;
+pub mod process_monitor;
+pub mod rpc;
 
 pub use action_colors::StyleVariant;
-pub const ADDONS: LazyCell<ExternalAddons> = LazyCell::new(|| unsafe {
+pub static ADDONS: LazyLock<ExternalAddons> = LazyLock::new(|| unsafe {
     let mut addons = ExternalAddons::new();
     addons.auto_load_addons().unwrap();
     addons
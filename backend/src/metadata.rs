@@ -0,0 +1,291 @@
+use crate::GameField;
+use gami_sdk::{ExternalId, GameData, GameMetadata};
+use std::collections::HashMap;
+
+/// User ranking of metadata scanners for each field, best first
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MetadataPriority {
+    pub fields: HashMap<GameField, Vec<String>>,
+}
+impl MetadataPriority {
+    pub fn for_field(&self, field: GameField) -> &[String] {
+        self.fields
+            .get(&field)
+            .map(Vec::as_slice)
+            .unwrap_or_default()
+    }
+    /// Ranks `available` scanners for a field of a game from `library_type`
+    ///
+    /// Configured scanners come first, then the library's own scanner, then the rest by key.
+    pub fn ranked(&self, field: GameField, library_type: &str, available: &[&str]) -> Vec<String> {
+        let mut ranked: Vec<String> = self
+            .for_field(field)
+            .iter()
+            .filter(|source| available.contains(&source.as_str()))
+            .cloned()
+            .collect();
+        let mut rest: Vec<&str> = available
+            .iter()
+            .copied()
+            .filter(|source| !ranked.iter().any(|s| s == source))
+            .collect();
+        rest.sort_by_key(|source| (*source != library_type, *source));
+        ranked.extend(rest.into_iter().map(String::from));
+        ranked
+    }
+}
+
+/// Metadata combined from several scanners, with the scanner each field was taken from
+#[derive(Debug, Clone, Default)]
+pub struct MergedMetadata {
+    pub metadata: GameMetadata,
+    pub sources: HashMap<GameField, String>,
+}
+
+/// Whether a scanner reported a value for a field
+fn provides(metadata: &GameMetadata, field: GameField) -> bool {
+    match field {
+        GameField::Description => metadata.description.clone().into_rust().is_some(),
+        GameField::IconUrl => metadata.icon_url.clone().into_rust().is_some(),
+        GameField::HeaderUrl => metadata.header_url.clone().into_rust().is_some(),
+        GameField::CoverUrl => metadata.cover_url.clone().into_rust().is_some(),
+        GameField::ReleaseDate => metadata.release_date_timestamp.into_rust().is_some(),
+        GameField::Genres => !metadata.genres.is_empty(),
+        GameField::Developers => !metadata.developers.is_empty(),
+        GameField::Publishers => !metadata.publishers.is_empty(),
+        _ => false,
+    }
+}
+
+fn copy_field(to: &mut GameMetadata, from: &GameMetadata, field: GameField) {
+    match field {
+        GameField::Description => to.description = from.description.clone(),
+        GameField::IconUrl => to.icon_url = from.icon_url.clone(),
+        GameField::HeaderUrl => to.header_url = from.header_url.clone(),
+        GameField::CoverUrl => to.cover_url = from.cover_url.clone(),
+        GameField::ReleaseDate => to.release_date_timestamp = from.release_date_timestamp,
+        GameField::Genres => to.genres = from.genres.clone(),
+        GameField::Developers => to.developers = from.developers.clone(),
+        GameField::Publishers => to.publishers = from.publishers.clone(),
+        _ => {}
+    }
+}
+
+/// Combines what several scanners reported for one game of `library_type`
+///
+/// Each field is taken from the best ranked scanner providing it, external IDs from all of them.
+pub fn merge_metadata(
+    library_type: &str,
+    results: &[(String, GameMetadata)],
+    priority: &MetadataPriority,
+) -> MergedMetadata {
+    let available: Vec<&str> = results.iter().map(|(key, _)| key.as_str()).collect();
+    let by_key: HashMap<&str, &GameMetadata> = results
+        .iter()
+        .map(|(key, metadata)| (key.as_str(), metadata))
+        .collect();
+    let mut merged = MergedMetadata::default();
+    for field in GameField::ALL.into_iter().filter(|f| f.from_metadata()) {
+        let best = priority
+            .ranked(field, library_type, &available)
+            .into_iter()
+            .find(|key| provides(by_key[key.as_str()], field));
+        if let Some(key) = best {
+            copy_field(&mut merged.metadata, by_key[key.as_str()], field);
+            merged.sources.insert(field, key);
+        }
+    }
+
+    // fields users can't rank follow the library's own order
+    let mut general = available.clone();
+    general.sort_by_key(|source| (*source != library_type, *source));
+    for key in &general {
+        let metadata = by_key[key];
+        if merged.metadata.platforms.is_empty() {
+            merged.metadata.platforms = metadata.platforms.clone();
+        }
+        if merged.metadata.series.is_empty() {
+            merged.metadata.series = metadata.series.clone();
+        }
+        if merged.metadata.tags.is_empty() {
+            merged.metadata.tags = metadata.tags.clone();
+        }
+        if merged.metadata.last_played_timestamp.into_rust().is_none() {
+            merged.metadata.last_played_timestamp = metadata.last_played_timestamp;
+        }
+    }
+    let mut external_ids: Vec<ExternalId> = Vec::new();
+    for key in &general {
+        for ext in by_key[key].external_ids.iter() {
+            if !external_ids.iter().any(|e| *e.source == *ext.source) {
+                external_ids.push(ext.clone());
+            }
+        }
+    }
+    merged.metadata.external_ids = external_ids.into();
+    merged
+}
+
+/// A value metadata scanners propose for a field of a game
+#[derive(Debug, Clone, PartialEq)]
+pub struct MetadataChange {
+    pub field: GameField,
+    /// Scanner proposing the value
+    pub source: String,
+    pub old: String,
+    pub new: String,
+    /// Whether applying writes the change, initially only if the source may replace the value
+    pub accepted: bool,
+}
+
+/// How a metadata field of a game reads to users, genres in name order
+pub fn display_field(game: &GameData, field: GameField) -> String {
+    match field {
+        GameField::Description => game.description.clone(),
+        GameField::IconUrl => game.icon_url.clone().unwrap_or_default(),
+        GameField::HeaderUrl => game.header_url.clone().unwrap_or_default(),
+        GameField::CoverUrl => game.cover_url.clone().unwrap_or_default(),
+        GameField::ReleaseDate => game
+            .release_date
+            .map(|date| date.format("%Y-%m-%d").to_string())
+            .unwrap_or_default(),
+        GameField::Genres => {
+            let mut names: Vec<&str> = game.genres.iter().map(|g| g.name.trim_end()).collect();
+            names.sort();
+            names.dedup();
+            names.join(", ")
+        }
+        GameField::Developers => game.developers.join(", "),
+        GameField::Publishers => game.publishers.join(", "),
+        _ => String::new(),
+    }
+}
+
+/// Changes between a stored game and the game with fetched metadata applied
+///
+/// `sources` maps each fetched field to its scanner and whether it may replace the stored value.
+pub fn diff_metadata(
+    stored: &GameData,
+    proposed: &GameData,
+    sources: &HashMap<GameField, (String, bool)>,
+) -> Vec<MetadataChange> {
+    GameField::ALL
+        .into_iter()
+        .filter_map(|field| {
+            let (source, may_overwrite) = sources.get(&field)?;
+            let old = display_field(stored, field);
+            let new = display_field(proposed, field);
+            (old != new).then(|| MetadataChange {
+                field,
+                source: source.clone(),
+                old,
+                new,
+                accepted: *may_overwrite,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gami_sdk::GenreData;
+
+    fn metadata(developer: &str, publisher: Option<&str>) -> GameMetadata {
+        GameMetadata {
+            developers: vec![developer.to_string().into()].into(),
+            publishers: publisher
+                .map(|p| vec![p.to_string().into()])
+                .unwrap_or_default()
+                .into(),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_diff_metadata_lists_changed_fields() {
+        let stored = GameData {
+            description: "Old".into(),
+            developers: vec!["Valve".into()],
+            ..Default::default()
+        };
+        let proposed = GameData {
+            description: "New".into(),
+            developers: vec!["Valve".into()],
+            genres: vec![GenreData {
+                library_id: "1".to_string().into(),
+                name: "Action".to_string().into(),
+            }],
+            ..Default::default()
+        };
+        let sources = HashMap::from([
+            (GameField::Description, ("igdb".to_string(), false)),
+            (GameField::Developers, ("steam".to_string(), true)),
+            (GameField::Genres, ("steam".to_string(), true)),
+        ]);
+        let changes = diff_metadata(&stored, &proposed, &sources);
+        assert_eq!(
+            changes,
+            vec![
+                MetadataChange {
+                    field: GameField::Description,
+                    source: "igdb".into(),
+                    old: "Old".into(),
+                    new: "New".into(),
+                    accepted: false,
+                },
+                MetadataChange {
+                    field: GameField::Genres,
+                    source: "steam".into(),
+                    old: "".into(),
+                    new: "Action".into(),
+                    accepted: true,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_ranked_defaults_to_own_library_first() {
+        let mut priority = MetadataPriority::default();
+        assert_eq!(
+            priority.ranked(GameField::Developers, "steam", &["igdb", "steam", "local"]),
+            vec!["steam", "igdb", "local"]
+        );
+        priority
+            .fields
+            .insert(GameField::Developers, vec!["local".into(), "gone".into()]);
+        assert_eq!(
+            priority.ranked(GameField::Developers, "steam", &["igdb", "steam", "local"]),
+            vec!["local", "steam", "igdb"]
+        );
+    }
+
+    #[test]
+    fn test_merge_takes_each_field_from_best_source() {
+        let results = vec![
+            ("steam".to_string(), metadata("Valve", None)),
+            (
+                "igdb".to_string(),
+                metadata("Valve Corporation", Some("Valve")),
+            ),
+        ];
+        let mut priority = MetadataPriority::default();
+        let merged = merge_metadata("steam", &results, &priority);
+        assert_eq!(merged.metadata.developers[0].to_string(), "Valve");
+        assert_eq!(merged.sources[&GameField::Developers], "steam");
+        // steam has no publishers, so the next scanner provides them
+        assert_eq!(merged.sources[&GameField::Publishers], "igdb");
+        assert!(!merged.sources.contains_key(&GameField::Description));
+
+        priority
+            .fields
+            .insert(GameField::Developers, vec!["igdb".into()]);
+        let merged = merge_metadata("steam", &results, &priority);
+        assert_eq!(
+            merged.metadata.developers[0].to_string(),
+            "Valve Corporation"
+        );
+        assert_eq!(merged.sources[&GameField::Developers], "igdb");
+    }
+}
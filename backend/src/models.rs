@@ -1,3 +1,5 @@
+use std::fmt;
+
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum GameTextField {
     Name,
@@ -5,6 +7,7 @@ pub enum GameTextField {
     IconUrl,
     HeaderUrl,
     CoverUrl,
+    InstallPath,
 }
 #[derive(Debug, Copy, Clone, PartialEq)]
 pub enum GameDateField {
@@ -16,6 +19,136 @@ pub enum GameDurationField {
     TimePlayed,
 }
 #[derive(Debug, Copy, Clone, PartialEq)]
+pub enum GameListField {
+    Genres,
+    Developers,
+    Publishers,
+}
+/// A game field users can edit, remembered once edited so metadata refreshes leave it alone
+#[derive(Debug, Copy, Clone, PartialEq, Eq, Hash)]
+pub enum GameField {
+    Name,
+    Description,
+    IconUrl,
+    HeaderUrl,
+    CoverUrl,
+    InstallPath,
+    ReleaseDate,
+    LastPlayed,
+    PlayTime,
+    CompletionStatus,
+    Genres,
+    Developers,
+    Publishers,
+}
+impl GameField {
+    pub const ALL: [GameField; 13] = [
+        GameField::Name,
+        GameField::Description,
+        GameField::IconUrl,
+        GameField::HeaderUrl,
+        GameField::CoverUrl,
+        GameField::InstallPath,
+        GameField::ReleaseDate,
+        GameField::LastPlayed,
+        GameField::PlayTime,
+        GameField::CompletionStatus,
+        GameField::Genres,
+        GameField::Developers,
+        GameField::Publishers,
+    ];
+    /// Name stored in the database
+    pub fn key(self) -> &'static str {
+        match self {
+            GameField::Name => "name",
+            GameField::Description => "description",
+            GameField::IconUrl => "icon_url",
+            GameField::HeaderUrl => "header_url",
+            GameField::CoverUrl => "cover_url",
+            GameField::InstallPath => "install_path",
+            GameField::ReleaseDate => "release_date",
+            GameField::LastPlayed => "last_played",
+            GameField::PlayTime => "play_time",
+            GameField::CompletionStatus => "completion_status",
+            GameField::Genres => "genres",
+            GameField::Developers => "developers",
+            GameField::Publishers => "publishers",
+        }
+    }
+    pub fn from_key(key: &str) -> Option<Self> {
+        Self::ALL.into_iter().find(|field| field.key() == key)
+    }
+    /// Whether metadata scanners provide this field
+    pub fn from_metadata(self) -> bool {
+        matches!(
+            self,
+            GameField::Description
+                | GameField::IconUrl
+                | GameField::HeaderUrl
+                | GameField::CoverUrl
+                | GameField::ReleaseDate
+                | GameField::Genres
+                | GameField::Developers
+                | GameField::Publishers
+        )
+    }
+}
+impl fmt::Display for GameField {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            GameField::Name => "Name",
+            GameField::Description => "Description",
+            GameField::IconUrl => "Icon URL",
+            GameField::HeaderUrl => "Header URL",
+            GameField::CoverUrl => "Cover URL",
+            GameField::InstallPath => "Install Path",
+            GameField::ReleaseDate => "Release Date",
+            GameField::LastPlayed => "Last Played",
+            GameField::PlayTime => "Playtime",
+            GameField::CompletionStatus => "Completion Status",
+            GameField::Genres => "Genres",
+            GameField::Developers => "Developers",
+            GameField::Publishers => "Publishers",
+        })
+    }
+}
+impl From<GameTextField> for GameField {
+    fn from(value: GameTextField) -> Self {
+        match value {
+            GameTextField::Name => GameField::Name,
+            GameTextField::Description => GameField::Description,
+            GameTextField::IconUrl => GameField::IconUrl,
+            GameTextField::HeaderUrl => GameField::HeaderUrl,
+            GameTextField::CoverUrl => GameField::CoverUrl,
+            GameTextField::InstallPath => GameField::InstallPath,
+        }
+    }
+}
+impl From<GameDateField> for GameField {
+    fn from(value: GameDateField) -> Self {
+        match value {
+            GameDateField::LastPlayed => GameField::LastPlayed,
+            GameDateField::ReleaseDate => GameField::ReleaseDate,
+        }
+    }
+}
+impl From<GameDurationField> for GameField {
+    fn from(value: GameDurationField) -> Self {
+        match value {
+            GameDurationField::TimePlayed => GameField::PlayTime,
+        }
+    }
+}
+impl From<GameListField> for GameField {
+    fn from(value: GameListField) -> Self {
+        match value {
+            GameListField::Genres => GameField::Genres,
+            GameListField::Developers => GameField::Developers,
+            GameListField::Publishers => GameField::Publishers,
+        }
+    }
+}
+#[derive(Debug, Copy, Clone, PartialEq)]
 pub enum Direction {
     Left,
     Right,
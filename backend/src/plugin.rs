@@ -1,7 +1,7 @@
 use gami_sdk::{
-    ConfigSchemaMetadata, GameInstallStatus, GameLibrary, GameLibraryRef, GameLibraryRefOwned,
-    GameMetadata, GameMetadataScanner, PluginDeclaration, PluginMetadata,
-    ScannedGameLibraryMetadata, ADDONS_DIR,
+    ActionError, ConfigSchemaMetadata, GameInstallStatus, GameLibrary, GameLibraryRef,
+    GameLibraryRefOwned, GameMetadata, GameMetadataScanner, LaunchCommand, LibraryAccount,
+    PluginDeclaration, PluginMetadata, ScannedGameLibraryMetadata, ADDONS_DIR, ANY_LIBRARY_TYPE,
 };
 use libloading::Library;
 use std::collections::HashMap;
@@ -15,21 +15,31 @@ use std::{fs, io};
 #[derive(Clone)]
 pub struct GameLibraryProxy {
     pub inner: Arc<dyn GameLibrary + Send + Sync>,
-    pub _lib: Arc<Library>,
+    /// `None` for libraries registered in-process
+    pub _lib: Option<Arc<Library>>,
 }
 impl GameLibrary for GameLibraryProxy {
-    fn scan(&self) -> Vec<ScannedGameLibraryMetadata> {
-        self.inner.scan()
+    fn get_accounts(&self) -> Vec<LibraryAccount> {
+        self.inner.get_accounts()
     }
-    fn launch(&self, game: GameLibraryRef) {
+    fn scan(&self, account_id: &str) -> Vec<ScannedGameLibraryMetadata> {
+        self.inner.scan(account_id)
+    }
+    fn launch(&self, game: GameLibraryRef) -> Result<(), ActionError> {
         self.inner.launch(game)
     }
+    fn launch_command(&self, game: GameLibraryRef) -> Option<LaunchCommand> {
+        self.inner.launch_command(game)
+    }
+    fn reports_play_stats(&self) -> bool {
+        self.inner.reports_play_stats()
+    }
 
-    fn install(&self, game: GameLibraryRef) {
+    fn install(&self, game: GameLibraryRef) -> Result<(), ActionError> {
         self.inner.install(game)
     }
 
-    fn uninstall(&self, game: GameLibraryRef) {
+    fn uninstall(&self, game: GameLibraryRef) -> Result<(), ActionError> {
         self.inner.uninstall(game)
     }
 
@@ -40,10 +50,14 @@ impl GameLibrary for GameLibraryProxy {
 #[derive(Clone)]
 pub struct GameMetadataScannerProxy {
     pub inner: Arc<dyn GameMetadataScanner + Send + Sync>,
-    pub _lib: Arc<Library>,
+    /// `None` for scanners registered in-process
+    pub _lib: Option<Arc<Library>>,
 }
 
 impl GameMetadataScanner for GameMetadataScannerProxy {
+    fn supported_library_types(&self) -> Vec<String> {
+        self.inner.supported_library_types()
+    }
     fn get_metadata(&self, game: GameLibraryRef) -> Option<GameMetadata> {
         self.inner.get_metadata(game)
     }
@@ -82,6 +96,60 @@ impl ExternalAddons {
         self.meta_scanners.get(name)
     }
 
+    pub fn get_metadata_scanner_keys(&self) -> Vec<&str> {
+        self.meta_scanners.keys().map(String::as_str).collect()
+    }
+
+    /// Every scanner able to describe games of `library_type`, sorted by key
+    pub fn get_metadata_scanners_for(
+        &self,
+        library_type: &str,
+    ) -> Vec<(&str, &GameMetadataScannerProxy)> {
+        let mut scanners: Vec<(&str, &GameMetadataScannerProxy)> = self
+            .meta_scanners
+            .iter()
+            .filter(|(key, scanner)| {
+                *key == library_type
+                    || scanner
+                        .supported_library_types()
+                        .iter()
+                        .any(|t| t == library_type || t == ANY_LIBRARY_TYPE)
+            })
+            .map(|(key, scanner)| (key.as_str(), scanner))
+            .collect();
+        scanners.sort_by_key(|(key, _)| *key);
+        scanners
+    }
+
+    /// Adds a game library implemented in-process instead of loaded from an addon
+    pub fn register_library(&mut self, name: &str, lib: Arc<dyn GameLibrary + Send + Sync>) {
+        self.game_libs.insert(
+            name.to_string(),
+            GameLibraryProxy {
+                inner: lib,
+                _lib: None,
+            },
+        );
+    }
+
+    /// Adds a metadata scanner implemented in-process instead of loaded from an addon
+    pub fn register_metadata_scanner(
+        &mut self,
+        name: &str,
+        scanner: Arc<dyn GameMetadataScanner + Send + Sync>,
+    ) {
+        self.meta_scanners.insert(
+            name.to_string(),
+            GameMetadataScannerProxy {
+                inner: scanner,
+                _lib: None,
+            },
+        );
+    }
+
+    /// # Safety
+    /// Loads and runs every library in the addons directory, which must be trusted gami addons
+    /// built against this SDK version.
     pub unsafe fn auto_load_addons(&mut self) -> io::Result<()> {
         log::info!("Automatically loading addons");
 
@@ -133,7 +201,7 @@ impl ExternalAddons {
         if decl.rustc_version != gami_sdk::RUSTC_VERSION
             || decl.core_version != gami_sdk::CORE_VERSION
         {
-            return Err(io::Error::new(io::ErrorKind::Other, "Version mismatch"));
+            return Err(io::Error::other("Version mismatch"));
         }
 
         let mut registrar = PluginRegistrar::new(Arc::clone(&library));
@@ -177,7 +245,7 @@ impl gami_sdk::PluginRegistrar for PluginRegistrar {
     fn register_library(&mut self, name: &str, lib: Arc<dyn GameLibrary + Send + Sync>) {
         let proxy = GameLibraryProxy {
             inner: lib,
-            _lib: Arc::clone(&self.lib),
+            _lib: Some(Arc::clone(&self.lib)),
         };
         self.game_libs.insert(name.to_string(), proxy);
     }
@@ -188,7 +256,7 @@ impl gami_sdk::PluginRegistrar for PluginRegistrar {
     ) {
         let proxy = GameMetadataScannerProxy {
             inner: lib,
-            _lib: Arc::clone(&self.lib),
+            _lib: Some(Arc::clone(&self.lib)),
         };
         self.game_meta_scanners.insert(name.to_string(), proxy);
     }
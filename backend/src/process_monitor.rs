@@ -0,0 +1,391 @@
+//! Telling which library games are running from the processes of the system
+//!
+//! A process belongs to a game when it descends from a process Gami launched for it, carries the
+//! `SteamAppId` of a Steam game in its environment (Steam's `reaper` passes it on to the whole tree),
+//! or runs an executable inside the game's own install directory. Only Linux is supported for now.
+
+use gami_sdk::GameData;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::io;
+use std::path::{Component, Path, PathBuf};
+use std::sync::{LazyLock, Mutex};
+
+/// Library type of the Steam addon, whose games are recognized by their app ID
+const STEAM_LIBRARY: &str = "steam";
+/// Parents followed up from a process looking for one Gami launched
+const MAX_ANCESTORS: usize = 32;
+
+/// Processes started by [`crate::launch::launch_game`], with the game they run
+static TRACKED: LazyLock<Mutex<HashMap<u32, i32>>> = LazyLock::new(Default::default);
+
+pub(crate) fn track(pid: u32, game_id: i32) {
+    TRACKED.lock().unwrap().insert(pid, game_id);
+}
+pub(crate) fn untrack(pid: u32) {
+    TRACKED.lock().unwrap().remove(&pid);
+}
+
+/// What the monitor knows about a process
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ProcessInfo {
+    pub pid: u32,
+    pub ppid: u32,
+    pub exe: Option<PathBuf>,
+    pub cmdline: Vec<String>,
+    /// `SteamAppId` from the environment, or the `AppId=` argument of Steam's `reaper`
+    pub steam_app_id: Option<String>,
+}
+
+/// A library game and the processes running it
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RunningGame {
+    pub game_id: i32,
+    pub name: String,
+    pub pids: Vec<u32>,
+}
+
+/// Reads every process of the system that is readable by the user
+#[cfg(target_os = "linux")]
+pub fn list_processes() -> Vec<ProcessInfo> {
+    let Ok(entries) = std::fs::read_dir("/proc") else {
+        return Vec::new();
+    };
+    entries
+        .flatten()
+        .filter_map(|entry| entry.file_name().to_str()?.parse::<u32>().ok())
+        .filter_map(read_process)
+        .collect()
+}
+
+#[cfg(not(target_os = "linux"))]
+pub fn list_processes() -> Vec<ProcessInfo> {
+    Vec::new()
+}
+
+#[cfg(target_os = "linux")]
+fn read_process(pid: u32) -> Option<ProcessInfo> {
+    use std::fs;
+
+    let dir = Path::new("/proc").join(pid.to_string());
+    // the command name in parentheses may contain spaces, the parent PID follows it
+    let stat = fs::read_to_string(dir.join("stat")).ok()?;
+    let ppid = stat
+        .get(stat.rfind(')')? + 1..)?
+        .split_whitespace()
+        .nth(1)?
+        .parse()
+        .ok()?;
+    let cmdline: Vec<String> = fs::read(dir.join("cmdline"))
+        .unwrap_or_default()
+        .split(|b| *b == 0)
+        .filter(|arg| !arg.is_empty())
+        .map(|arg| String::from_utf8_lossy(arg).into_owned())
+        .collect();
+    let from_env = fs::read(dir.join("environ")).ok().and_then(|environ| {
+        environ
+            .split(|b| *b == 0)
+            .find_map(|var| var.strip_prefix(b"SteamAppId="))
+            .map(|id| String::from_utf8_lossy(id).into_owned())
+    });
+    let from_reaper = cmdline
+        .first()
+        .filter(|program| program.ends_with("reaper"))
+        .and_then(|_| cmdline.iter().find_map(|arg| arg.strip_prefix("AppId=")))
+        .map(String::from);
+    Some(ProcessInfo {
+        pid,
+        ppid,
+        exe: fs::read_link(dir.join("exe")).ok(),
+        steam_app_id: from_env.or(from_reaper).filter(|id| id != "0"),
+        cmdline,
+    })
+}
+
+/// Directory components an install directory needs below the root, so `/games` or `/opt` never match
+const MIN_INSTALL_DEPTH: usize = 2;
+
+/// Install directories that identify a single game, each with its game
+///
+/// Shallow directories, the home directory and its parents are skipped, so are directories shared
+/// by several games or holding another game's, like an emulator's ROM folder or a library root.
+fn game_install_dirs(games: &[GameData]) -> Vec<(&Path, i32)> {
+    let home = dirs::home_dir();
+    let dirs: Vec<(&Path, i32)> = games
+        .iter()
+        .filter_map(|game| Some((Path::new(game.install_path.as_deref()?), game.id)))
+        .filter(|(dir, _)| {
+            dir.is_absolute()
+                && dir
+                    .components()
+                    .filter(|c| matches!(c, Component::Normal(_)))
+                    .count()
+                    >= MIN_INSTALL_DEPTH
+                && !home.as_deref().is_some_and(|home| home.starts_with(dir))
+        })
+        .collect();
+    dirs.iter()
+        .filter(|(dir, id)| {
+            !dirs
+                .iter()
+                .any(|(other, other_id)| other_id != id && other.starts_with(dir))
+        })
+        .copied()
+        .collect()
+}
+
+/// Groups processes by the game they run, see the module docs for how they are recognized
+///
+/// `tracked` maps processes Gami launched to their game.
+pub fn match_games(
+    processes: &[ProcessInfo],
+    games: &[GameData],
+    tracked: &HashMap<u32, i32>,
+) -> Vec<RunningGame> {
+    let parents: HashMap<u32, u32> = processes.iter().map(|p| (p.pid, p.ppid)).collect();
+    let launched_by_gami = |pid: u32| {
+        let mut pid = pid;
+        for _ in 0..MAX_ANCESTORS {
+            if let Some(game_id) = tracked.get(&pid) {
+                return Some(*game_id);
+            }
+            pid = *parents.get(&pid)?;
+        }
+        None
+    };
+    let steam_games: HashMap<&str, i32> = games
+        .iter()
+        .flat_map(|game| {
+            let own = (game.library_type.as_str(), game.library_id.as_str());
+            let sources = game
+                .sources
+                .iter()
+                .map(|s| (s.library_type.as_str(), s.library_id.as_str()));
+            [own]
+                .into_iter()
+                .chain(sources)
+                .filter(|(library_type, _)| *library_type == STEAM_LIBRARY)
+                .map(move |(_, library_id)| (library_id, game.id))
+        })
+        .collect();
+    let install_dirs = game_install_dirs(games);
+    let mut running: BTreeMap<i32, Vec<u32>> = BTreeMap::new();
+    for process in processes {
+        let program = process
+            .exe
+            .clone()
+            .or_else(|| process.cmdline.first().map(PathBuf::from));
+        let game_id = launched_by_gami(process.pid)
+            .or_else(|| steam_games.get(process.steam_app_id.as_deref()?).copied())
+            .or_else(|| {
+                let program = program.as_deref()?;
+                install_dirs
+                    .iter()
+                    .find(|(dir, _)| program.starts_with(dir))
+                    .map(|(_, game_id)| *game_id)
+            });
+        if let Some(game_id) = game_id {
+            running.entry(game_id).or_default().push(process.pid);
+        }
+    }
+    running
+        .into_iter()
+        .filter_map(|(game_id, pids)| {
+            let game = games.iter().find(|game| game.id == game_id)?;
+            Some(RunningGame {
+                game_id,
+                name: game.name.clone(),
+                pids,
+            })
+        })
+        .collect()
+}
+
+/// Which of `games` are running right now
+pub fn running_games(games: &[GameData]) -> Vec<RunningGame> {
+    let tracked = TRACKED.lock().unwrap().clone();
+    match_games(&list_processes(), games, &tracked)
+}
+
+/// Asks every process of a game to terminate
+pub fn stop_game(game: &RunningGame) -> io::Result<()> {
+    let pids = game.pids.iter().map(u32::to_string);
+    let status = if cfg!(windows) {
+        std::process::Command::new("taskkill")
+            .args(pids.flat_map(|pid| ["/PID".to_string(), pid]))
+            .status()?
+    } else {
+        std::process::Command::new("kill")
+            .arg("-TERM")
+            .args(pids)
+            .status()?
+    };
+    if status.success() {
+        Ok(())
+    } else {
+        Err(io::Error::other(format!(
+            "could not stop {}: kill {}",
+            game.name, status
+        )))
+    }
+}
+
+/// A game that started or stopped between two polls
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum MonitorEvent {
+    Started(RunningGame),
+    Stopped(RunningGame),
+}
+
+/// Remembers the running games of the last poll to report what changed
+#[derive(Debug, Clone, Default)]
+pub struct ProcessMonitor {
+    running: BTreeMap<i32, RunningGame>,
+}
+
+impl ProcessMonitor {
+    pub fn running(&self) -> impl Iterator<Item = &RunningGame> {
+        self.running.values()
+    }
+    pub fn get(&self, game_id: i32) -> Option<&RunningGame> {
+        self.running.get(&game_id)
+    }
+
+    /// Takes the games running now, returning which started and stopped since the last call
+    pub fn update(&mut self, running: Vec<RunningGame>) -> Vec<MonitorEvent> {
+        let mut now: BTreeMap<i32, RunningGame> = running
+            .into_iter()
+            .map(|game| (game.game_id, game))
+            .collect();
+        let mut events: Vec<MonitorEvent> = self
+            .running
+            .values()
+            .filter(|game| !now.contains_key(&game.game_id))
+            .cloned()
+            .map(MonitorEvent::Stopped)
+            .collect();
+        events.extend(
+            now.values()
+                .filter(|game| !self.running.contains_key(&game.game_id))
+                .cloned()
+                .map(MonitorEvent::Started),
+        );
+        std::mem::swap(&mut self.running, &mut now);
+        events
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gami_sdk::GameSource;
+
+    fn process(pid: u32, ppid: u32, exe: &str) -> ProcessInfo {
+        ProcessInfo {
+            pid,
+            ppid,
+            exe: Some(exe.into()),
+            ..ProcessInfo::default()
+        }
+    }
+
+    fn game(id: i32, library_type: &str, library_id: &str, install_path: Option<&str>) -> GameData {
+        GameData {
+            id,
+            name: format!("Game {}", id),
+            library_type: library_type.into(),
+            library_id: library_id.into(),
+            install_path: install_path.map(String::from),
+            sources: vec![GameSource {
+                id,
+                library_type: library_type.into(),
+                library_id: library_id.into(),
+                ..GameSource::default()
+            }],
+            ..GameData::default()
+        }
+    }
+
+    #[test]
+    fn test_matches_by_launch_steam_app_id_and_install_dir() {
+        let games = [
+            game(1, "emulation", "zelda", None),
+            game(2, STEAM_LIBRARY, "367520", None),
+            game(3, "gog", "celeste", Some("/games/Celeste")),
+        ];
+        let processes = [
+            process(10, 1, "/usr/bin/retroarch"),
+            process(11, 10, "/usr/bin/retroarch-helper"),
+            ProcessInfo {
+                steam_app_id: Some("367520".into()),
+                ..process(20, 1, "/usr/bin/wine64-preloader")
+            },
+            process(30, 1, "/games/Celeste/Celeste.bin.x86_64"),
+            process(40, 1, "/games/Other/game"),
+            process(50, 1, "/usr/bin/bash"),
+        ];
+        let tracked = HashMap::from([(10, 1)]);
+
+        let running = match_games(&processes, &games, &tracked);
+
+        let pids: Vec<(i32, Vec<u32>)> = running
+            .into_iter()
+            .map(|game| (game.game_id, game.pids))
+            .collect();
+        assert_eq!(pids, [(1, vec![10, 11]), (2, vec![20]), (3, vec![30])]);
+    }
+
+    #[test]
+    fn test_root_install_dir_matches_nothing() {
+        let games = [game(1, "gog", "celeste", Some("/"))];
+        let running = match_games(&[process(10, 1, "/usr/bin/bash")], &games, &HashMap::new());
+        assert!(running.is_empty());
+    }
+
+    #[test]
+    fn test_only_game_specific_install_dirs_match() {
+        let games = [
+            game(1, "gog", "celeste", Some("/usr")),
+            game(2, "gog", "hades", Some("/opt/games")),
+            game(3, "gog", "braid", Some("/opt/games/Braid")),
+            game(4, "emulation", "zelda", Some("/srv/roms")),
+            game(5, "emulation", "metroid", Some("/srv/roms")),
+        ];
+        let processes = [
+            process(10, 1, "/usr/bin/bash"),
+            process(20, 1, "/opt/games/Other/game"),
+            process(30, 1, "/opt/games/Braid/braid"),
+            process(40, 1, "/srv/roms/tool"),
+        ];
+
+        let running = match_games(&processes, &games, &HashMap::new());
+
+        let pids: Vec<(i32, Vec<u32>)> = running
+            .into_iter()
+            .map(|game| (game.game_id, game.pids))
+            .collect();
+        assert_eq!(pids, [(3, vec![30])]);
+    }
+
+    #[test]
+    fn test_monitor_reports_changes() {
+        let running = |id: i32| RunningGame {
+            game_id: id,
+            name: format!("Game {}", id),
+            pids: vec![id as u32],
+        };
+        let mut monitor = ProcessMonitor::default();
+        assert_eq!(
+            monitor.update(vec![running(1)]),
+            [MonitorEvent::Started(running(1))]
+        );
+        assert!(monitor.update(vec![running(1)]).is_empty());
+        assert_eq!(
+            monitor.update(vec![running(2)]),
+            [
+                MonitorEvent::Stopped(running(1)),
+                MonitorEvent::Started(running(2))
+            ]
+        );
+    }
+}
@@ -0,0 +1,408 @@
+//! Protocol of the local control API served by `gami-daemon`
+//!
+//! Each line on the socket is one JSON-RPC 2.0 message. Clients that call `events.subscribe`
+//! also receive an `event` notification for every [`Event`].
+
+use crate::action_executor::ActionFailure;
+use crate::db::filter::{FacetFilters, FilterExpr};
+use crate::db::ops::{GamesFilters, HiddenFilter, Sort};
+use crate::launch::LaunchError;
+use chrono::{DateTime, NaiveDate, Utc};
+use gami_sdk::{CompletionStatus, GameData, GameInstallStatus, BASE_DATA_DIR};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::fmt;
+use std::path::PathBuf;
+
+pub const JSONRPC_VERSION: &str = "2.0";
+
+pub const PARSE_ERROR: i32 = -32700;
+pub const INVALID_REQUEST: i32 = -32600;
+pub const METHOD_NOT_FOUND: i32 = -32601;
+pub const INVALID_PARAMS: i32 = -32602;
+pub const INTERNAL_ERROR: i32 = -32603;
+/// A game or library that doesn't exist
+pub const NOT_FOUND: i32 = 1;
+pub const SYNC_IN_PROGRESS: i32 = 2;
+/// The pre-launch script failed or the game could not be started, see the launch log
+pub const LAUNCH_FAILED: i32 = 3;
+/// `games.stop` was called for a game that isn't running
+pub const NOT_RUNNING: i32 = 4;
+/// The library could not install or uninstall the game, or took too long
+pub const ACTION_FAILED: i32 = 5;
+
+/// Method of the notifications carrying an [`Event`]
+pub const EVENT_METHOD: &str = "event";
+
+/// Socket the daemon listens on unless told otherwise
+///
+/// Lives in the runtime directory where there is one, so it's private to the user.
+pub fn default_socket_path() -> PathBuf {
+    std::env::var_os("XDG_RUNTIME_DIR")
+        .map(|dir| PathBuf::from(dir).join("gami"))
+        .unwrap_or_else(|| BASE_DATA_DIR.clone())
+        .join("daemon.sock")
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Request {
+    pub jsonrpc: String,
+    /// Missing for notifications, which get no response
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub id: Option<Value>,
+    pub method: String,
+    #[serde(default)]
+    pub params: Value,
+}
+impl Request {
+    pub fn new(id: u64, method: &str, params: Value) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id: Some(id.into()),
+            method: method.into(),
+            params,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Response {
+    pub jsonrpc: String,
+    pub id: Value,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub result: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<RpcError>,
+}
+impl Response {
+    pub fn new(id: Value, result: Result<Value, RpcError>) -> Self {
+        let (result, error) = match result {
+            Ok(result) => (Some(result), None),
+            Err(error) => (None, Some(error)),
+        };
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            id,
+            result,
+            error,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct RpcError {
+    pub code: i32,
+    pub message: String,
+}
+impl RpcError {
+    pub fn new(code: i32, message: impl Into<String>) -> Self {
+        Self {
+            code,
+            message: message.into(),
+        }
+    }
+}
+impl fmt::Display for RpcError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (code {})", self.message, self.code)
+    }
+}
+impl From<LaunchError> for RpcError {
+    fn from(err: LaunchError) -> Self {
+        match err {
+            LaunchError::NoLibrary(_) => RpcError::new(NOT_FOUND, err.to_string()),
+            LaunchError::Db(err) => err.into(),
+            err => RpcError::new(LAUNCH_FAILED, err.to_string()),
+        }
+    }
+}
+impl From<ActionFailure> for RpcError {
+    fn from(err: ActionFailure) -> Self {
+        match err {
+            ActionFailure::NoLibrary(_) => RpcError::new(NOT_FOUND, err.to_string()),
+            err => RpcError::new(ACTION_FAILED, err.to_string()),
+        }
+    }
+}
+impl From<sea_orm::DbErr> for RpcError {
+    fn from(err: sea_orm::DbErr) -> Self {
+        RpcError::new(INTERNAL_ERROR, format!("database error: {}", err))
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct Notification {
+    pub jsonrpc: String,
+    pub method: String,
+    pub params: Event,
+}
+impl Notification {
+    pub fn new(event: Event) -> Self {
+        Self {
+            jsonrpc: JSONRPC_VERSION.into(),
+            method: EVENT_METHOD.into(),
+            params: event,
+        }
+    }
+}
+
+/// Something that happened in the daemon, pushed to subscribed clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum Event {
+    SyncStarted {
+        libraries: Vec<String>,
+    },
+    /// `done` of `total` libraries were synced, the last being `library`
+    SyncProgress {
+        library: String,
+        done: usize,
+        total: usize,
+    },
+    SyncFinished {
+        added: u64,
+    },
+    SyncFailed {
+        message: String,
+    },
+    InstallStatusChanged {
+        game_id: i32,
+        status: GameInstallStatus,
+    },
+    SessionStarted {
+        game_id: i32,
+        name: String,
+    },
+    /// A game the daemon launched exited and its post-exit script ran
+    ///
+    /// Not sent when the library's client started the game, only [`Event::GameStopped`] tells then.
+    SessionEnded {
+        game_id: i32,
+        name: String,
+    },
+    /// The process monitor found the game running, however it was started
+    GameRunning {
+        game_id: i32,
+        name: String,
+    },
+    GameStopped {
+        game_id: i32,
+        name: String,
+    },
+}
+
+/// Params of `games.list`, mirroring [`GamesFilters`]
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ListParams {
+    pub search: String,
+    /// ID or name of a library account, resolved by the daemon
+    pub account: Option<String>,
+    pub sort: Vec<Sort>,
+    pub tags: Vec<i32>,
+    pub favourites_only: bool,
+    pub hidden: HiddenFilter,
+    pub expr: Option<FilterExpr>,
+    pub facets: FacetFilters,
+}
+/// Takes everything but the account, which has to be looked up in the store
+impl From<ListParams> for GamesFilters {
+    fn from(params: ListParams) -> Self {
+        GamesFilters {
+            search: params.search,
+            sort: params.sort,
+            tags: params.tags,
+            favourites_only: params.favourites_only,
+            hidden: params.hidden,
+            expr: params.expr,
+            facets: params.facets,
+            ..GamesFilters::default()
+        }
+    }
+}
+
+/// Params of `games.search`
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SearchParams {
+    pub query: String,
+    pub limit: Option<usize>,
+}
+
+/// Params of `games.launch`, `games.install` and `games.uninstall`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameParams {
+    pub id: i32,
+}
+
+/// Params of `library.sync`, every library is synced without one
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SyncParams {
+    pub library: Option<String>,
+}
+
+/// Result of `status`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DaemonStatus {
+    pub version: String,
+    pub games: u64,
+    pub syncing: bool,
+    pub addons: Vec<String>,
+    pub libraries: Vec<String>,
+}
+
+/// A game as listed to clients
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct GameSummary {
+    pub id: i32,
+    pub name: String,
+    pub library_type: String,
+    pub library_id: String,
+    pub account_id: String,
+    pub install_status: GameInstallStatus,
+    pub completion_status: CompletionStatus,
+    pub play_time_secs: i64,
+    pub last_played: Option<DateTime<Utc>>,
+    pub release_date: Option<NaiveDate>,
+    pub genres: Vec<String>,
+    pub tags: Vec<String>,
+    pub favourite: bool,
+    pub hidden: bool,
+}
+impl From<&GameData> for GameSummary {
+    fn from(game: &GameData) -> Self {
+        Self {
+            id: game.id,
+            name: game.name.clone(),
+            library_type: game.library_type.clone(),
+            library_id: game.library_id.clone(),
+            account_id: game.account_id.clone(),
+            install_status: game.install_status,
+            completion_status: game.completion_status,
+            play_time_secs: game.play_time.num_seconds(),
+            last_played: game.last_played,
+            release_date: game.release_date,
+            genres: game.genres.iter().map(|g| g.name.to_string()).collect(),
+            tags: game.tags.iter().map(|t| t.name.clone()).collect(),
+            favourite: game.favourite,
+            hidden: game.hidden,
+        }
+    }
+}
+
+#[cfg(unix)]
+pub use client::{Client, ClientError};
+
+#[cfg(unix)]
+mod client {
+    use super::{Event, Request, Response, RpcError, EVENT_METHOD};
+    use serde::de::DeserializeOwned;
+    use serde::Serialize;
+    use serde_json::Value;
+    use std::collections::VecDeque;
+    use std::fmt;
+    use std::io::{self, BufRead, BufReader, Write};
+    use std::os::unix::net::UnixStream;
+    use std::path::Path;
+
+    #[derive(Debug)]
+    pub enum ClientError {
+        Io(io::Error),
+        Parse(serde_json::Error),
+        Rpc(RpcError),
+    }
+    impl fmt::Display for ClientError {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                ClientError::Io(err) => write!(f, "could not reach the daemon: {}", err),
+                ClientError::Parse(err) => write!(f, "unexpected message from the daemon: {}", err),
+                ClientError::Rpc(err) => err.fmt(f),
+            }
+        }
+    }
+    impl From<io::Error> for ClientError {
+        fn from(err: io::Error) -> Self {
+            ClientError::Io(err)
+        }
+    }
+    impl From<serde_json::Error> for ClientError {
+        fn from(err: serde_json::Error) -> Self {
+            ClientError::Parse(err)
+        }
+    }
+
+    /// Blocking connection to the daemon, for tools that make a few calls
+    pub struct Client {
+        reader: BufReader<UnixStream>,
+        writer: UnixStream,
+        next_id: u64,
+        /// Events that arrived while waiting for a response
+        events: VecDeque<Event>,
+    }
+
+    impl Client {
+        pub fn connect(path: &Path) -> io::Result<Self> {
+            let writer = UnixStream::connect(path)?;
+            Ok(Self {
+                reader: BufReader::new(writer.try_clone()?),
+                writer,
+                next_id: 1,
+                events: VecDeque::new(),
+            })
+        }
+
+        pub fn call<P: Serialize, R: DeserializeOwned>(
+            &mut self,
+            method: &str,
+            params: P,
+        ) -> Result<R, ClientError> {
+            let id = self.next_id;
+            self.next_id += 1;
+            let request = Request::new(id, method, serde_json::to_value(params)?);
+            let mut line = serde_json::to_string(&request)?;
+            line.push('\n');
+            self.writer.write_all(line.as_bytes())?;
+            loop {
+                let message = self.read_message()?;
+                if message.get("method").and_then(Value::as_str) == Some(EVENT_METHOD) {
+                    self.events.push_back(parse_event(message)?);
+                    continue;
+                }
+                let response: Response = serde_json::from_value(message)?;
+                if response.id != id {
+                    continue;
+                }
+                return match response.error {
+                    Some(err) => Err(ClientError::Rpc(err)),
+                    None => Ok(serde_json::from_value(response.result.unwrap_or_default())?),
+                };
+            }
+        }
+
+        /// Waits for the next event, only sent after calling `events.subscribe`
+        pub fn next_event(&mut self) -> Result<Event, ClientError> {
+            if let Some(event) = self.events.pop_front() {
+                return Ok(event);
+            }
+            loop {
+                let message = self.read_message()?;
+                if message.get("method").and_then(Value::as_str) == Some(EVENT_METHOD) {
+                    return parse_event(message);
+                }
+            }
+        }
+
+        fn read_message(&mut self) -> Result<Value, ClientError> {
+            let mut line = String::new();
+            if self.reader.read_line(&mut line)? == 0 {
+                return Err(io::Error::from(io::ErrorKind::UnexpectedEof).into());
+            }
+            Ok(serde_json::from_str(&line)?)
+        }
+    }
+
+    fn parse_event(mut message: Value) -> Result<Event, ClientError> {
+        Ok(serde_json::from_value(message["params"].take())?)
+    }
+}
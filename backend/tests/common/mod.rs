@@ -0,0 +1,184 @@
+//! In-process fakes of the addon interfaces, backed by an in-memory library store
+
+// every test crate includes this module but uses only part of it
+#![allow(dead_code)]
+
+use gami_backend::db::{DbLocation, LibraryStore};
+use gami_backend::plugin::ExternalAddons;
+use gami_sdk::{
+    ActionError, GameInstallStatus, GameLibrary, GameLibraryRef, GameLibraryRefOwned, GameMetadata,
+    GameMetadataScanner, GenreData, LaunchCommand, LibraryAccount, ScannedGameLibraryMetadata,
+};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+pub const FAKE_LIBRARY: &str = "fake";
+
+/// Game library returning whatever games the test scripted for its next scans
+#[derive(Default)]
+pub struct FakeLibrary {
+    games: Mutex<Vec<ScannedGameLibraryMetadata>>,
+    /// Accounts with the games scanned for each, all scans return `games` while there are none
+    accounts: Mutex<Vec<(LibraryAccount, Vec<ScannedGameLibraryMetadata>)>>,
+    launch_command: Mutex<Option<LaunchCommand>>,
+    action_error: Mutex<Option<ActionError>>,
+    play_stats: Mutex<bool>,
+}
+
+impl FakeLibrary {
+    pub fn set_games(&self, games: Vec<ScannedGameLibraryMetadata>) {
+        *self.games.lock().unwrap() = games;
+    }
+    /// Gives the library accounts, each scanning its own games
+    pub fn set_accounts(&self, accounts: Vec<(&str, Vec<ScannedGameLibraryMetadata>)>) {
+        *self.accounts.lock().unwrap() = accounts
+            .into_iter()
+            .map(|(name, games)| {
+                let account = LibraryAccount {
+                    account_id: name.to_lowercase().into(),
+                    name: name.into(),
+                };
+                (account, games)
+            })
+            .collect();
+    }
+    /// Makes every game run `command` instead of being handed to a client
+    pub fn set_launch_command(&self, command: LaunchCommand) {
+        *self.launch_command.lock().unwrap() = Some(command);
+    }
+    /// Makes scans count as reporting play stats, so resyncs update them
+    pub fn report_play_stats(&self) {
+        *self.play_stats.lock().unwrap() = true;
+    }
+    /// Makes launching, installing and uninstalling fail like a missing client would
+    pub fn fail_actions(&self, err: ActionError) {
+        *self.action_error.lock().unwrap() = Some(err);
+    }
+    fn action_result(&self) -> Result<(), ActionError> {
+        self.action_error
+            .lock()
+            .unwrap()
+            .clone()
+            .map_or(Ok(()), Err)
+    }
+}
+
+impl GameLibrary for FakeLibrary {
+    fn get_accounts(&self) -> Vec<LibraryAccount> {
+        let accounts = self.accounts.lock().unwrap();
+        accounts
+            .iter()
+            .map(|(account, _)| account.clone())
+            .collect()
+    }
+    fn scan(&self, account_id: &str) -> Vec<ScannedGameLibraryMetadata> {
+        let accounts = self.accounts.lock().unwrap();
+        match accounts
+            .iter()
+            .find(|(account, _)| *account.account_id == *account_id)
+        {
+            Some((_, games)) => games.clone(),
+            None => self.games.lock().unwrap().clone(),
+        }
+    }
+    fn launch(&self, _game: GameLibraryRef) -> Result<(), ActionError> {
+        self.action_result()
+    }
+    fn launch_command(&self, _game: GameLibraryRef) -> Option<LaunchCommand> {
+        self.launch_command.lock().unwrap().clone()
+    }
+    fn reports_play_stats(&self) -> bool {
+        *self.play_stats.lock().unwrap()
+    }
+    fn install(&self, _game: GameLibraryRef) -> Result<(), ActionError> {
+        self.action_result()
+    }
+    fn uninstall(&self, _game: GameLibraryRef) -> Result<(), ActionError> {
+        self.action_result()
+    }
+    fn check_install_status(&self, _game: GameLibraryRef) -> GameInstallStatus {
+        GameInstallStatus::InLibrary
+    }
+}
+
+/// Metadata scanner describing games by their library id
+#[derive(Default)]
+pub struct FakeScanner {
+    metadatas: Mutex<HashMap<String, GameMetadata>>,
+}
+
+impl FakeScanner {
+    pub fn set_metadata(&self, library_id: &str, metadata: GameMetadata) {
+        self.metadatas
+            .lock()
+            .unwrap()
+            .insert(library_id.to_string(), metadata);
+    }
+}
+
+impl GameMetadataScanner for FakeScanner {
+    fn get_metadata(&self, game: GameLibraryRef) -> Option<GameMetadata> {
+        let game = game.to_owned();
+        self.metadatas
+            .lock()
+            .unwrap()
+            .get(&game.library_id)
+            .cloned()
+    }
+    fn get_metadatas<'a>(
+        &self,
+        games: &[GameLibraryRef<'a>],
+    ) -> HashMap<GameLibraryRefOwned, GameMetadata> {
+        games
+            .iter()
+            .filter_map(|game| Some(((*game).into(), self.get_metadata(*game)?)))
+            .collect()
+    }
+}
+
+pub struct Harness {
+    pub store: LibraryStore,
+    pub addons: ExternalAddons,
+    pub library: Arc<FakeLibrary>,
+    pub scanner: Arc<FakeScanner>,
+}
+
+/// Opens an empty in-memory store with [`FakeLibrary`] and its [`FakeScanner`] registered
+pub async fn harness() -> Harness {
+    harness_at(DbLocation::Memory).await
+}
+
+/// Like [`harness`], with the store at `location`
+pub async fn harness_at(location: DbLocation) -> Harness {
+    let store = LibraryStore::open(location)
+        .await
+        .expect("store should open");
+    let library = Arc::new(FakeLibrary::default());
+    let scanner = Arc::new(FakeScanner::default());
+    let mut addons = ExternalAddons::new();
+    addons.register_library(FAKE_LIBRARY, library.clone());
+    addons.register_metadata_scanner(FAKE_LIBRARY, scanner.clone());
+    Harness {
+        store,
+        addons,
+        library,
+        scanner,
+    }
+}
+
+pub fn scanned(library_id: &str, name: &str, playtime_secs: u64) -> ScannedGameLibraryMetadata {
+    ScannedGameLibraryMetadata {
+        name: name.into(),
+        library_type: FAKE_LIBRARY.into(),
+        library_id: library_id.into(),
+        playtime_secs,
+        ..ScannedGameLibraryMetadata::default()
+    }
+}
+
+pub fn genre(library_id: &str, name: &str) -> GenreData {
+    GenreData {
+        name: name.into(),
+        library_id: library_id.into(),
+    }
+}
@@ -0,0 +1,210 @@
+mod common;
+
+use common::{harness, scanned, Harness, FAKE_LIBRARY};
+use gami_backend::db::filter::{CompareOp, FacetFilters, FilterExpr, Year};
+use gami_backend::db::ops::GamesFilters;
+use gami_sdk::{CompletionStatus, GameMetadata};
+
+/// Syncs Braid (2008), Celeste (2018, tagged Co-op) and Hades (2020, played)
+async fn library() -> Harness {
+    let h = harness().await;
+    h.library.set_games(vec![
+        scanned("1", "Braid", 0),
+        scanned("2", "Celeste", 0),
+        scanned("3", "Hades", 0),
+    ]);
+    for (library_id, released) in [
+        ("1", 1_217_980_800),
+        ("2", 1_516_838_400),
+        ("3", 1_600_300_800),
+    ] {
+        h.scanner.set_metadata(
+            library_id,
+            GameMetadata {
+                release_date_timestamp: Some(released).into(),
+                ..GameMetadata::default()
+            },
+        );
+    }
+    h.store.sync_library(&h.addons).await.unwrap();
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    let tag = h
+        .store
+        .create_tag("Co-op".into(), "#808080".into())
+        .await
+        .unwrap();
+    h.store
+        .add_tag_to_games(tag.id, vec![games[1].id])
+        .await
+        .unwrap();
+    h.store
+        .set_completion_status_bulk(vec![games[2].id], CompletionStatus::Played)
+        .await
+        .unwrap();
+    h
+}
+
+async fn matching(h: &Harness, filters: GamesFilters) -> Vec<String> {
+    h.store
+        .get_games(filters)
+        .await
+        .unwrap()
+        .into_iter()
+        .map(|game| game.name)
+        .collect()
+}
+
+async fn matching_expr(h: &Harness, expr: FilterExpr) -> Vec<String> {
+    let filters = GamesFilters {
+        expr: Some(expr),
+        ..GamesFilters::default()
+    };
+    matching(h, filters).await
+}
+
+fn release_year(op: CompareOp, year: i32) -> FilterExpr {
+    FilterExpr::ReleaseYear {
+        op,
+        year: Year::new(year).unwrap(),
+    }
+}
+
+#[tokio::test]
+async fn test_and_or_not_combine_children() {
+    let h = library().await;
+    let played = || FilterExpr::CompletionStatus(CompletionStatus::Played);
+    let coop = || FilterExpr::Tag("Co-op".into());
+
+    assert_eq!(
+        matching_expr(&h, FilterExpr::Or(vec![played(), coop()])).await,
+        ["Celeste", "Hades"]
+    );
+    assert_eq!(
+        matching_expr(&h, FilterExpr::And(vec![played(), coop()])).await,
+        Vec::<String>::new()
+    );
+    assert_eq!(
+        matching_expr(
+            &h,
+            FilterExpr::Not(Box::new(FilterExpr::Or(vec![played(), coop()])))
+        )
+        .await,
+        ["Braid"]
+    );
+    assert_eq!(
+        matching_expr(&h, FilterExpr::And(Vec::new())).await,
+        ["Braid", "Celeste", "Hades"]
+    );
+    assert!(matching_expr(&h, FilterExpr::Or(Vec::new()))
+        .await
+        .is_empty());
+}
+
+#[tokio::test]
+async fn test_tag_and_status_match_their_games() {
+    let h = library().await;
+
+    assert_eq!(
+        matching_expr(&h, FilterExpr::Tag("Co-op".into())).await,
+        ["Celeste"]
+    );
+    assert!(matching_expr(&h, FilterExpr::Tag("Solo".into()))
+        .await
+        .is_empty());
+    assert_eq!(
+        matching_expr(&h, FilterExpr::CompletionStatus(CompletionStatus::Played)).await,
+        ["Hades"]
+    );
+}
+
+#[tokio::test]
+async fn test_release_year_compares_whole_years() {
+    let h = library().await;
+
+    assert_eq!(
+        matching_expr(&h, release_year(CompareOp::Eq, 2018)).await,
+        ["Celeste"]
+    );
+    assert_eq!(
+        matching_expr(&h, release_year(CompareOp::Lt, 2018)).await,
+        ["Braid"]
+    );
+    assert_eq!(
+        matching_expr(&h, release_year(CompareOp::Le, 2018)).await,
+        ["Braid", "Celeste"]
+    );
+    assert_eq!(
+        matching_expr(&h, release_year(CompareOp::Ge, 2018)).await,
+        ["Celeste", "Hades"]
+    );
+    assert_eq!(
+        matching_expr(&h, release_year(CompareOp::Gt, 2018)).await,
+        ["Hades"]
+    );
+}
+
+#[tokio::test]
+async fn test_facets_match_any_value_and_every_facet() {
+    let h = library().await;
+    let facets = FacetFilters {
+        completion_status: vec![CompletionStatus::Played, CompletionStatus::Backlog],
+        release_year_min: Year::new(2010),
+        release_year_max: Year::new(2019),
+        ..FacetFilters::default()
+    };
+
+    let filters = GamesFilters {
+        facets,
+        ..GamesFilters::default()
+    };
+    assert_eq!(matching(&h, filters).await, ["Celeste"]);
+
+    let played = GamesFilters {
+        facets: FacetFilters {
+            completion_status: vec![CompletionStatus::Played],
+            ..FacetFilters::default()
+        },
+        ..GamesFilters::default()
+    };
+    assert_eq!(matching(&h, played).await, ["Hades"]);
+}
+
+#[tokio::test]
+async fn test_account_matches_only_its_games() {
+    let h = harness().await;
+    h.library.set_accounts(vec![
+        (
+            "Alice",
+            vec![scanned("1", "Braid", 0), scanned("2", "Celeste", 0)],
+        ),
+        (
+            "Bob",
+            vec![scanned("2", "Celeste", 0), scanned("3", "Hades", 0)],
+        ),
+    ]);
+    h.store.sync_library(&h.addons).await.unwrap();
+    let accounts = h.store.get_accounts().await.unwrap();
+    assert_eq!(accounts.len(), 2);
+
+    let games_of = |name: &str| GamesFilters {
+        account: accounts
+            .iter()
+            .find(|account| account.name == name)
+            .cloned(),
+        ..GamesFilters::default()
+    };
+    assert_eq!(matching(&h, games_of("Alice")).await, ["Braid", "Celeste"]);
+    assert_eq!(matching(&h, games_of("Bob")).await, ["Celeste", "Hades"]);
+    assert_eq!(
+        matching(&h, GamesFilters::default()).await,
+        ["Braid", "Celeste", "Celeste", "Hades"]
+    );
+
+    let mut other_library = accounts[0].clone();
+    other_library.library_type = format!("{}-other", FAKE_LIBRARY);
+    let filters = GamesFilters {
+        account: Some(other_library),
+        ..GamesFilters::default()
+    };
+    assert!(matching(&h, filters).await.is_empty());
+}
@@ -0,0 +1,70 @@
+-- Subset of the GOG Galaxy 2.0 galaxy-2.0.db schema with a few games
+CREATE TABLE GamePieceTypes (
+    id INTEGER PRIMARY KEY,
+    type TEXT UNIQUE NOT NULL
+);
+CREATE TABLE GamePieces (
+    releaseKey TEXT NOT NULL,
+    gamePieceTypeId INTEGER NOT NULL,
+    userId INTEGER NOT NULL,
+    value TEXT NOT NULL
+);
+CREATE TABLE LibraryReleases (
+    releaseKey TEXT NOT NULL,
+    userId INTEGER NOT NULL
+);
+CREATE TABLE GameTimes (
+    releaseKey TEXT NOT NULL,
+    userId INTEGER NOT NULL,
+    minutesInGame INTEGER NOT NULL
+);
+CREATE TABLE LastPlayedDates (
+    gameReleaseKey TEXT NOT NULL,
+    userId INTEGER NOT NULL,
+    lastPlayedDate TEXT
+);
+CREATE TABLE UserReleaseTags (
+    releaseKey TEXT NOT NULL,
+    userId INTEGER NOT NULL,
+    tag TEXT NOT NULL
+);
+CREATE TABLE UserReleaseProperties (
+    releaseKey TEXT NOT NULL,
+    userId INTEGER NOT NULL,
+    isHidden INTEGER NOT NULL
+);
+
+INSERT INTO GamePieceTypes (id, type)
+VALUES (1, 'originalTitle'),
+       (2, 'title'),
+       (3, 'originalMeta'),
+       (4, 'meta'),
+       (5, 'summary');
+
+INSERT INTO LibraryReleases (releaseKey, userId)
+VALUES ('steam_620', 4242),
+       ('gog_1207664663', 4242),
+       ('gog_1207664663', 4343);
+
+INSERT INTO GamePieces (releaseKey, gamePieceTypeId, userId, value)
+VALUES ('gog_1207664663', 1, 4242, '{"title":"The Witcher 3: Wild Hunt"}'),
+       ('gog_1207664663', 2, 4242, '{"title":"The Witcher 3: Wild Hunt - GOTY"}'),
+       ('gog_1207664663', 3, 4242, '{"releaseDate":1431993600,"developers":["CD PROJEKT RED"],"publishers":["CD PROJEKT RED"],"genres":["Role-playing"]}'),
+       ('gog_1207664663', 5, 4242, '{"summary":"Geralt hunts monsters."}'),
+       ('steam_620', 1, 4242, '{"title":"Portal 2"}'),
+       ('steam_620', 3, 4242, '{"releaseDate":null,"developers":[],"publishers":[]}');
+
+INSERT INTO GameTimes (releaseKey, userId, minutesInGame)
+VALUES ('gog_1207664663', 4242, 6120),
+       ('steam_620', 4242, 0);
+
+INSERT INTO LastPlayedDates (gameReleaseKey, userId, lastPlayedDate)
+VALUES ('gog_1207664663', 4242, '2023-11-04 20:41:09');
+
+INSERT INTO UserReleaseTags (releaseKey, userId, tag)
+VALUES ('gog_1207664663', 4242, 'RPG'),
+       ('gog_1207664663', 4242, 'Beaten');
+
+INSERT INTO UserReleaseProperties (releaseKey, userId, isHidden)
+VALUES ('steam_620', 4242, 1),
+       ('gog_1207664663', 4242, 0);
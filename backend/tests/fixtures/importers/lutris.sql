@@ -0,0 +1,51 @@
+-- Subset of the Lutris pga.db schema with a few games
+CREATE TABLE games (
+    id INTEGER PRIMARY KEY,
+    name TEXT,
+    slug TEXT,
+    installer_slug TEXT,
+    parent_slug TEXT,
+    platform TEXT,
+    runner TEXT,
+    executable TEXT,
+    directory TEXT,
+    updated DATETIME,
+    lastplayed INTEGER,
+    installed INTEGER,
+    installed_at INTEGER,
+    year INTEGER,
+    configpath TEXT,
+    has_custom_banner INTEGER,
+    has_custom_icon INTEGER,
+    has_custom_coverart_big INTEGER,
+    playtime REAL,
+    hidden INTEGER,
+    service TEXT,
+    service_id TEXT,
+    discord_id TEXT
+);
+CREATE TABLE categories (
+    id INTEGER PRIMARY KEY,
+    name TEXT UNIQUE
+);
+CREATE TABLE games_categories (
+    game_id INTEGER,
+    category_id INTEGER
+);
+
+INSERT INTO games (id, name, slug, runner, directory, lastplayed, installed, playtime, service, service_id)
+VALUES (1, 'Hades', 'hades', 'wine', '/home/user/Games/hades', 1709320500, 1, 1.5, 'gog', '1207658924'),
+       (2, 'GZDoom', 'gzdoom', 'linux', '', 0, 0, NULL, NULL, NULL),
+       (3, 'Control', 'control', 'wine', NULL, NULL, 0, 0.0, 'egs', 'calluna');
+
+INSERT INTO categories (id, name)
+VALUES (1, 'favorite'),
+       (2, '.hidden'),
+       (3, 'Roguelike'),
+       (4, 'Completed');
+
+INSERT INTO games_categories (game_id, category_id)
+VALUES (1, 1),
+       (1, 3),
+       (1, 4),
+       (2, 2);
@@ -0,0 +1,163 @@
+#![cfg(unix)]
+
+mod common;
+
+use common::{harness, scanned, Harness};
+use gami_backend::action_executor::{run_library_action, ActionFailure, LibraryAction};
+use gami_backend::db::ops::GamesFilters;
+use gami_backend::launch::{
+    launch_game, LaunchError, LaunchProfile, LaunchStage, PostLaunchAction,
+};
+use gami_sdk::{ActionError, GameData, LaunchCommand};
+
+async fn synced_game(h: &Harness) -> GameData {
+    h.library.set_games(vec![scanned("1", "Celeste", 0)]);
+    h.store.sync_library(&h.addons).await.unwrap();
+    h.store
+        .get_games(GamesFilters::default())
+        .await
+        .unwrap()
+        .remove(0)
+}
+
+#[tokio::test]
+async fn test_failing_pre_launch_script_cancels_the_launch() {
+    let h = harness().await;
+    let game = synced_game(&h).await;
+    let profile = LaunchProfile {
+        pre_launch: Some("echo mounting saves; exit 3".into()),
+        ..LaunchProfile::default()
+    };
+    h.store
+        .set_launch_profile(Some(game.id), profile)
+        .await
+        .unwrap();
+
+    let result = launch_game(&h.store, &h.addons, &game).await;
+
+    assert!(matches!(result, Err(LaunchError::PreLaunchFailed(_))));
+    let log = h.store.get_launch_log(game.id).await.unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].stage, LaunchStage::PreLaunch);
+    assert!(!log[0].success);
+    assert!(log[0].output.starts_with("mounting saves"));
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(games[0].last_played, None);
+}
+
+#[tokio::test]
+async fn test_tracked_launch_applies_profile_and_runs_post_exit_script() {
+    let h = harness().await;
+    let game = synced_game(&h).await;
+    h.library.set_launch_command(LaunchCommand {
+        program: "sh".into(),
+        args: vec!["-c".into(), "test \"$MODE\" = game".into()],
+        working_dir: None,
+    });
+    let global = LaunchProfile {
+        wrappers: vec!["env".into()],
+        env: vec![("MODE".into(), "global".into())],
+        ..LaunchProfile::default()
+    };
+    let own = LaunchProfile {
+        post_exit: Some("echo \"$MODE $GAMI_GAME_NAME\"".into()),
+        env: vec![("MODE".into(), "game".into())],
+        post_launch_action: Some(PostLaunchAction::Minimize),
+        ..LaunchProfile::default()
+    };
+    h.store.set_launch_profile(None, global).await.unwrap();
+    h.store
+        .set_launch_profile(Some(game.id), own)
+        .await
+        .unwrap();
+
+    let launched = launch_game(&h.store, &h.addons, &game).await.unwrap();
+    assert_eq!(
+        launched.post_launch_action,
+        Some(PostLaunchAction::Minimize)
+    );
+    launched
+        .exit
+        .expect("the game process is known")
+        .await
+        .unwrap();
+
+    let log = h.store.get_launch_log(game.id).await.unwrap();
+    let stages: Vec<LaunchStage> = log.iter().map(|entry| entry.stage).collect();
+    assert_eq!(
+        stages,
+        [
+            LaunchStage::PostExit,
+            LaunchStage::Launch,
+            LaunchStage::Launch
+        ]
+    );
+    assert!(log.iter().all(|entry| entry.success), "{:?}", log);
+    assert_eq!(log[0].output, "game Celeste");
+    assert!(
+        log[2].output.contains("\"env\" \"sh\""),
+        "{}",
+        log[2].output
+    );
+    let sessions = h.store.get_play_sessions(game.id).await.unwrap();
+    assert_eq!(sessions.len(), 1);
+    assert!(sessions[0].started_at <= sessions[0].ended_at);
+}
+
+#[tokio::test]
+async fn test_client_launch_reports_ignored_settings() {
+    let h = harness().await;
+    let game = synced_game(&h).await;
+    let profile = LaunchProfile {
+        pre_launch: Some("true".into()),
+        post_exit: Some("sync-saves".into()),
+        wrappers: vec!["gamemoderun".into()],
+        ..LaunchProfile::default()
+    };
+    h.store
+        .set_launch_profile(Some(game.id), profile)
+        .await
+        .unwrap();
+
+    let launched = launch_game(&h.store, &h.addons, &game).await.unwrap();
+
+    assert!(launched.exit.is_none());
+    assert_eq!(launched.ignored, ["wrappers", "the post-exit script"]);
+    let log = h.store.get_launch_log(game.id).await.unwrap();
+    assert_eq!(log[0].stage, LaunchStage::Launch);
+    assert!(
+        log[0]
+            .output
+            .ends_with("so wrappers, the post-exit script were not applied"),
+        "{}",
+        log[0].output
+    );
+}
+
+#[tokio::test]
+async fn test_client_failures_are_reported_and_logged() {
+    let h = harness().await;
+    let game = synced_game(&h).await;
+    h.library
+        .fail_actions(ActionError::ClientMissing("Fake client".into()));
+
+    let launched = launch_game(&h.store, &h.addons, &game).await;
+    let installed = run_library_action(&h.addons, LibraryAction::Install, &game).await;
+
+    assert!(matches!(
+        launched,
+        Err(LaunchError::Library(ActionFailure::Library(
+            ActionError::ClientMissing(_)
+        )))
+    ));
+    assert_eq!(
+        installed.unwrap_err().to_string(),
+        "Fake client is not installed"
+    );
+    let log = h.store.get_launch_log(game.id).await.unwrap();
+    assert_eq!(log.len(), 1);
+    assert!(!log[0].success);
+    assert!(log[0].output.ends_with("Fake client is not installed"));
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(games[0].last_played, None);
+}
@@ -0,0 +1,525 @@
+mod common;
+
+use chrono::DateTime;
+use common::{genre, harness, harness_at, scanned, Harness};
+use gami_backend::db::backup::SnapshotKind;
+use gami_backend::db::ops::{GamesFilters, Sort, SortField, SortOrder};
+use gami_backend::db::{DbLocation, USER_SOURCE};
+use gami_backend::export::ImportMode;
+use gami_backend::launch::{LaunchProfile, LaunchStage};
+use gami_backend::GameField;
+use gami_sdk::{CompletionStatus, GameData, GameMetadata, GenreData, ScannedGameLibraryMetadata};
+use std::collections::HashSet;
+
+fn names(games: &[GameData]) -> Vec<&str> {
+    games.iter().map(|game| game.name.as_str()).collect()
+}
+
+fn genre_names(game: &GameData) -> Vec<String> {
+    let mut names: Vec<String> = game.genres.iter().map(|g| g.name.to_string()).collect();
+    names.sort();
+    names
+}
+
+fn with_genres(description: &str, genres: Vec<GenreData>) -> GameMetadata {
+    GameMetadata {
+        description: Some(description.into()).into(),
+        genres: genres.into(),
+        ..GameMetadata::default()
+    }
+}
+
+#[tokio::test]
+async fn test_sync_inserts_games_with_metadata() {
+    let h = harness().await;
+    h.library.set_games(vec![scanned("1", "Celeste", 3600)]);
+    h.scanner.set_metadata(
+        "1",
+        with_genres(
+            "Climb a mountain",
+            vec![genre("plat", "Platformer"), genre("indie", "Indie")],
+        ),
+    );
+
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(names(&games), ["Celeste"]);
+    assert_eq!(games[0].description, "Climb a mountain");
+    assert_eq!(games[0].play_time.num_seconds(), 3600);
+    assert_eq!(genre_names(&games[0]), ["Indie", "Platformer"]);
+}
+
+#[tokio::test]
+async fn test_resync_only_adds_new_games() {
+    let h = harness().await;
+    h.library.set_games(vec![scanned("1", "Celeste", 0)]);
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    h.library
+        .set_games(vec![scanned("1", "Celeste", 0), scanned("2", "Hades", 0)]);
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(names(&games), ["Celeste", "Hades"]);
+}
+
+#[tokio::test]
+async fn test_resync_raises_play_stats() {
+    let h = harness().await;
+    h.library.report_play_stats();
+    h.library.set_games(vec![scanned("1", "Celeste", 600)]);
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let mut played = scanned("1", "Celeste", 1800);
+    played.last_played_epoch = Some(1_700_000_000).into();
+    h.library.set_games(vec![played]);
+    h.store.sync_library(&h.addons).await.unwrap();
+    h.library.set_games(vec![scanned("1", "Celeste", 60)]);
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(games[0].play_time.num_seconds(), 1800);
+    assert_eq!(
+        games[0].last_played.map(|date| date.timestamp()),
+        Some(1_700_000_000)
+    );
+}
+
+#[tokio::test]
+async fn test_resync_keeps_play_stats_of_libraries_not_reporting_them() {
+    let h = harness().await;
+    h.library.set_games(vec![scanned("1", "Celeste", 600)]);
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let mut played = scanned("1", "Celeste", 1800);
+    played.last_played_epoch = Some(1_700_000_000).into();
+    h.library.set_games(vec![played]);
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(games[0].play_time.num_seconds(), 600);
+    assert_eq!(games[0].last_played, None);
+}
+
+#[tokio::test]
+async fn test_update_game_links_genres_by_name() {
+    let h = harness().await;
+    h.library.set_games(vec![scanned("1", "Celeste", 0)]);
+    h.scanner
+        .set_metadata("1", with_genres("", vec![genre("plat", "Platformer")]));
+    h.store.sync_library(&h.addons).await.unwrap();
+    let mut game = h.store.get_games(GamesFilters::default()).await.unwrap()[0].clone();
+
+    game.description = "Edited by hand".into();
+    game.genres = vec![genre("", "Platformer"), genre("", "Precision")];
+    let manual = HashSet::from([GameField::Description, GameField::Genres]);
+    h.store.update_game(game.clone(), manual).await.unwrap();
+
+    let updated = &h.store.get_games(GamesFilters::default()).await.unwrap()[0];
+    assert_eq!(updated.description, "Edited by hand");
+    assert_eq!(genre_names(updated), ["Platformer", "Precision"]);
+    let sources = h.store.get_field_sources(game.id).await.unwrap();
+    let description = sources
+        .iter()
+        .find(|s| s.field == GameField::Description)
+        .unwrap();
+    assert_eq!(description.source, USER_SOURCE);
+    let counts = h
+        .store
+        .get_facet_counts(GamesFilters::default())
+        .await
+        .unwrap();
+    assert_eq!(
+        counts.genres,
+        [("Platformer".to_string(), 1), ("Precision".to_string(), 1)]
+    );
+}
+
+#[tokio::test]
+async fn test_duplicate_genres_share_one_row() {
+    let h = harness().await;
+    h.library.set_games(vec![
+        scanned("1", "Celeste", 0),
+        scanned("2", "Hollow Knight", 0),
+    ]);
+    h.scanner
+        .set_metadata("1", with_genres("", vec![genre("plat", "Platformer")]));
+    h.scanner.set_metadata(
+        "2",
+        with_genres(
+            "",
+            vec![genre("plat ", "Platformer "), genre("plat", "Platformer")],
+        ),
+    );
+
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let export = h.store.export_library().await.unwrap();
+    assert_eq!(export.genres.len(), 1);
+    assert_eq!(export.genres[0].name, "Platformer");
+    let counts = h
+        .store
+        .get_facet_counts(GamesFilters::default())
+        .await
+        .unwrap();
+    assert_eq!(counts.genres, [("Platformer".to_string(), 2)]);
+}
+
+#[tokio::test]
+async fn test_games_sort_by_every_key_in_order() {
+    let h = harness().await;
+    h.library.set_games(vec![
+        scanned("1", "Celeste", 60),
+        scanned("2", "Hades", 7200),
+        scanned("3", "Braid", 60),
+    ]);
+    h.store.sync_library(&h.addons).await.unwrap();
+
+    let by_playtime = GamesFilters {
+        sort: vec![
+            Sort {
+                field: SortField::Playtime,
+                order: SortOrder::Descending,
+            },
+            Sort {
+                field: SortField::Name,
+                order: SortOrder::Ascending,
+            },
+        ],
+        ..GamesFilters::default()
+    };
+    let games = h.store.get_games(by_playtime).await.unwrap();
+    assert_eq!(names(&games), ["Hades", "Braid", "Celeste"]);
+
+    let default = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(names(&default), ["Braid", "Celeste", "Hades"]);
+}
+
+#[tokio::test]
+async fn test_delete_game_removes_its_linked_rows() {
+    let h = harness().await;
+    h.library
+        .set_games(vec![scanned("1", "Celeste", 0), scanned("2", "Hades", 0)]);
+    h.store.sync_library(&h.addons).await.unwrap();
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    let (celeste, hades) = (games[0].id, games[1].id);
+    for id in [celeste, hades] {
+        h.store
+            .add_launch_log(id, LaunchStage::Launch, true, String::new())
+            .await
+            .unwrap();
+    }
+
+    h.store.delete_game(celeste).await.unwrap();
+
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(names(&games), ["Hades"]);
+    assert!(h.store.get_launch_log(celeste).await.unwrap().is_empty());
+    assert_eq!(h.store.get_launch_log(hades).await.unwrap().len(), 1);
+}
+
+fn tagged(tag_id: i32) -> GamesFilters {
+    GamesFilters {
+        tags: vec![tag_id],
+        ..GamesFilters::default()
+    }
+}
+
+async fn synced_ids(h: &Harness, games: Vec<ScannedGameLibraryMetadata>) -> Vec<i32> {
+    h.library.set_games(games);
+    h.store.sync_library(&h.addons).await.unwrap();
+    h.store
+        .get_games(GamesFilters::default())
+        .await
+        .unwrap()
+        .iter()
+        .map(|game| game.id)
+        .collect()
+}
+
+#[tokio::test]
+async fn test_bulk_status_and_hidden_edits_undo() {
+    let h = harness().await;
+    let ids = synced_ids(
+        &h,
+        vec![scanned("1", "Braid", 0), scanned("2", "Celeste", 0)],
+    )
+    .await;
+    h.store
+        .set_completion_status_bulk(vec![ids[0]], CompletionStatus::Playing)
+        .await
+        .unwrap();
+
+    let undo = h
+        .store
+        .set_completion_status_bulk(ids.clone(), CompletionStatus::Played)
+        .await
+        .unwrap();
+    let statuses = |games: Vec<GameData>| {
+        games
+            .into_iter()
+            .map(|game| game.completion_status)
+            .collect::<Vec<_>>()
+    };
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(
+        statuses(games),
+        [CompletionStatus::Played, CompletionStatus::Played]
+    );
+    h.store.undo_bulk(undo).await.unwrap();
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(
+        statuses(games),
+        [CompletionStatus::Playing, CompletionStatus::Backlog]
+    );
+
+    let undo = h.store.set_hidden_bulk(ids, true).await.unwrap();
+    assert!(h
+        .store
+        .get_games(GamesFilters::default())
+        .await
+        .unwrap()
+        .is_empty());
+    h.store.undo_bulk(undo).await.unwrap();
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(names(&games), ["Braid", "Celeste"]);
+}
+
+#[tokio::test]
+async fn test_bulk_tag_undo_only_reverts_changed_games() {
+    let h = harness().await;
+    let ids = synced_ids(
+        &h,
+        vec![scanned("1", "Braid", 0), scanned("2", "Celeste", 0)],
+    )
+    .await;
+    let tag = h
+        .store
+        .create_tag("Co-op".into(), "#808080".into())
+        .await
+        .unwrap();
+    h.store
+        .add_tag_to_games(tag.id, vec![ids[0]])
+        .await
+        .unwrap();
+
+    let undo = h.store.add_tag_bulk(tag.id, ids.clone()).await.unwrap();
+    let games = h.store.get_games(tagged(tag.id)).await.unwrap();
+    assert_eq!(names(&games), ["Braid", "Celeste"]);
+    h.store.undo_bulk(undo).await.unwrap();
+    let games = h.store.get_games(tagged(tag.id)).await.unwrap();
+    assert_eq!(names(&games), ["Braid"]);
+
+    let undo = h.store.remove_tag_bulk(tag.id, ids).await.unwrap();
+    assert!(h.store.get_games(tagged(tag.id)).await.unwrap().is_empty());
+    h.store.undo_bulk(undo).await.unwrap();
+    let games = h.store.get_games(tagged(tag.id)).await.unwrap();
+    assert_eq!(names(&games), ["Braid"]);
+}
+
+#[tokio::test]
+async fn test_bulk_delete_undo_restores_games_and_their_rows() {
+    let h = harness().await;
+    h.scanner
+        .set_metadata("1", with_genres("", vec![genre("plat", "Platformer")]));
+    let ids = synced_ids(
+        &h,
+        vec![scanned("1", "Celeste", 0), scanned("2", "Hades", 0)],
+    )
+    .await;
+    let tag = h
+        .store
+        .create_tag("Co-op".into(), "#808080".into())
+        .await
+        .unwrap();
+    h.store
+        .add_tag_to_games(tag.id, vec![ids[0]])
+        .await
+        .unwrap();
+    h.store
+        .add_launch_log(ids[0], LaunchStage::Launch, true, "started".into())
+        .await
+        .unwrap();
+    let before = h.store.export_library().await.unwrap();
+
+    let undo = h.store.delete_games(vec![ids[0]]).await.unwrap();
+    let games = h.store.get_games(GamesFilters::default()).await.unwrap();
+    assert_eq!(names(&games), ["Hades"]);
+    assert!(h.store.get_launch_log(ids[0]).await.unwrap().is_empty());
+
+    h.store.undo_bulk(undo).await.unwrap();
+    assert_eq!(h.store.export_library().await.unwrap(), before);
+    let games = h.store.get_games(tagged(tag.id)).await.unwrap();
+    assert_eq!(names(&games), ["Celeste"]);
+    assert_eq!(genre_names(&games[0]), ["Platformer"]);
+    let log = h.store.get_launch_log(ids[0]).await.unwrap();
+    assert_eq!(log.len(), 1);
+    assert_eq!(log[0].output, "started");
+}
+
+#[tokio::test]
+async fn test_bulk_delete_snapshots_the_library_first() {
+    let dir = std::env::temp_dir().join(format!("gami-delete-{}", std::process::id()));
+    std::fs::create_dir_all(&dir).unwrap();
+    let h = harness_at(DbLocation::File(dir.join("data.db"))).await;
+    let ids = synced_ids(&h, vec![scanned("1", "Celeste", 0)]).await;
+
+    h.store.delete_games(ids).await.unwrap();
+    let snapshots = h.store.list_snapshots().await;
+    let _ = std::fs::remove_dir_all(&dir);
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].kind, SnapshotKind::Before("delete".into()));
+    assert_eq!(snapshots[0].games, Some(1));
+}
+
+#[tokio::test]
+async fn test_export_round_trips_through_a_fresh_store() {
+    let h = harness().await;
+    h.scanner
+        .set_metadata("1", with_genres("Climb", vec![genre("plat", "Platformer")]));
+    let ids = synced_ids(
+        &h,
+        vec![scanned("1", "Celeste", 3600), scanned("2", "Hades", 0)],
+    )
+    .await;
+    let tag = h
+        .store
+        .create_tag("Co-op".into(), "#808080".into())
+        .await
+        .unwrap();
+    h.store
+        .add_tag_to_games(tag.id, vec![ids[1]])
+        .await
+        .unwrap();
+    let mut celeste = h
+        .store
+        .get_games(GamesFilters::default())
+        .await
+        .unwrap()
+        .remove(0);
+    celeste.notes = "B-sides left".into();
+    h.store
+        .update_game(celeste, HashSet::from([GameField::Description]))
+        .await
+        .unwrap();
+    for (start, end) in [
+        (1_700_000_000, 1_700_003_600),
+        (1_700_100_000, 1_700_100_600),
+    ] {
+        h.store
+            .add_play_session(
+                ids[0],
+                DateTime::from_timestamp(start, 0).unwrap(),
+                DateTime::from_timestamp(end, 0).unwrap(),
+            )
+            .await
+            .unwrap();
+    }
+    let own = LaunchProfile {
+        pre_launch: Some("mount-saves".into()),
+        ..LaunchProfile::default()
+    };
+    let global = LaunchProfile {
+        wrappers: vec!["gamemoderun".into()],
+        ..LaunchProfile::default()
+    };
+    h.store
+        .set_launch_profile(Some(ids[1]), own.clone())
+        .await
+        .unwrap();
+    h.store
+        .set_launch_profile(None, global.clone())
+        .await
+        .unwrap();
+    let export = h.store.export_library().await.unwrap();
+    assert_eq!(export.games[0].notes, "B-sides left");
+    assert_eq!(export.games[1].launch_profile, own);
+    assert_eq!(export.global_launch_profile, global);
+    assert_eq!(export.games[0].play_sessions.len(), 2);
+
+    let fresh = harness().await;
+    fresh
+        .store
+        .import_library(export.clone(), ImportMode::Replace)
+        .await
+        .unwrap();
+    assert_eq!(fresh.store.export_library().await.unwrap(), export);
+
+    fresh
+        .store
+        .import_library(export.clone(), ImportMode::Merge)
+        .await
+        .unwrap();
+    assert_eq!(fresh.store.export_library().await.unwrap(), export);
+}
+
+#[tokio::test]
+async fn test_replacing_import_drops_old_launch_profiles_and_logs() {
+    let h = harness().await;
+    let ids = synced_ids(&h, vec![scanned("1", "Celeste", 0)]).await;
+    let export = h.store.export_library().await.unwrap();
+    let profile = LaunchProfile {
+        pre_launch: Some("rm -rf saves".into()),
+        ..LaunchProfile::default()
+    };
+    h.store
+        .set_launch_profile(Some(ids[0]), profile)
+        .await
+        .unwrap();
+    h.store
+        .add_launch_log(ids[0], LaunchStage::Launch, true, "started".into())
+        .await
+        .unwrap();
+
+    h.store
+        .import_library(export, ImportMode::Replace)
+        .await
+        .unwrap();
+    assert!(h
+        .store
+        .get_launch_profile(Some(ids[0]))
+        .await
+        .unwrap()
+        .is_empty());
+    assert!(h.store.get_launch_log(ids[0]).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn test_filtered_games_keep_their_sources_and_tags() {
+    let h = harness().await;
+    let ids = synced_ids(
+        &h,
+        vec![
+            scanned("1", "Celeste", 3600),
+            scanned("2", "Celeste", 600),
+            scanned("3", "Hades", 0),
+        ],
+    )
+    .await;
+    h.store.merge_games(ids[0], vec![ids[1]]).await.unwrap();
+    let tag = h
+        .store
+        .create_tag("Co-op".into(), "#808080".into())
+        .await
+        .unwrap();
+    h.store
+        .add_tag_to_games(tag.id, vec![ids[0], ids[2]])
+        .await
+        .unwrap();
+
+    let games = h.store.get_games(tagged(tag.id)).await.unwrap();
+    assert_eq!(names(&games), ["Celeste", "Hades"]);
+    assert_eq!(games[0].sources.len(), 2);
+    assert_eq!(games[0].play_time.num_seconds(), 4200);
+    assert!(games.iter().all(|game| game.tags.len() == 1));
+
+    let search = GamesFilters {
+        search: "celeste".into(),
+        ..GamesFilters::default()
+    };
+    let games = h.store.get_games(search).await.unwrap();
+    assert_eq!(names(&games), ["Celeste"]);
+    assert_eq!(games[0].sources.len(), 2);
+    assert_eq!(games[0].tags[0].name, "Co-op");
+}
@@ -0,0 +1,390 @@
+use gami_backend::db::filter::FacetFilters;
+use gami_backend::db::ops::{HiddenFilter, Sort, SortField, SortOrder};
+use gami_backend::export::{ExportFormat, ImportMode};
+use gami_sdk::{CompletionStatus, EditableEnum, GameInstallStatus};
+use pico_args::Arguments;
+use std::fmt::Display;
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+Usage: gami <command> [options]
+
+Commands:
+  sync [--library <type>]            Add new games from every library, or only one
+  list [options] [--json]            List games, sorted by name unless --sort is given
+      --search <query>               Full text search, like the library search bar
+      --sort <field>[:asc|desc]      Sort key, repeat for ties: name, last-played, playtime,
+                                     release-date, completion-status, install-status, library
+      --library <type>               Only games of a library, repeatable
+      --account <id|name>            Only games of a library account
+      --status <status>              Only games with a completion status, repeatable
+      --install-status <status>      Only games with an install status, repeatable
+      --genre <name>                 Only games of a genre, repeatable
+      --tag <name>                   Only games with any of these tags, repeatable
+      --played-within <days>         Only games played in the last days
+      --favourites                   Only favourite games
+      --hidden <exclude|include|only>
+  launch <id|name>                   Launch a game through its library
+  install <id|name>
+  uninstall <id|name>
+  set-status <status> <id|name>...   Set the completion status: backlog, playing, played, on-hold
+  export <path> [--format <json|csv|gami>]
+                                     Export the library, the format defaults to the extension
+  import <path> [--replace]          Import an export or backup, merging unless --replace is given
+  addons list                        List the installed addons
+  config get <addon> [<key>] [--account <id>]
+  config set <addon> <key> <value> [--account <id>]
+                                     Read or change an addon's config, or an account override
+
+Exit codes: 0 on success, 1 on failure, 2 on invalid usage, 3 when a game or addon isn't found";
+
+const INSTALL_STATUSES: [GameInstallStatus; 4] = [
+    GameInstallStatus::Installed,
+    GameInstallStatus::Installing,
+    GameInstallStatus::Queued,
+    GameInstallStatus::InLibrary,
+];
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    Help,
+    Sync {
+        library: Option<String>,
+    },
+    List {
+        filters: ListFilters,
+        json: bool,
+    },
+    Launch {
+        game: String,
+    },
+    Install {
+        game: String,
+    },
+    Uninstall {
+        game: String,
+    },
+    SetStatus {
+        status: CompletionStatus,
+        games: Vec<String>,
+    },
+    Export {
+        path: PathBuf,
+        format: Option<ExportFormat>,
+    },
+    Import {
+        path: PathBuf,
+        mode: ImportMode,
+    },
+    AddonsList,
+    ConfigGet {
+        addon: String,
+        key: Option<String>,
+        account: Option<String>,
+    },
+    ConfigSet {
+        addon: String,
+        key: String,
+        value: String,
+        account: Option<String>,
+    },
+}
+
+/// Filters of `list`, tags and the account are resolved once the library is open
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct ListFilters {
+    pub search: String,
+    pub sort: Vec<Sort>,
+    pub tags: Vec<String>,
+    /// Account ID or name
+    pub account: Option<String>,
+    pub favourites_only: bool,
+    pub hidden: HiddenFilter,
+    pub facets: FacetFilters,
+}
+
+/// Reads the command line, errors are meant to be shown with [`USAGE`]
+pub fn parse(mut args: Arguments) -> Result<Command, String> {
+    if args.contains(["-h", "--help"]) {
+        return Ok(Command::Help);
+    }
+    let subcommand = args.subcommand().map_err(|err| err.to_string())?;
+    let command = match subcommand.as_deref() {
+        None | Some("help") => Command::Help,
+        Some("sync") => Command::Sync {
+            library: args.opt_value_from_str("--library").map_err(to_string)?,
+        },
+        Some("list") => parse_list(&mut args)?,
+        Some("launch") => Command::Launch {
+            game: args.free_from_str().map_err(to_string)?,
+        },
+        Some("install") => Command::Install {
+            game: args.free_from_str().map_err(to_string)?,
+        },
+        Some("uninstall") => Command::Uninstall {
+            game: args.free_from_str().map_err(to_string)?,
+        },
+        Some("set-status") => {
+            let status = args
+                .free_from_fn(|value| parse_choice("status", CompletionStatus::ALL, value))
+                .map_err(to_string)?;
+            let games = free_values(&mut args)?;
+            if games.is_empty() {
+                return Err("set-status needs at least one game".into());
+            }
+            Command::SetStatus { status, games }
+        }
+        Some("export") => Command::Export {
+            format: args
+                .opt_value_from_fn("--format", parse_format)
+                .map_err(to_string)?,
+            path: args.free_from_str().map_err(to_string)?,
+        },
+        Some("import") => Command::Import {
+            mode: if args.contains("--replace") {
+                ImportMode::Replace
+            } else {
+                ImportMode::Merge
+            },
+            path: args.free_from_str().map_err(to_string)?,
+        },
+        Some("addons") => match args.subcommand().map_err(to_string)?.as_deref() {
+            Some("list") => Command::AddonsList,
+            _ => return Err("expected `addons list`".into()),
+        },
+        Some("config") => {
+            let account = args.opt_value_from_str("--account").map_err(to_string)?;
+            match args.subcommand().map_err(to_string)?.as_deref() {
+                Some("get") => Command::ConfigGet {
+                    addon: args.free_from_str().map_err(to_string)?,
+                    key: args.opt_free_from_str().map_err(to_string)?,
+                    account,
+                },
+                Some("set") => Command::ConfigSet {
+                    addon: args.free_from_str().map_err(to_string)?,
+                    key: args.free_from_str().map_err(to_string)?,
+                    value: args.free_from_str().map_err(to_string)?,
+                    account,
+                },
+                _ => return Err("expected `config get` or `config set`".into()),
+            }
+        }
+        Some(other) => return Err(format!("unknown command `{}`", other)),
+    };
+    let rest = args.finish();
+    if !rest.is_empty() {
+        return Err(format!("unexpected arguments: {:?}", rest));
+    }
+    Ok(command)
+}
+
+fn parse_list(args: &mut Arguments) -> Result<Command, String> {
+    let hidden = args
+        .opt_value_from_fn("--hidden", |value| {
+            parse_choice("hidden filter", &["exclude", "include", "only"], value)
+        })
+        .map_err(to_string)?;
+    let facets = FacetFilters {
+        install_status: args
+            .values_from_fn("--install-status", |value| {
+                parse_choice("install status", &INSTALL_STATUSES, value)
+            })
+            .map_err(to_string)?,
+        completion_status: args
+            .values_from_fn("--status", |value| {
+                parse_choice("status", CompletionStatus::ALL, value)
+            })
+            .map_err(to_string)?,
+        library_types: args.values_from_str("--library").map_err(to_string)?,
+        genres: args.values_from_str("--genre").map_err(to_string)?,
+        played_within_days: args
+            .opt_value_from_str("--played-within")
+            .map_err(to_string)?,
+        ..FacetFilters::default()
+    };
+    let filters = ListFilters {
+        search: args
+            .opt_value_from_str("--search")
+            .map_err(to_string)?
+            .unwrap_or_default(),
+        sort: args
+            .values_from_fn("--sort", parse_sort)
+            .map_err(to_string)?,
+        tags: args.values_from_str("--tag").map_err(to_string)?,
+        account: args.opt_value_from_str("--account").map_err(to_string)?,
+        favourites_only: args.contains("--favourites"),
+        hidden: match hidden {
+            Some("include") => HiddenFilter::Include,
+            Some("only") => HiddenFilter::Only,
+            _ => HiddenFilter::Exclude,
+        },
+        facets,
+    };
+    Ok(Command::List {
+        filters,
+        json: args.contains("--json"),
+    })
+}
+
+fn free_values(args: &mut Arguments) -> Result<Vec<String>, String> {
+    let mut values = Vec::new();
+    while let Some(value) = args.opt_free_from_str().map_err(to_string)? {
+        values.push(value);
+    }
+    Ok(values)
+}
+
+fn parse_sort(value: &str) -> Result<Sort, String> {
+    let (field, order) = value.split_once(':').unwrap_or((value, "asc"));
+    let order = match order {
+        "asc" => SortOrder::Ascending,
+        "desc" => SortOrder::Descending,
+        _ => {
+            return Err(format!(
+                "unknown sort order `{}`, expected asc or desc",
+                order
+            ))
+        }
+    };
+    Ok(Sort {
+        field: parse_choice("sort field", &SortField::ALL, field)?,
+        order,
+    })
+}
+
+fn parse_format(value: &str) -> Result<ExportFormat, String> {
+    ExportFormat::ALL
+        .into_iter()
+        .find(|format| format.extension() == value.to_lowercase())
+        .ok_or_else(|| format!("unknown format `{}`, expected json, csv or gami", value))
+}
+
+/// Lowercase alphanumerics only, so "On hold", "on-hold" and "OnHold" all match
+fn slug(value: &str) -> String {
+    value
+        .chars()
+        .filter(|c| c.is_alphanumeric())
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// How a choice is spelled in messages, "Last played" and "OnHold" become "last-played" and "on-hold"
+fn kebab(value: &str) -> String {
+    let mut kebab = String::new();
+    let mut prev_lower = false;
+    for c in value.chars() {
+        if c == ' ' || (c.is_uppercase() && prev_lower) {
+            kebab.push('-');
+        }
+        if c != ' ' {
+            kebab.extend(c.to_lowercase());
+        }
+        prev_lower = c.is_lowercase();
+    }
+    kebab
+}
+
+fn parse_choice<T: Copy + Display>(kind: &str, choices: &[T], value: &str) -> Result<T, String> {
+    choices
+        .iter()
+        .copied()
+        .find(|choice| slug(&choice.to_string()) == slug(value))
+        .ok_or_else(|| {
+            let names: Vec<String> = choices.iter().map(|c| kebab(&c.to_string())).collect();
+            format!(
+                "unknown {} `{}`, expected one of: {}",
+                kind,
+                value,
+                names.join(", ")
+            )
+        })
+}
+
+fn to_string(err: pico_args::Error) -> String {
+    err.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::ffi::OsString;
+
+    fn parse_args(args: &[&str]) -> Result<Command, String> {
+        parse(Arguments::from_vec(
+            args.iter().map(OsString::from).collect(),
+        ))
+    }
+
+    #[test]
+    fn test_list_filters_and_sort() {
+        let command = parse_args(&[
+            "list",
+            "--sort",
+            "playtime:desc",
+            "--sort",
+            "name",
+            "--status",
+            "on-hold",
+            "--library",
+            "steam",
+            "--account",
+            "Gabe",
+            "--hidden",
+            "include",
+            "--json",
+        ])
+        .unwrap();
+        let Command::List { filters, json } = command else {
+            panic!("expected a list command, got {:?}", command);
+        };
+        assert!(json);
+        assert_eq!(
+            filters.sort,
+            [
+                Sort {
+                    field: SortField::Playtime,
+                    order: SortOrder::Descending,
+                },
+                Sort {
+                    field: SortField::Name,
+                    order: SortOrder::Ascending,
+                },
+            ]
+        );
+        assert_eq!(filters.facets.completion_status, [CompletionStatus::OnHold]);
+        assert_eq!(filters.facets.library_types, ["steam"]);
+        assert_eq!(filters.account.as_deref(), Some("Gabe"));
+        assert_eq!(filters.hidden, HiddenFilter::Include);
+    }
+
+    #[test]
+    fn test_set_status_takes_several_games() {
+        assert_eq!(
+            parse_args(&["set-status", "played", "12", "Celeste"]).unwrap(),
+            Command::SetStatus {
+                status: CompletionStatus::Played,
+                games: vec!["12".into(), "Celeste".into()],
+            }
+        );
+        assert!(parse_args(&["set-status", "played"]).is_err());
+        assert!(parse_args(&["set-status", "finished", "12"]).is_err());
+    }
+
+    #[test]
+    fn test_rejects_unknown_input() {
+        assert!(parse_args(&["frobnicate"]).is_err());
+        assert!(parse_args(&["list", "--sort", "rating"]).is_err());
+        assert!(parse_args(&["sync", "--verbose"]).is_err());
+        assert_eq!(parse_args(&[]).unwrap(), Command::Help);
+    }
+
+    #[test]
+    fn test_choices_ignore_case_and_separators() {
+        assert_eq!(
+            parse_choice("status", CompletionStatus::ALL, "On Hold"),
+            Ok(CompletionStatus::OnHold)
+        );
+        assert_eq!(kebab("OnHold"), "on-hold");
+        assert_eq!(kebab("Last played"), "last-played");
+    }
+}
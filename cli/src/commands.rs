@@ -0,0 +1,229 @@
+use crate::args::{Command, ListFilters, USAGE};
+use crate::CliError;
+use gami_backend::action_executor::{run_library_action, LibraryAction};
+use gami_backend::db::ops::{GamesFilters, HiddenFilter};
+use gami_backend::db::LibraryStore;
+use gami_backend::export::{self, ExportFormat};
+use gami_backend::rpc::GameSummary;
+use gami_backend::{addon_config, launch, ADDONS};
+use gami_sdk::GameData;
+
+pub async fn run(store: &LibraryStore, command: Command) -> Result<(), CliError> {
+    match command {
+        Command::Help => println!("{}", USAGE),
+        Command::Sync { library } => match library {
+            Some(library) => {
+                if ADDONS.get_game_library(&library).is_none() {
+                    return Err(CliError::NotFound(format!(
+                        "no library named `{}`",
+                        library
+                    )));
+                }
+                store.sync_library_type(&ADDONS, &library).await?;
+            }
+            None => store.sync_library(&ADDONS).await?,
+        },
+        Command::List { filters, json } => {
+            let filters = games_filters(store, filters).await?;
+            let games = store.get_games(filters).await?;
+            if json {
+                let games: Vec<GameSummary> = games.iter().map(GameSummary::from).collect();
+                let json = serde_json::to_string_pretty(&games)
+                    .map_err(|err| CliError::Failed(err.to_string()))?;
+                println!("{}", json);
+            } else {
+                for game in &games {
+                    println!(
+                        "{}\t{}\t{}\t{}\t{}\t{:.1}h",
+                        game.id,
+                        game.name,
+                        game.library_type,
+                        game.install_status,
+                        game.completion_status,
+                        game.play_time.num_minutes() as f64 / 60.0
+                    );
+                }
+            }
+        }
+        Command::Launch { game } => {
+            let game = find_game(store, &game).await?;
+            let launched = launch::launch_game(store, &ADDONS, &game).await?;
+            println!("Launched {}", game.name);
+            if !launched.ignored.is_empty() {
+                eprintln!(
+                    "gami: the library's client started the game, so {} were not applied",
+                    launched.ignored.join(", ")
+                );
+            }
+            // the post-exit script runs in this process, so it has to outlive the game
+            if let Some(exit) = launched.exit {
+                println!("Waiting for the game to exit");
+                exit.await
+                    .map_err(|err| CliError::Failed(err.to_string()))?;
+            }
+        }
+        Command::Install { game } => {
+            let game = find_game(store, &game).await?;
+            run_library_action(&ADDONS, LibraryAction::Install, &game).await?;
+        }
+        Command::Uninstall { game } => {
+            let game = find_game(store, &game).await?;
+            run_library_action(&ADDONS, LibraryAction::Uninstall, &game).await?;
+        }
+        Command::SetStatus { status, games } => {
+            let mut game_ids = Vec::new();
+            for game in games {
+                game_ids.push(find_game(store, &game).await?.id);
+            }
+            store.set_completion_status_bulk(game_ids, status).await?;
+        }
+        Command::Export { path, format } => {
+            let format = match format.or_else(|| ExportFormat::from_path(&path)) {
+                Some(format) => format,
+                None => {
+                    return Err(CliError::Usage(
+                        "can't tell the format from the extension, pass --format".into(),
+                    ))
+                }
+            };
+            store.export_to_file(path.clone(), format).await?;
+            println!("Exported the library to {}", path.display());
+        }
+        Command::Import { path, mode } => {
+            let backup =
+                export::read_file(&path).map_err(|err| CliError::Failed(err.to_string()))?;
+            let count = backup.library.games.len();
+            store.import_backup(backup, mode).await?;
+            println!("Imported {} games from {}", count, path.display());
+        }
+        Command::AddonsList => {
+            for addon in ADDONS.get_addon_metadatas() {
+                println!("{}\t{}", addon.id, addon.name);
+            }
+        }
+        Command::ConfigGet {
+            addon,
+            key,
+            account,
+        } => {
+            require_addon(&addon)?;
+            let config = addon_config::read_config(&addon, account.as_deref())?;
+            match key {
+                Some(key) => match config.get(&key) {
+                    Some(value) => println!("{}", value),
+                    None => return Err(CliError::NotFound(format!("`{}` is not set", key))),
+                },
+                None => {
+                    let mut entries: Vec<_> = config.iter().collect();
+                    entries.sort();
+                    for (key, value) in entries {
+                        println!("{}={}", key, value);
+                    }
+                }
+            }
+        }
+        Command::ConfigSet {
+            addon,
+            key,
+            value,
+            account,
+        } => {
+            require_addon(&addon)?;
+            addon_config::validate_value(&addon, &key, &value)
+                .map_err(|err| CliError::Usage(format!("{}: {}", key, err)))?;
+            let mut config = addon_config::read_config(&addon, account.as_deref())?;
+            config.insert(key, value);
+            addon_config::write_config(&addon, account.as_deref(), &config)?;
+        }
+    }
+    Ok(())
+}
+
+async fn games_filters(
+    store: &LibraryStore,
+    filters: ListFilters,
+) -> Result<GamesFilters, CliError> {
+    let mut tags = Vec::new();
+    if !filters.tags.is_empty() {
+        let all_tags = store.get_tags().await?;
+        for name in &filters.tags {
+            let tag = all_tags
+                .iter()
+                .find(|tag| tag.name.eq_ignore_ascii_case(name))
+                .ok_or_else(|| CliError::NotFound(format!("no tag named `{}`", name)))?;
+            tags.push(tag.id);
+        }
+    }
+    let account = match filters.account {
+        Some(query) => Some(
+            store
+                .find_account(&query)
+                .await?
+                .ok_or_else(|| CliError::NotFound(format!("no account `{}`", query)))?,
+        ),
+        None => None,
+    };
+    Ok(GamesFilters {
+        search: filters.search,
+        sort: filters.sort,
+        account,
+        tags,
+        favourites_only: filters.favourites_only,
+        hidden: filters.hidden,
+        facets: filters.facets,
+        ..GamesFilters::default()
+    })
+}
+
+/// Finds a game by id, exact name or, failing both, a unique part of its name
+async fn find_game(store: &LibraryStore, query: &str) -> Result<GameData, CliError> {
+    let games = store
+        .get_games(GamesFilters {
+            hidden: HiddenFilter::Include,
+            ..GamesFilters::default()
+        })
+        .await?;
+    if let Ok(id) = query.parse::<i32>() {
+        if let Some(game) = games.iter().find(|game| game.id == id) {
+            return Ok(game.clone());
+        }
+    }
+    let mut matches: Vec<&GameData> = games
+        .iter()
+        .filter(|game| game.name.eq_ignore_ascii_case(query))
+        .collect();
+    if matches.is_empty() {
+        let query = query.to_lowercase();
+        matches = games
+            .iter()
+            .filter(|game| game.name.to_lowercase().contains(&query))
+            .collect();
+    }
+    match matches.as_slice() {
+        [game] => Ok((*game).clone()),
+        [] => Err(CliError::NotFound(format!("no game matches `{}`", query))),
+        many => {
+            let names: Vec<String> = many
+                .iter()
+                .map(|game| format!("{} ({})", game.name, game.id))
+                .collect();
+            Err(CliError::Failed(format!(
+                "`{}` matches several games, use an id: {}",
+                query,
+                names.join(", ")
+            )))
+        }
+    }
+}
+
+fn require_addon(addon: &str) -> Result<(), CliError> {
+    if ADDONS
+        .get_addon_metadatas()
+        .iter()
+        .any(|meta| &*meta.id == addon)
+    {
+        Ok(())
+    } else {
+        Err(CliError::NotFound(format!("no addon named `{}`", addon)))
+    }
+}
@@ -0,0 +1,88 @@
+use gami_backend::action_executor::ActionFailure;
+use gami_backend::db::DbErr;
+use gami_backend::launch::LaunchError;
+use std::fmt;
+use std::process::ExitCode;
+
+mod args;
+mod commands;
+
+/// Why a command failed, deciding the exit code
+#[derive(Debug)]
+pub enum CliError {
+    /// Bad arguments, also printing the usage
+    Usage(String),
+    /// A game, tag, account, library, addon or config key that doesn't exist
+    NotFound(String),
+    Failed(String),
+}
+impl CliError {
+    fn exit_code(&self) -> ExitCode {
+        ExitCode::from(match self {
+            CliError::Failed(_) => 1,
+            CliError::Usage(_) => 2,
+            CliError::NotFound(_) => 3,
+        })
+    }
+}
+impl fmt::Display for CliError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            CliError::Usage(err) | CliError::NotFound(err) | CliError::Failed(err) => {
+                f.write_str(err)
+            }
+        }
+    }
+}
+impl From<DbErr> for CliError {
+    fn from(err: DbErr) -> Self {
+        CliError::Failed(format!("database error: {}", err))
+    }
+}
+impl From<LaunchError> for CliError {
+    fn from(err: LaunchError) -> Self {
+        match err {
+            LaunchError::NoLibrary(_) => CliError::NotFound(err.to_string()),
+            err => CliError::Failed(err.to_string()),
+        }
+    }
+}
+impl From<ActionFailure> for CliError {
+    fn from(err: ActionFailure) -> Self {
+        match err {
+            ActionFailure::NoLibrary(_) => CliError::NotFound(err.to_string()),
+            err => CliError::Failed(err.to_string()),
+        }
+    }
+}
+impl From<std::io::Error> for CliError {
+    fn from(err: std::io::Error) -> Self {
+        CliError::Failed(err.to_string())
+    }
+}
+
+#[tokio::main]
+async fn main() -> ExitCode {
+    env_logger::Builder::from_env(env_logger::Env::default().default_filter_or("warn")).init();
+
+    let command = match args::parse(pico_args::Arguments::from_env()) {
+        Ok(command) => command,
+        Err(err) => return fail(CliError::Usage(err)),
+    };
+    let store = match gami_backend::db::init().await {
+        Ok(store) => store,
+        Err(err) => return fail(err.into()),
+    };
+    match commands::run(store, command).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => fail(err),
+    }
+}
+
+fn fail(err: CliError) -> ExitCode {
+    eprintln!("gami: {}", err);
+    if let CliError::Usage(_) = err {
+        eprintln!("\n{}", args::USAGE);
+    }
+    err.exit_code()
+}
@@ -0,0 +1,110 @@
+#[cfg(unix)]
+mod server;
+
+/// How often the libraries are asked whether games were installed or removed behind our back
+#[cfg(unix)]
+const INSTALL_STATUS_INTERVAL: std::time::Duration = std::time::Duration::from_secs(60);
+/// How often the running processes are matched against the library
+#[cfg(unix)]
+const RUNNING_GAMES_INTERVAL: std::time::Duration = std::time::Duration::from_secs(5);
+
+#[cfg(unix)]
+#[tokio::main]
+async fn main() -> std::process::ExitCode {
+    use std::process::ExitCode;
+
+    env_logger::Builder::from_env(
+        env_logger::Env::default().default_filter_or("warn,gami_daemon=info"),
+    )
+    .init();
+    let mut args = pico_args::Arguments::from_env();
+    let socket = match args.opt_value_from_os_str("--socket", |s| {
+        Ok::<_, std::convert::Infallible>(std::path::PathBuf::from(s))
+    }) {
+        Ok(socket) => socket.unwrap_or_else(gami_backend::rpc::default_socket_path),
+        Err(err) => {
+            eprintln!(
+                "gami-daemon: {}\n\nUsage: gami-daemon [--socket <path>]",
+                err
+            );
+            return ExitCode::from(2);
+        }
+    };
+    match run(socket).await {
+        Ok(()) => ExitCode::SUCCESS,
+        Err(err) => {
+            eprintln!("gami-daemon: {}", err);
+            ExitCode::FAILURE
+        }
+    }
+}
+
+#[cfg(unix)]
+async fn run(socket: std::path::PathBuf) -> std::io::Result<()> {
+    use gami_backend::ADDONS;
+    use std::fs;
+    use std::io;
+    use std::os::unix::fs::PermissionsExt;
+    use tokio::net::{UnixListener, UnixStream};
+    use tokio::signal::unix::{signal, SignalKind};
+
+    if socket.exists() {
+        if UnixStream::connect(&socket).await.is_ok() {
+            return Err(io::Error::new(
+                io::ErrorKind::AddrInUse,
+                format!("a daemon already listens on {}", socket.display()),
+            ));
+        }
+        // left behind by a daemon that didn't shut down cleanly
+        fs::remove_file(&socket)?;
+    }
+    if let Some(parent) = socket.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let store = gami_backend::db::init()
+        .await
+        .map_err(io::Error::other)?
+        .clone();
+    let server = server::Server::new(store, &ADDONS);
+    let listener = UnixListener::bind(&socket)?;
+    fs::set_permissions(&socket, fs::Permissions::from_mode(0o600))?;
+    log::info!("Listening on {}", socket.display());
+
+    let refresher = {
+        let server = server.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(INSTALL_STATUS_INTERVAL);
+            loop {
+                interval.tick().await;
+                server.refresh_install_statuses().await;
+            }
+        })
+    };
+    let monitor = {
+        let server = server.clone();
+        tokio::spawn(async move {
+            let mut interval = tokio::time::interval(RUNNING_GAMES_INTERVAL);
+            loop {
+                interval.tick().await;
+                server.poll_running_games().await;
+            }
+        })
+    };
+    let mut terminate = signal(SignalKind::terminate())?;
+    let result = tokio::select! {
+        result = server.serve(listener) => result,
+        _ = tokio::signal::ctrl_c() => Ok(()),
+        _ = terminate.recv() => Ok(()),
+    };
+    refresher.abort();
+    monitor.abort();
+    log::info!("Shutting down");
+    fs::remove_file(&socket)?;
+    result
+}
+
+#[cfg(not(unix))]
+fn main() -> std::process::ExitCode {
+    eprintln!("gami-daemon: only Unix domain sockets are supported for now");
+    std::process::ExitCode::FAILURE
+}
@@ -0,0 +1,456 @@
+use gami_backend::action_executor::{run_library_action, LibraryAction};
+use gami_backend::db::ops::{GamesFilters, HiddenFilter};
+use gami_backend::db::LibraryStore;
+use gami_backend::launch;
+use gami_backend::plugin::ExternalAddons;
+use gami_backend::process_monitor::{self, MonitorEvent, ProcessMonitor, RunningGame};
+use gami_backend::rpc::{
+    DaemonStatus, Event, GameParams, GameSummary, ListParams, Notification, Request, Response,
+    RpcError, SearchParams, SyncParams, INTERNAL_ERROR, INVALID_PARAMS, INVALID_REQUEST,
+    JSONRPC_VERSION, METHOD_NOT_FOUND, NOT_FOUND, NOT_RUNNING, PARSE_ERROR, SYNC_IN_PROGRESS,
+};
+use gami_sdk::GameData;
+use serde::de::DeserializeOwned;
+use serde_json::Value;
+use std::io;
+use std::sync::Arc;
+use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+use tokio::net::{UnixListener, UnixStream};
+use tokio::sync::{broadcast, mpsc, Mutex};
+use tokio::task::JoinHandle;
+
+/// Events kept for subscribers that fall behind before they miss some
+const EVENT_BUFFER: usize = 256;
+
+/// Owns the library and addons, answering clients of the control socket
+pub struct Server {
+    store: LibraryStore,
+    addons: &'static ExternalAddons,
+    events: broadcast::Sender<Event>,
+    /// Held while a sync runs, so a second one is refused instead of racing it
+    syncing: Mutex<()>,
+    /// Games found running by the last poll
+    monitor: Mutex<ProcessMonitor>,
+}
+
+impl Server {
+    pub fn new(store: LibraryStore, addons: &'static ExternalAddons) -> Arc<Self> {
+        Arc::new(Self {
+            store,
+            addons,
+            events: broadcast::channel(EVENT_BUFFER).0,
+            syncing: Mutex::new(()),
+            monitor: Mutex::default(),
+        })
+    }
+
+    pub async fn serve(self: Arc<Self>, listener: UnixListener) -> io::Result<()> {
+        loop {
+            let (stream, _) = listener.accept().await?;
+            let server = Arc::clone(&self);
+            tokio::spawn(async move {
+                if let Err(err) = server.handle_connection(stream).await {
+                    log::warn!("Client connection failed: {}", err);
+                }
+            });
+        }
+    }
+
+    async fn handle_connection(self: Arc<Self>, stream: UnixStream) -> io::Result<()> {
+        let (read, mut write) = stream.into_split();
+        let (lines_tx, mut lines_rx) = mpsc::unbounded_channel::<String>();
+        let writer = tokio::spawn(async move {
+            while let Some(mut line) = lines_rx.recv().await {
+                line.push('\n');
+                write.write_all(line.as_bytes()).await?;
+            }
+            io::Result::Ok(())
+        });
+        let mut forwarder: Option<JoinHandle<()>> = None;
+        let mut lines = BufReader::new(read).lines();
+        while let Some(line) = lines.next_line().await? {
+            if line.trim().is_empty() {
+                continue;
+            }
+            let response = match serde_json::from_str::<Request>(&line) {
+                Ok(request) if request.jsonrpc != JSONRPC_VERSION => Response::new(
+                    request.id.unwrap_or_default(),
+                    Err(RpcError::new(INVALID_REQUEST, "expected JSON-RPC 2.0")),
+                ),
+                Ok(request) => {
+                    if request.method == "events.subscribe" && forwarder.is_none() {
+                        forwarder = Some(self.forward_events(lines_tx.clone()));
+                    }
+                    let result = self.dispatch(&request.method, request.params).await;
+                    let Some(id) = request.id else {
+                        continue;
+                    };
+                    Response::new(id, result)
+                }
+                Err(err) => Response::new(
+                    Value::Null,
+                    Err(RpcError::new(PARSE_ERROR, err.to_string())),
+                ),
+            };
+            if lines_tx.send(serde_json::to_string(&response)?).is_err() {
+                break;
+            }
+        }
+        if let Some(forwarder) = forwarder {
+            forwarder.abort();
+        }
+        drop(lines_tx);
+        writer.await.map_err(io::Error::other)?
+    }
+
+    fn forward_events(&self, lines: mpsc::UnboundedSender<String>) -> JoinHandle<()> {
+        let mut events = self.events.subscribe();
+        tokio::spawn(async move {
+            loop {
+                let event = match events.recv().await {
+                    Ok(event) => event,
+                    Err(broadcast::error::RecvError::Lagged(missed)) => {
+                        log::warn!("Client missed {} events", missed);
+                        continue;
+                    }
+                    Err(broadcast::error::RecvError::Closed) => break,
+                };
+                let Ok(line) = serde_json::to_string(&Notification::new(event)) else {
+                    continue;
+                };
+                if lines.send(line).is_err() {
+                    break;
+                }
+            }
+        })
+    }
+
+    fn emit(&self, event: Event) {
+        // only fails without subscribers
+        let _ = self.events.send(event);
+    }
+
+    async fn dispatch(&self, method: &str, params: Value) -> Result<Value, RpcError> {
+        match method {
+            "status" => to_value(self.status().await?),
+            "games.list" => {
+                let mut params: ListParams = optional_params(params)?;
+                let account = match params.account.take() {
+                    Some(query) => {
+                        Some(self.store.find_account(&query).await?.ok_or_else(|| {
+                            RpcError::new(NOT_FOUND, format!("no account {}", query))
+                        })?)
+                    }
+                    None => None,
+                };
+                let filters = GamesFilters {
+                    account,
+                    ..params.into()
+                };
+                to_value(self.list(filters).await?)
+            }
+            "games.search" => {
+                let params: SearchParams = optional_params(params)?;
+                let mut games = self
+                    .list(GamesFilters {
+                        search: params.query,
+                        ..GamesFilters::default()
+                    })
+                    .await?;
+                if let Some(limit) = params.limit {
+                    games.truncate(limit);
+                }
+                to_value(games)
+            }
+            "games.launch" => {
+                let GameParams { id } = required_params(params)?;
+                self.launch(id).await.map(|()| Value::Null)
+            }
+            "games.install" | "games.uninstall" => {
+                let GameParams { id } = required_params(params)?;
+                let install = method == "games.install";
+                self.run_install_action(id, install)
+                    .await
+                    .map(|()| Value::Null)
+            }
+            "games.running" => {
+                let running: Vec<RunningGame> =
+                    self.monitor.lock().await.running().cloned().collect();
+                to_value(running)
+            }
+            "games.stop" => {
+                let GameParams { id } = required_params(params)?;
+                self.stop(id).await.map(|()| Value::Null)
+            }
+            "library.sync" => {
+                let params: SyncParams = optional_params(params)?;
+                let added = self.sync(params.library).await?;
+                Ok(serde_json::json!({ "added": added }))
+            }
+            "events.subscribe" => Ok(Value::Bool(true)),
+            _ => Err(RpcError::new(
+                METHOD_NOT_FOUND,
+                format!("unknown method {}", method),
+            )),
+        }
+    }
+
+    async fn status(&self) -> Result<DaemonStatus, RpcError> {
+        let mut libraries: Vec<String> = self
+            .addons
+            .get_keys()
+            .into_iter()
+            .map(String::from)
+            .collect();
+        libraries.sort();
+        Ok(DaemonStatus {
+            version: env!("CARGO_PKG_VERSION").into(),
+            games: self.store.count_games().await?,
+            syncing: self.syncing.try_lock().is_err(),
+            addons: self
+                .addons
+                .get_addon_metadatas()
+                .iter()
+                .map(|meta| meta.id.to_string())
+                .collect(),
+            libraries,
+        })
+    }
+
+    async fn list(&self, filters: GamesFilters) -> Result<Vec<GameSummary>, RpcError> {
+        let games = self.store.get_games(filters).await?;
+        Ok(games.iter().map(GameSummary::from).collect())
+    }
+
+    async fn find_game(&self, id: i32) -> Result<GameData, RpcError> {
+        let games = self
+            .store
+            .get_games(GamesFilters {
+                hidden: HiddenFilter::Include,
+                ..GamesFilters::default()
+            })
+            .await?;
+        games
+            .into_iter()
+            .find(|game| game.id == id)
+            .ok_or_else(|| RpcError::new(NOT_FOUND, format!("no game with id {}", id)))
+    }
+
+    async fn launch(&self, id: i32) -> Result<(), RpcError> {
+        let game = self.find_game(id).await?;
+        let launched = launch::launch_game(&self.store, self.addons, &game).await?;
+        self.emit(Event::SessionStarted {
+            game_id: game.id,
+            name: game.name.clone(),
+        });
+        if let Some(exit) = launched.exit {
+            let events = self.events.clone();
+            tokio::spawn(async move {
+                if let Err(err) = exit.await {
+                    log::warn!("Lost track of {}: {}", game.name, err);
+                }
+                // only fails without subscribers
+                let _ = events.send(Event::SessionEnded {
+                    game_id: game.id,
+                    name: game.name,
+                });
+            });
+        }
+        Ok(())
+    }
+
+    async fn stop(&self, id: i32) -> Result<(), RpcError> {
+        let game = self.monitor.lock().await.get(id).cloned();
+        let game =
+            game.ok_or_else(|| RpcError::new(NOT_RUNNING, format!("game {} isn't running", id)))?;
+        tokio::task::spawn_blocking(move || process_monitor::stop_game(&game))
+            .await
+            .map_err(|err| RpcError::new(INTERNAL_ERROR, err.to_string()))?
+            .map_err(|err| RpcError::new(INTERNAL_ERROR, err.to_string()))?;
+        self.poll_running_games().await;
+        Ok(())
+    }
+
+    async fn run_install_action(&self, id: i32, install: bool) -> Result<(), RpcError> {
+        let game = self.find_game(id).await?;
+        let action = if install {
+            LibraryAction::Install
+        } else {
+            LibraryAction::Uninstall
+        };
+        run_library_action(self.addons, action, &game).await?;
+        self.refresh_install_statuses().await;
+        Ok(())
+    }
+
+    /// Syncs one library or all of them, returning how many games were added
+    pub async fn sync(&self, library: Option<String>) -> Result<u64, RpcError> {
+        let Ok(_guard) = self.syncing.try_lock() else {
+            return Err(RpcError::new(SYNC_IN_PROGRESS, "a sync is already running"));
+        };
+        let libraries = match library {
+            Some(library) if self.addons.get_game_library(&library).is_none() => {
+                return Err(RpcError::new(
+                    NOT_FOUND,
+                    format!("no library named {}", library),
+                ))
+            }
+            Some(library) => vec![library],
+            None => {
+                let mut keys: Vec<String> = self
+                    .addons
+                    .get_keys()
+                    .into_iter()
+                    .map(String::from)
+                    .collect();
+                keys.sort();
+                keys
+            }
+        };
+        let before = self.store.count_games().await?;
+        self.emit(Event::SyncStarted {
+            libraries: libraries.clone(),
+        });
+        for (index, library) in libraries.iter().enumerate() {
+            if let Err(err) = self.store.sync_library_type(self.addons, library).await {
+                self.emit(Event::SyncFailed {
+                    message: err.to_string(),
+                });
+                return Err(err.into());
+            }
+            self.emit(Event::SyncProgress {
+                library: library.clone(),
+                done: index + 1,
+                total: libraries.len(),
+            });
+        }
+        let added = self.store.count_games().await?.saturating_sub(before);
+        self.emit(Event::SyncFinished { added });
+        self.refresh_install_statuses().await;
+        Ok(added)
+    }
+
+    /// Stores the install statuses the libraries report, announcing the ones that changed
+    pub async fn refresh_install_statuses(&self) {
+        match self.store.refresh_install_statuses(self.addons).await {
+            Ok(changes) => {
+                for (game_id, status) in changes {
+                    self.emit(Event::InstallStatusChanged { game_id, status });
+                }
+            }
+            Err(err) => log::warn!("Could not refresh install statuses: {}", err),
+        }
+    }
+
+    /// Checks which games run now, announcing the ones that started or stopped
+    pub async fn poll_running_games(&self) {
+        let games = match self
+            .store
+            .get_games(GamesFilters {
+                hidden: HiddenFilter::Include,
+                ..GamesFilters::default()
+            })
+            .await
+        {
+            Ok(games) => games,
+            Err(err) => {
+                log::warn!("Could not check for running games: {}", err);
+                return;
+            }
+        };
+        let running =
+            match tokio::task::spawn_blocking(move || process_monitor::running_games(&games)).await
+            {
+                Ok(running) => running,
+                Err(err) => {
+                    log::warn!("Could not check for running games: {}", err);
+                    return;
+                }
+            };
+        for event in self.monitor.lock().await.update(running) {
+            self.emit(match event {
+                MonitorEvent::Started(game) => Event::GameRunning {
+                    game_id: game.game_id,
+                    name: game.name,
+                },
+                MonitorEvent::Stopped(game) => Event::GameStopped {
+                    game_id: game.game_id,
+                    name: game.name,
+                },
+            });
+        }
+    }
+}
+
+fn to_value(value: impl serde::Serialize) -> Result<Value, RpcError> {
+    serde_json::to_value(value).map_err(|err| RpcError::new(INTERNAL_ERROR, err.to_string()))
+}
+
+/// Reads params that may be left out, taking their defaults
+fn optional_params<T: DeserializeOwned + Default>(params: Value) -> Result<T, RpcError> {
+    if params.is_null() {
+        return Ok(T::default());
+    }
+    required_params(params)
+}
+
+fn required_params<T: DeserializeOwned>(params: Value) -> Result<T, RpcError> {
+    serde_json::from_value(params).map_err(|err| RpcError::new(INVALID_PARAMS, err.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use gami_backend::db::DbLocation;
+    use gami_backend::rpc::Client;
+    use std::path::PathBuf;
+
+    async fn start(name: &str) -> PathBuf {
+        let store = LibraryStore::open(DbLocation::Memory).await.unwrap();
+        let addons: &'static ExternalAddons = Box::leak(Box::new(ExternalAddons::new()));
+        let path = std::env::temp_dir().join(format!("gami-{}-{}.sock", name, std::process::id()));
+        let _ = std::fs::remove_file(&path);
+        let listener = UnixListener::bind(&path).unwrap();
+        tokio::spawn(Server::new(store, addons).serve(listener));
+        path
+    }
+
+    #[tokio::test(flavor = "multi_thread")]
+    async fn test_answers_calls_and_pushes_events() {
+        let path = start("events").await;
+        let result = tokio::task::spawn_blocking(move || {
+            let mut client = Client::connect(&path).unwrap();
+            let status: DaemonStatus = client.call("status", Value::Null).unwrap();
+            assert_eq!(status.games, 0);
+            assert!(!status.syncing);
+            let games: Vec<GameSummary> = client.call("games.list", ListParams::default()).unwrap();
+            assert!(games.is_empty());
+            let of_account = ListParams {
+                account: Some("nobody".into()),
+                ..ListParams::default()
+            };
+            let unknown_account = client.call::<_, Value>("games.list", of_account);
+            assert!(matches!(unknown_account, Err(gami_backend::rpc::ClientError::Rpc(err)) if err.code == NOT_FOUND));
+
+            let missing = client.call::<_, Value>("games.launch", GameParams { id: 7 });
+            assert!(matches!(missing, Err(gami_backend::rpc::ClientError::Rpc(err)) if err.code == NOT_FOUND));
+            let unknown = client.call::<_, Value>("games.frobnicate", Value::Null);
+            assert!(matches!(unknown, Err(gami_backend::rpc::ClientError::Rpc(err)) if err.code == METHOD_NOT_FOUND));
+
+            let _: bool = client.call("events.subscribe", Value::Null).unwrap();
+            let _: Value = client.call("library.sync", SyncParams::default()).unwrap();
+            let events = [client.next_event().unwrap(), client.next_event().unwrap()];
+            std::fs::remove_file(&path).unwrap();
+            events
+        })
+        .await
+        .unwrap();
+        assert_eq!(
+            result,
+            [
+                Event::SyncStarted { libraries: vec![] },
+                Event::SyncFinished { added: 0 },
+            ]
+        );
+    }
+}
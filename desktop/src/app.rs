@@ -6,13 +6,14 @@ use crate::pages::library::LibraryPage;
 use crate::pages::settings::SettingsPage;
 use crate::pages::tools::ToolsPage;
 use crate::widgets::nav_view::NavView;
-use crate::{pages, widgets};
+use crate::{pages, settings, widgets};
+use gami_backend::db;
 use gami_backend::Direction;
 use iced::advanced::graphics::image::image_rs::ImageFormat;
 use iced::application::Title;
 use iced::keyboard::key::Named;
 use iced::widget::Row;
-use iced::window::{icon, Icon, Id, Mode};
+use iced::window::{icon, Icon, Id};
 use iced::{keyboard, window, Element, Task};
 
 #[derive(Copy, Clone, Debug, PartialEq, Default)]
@@ -29,7 +30,7 @@ pub struct App {
 }
 
 impl App {
-    pub fn view(&self) -> Row<Message> {
+    pub fn view(&self) -> Row<'_, Message> {
         let nav = Element::new(self.nav.view()).map(Message::NavView);
         let page = self.page.view().map(Message::Page);
         if self.state == AppState::Desktop {
@@ -38,27 +39,40 @@ impl App {
             iced::widget::row![page]
         }
     }
-    pub fn move_dir_auto(&mut self, dir: Direction) -> Task<Message> {
+    fn update_library(&mut self, message: library::Message) -> Task<Message> {
         if let AppPage::Library(inner_lib) = &mut self.page {
             inner_lib
-                .update(library::Message::MoveInDir(dir))
+                .update(message)
                 .map(PageMessage::Library)
                 .map(Message::Page)
         } else {
             Task::none()
         }
     }
+    pub fn move_dir_auto(&mut self, dir: Direction) -> Task<Message> {
+        self.update_library(library::Message::MoveInDir(dir))
+    }
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::Startup1 => {
-                if let AppPage::Library(inner_lib) = &mut self.page {
+                let reload = if let AppPage::Library(inner_lib) = &mut self.page {
                     inner_lib
                         .update(library::Message::ReloadCache)
                         .map(PageMessage::Library)
                         .map(Message::Page)
                 } else {
                     Task::none()
-                }
+                };
+                Task::batch([reload, self.update(Message::ScheduledBackup)])
+            }
+            Message::ScheduledBackup => {
+                let policy = settings::load().unwrap_or_default().backups.policy();
+                Task::future(async move {
+                    if let Err(err) = db::store().run_scheduled(policy).await {
+                        log::warn!("Scheduled database snapshot failed: {}", err);
+                    }
+                })
+                .map(|_| Message::Page(PageMessage::Library(library::Message::NoOp)))
             }
             Message::Startup2 => window::get_oldest()
                 .and_then(move |id: Id| {
@@ -78,7 +92,7 @@ impl App {
                     widgets::nav_view::Message::NavSelected(index) => {
                         self.page = match index {
                             0 => AppPage::Library(LibraryPage::new()),
-                            1 => AppPage::Achievements(Achievements::default()),
+                            1 => AppPage::Achievements(Achievements),
                             2 => AppPage::AddOns(AddOns::new()),
                             3 => AppPage::Tools(ToolsPage::default()),
                             4 => AppPage::Settings(SettingsPage::default()),
@@ -96,6 +110,14 @@ impl App {
                                 .update(pages::settings::Message::LoadSettings)
                                 .map(PageMessage::Settings)
                                 .map(Message::Page),
+                            AppPage::AddOns(page) => page
+                                .update(pages::add_ons::AddOnMessage::LoadAccounts)
+                                .map(PageMessage::AddOns)
+                                .map(Message::Page),
+                            AppPage::Tools(page) => page
+                                .update(pages::tools::Message::LoadSnapshots)
+                                .map(PageMessage::Tools)
+                                .map(Message::Page),
                             _ => Task::none(),
                         }
                     }
@@ -115,6 +137,18 @@ impl App {
             Message::KeyDown(keyboard::Key::Named(Named::ArrowDown), _) => {
                 self.move_dir_auto(Direction::Down)
             }
+            Message::KeyDown(keyboard::Key::Named(Named::Escape), _) => {
+                self.update_library(library::Message::ClearSelection)
+            }
+            Message::KeyDown(keyboard::Key::Character(c), mods)
+                if mods.command() && c.as_str() == "a" =>
+            {
+                self.update_library(library::Message::SelectAll)
+            }
+            Message::ModifiersChanged(mods) => {
+                self.update_library(library::Message::ModifiersChanged(mods))
+            }
+            Message::CheckRunningGames => self.update_library(library::Message::CheckRunningGames),
             Message::SwitchState(state) => {
                 self.state = state;
                 Task::none()
@@ -128,13 +162,18 @@ impl App {
 }
 
 #[derive(Clone, Debug)]
+#[allow(clippy::large_enum_variant)]
 pub enum Message {
     Startup1,
     Page(PageMessage),
     NavView(widgets::nav_view::Message),
     KeyDown(keyboard::Key, keyboard::Modifiers),
+    ModifiersChanged(keyboard::Modifiers),
     SwitchState(AppState),
     Startup2,
+    /// Takes the database snapshots that are due
+    ScheduledBackup,
+    CheckRunningGames,
 }
 
 pub struct AppTitle;
@@ -1,6 +1,7 @@
 use app::{App, AppTitle, Message};
 use iced::futures::{SinkExt, Stream};
-use iced::{keyboard, stream, Subscription};
+use iced::{event, keyboard, stream, time, Event, Subscription};
+use std::time::Duration;
 
 mod app;
 mod models;
@@ -8,6 +9,11 @@ mod pages;
 mod settings;
 mod widgets;
 
+/// How often to check whether a daily or weekly database snapshot is due
+const BACKUP_CHECK_INTERVAL: Duration = Duration::from_secs(60 * 60);
+/// How often the running processes are matched against the listed games
+const RUNNING_GAMES_INTERVAL: Duration = Duration::from_secs(5);
+
 fn startup_msg_worker() -> impl Stream<Item = Message> {
     stream::channel(100, |mut output| async move {
         output.send(Message::Startup1).await.unwrap();
@@ -19,14 +25,24 @@ pub async fn main() -> iced::Result {
     env_logger::init();
 
     log::info!("Starting Application");
-    gami_backend::db::init().await;
+    gami_backend::db::init()
+        .await
+        .expect("Could not open the game database");
 
     let settings = settings::load().ok().unwrap_or_default();
     iced::application(AppTitle, App::update, App::view)
         .subscription(|_| {
             Subscription::batch([
                 keyboard::on_key_press(|key, mods| Some(Message::KeyDown(key, mods))),
+                event::listen_with(|event, _, _| match event {
+                    Event::Keyboard(keyboard::Event::ModifiersChanged(mods)) => {
+                        Some(Message::ModifiersChanged(mods))
+                    }
+                    _ => None,
+                }),
                 Subscription::run(startup_msg_worker),
+                time::every(BACKUP_CHECK_INTERVAL).map(|_| Message::ScheduledBackup),
+                time::every(RUNNING_GAMES_INTERVAL).map(|_| Message::CheckRunningGames),
             ])
         })
         .theme(move |_| settings.appearance.theme.into())
@@ -1,8 +1,10 @@
 use iced::Theme;
 
 use bitcode::{Decode, Encode};
+use gami_backend::launch;
 use std::fmt;
 
+/// [`launch::PostLaunchAction`] as kept in the settings file
 #[derive(Encode, Decode, Default, Debug, PartialEq, Clone, Copy)]
 pub enum PostLaunchAction {
     #[default]
@@ -10,20 +12,22 @@ pub enum PostLaunchAction {
     Minimize,
     Exit,
 }
-impl PostLaunchAction {
-    pub const ALL: [PostLaunchAction; 3] = [
-        PostLaunchAction::DoNothing,
-        PostLaunchAction::Minimize,
-        PostLaunchAction::Exit,
-    ];
+impl From<PostLaunchAction> for launch::PostLaunchAction {
+    fn from(value: PostLaunchAction) -> Self {
+        match value {
+            PostLaunchAction::DoNothing => Self::DoNothing,
+            PostLaunchAction::Minimize => Self::Minimize,
+            PostLaunchAction::Exit => Self::Exit,
+        }
+    }
 }
-impl fmt::Display for PostLaunchAction {
-    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
-        f.write_str(match self {
-            Self::DoNothing => "Do Nothing",
-            Self::Minimize => "Minimize",
-            Self::Exit => "Exit",
-        })
+impl From<launch::PostLaunchAction> for PostLaunchAction {
+    fn from(value: launch::PostLaunchAction) -> Self {
+        match value {
+            launch::PostLaunchAction::DoNothing => Self::DoNothing,
+            launch::PostLaunchAction::Minimize => Self::Minimize,
+            launch::PostLaunchAction::Exit => Self::Exit,
+        }
     }
 }
 
@@ -76,9 +80,9 @@ pub enum MyTheme {
     /// The built-in Ferra variant:
     Ferra,
 }
-impl Into<Theme> for MyTheme {
-    fn into(self) -> Theme {
-        match self {
+impl From<MyTheme> for Theme {
+    fn from(theme: MyTheme) -> Self {
+        match theme {
             MyTheme::Light => Theme::Light,
             MyTheme::Dark => Theme::Dark,
             MyTheme::Dracula => Theme::Dracula,
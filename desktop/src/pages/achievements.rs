@@ -8,7 +8,7 @@ pub struct Achievements;
 pub enum Message {}
 
 impl Achievements {
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         text("Achievements: TODO").into()
     }
     pub fn update(&mut self, _message: Message) {}
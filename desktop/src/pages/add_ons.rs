@@ -1,28 +1,52 @@
-use gami_backend::ADDONS;
-use gami_sdk::{load_schema, resolve_addon_config_json_path, ConfigsSchema, PluginMetadata};
+use crate::pages::db_task;
+use gami_backend::addon_config::{read_config, write_config, AddonConfig};
+use gami_backend::db::Account;
+use gami_backend::{db, ADDONS};
+use gami_sdk::{load_schema, ConfigsSchema, PluginMetadata};
 use iced::font::Weight;
-use iced::widget::{button, column, row, scrollable, text, text_input, Column};
+use iced::widget::{button, column, pick_list, row, scrollable, text, text_input, Column};
 use iced::{Alignment, Element, Font, Length, Task};
-use std::collections::HashMap;
-use std::fs::File;
+use std::fmt;
 use std::ops::Deref;
 use std::sync::{Arc, Mutex};
 
-type Config = HashMap<String, String>;
-fn get_json(id: String) -> Config {
-    let path = resolve_addon_config_json_path(&id);
-    if !path.exists() {
-        Config::new()
-    } else {
-        serde_json::from_reader(std::fs::File::open(path).unwrap()).unwrap()
+type Config = AddonConfig;
+fn account_id(target: &ConfigTarget) -> Option<&str> {
+    match target {
+        ConfigTarget::AddOn => None,
+        ConfigTarget::Account(account) => Some(&account.account_id),
     }
 }
-async fn write_json(id: String, config: Arc<Mutex<Config>>) {
+fn get_json(id: &str, account: &ConfigTarget) -> Result<Config, String> {
+    read_config(id, account_id(account))
+        .map_err(|err| format!("Could not read the config of {}: {}", id, err))
+}
+async fn write_json(
+    id: String,
+    account: ConfigTarget,
+    config: Arc<Mutex<Config>>,
+) -> Result<(), String> {
     tokio::task::spawn_blocking(move || {
         let config = config.lock().unwrap();
-        let file = File::create(resolve_addon_config_json_path(&id)).unwrap();
-        serde_json::to_writer(file, config.deref()).unwrap();
-    });
+        write_config(&id, account_id(&account), config.deref())
+            .map_err(|err| format!("Could not save the config of {}: {}", id, err))
+    })
+    .await
+    .map_err(|err| err.to_string())?
+}
+/// Which config file is being edited: the add-on wide one or an account override
+#[derive(Clone, Debug, PartialEq)]
+pub enum ConfigTarget {
+    AddOn,
+    Account(Account),
+}
+impl fmt::Display for ConfigTarget {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::AddOn => f.write_str("All accounts"),
+            Self::Account(account) => f.write_str(&account.name),
+        }
+    }
 }
 #[derive(Clone, Debug)]
 pub struct AddOns {
@@ -30,37 +54,75 @@ pub struct AddOns {
     selected: usize,
     curr: ConfigsSchema,
     curr_config: Arc<Mutex<Config>>,
+    accounts: Vec<Account>,
+    targets: Vec<ConfigTarget>,
+    target: ConfigTarget,
+    /// Why the config couldn't be read or saved
+    error: Option<String>,
+    /// Whether the config was read, a config that failed to is not overwritten
+    editable: bool,
 }
 impl AddOns {
     pub fn new() -> Self {
         let metadatas = ADDONS.get_addon_metadatas().to_vec();
-        let curr_config = metadatas
-            .iter()
-            .map(|v| v.id.clone())
-            .map(Into::into)
-            .map(get_json)
-            .next()
-            .unwrap_or_default();
-        let curr = if metadatas.len() == 0 {
-            HashMap::new()
+        let curr = if metadatas.is_empty() {
+            ConfigsSchema::new()
         } else {
             load_schema(&metadatas[0].id)
         };
-        Self {
+        let mut page = Self {
             metadatas,
             selected: 0,
             curr,
-            curr_config: Arc::new(Mutex::new(curr_config)),
-        }
+            curr_config: Arc::default(),
+            accounts: Vec::new(),
+            targets: vec![ConfigTarget::AddOn],
+            target: ConfigTarget::AddOn,
+            error: None,
+            editable: false,
+        };
+        page.load_config();
+        page
+    }
+    /// Reads the config of the selected add-on and target
+    fn load_config(&mut self) {
+        let Some(metadata) = self.metadatas.get(self.selected) else {
+            return;
+        };
+        let config = get_json(&metadata.id, &self.target);
+        self.editable = config.is_ok();
+        self.error = config.as_ref().err().cloned();
+        *self.curr_config.lock().unwrap() = config.unwrap_or_default();
+    }
+    fn refresh_targets(&mut self) {
+        let id: &str = self
+            .metadatas
+            .get(self.selected)
+            .map(|m| &*m.id)
+            .unwrap_or_default();
+        self.targets = [ConfigTarget::AddOn]
+            .into_iter()
+            .chain(
+                self.accounts
+                    .iter()
+                    .filter(|a| a.library_type == id)
+                    .cloned()
+                    .map(ConfigTarget::Account),
+            )
+            .collect();
     }
 }
 #[derive(Debug, Clone)]
 pub enum AddOnMessage {
+    LoadAccounts,
+    AccountsLoaded(Vec<Account>),
     Selected(usize),
+    TargetSelected(ConfigTarget),
     InputChanged(String, String),
+    Saved(Result<(), String>),
 }
 impl AddOns {
-    pub fn view(&self) -> Element<AddOnMessage> {
+    pub fn view(&self) -> Element<'_, AddOnMessage> {
         let curr_config = self.curr_config.lock().unwrap();
         let items: Element<AddOnMessage> = Column::with_children(self.curr.iter().map(|(k, v)| {
             row![
@@ -72,7 +134,10 @@ impl AddOns {
                     .align_x(Alignment::End)
                     .width(Length::FillPortion(1)),
                 text_input("Enter value", curr_config.get(k).unwrap_or(&"".to_owned()))
-                    .on_input(move |v| AddOnMessage::InputChanged(k.clone(), v))
+                    .on_input_maybe(
+                        self.editable
+                            .then_some(move |v| AddOnMessage::InputChanged(k.clone(), v))
+                    )
                     .width(Length::FillPortion(2))
             ]
             .spacing(10)
@@ -95,34 +160,68 @@ impl AddOns {
             )))
             .width(Length::FillPortion(1)),
             column![
-                text("Settings").font(Font {
-                    weight: Weight::Bold,
-                    ..Font::default()
-                }),
+                row![
+                    text("Settings")
+                        .font(Font {
+                            weight: Weight::Bold,
+                            ..Font::default()
+                        })
+                        .width(Length::Fill),
+                    pick_list(
+                        self.targets.as_slice(),
+                        Some(self.target.clone()),
+                        AddOnMessage::TargetSelected
+                    ),
+                ],
                 items
             ]
+            .push_maybe(
+                self.error
+                    .as_deref()
+                    .map(|error| text(error).style(text::danger))
+            )
             .width(Length::FillPortion(5)),
         ]
         .spacing(5)
         .into()
     }
     pub fn update(&mut self, message: AddOnMessage) -> Task<AddOnMessage> {
-        let id: &str = &self.metadatas[self.selected].id;
+        if self.metadatas.is_empty() {
+            return Task::none();
+        }
         let curr_config = self.curr_config.clone();
         let selected = self.selected;
         match message {
+            AddOnMessage::LoadAccounts => {
+                return db_task(db::store().get_accounts(), AddOnMessage::AccountsLoaded);
+            }
+            AddOnMessage::AccountsLoaded(accounts) => {
+                self.accounts = accounts;
+                self.refresh_targets();
+            }
             AddOnMessage::Selected(index) => {
+                if index != self.selected {
+                    self.target = ConfigTarget::AddOn;
+                }
                 self.selected = index;
-                self.curr = load_schema(id);
-
-                *self.curr_config.lock().unwrap() = get_json(id.to_owned());
+                self.refresh_targets();
+                self.curr = load_schema(&self.metadatas[self.selected].id);
+                self.load_config();
+            }
+            AddOnMessage::TargetSelected(target) => {
+                self.target = target;
+                return self.update(AddOnMessage::Selected(selected));
             }
             AddOnMessage::InputChanged(key, value) => {
+                let id: &str = &self.metadatas[self.selected].id;
                 self.curr_config.lock().unwrap().insert(key, value);
-                return Task::perform(write_json(id.into(), curr_config.clone()), move |_| {
-                    AddOnMessage::Selected(selected)
-                });
+                return Task::perform(
+                    write_json(id.into(), self.target.clone(), curr_config.clone()),
+                    AddOnMessage::Saved,
+                );
             }
+            AddOnMessage::Saved(Ok(())) => return self.update(AddOnMessage::Selected(selected)),
+            AddOnMessage::Saved(Err(err)) => self.error = Some(err),
         }
         Task::none()
     }
@@ -2,6 +2,7 @@ use crate::pages;
 use iced::{Element, Task};
 
 #[derive(Debug, Clone)]
+#[allow(clippy::large_enum_variant)]
 pub enum PageMessage {
     AddOns(pages::add_ons::AddOnMessage),
     Library(pages::library::Message),
@@ -11,7 +12,7 @@ pub enum PageMessage {
 }
 
 #[derive(Clone, Debug)]
-
+#[allow(clippy::large_enum_variant)]
 pub enum AppPage {
     AddOns(pages::add_ons::AddOns),
     Library(pages::library::LibraryPage),
@@ -26,7 +27,7 @@ impl Default for AppPage {
 }
 
 impl AppPage {
-    pub fn view(&self) -> Element<PageMessage> {
+    pub fn view(&self) -> Element<'_, PageMessage> {
         match self {
             AppPage::AddOns(counter) => Element::from(counter.view()).map(PageMessage::AddOns),
             AppPage::Library(lib) => Element::from(lib.view()).map(PageMessage::Library),
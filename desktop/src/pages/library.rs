@@ -1,23 +1,35 @@
+use crate::pages::db_task;
+use crate::settings;
+use crate::widgets::collection_editor::{CollectionEditor, EditorMessage};
+use crate::widgets::facet_sidebar::{FacetMessage, FacetSidebar};
+use crate::widgets::game_editor::{local_image_path, GameEditor, GameEditorMessage};
 use crate::widgets::library_table::{LibraryTable, TableMessage};
-use crate::widgets::number_input::number_input;
-use chrono::{DateTime, Utc};
-use gami_backend::db::ops::{GamesFilters, SortField, SortOrder};
-use gami_backend::{db, get_actions, Direction, GameAction, GameTextField, ADDONS};
-use gami_sdk::{
-    CompletionStatus, EditableEnum, GameCommon, GameData, GameInstallStatus, GameLibrary,
+use crate::widgets::metadata_review::{MetadataReview, MetadataReviewMessage};
+use chrono::{DateTime, Local, Utc};
+use gami_backend::action_executor::{run_library_action, LibraryAction};
+use gami_backend::db::ops::{
+    BulkUndo, FacetCounts, GamesFilters, HiddenFilter, Sort, SortField, SortOrder,
 };
+use gami_backend::db::{Account, Collection, FieldSource};
+use gami_backend::launch::PostLaunchAction;
+use gami_backend::process_monitor::{self, RunningGame};
+use gami_backend::{db, get_actions, launch, Direction, GameAction, GameField, ADDONS};
+use gami_sdk::{CompletionStatus, EditableEnum, GameData, GameInstallStatus, TagData};
 use iced::advanced::svg::Handle;
 use iced::alignment::Vertical;
 use iced::font::Weight;
+use iced::futures::SinkExt;
 use iced::widget::{
-    button, column, container, image, pick_list, row, scrollable, text, text_input, tooltip,
-    Button, Column, Container, Row, Scrollable, Svg,
+    button, checkbox, column, container, image, pick_list, row, scrollable, text, text_input,
+    tooltip, Column, Container, Row, Scrollable, Svg, Text,
 };
-use iced::{ContentFit, Element, Fill, Font, Length, Task, Theme};
+use iced::window::Mode;
+use iced::{keyboard, stream, window, Color, ContentFit, Element, Fill, Font, Length, Task, Theme};
 use iced_aw::ContextMenu;
-use std::cell::LazyCell;
 use std::cmp::PartialEq;
-use url::Url;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::sync::LazyLock;
 
 #[derive(Copy, Clone, Debug, PartialEq)]
 pub enum LibraryViewType {
@@ -32,7 +44,7 @@ struct LibraryViewTypeMeta {
     name: &'static str,
     icon: Handle,
 }
-const VIEW_TYPES: LazyCell<[LibraryViewTypeMeta; 3]> = LazyCell::new(|| {
+static VIEW_TYPES: LazyLock<[LibraryViewTypeMeta; 3]> = LazyLock::new(|| {
     [
         LibraryViewTypeMeta {
             value: LibraryViewType::List,
@@ -51,14 +63,71 @@ const VIEW_TYPES: LazyCell<[LibraryViewTypeMeta; 3]> = LazyCell::new(|| {
         },
     ]
 });
+#[derive(Clone, Debug, PartialEq)]
+pub enum AccountFilter {
+    All,
+    Account(Account),
+}
+impl fmt::Display for AccountFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("All accounts"),
+            Self::Account(account) => write!(f, "{} ({})", account.name, account.library_type),
+        }
+    }
+}
+#[derive(Clone, Debug, PartialEq)]
+pub enum TagFilter {
+    All,
+    Tag(TagData),
+}
+impl fmt::Display for TagFilter {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::All => f.write_str("All tags"),
+            Self::Tag(tag) => tag.fmt(f),
+        }
+    }
+}
+const TAG_COLORS: [&str; 8] = [
+    "#e06c75", "#e5c07b", "#98c379", "#56b6c2", "#61afef", "#c678dd", "#d19a66", "#abb2bf",
+];
 #[derive(Clone, Debug)]
 pub struct LibraryPage {
-    edit_game: Option<GameData>,
+    game_editor: Option<GameEditor>,
+    metadata_review: Option<MetadataReview>,
     curr_index: usize,
     view_type: LibraryViewType,
     games: Vec<GameData>,
     table: LibraryTable,
     filters: GamesFilters,
+    accounts: Vec<AccountFilter>,
+    tags: Vec<TagData>,
+    tag_filters: Vec<TagFilter>,
+    tag_game: Option<GameData>,
+    new_tag_name: String,
+    new_tag_color: usize,
+    collections: Vec<Collection>,
+    active_collection: Option<i32>,
+    collection_editor: Option<CollectionEditor>,
+    show_facets: bool,
+    facet_sidebar: FacetSidebar,
+    facet_counts: FacetCounts,
+    show_column_editor: bool,
+    /// IDs of the games selected for bulk edits, besides the current one
+    selected: HashSet<i32>,
+    /// Index a shift selection range starts from
+    anchor: usize,
+    modifiers: keyboard::Modifiers,
+    bulk_undo: Option<BulkUndo>,
+    /// Why the last game action failed, cleared by the next one that succeeds
+    action_error: Option<String>,
+    /// What the last launch could not apply, see [`Message::LaunchSettingsIgnored`]
+    launch_notice: Option<String>,
+    /// Games of the library found running by the last check
+    running: HashMap<i32, RunningGame>,
+    /// Origin of the current game's field values
+    field_sources: Vec<FieldSource>,
 }
 
 #[derive(Debug, Clone)]
@@ -71,15 +140,69 @@ pub enum Message {
     ReloadCache,
     CacheReloaded(Vec<GameData>),
     SelectGame(usize),
+    ModifiersChanged(keyboard::Modifiers),
+    SelectAll,
+    ClearSelection,
+    BulkCompletionStatus(CompletionStatus),
+    BulkAddTag(TagData),
+    BulkRemoveTag(TagData),
+    BulkSetHidden(bool),
+    BulkDelete,
+    BulkRefreshMetadata,
+    BulkInstall,
+    BulkUninstall,
+    BulkApplied(BulkUndo),
+    UndoBulk,
     SearchChanged(String),
-    SortFieldChanged(SortField),
-    ToggleSortDirection,
-    CloseEditor,
-    EditorTextChanged(GameTextField, String),
-    EditorCompletionStatusChanged(CompletionStatus),
-    SaveEditor,
+    SortFieldChanged(usize, SortField),
+    ToggleSortDirection(usize),
+    AddSortKey,
+    RemoveSortKey(usize),
+    ToggleColumnEditor,
+    AccountsLoaded(Vec<Account>),
+    AccountSelected(AccountFilter),
+    TagsLoaded(Vec<TagData>),
+    TagFilterSelected(TagFilter),
+    HiddenFilterChanged(HiddenFilter),
+    FavouritesOnlyToggled(bool),
+    SetFavourite(i32, bool),
+    SetHidden(i32, bool),
+    ShowTagPicker(GameData),
+    CloseTagPicker,
+    SetGameTag(i32, bool),
+    NewTagNameChanged(String),
+    NewTagColorSelected(usize),
+    CreateTag,
+    TagCreated(TagData),
+    CollectionsLoaded(Vec<Collection>),
+    CollectionSelected(Option<i32>),
+    NewCollection,
+    EditCollection(i32),
+    DeleteCollection(i32),
+    CollectionEditor(EditorMessage),
+    CollectionSaved(Collection),
+    ToggleFacets,
+    Facets(FacetMessage),
+    FacetCountsLoaded(FacetCounts),
+    GameEditor(GameEditorMessage),
+    MetadataReview(MetadataReviewMessage),
+    FieldSourcesLoaded(i32, Vec<FieldSource>),
+    SetFieldLocked(i32, GameField, bool),
     MoveInDir(Direction),
     UpdateLastPlayed(i32, DateTime<Utc>),
+    ActionFailed(String),
+    ActionSucceeded,
+    /// A game started, with the post-launch action to apply and whether its exit will be noticed
+    GameLaunched(i32, DateTime<Utc>, PostLaunchAction, bool),
+    /// Profile settings the library's client did not apply to a launched game
+    LaunchSettingsIgnored(Vec<&'static str>),
+    /// A launched game's process exited
+    GameExited(PostLaunchAction),
+    /// Looks for running games among the listed ones
+    CheckRunningGames,
+    RunningGamesFound(Vec<RunningGame>),
+    SetPreferredSource(i32, i32),
+    UnmergeSource(i32),
     NoOp,
 }
 impl LibraryPage {
@@ -98,22 +221,50 @@ impl LibraryPage {
         })
     }
     pub fn new() -> Self {
-        let me = Self {
-            edit_game: None,
+        let library_settings = settings::load().unwrap_or_default().library;
+        let facets = serde_json::from_str(&library_settings.facets).unwrap_or_default();
+
+        Self {
+            game_editor: None,
+            metadata_review: None,
             view_type: LibraryViewType::List,
             games: Vec::new(),
             curr_index: 0,
-            table: LibraryTable::new(),
-            filters: GamesFilters::default(),
-        };
-        me
+            table: LibraryTable::new(&library_settings.table_columns),
+            facet_sidebar: FacetSidebar::new(&facets),
+            filters: GamesFilters {
+                facets,
+                sort: vec![Sort::default()],
+                ..GamesFilters::default()
+            },
+            accounts: vec![AccountFilter::All],
+            tags: Vec::new(),
+            tag_filters: vec![TagFilter::All],
+            tag_game: None,
+            new_tag_name: String::new(),
+            new_tag_color: 0,
+            collections: Vec::new(),
+            active_collection: None,
+            collection_editor: None,
+            show_facets: library_settings.show_facets,
+            facet_counts: FacetCounts::default(),
+            show_column_editor: false,
+            selected: HashSet::new(),
+            anchor: 0,
+            modifiers: keyboard::Modifiers::default(),
+            bulk_undo: None,
+            action_error: None,
+            launch_notice: None,
+            running: HashMap::new(),
+            field_sources: Vec::new(),
+        }
     }
     fn game_menu<'a>(
         &'a self,
         game: &'a GameData,
         underlay: Element<'a, Message>,
     ) -> Element<'a, Message> {
-        let actions = get_actions(game.install_status);
+        let actions = get_actions(game.install_status, self.running.contains_key(&game.id));
         ContextMenu::new(underlay, || {
             column(actions.iter().map(|ga| {
                 let svg: Svg<'static, Theme> = Svg::new(Handle::from_memory(ga.icon));
@@ -123,16 +274,341 @@ impl LibraryPage {
                     .width(Fill)
                     .into()
             }))
+            .push(
+                button(text("Add to tag…"))
+                    .style(button::secondary)
+                    .on_press_with(|| Message::ShowTagPicker(game.clone()))
+                    .width(Fill),
+            )
+            .push(
+                button(text(if game.favourite {
+                    "Unfavourite"
+                } else {
+                    "Favourite"
+                }))
+                .style(button::secondary)
+                .on_press(Message::SetFavourite(game.id, !game.favourite))
+                .width(Fill),
+            )
+            .push(
+                button(text(if game.hidden { "Unhide" } else { "Hide" }))
+                    .style(button::secondary)
+                    .on_press(Message::SetHidden(game.id, !game.hidden))
+                    .width(Fill),
+            )
             .width(120)
             .into()
         })
         .into()
     }
 
+    /// Remembers the filters and columns, a failure is logged as the library works on without
+    fn save_library_settings(&self) {
+        // saving defaults over settings that couldn't be read would lose them
+        let mut settings = match settings::load() {
+            Ok(settings) => settings,
+            Err(err) => {
+                log::error!("Could not read the settings to save the library's: {}", err);
+                return;
+            }
+        };
+        settings.library.show_facets = self.show_facets;
+        match serde_json::to_string(&self.filters.facets) {
+            Ok(facets) => settings.library.facets = facets,
+            Err(err) => log::error!("Could not encode the library filters: {}", err),
+        }
+        settings.library.table_columns = self.table.column_settings();
+        if let Err(err) = settings::save(&settings) {
+            log::error!("Could not save the library settings: {}", err);
+        }
+    }
+
+    fn filter_bar(&self) -> Element<'_, Message> {
+        row![
+            button(text(if self.show_facets {
+                "Hide filters"
+            } else {
+                "Filters"
+            }))
+            .style(if self.filters.facets.is_empty() {
+                button::secondary
+            } else {
+                button::primary
+            })
+            .on_press(Message::ToggleFacets),
+            button(text("Columns"))
+                .style(if self.show_column_editor {
+                    button::primary
+                } else {
+                    button::secondary
+                })
+                .on_press_maybe(if self.view_type == LibraryViewType::Table {
+                    Some(Message::ToggleColumnEditor)
+                } else {
+                    None
+                }),
+            pick_list(
+                self.accounts.as_slice(),
+                Some(
+                    self.filters
+                        .account
+                        .clone()
+                        .map(AccountFilter::Account)
+                        .unwrap_or(AccountFilter::All)
+                ),
+                Message::AccountSelected
+            )
+            .width(Length::FillPortion(3)),
+            pick_list(
+                self.tag_filters.as_slice(),
+                Some(
+                    self.tags
+                        .iter()
+                        .find(|t| self.filters.tags.contains(&t.id))
+                        .cloned()
+                        .map(TagFilter::Tag)
+                        .unwrap_or(TagFilter::All)
+                ),
+                Message::TagFilterSelected
+            )
+            .width(Length::FillPortion(3)),
+            pick_list(
+                &HiddenFilter::ALL[..],
+                Some(self.filters.hidden),
+                Message::HiddenFilterChanged
+            )
+            .width(Length::FillPortion(2)),
+            checkbox("Favourites only", self.filters.favourites_only)
+                .on_toggle(Message::FavouritesOnlyToggled)
+                .width(Length::FillPortion(2)),
+            button(text("Undo bulk edit"))
+                .style(button::secondary)
+                .on_press_maybe(self.bulk_undo.as_ref().map(|_| Message::UndoBulk)),
+            text(self.action_error.as_deref().unwrap_or_default()).style(text::danger),
+            text(self.launch_notice.as_deref().unwrap_or_default()).style(text::secondary),
+        ]
+        .spacing(3)
+        .align_y(Vertical::Center)
+        .into()
+    }
+
+    fn collections_sidebar(&self) -> Element<'_, Message> {
+        let entry = |name: &str, id: Option<i32>| -> Element<'_, Message> {
+            button(text(name.to_string()))
+                .style(if self.active_collection == id {
+                    button::primary
+                } else {
+                    button::text
+                })
+                .on_press(Message::CollectionSelected(id))
+                .width(Fill)
+                .into()
+        };
+        let mut sidebar = column![entry("All games", None)].extend(
+            self.collections
+                .iter()
+                .map(|collection| entry(&collection.name, Some(collection.id))),
+        );
+        if let Some(id) = self.active_collection {
+            sidebar = sidebar.push(
+                row![
+                    button(text("Edit")).on_press(Message::EditCollection(id)),
+                    button(text("Delete"))
+                        .style(button::danger)
+                        .on_press(Message::DeleteCollection(id)),
+                ]
+                .spacing(4),
+            );
+        }
+        scrollable(
+            sidebar
+                .push(
+                    button(text("New collection"))
+                        .style(button::secondary)
+                        .on_press(Message::NewCollection),
+                )
+                .spacing(2),
+        )
+        .width(Length::FillPortion(2))
+        .into()
+    }
+
+    fn tag_picker<'a>(&'a self, game: &'a GameData) -> Column<'a, Message> {
+        column![
+            row![
+                button(text("Close")).on_press(Message::CloseTagPicker),
+                text(format!("Tags for {}", game.name)).font(Font {
+                    weight: Weight::Semibold,
+                    ..Font::default()
+                }),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            Column::with_children(self.tags.iter().map(|tag| {
+                let tag_id = tag.id;
+                checkbox(tag.name.as_str(), game.tags.iter().any(|t| t.id == tag_id))
+                    .on_toggle(move |checked| Message::SetGameTag(tag_id, checked))
+                    .into()
+            }))
+            .spacing(4),
+            row![
+                text_input("New tag", &self.new_tag_name)
+                    .on_input(Message::NewTagNameChanged)
+                    .on_submit(Message::CreateTag),
+                Row::with_children(TAG_COLORS.iter().enumerate().map(|(index, color)| {
+                    let color = Color::parse(color).unwrap_or(Color::WHITE);
+                    button(
+                        text(if index == self.new_tag_color {
+                            "●"
+                        } else {
+                            "○"
+                        })
+                        .color(color),
+                    )
+                    .style(button::text)
+                    .on_press(Message::NewTagColorSelected(index))
+                    .into()
+                })),
+                button(text("Create"))
+                    .style(button::success)
+                    .on_press_maybe(if self.new_tag_name.trim().is_empty() {
+                        None
+                    } else {
+                        Some(Message::CreateTag)
+                    }),
+            ]
+            .spacing(4)
+            .align_y(Vertical::Center),
+        ]
+        .spacing(8)
+    }
+
+    fn is_selected(&self, index: usize, game: &GameData) -> bool {
+        index == self.curr_index || self.selected.contains(&game.id)
+    }
+    fn selected_ids(&self) -> Vec<i32> {
+        self.selected.iter().copied().collect()
+    }
+    /// Selects every game between the anchor and `index`, both included
+    fn select_range(&mut self, index: usize) {
+        let (start, end) = (self.anchor.min(index), self.anchor.max(index));
+        self.selected = self
+            .games
+            .iter()
+            .skip(start)
+            .take(end + 1 - start)
+            .map(|game| game.id)
+            .collect();
+    }
+    fn sync_table_selection(&mut self) {
+        let (curr_index, selected) = (self.curr_index, &self.selected);
+        self.table
+            .set_selected(|index, game| index == curr_index || selected.contains(&game.id));
+    }
+
+    fn bulk_editor(&self) -> Column<'_, Message> {
+        column![
+            row![
+                text(format!("{} games selected", self.selected.len())).font(Font {
+                    weight: Weight::Semibold,
+                    ..Font::default()
+                }),
+                button(text("Select all"))
+                    .style(button::secondary)
+                    .on_press(Message::SelectAll),
+                button(text("Clear selection"))
+                    .style(button::secondary)
+                    .on_press(Message::ClearSelection),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            pick_list(
+                CompletionStatus::ALL,
+                None::<CompletionStatus>,
+                Message::BulkCompletionStatus
+            )
+            .placeholder("Set completion status"),
+            row![
+                pick_list(self.tags.as_slice(), None::<TagData>, Message::BulkAddTag)
+                    .placeholder("Add tag"),
+                pick_list(
+                    self.tags.as_slice(),
+                    None::<TagData>,
+                    Message::BulkRemoveTag
+                )
+                .placeholder("Remove tag"),
+            ]
+            .spacing(4),
+            row![
+                button(text("Hide")).on_press(Message::BulkSetHidden(true)),
+                button(text("Unhide")).on_press(Message::BulkSetHidden(false)),
+                button(text("Refresh metadata")).on_press(Message::BulkRefreshMetadata),
+            ]
+            .spacing(4),
+            row![
+                button(text("Install"))
+                    .style(button::success)
+                    .on_press(Message::BulkInstall),
+                button(text("Uninstall"))
+                    .style(button::secondary)
+                    .on_press(Message::BulkUninstall),
+                button(text("Delete"))
+                    .style(button::danger)
+                    .on_press(Message::BulkDelete),
+            ]
+            .spacing(4),
+        ]
+        .spacing(8)
+    }
+
+    fn sort_keys(&self) -> Element<'_, Message> {
+        Row::with_children(self.filters.sort.iter().enumerate().map(|(index, sort)| {
+            let mut key = row![
+                button(
+                    Svg::new(if sort.order == SortOrder::Ascending {
+                        Handle::from_memory(include_bytes!("../icons/tabler--sort-descending.svg"))
+                    } else {
+                        Handle::from_memory(include_bytes!("../icons/tabler--sort-ascending.svg"))
+                    })
+                    .width(24)
+                    .height(24)
+                )
+                .on_press(Message::ToggleSortDirection(index)),
+                pick_list(&SortField::ALL[..], Some(sort.field), move |field| {
+                    Message::SortFieldChanged(index, field)
+                }),
+            ];
+            if index > 0 {
+                key = key.push(
+                    button(text("×"))
+                        .style(button::secondary)
+                        .on_press(Message::RemoveSortKey(index)),
+                );
+            }
+            key.into()
+        }))
+        .push(tooltip(
+            button(text("+")).style(button::secondary).on_press_maybe(
+                if self.filters.sort.len() < SortField::ALL.len() {
+                    Some(Message::AddSortKey)
+                } else {
+                    None
+                },
+            ),
+            container(text("Then sort by"))
+                .padding(6)
+                .style(container::rounded_box),
+            tooltip::Position::Bottom,
+        ))
+        .spacing(4)
+        .width(Length::FillPortion(5))
+        .into()
+    }
+
     fn toolbar(&self) -> Element<'_, Message> {
         Element::from(
             row![
-                text_input("Enter search", &self.filters.search)
+                text_input("Search, e.g. genre:rpg dev:valve", &self.filters.search)
                     .on_input(Message::SearchChanged)
                     .width(Length::FillPortion(7)),
                 Container::new(
@@ -153,28 +629,7 @@ impl LibraryPage {
                     .spacing(2)
                 )
                 .width(Length::FillPortion(3)),
-                row![
-                    button(
-                        Svg::new(if self.filters.sort.order == SortOrder::Ascending {
-                            Handle::from_memory(include_bytes!(
-                                "../icons/tabler--sort-descending.svg"
-                            ))
-                        } else {
-                            Handle::from_memory(include_bytes!(
-                                "../icons/tabler--sort-ascending.svg"
-                            ))
-                        })
-                        .width(24)
-                        .height(24)
-                    )
-                    .on_press(Message::ToggleSortDirection),
-                    pick_list(
-                        &SortField::ALL[..],
-                        Some(self.filters.sort.field),
-                        Message::SortFieldChanged
-                    )
-                ]
-                .width(Length::FillPortion(3)),
+                self.sort_keys(),
                 tooltip(
                     button(
                         Svg::new(Handle::from_memory(include_bytes!(
@@ -211,112 +666,6 @@ impl LibraryPage {
         )
     }
 
-    fn editor(game: &GameData) -> Column<Message> {
-        fn editor_row<'a>(
-            name: &'a str,
-            value: impl Into<Element<'a, Message>>,
-        ) -> Row<'a, Message> {
-            row![
-                text(format!("{}:", name))
-                    .font(Font {
-                        weight: Weight::Semibold,
-                        ..Font::default()
-                    })
-                    .width(Length::FillPortion(3)),
-                column![value.into()].width(Length::FillPortion(7))
-            ]
-        }
-        fn editor_text_row<'a>(
-            field: GameTextField,
-            name: &'a str,
-            curr: &'a str,
-            placeholder: &'a str,
-        ) -> Row<'a, Message> {
-            editor_row(
-                name,
-                text_input(placeholder, curr)
-                    .on_input(move |txt| Message::EditorTextChanged(field, txt)),
-            )
-        }
-        fn editor_enum_row<'a, TEnum, TMapper>(
-            name: &'a str,
-            curr: TEnum,
-            mapper: TMapper,
-        ) -> Row<'a, Message>
-        where
-            TEnum: Copy + EditableEnum,
-            TMapper: Fn(TEnum) -> Message + 'a,
-        {
-            editor_row(name, pick_list(TEnum::ALL, Some(curr), mapper))
-        }
-        fn editor_btn(
-            text_content: &'static str,
-            bytes: &'static [u8],
-        ) -> Button<'static, Message> {
-            button(
-                row![
-                    Svg::new(Handle::from_memory(bytes)).width(Length::FillPortion(1)),
-                    text(text_content).width(Length::FillPortion(9)),
-                ]
-                .spacing(10.0),
-            )
-        }
-        column![
-            row![
-                editor_btn(
-                    "Close",
-                    include_bytes!("../icons/tabler--arrow-back.svg").as_slice()
-                )
-                .on_press(Message::CloseEditor),
-                editor_btn(
-                    "Save",
-                    include_bytes!("../icons/tabler--device-floppy.svg").as_slice()
-                )
-                .style(button::success)
-                .on_press(Message::SaveEditor),
-            ]
-            .padding(6)
-            .spacing(20.0),
-            editor_text_row(
-                GameTextField::Name,
-                "Name",
-                game.name.as_str(),
-                "Enter name"
-            ),
-            editor_text_row(
-                GameTextField::Description,
-                "Description",
-                game.description.as_str(),
-                "Enter description"
-            ),
-            editor_text_row(
-                GameTextField::IconUrl,
-                "Icon URL",
-                game.icon_url.as_ref().map(|v| v.as_str()).unwrap_or(""),
-                "Enter icon URL"
-            ),
-            editor_text_row(
-                GameTextField::CoverUrl,
-                "Cover URL",
-                game.cover_url.as_ref().map(|v| v.as_str()).unwrap_or(""),
-                "Enter hero URL"
-            ),
-            editor_text_row(
-                GameTextField::HeaderUrl,
-                "Header URL",
-                game.header_url.as_ref().map(|v| v.as_str()).unwrap_or(""),
-                "Enter header URL"
-            ),
-            editor_enum_row(
-                "Completion Status",
-                game.completion_status,
-                Message::EditorCompletionStatusChanged
-            ),
-            number_input("Enter ID", game.id.clone())
-                .map(|v| Message::SearchChanged(format!("{:?}", v)))
-        ]
-    }
-
     fn game_details<'a>(&'a self, curr: &'a GameData) -> Column<'a, Message> {
         fn detail_row<'b>(
             name: &'static str,
@@ -335,13 +684,14 @@ impl LibraryPage {
         fn detail_row_text<'b>(name: &'static str, content: String) -> Row<'b, Message> {
             detail_row(name, text(content))
         }
-        let actions = get_actions(curr.install_status);
+        let running = self.running.contains_key(&curr.id);
+        let actions = get_actions(curr.install_status, running);
         let last_played = curr
             .last_played
             .map(|t| t.to_string())
             .unwrap_or("None".into());
         column![
-            Row::with_children(actions.into_iter().map(|ga| {
+            Row::with_children(actions.iter().map(|ga| {
                 button(
                     row![
                         Svg::new(Handle::from_memory(ga.icon)).width(Length::FillPortion(1)),
@@ -356,13 +706,22 @@ impl LibraryPage {
             }))
             .height(30)
             .spacing(2),
-            text(&curr.name),
+            row![text(&curr.name)]
+                .push_maybe(running.then(now_playing_badge))
+                .spacing(6)
+                .align_y(Vertical::Center),
             text(&curr.description),
             detail_row_text("ID", curr.id.to_string()),
             detail_row_text("Last Played", last_played),
             detail_row_text("Install Status", curr.install_status.to_string()),
             detail_row_text("Completion Status", curr.completion_status.to_string()),
             detail_row_text("Playtime", curr.play_time.to_string()),
+            detail_row_text("Developers", curr.developers.join(", ")),
+            detail_row_text("Publishers", curr.publishers.join(", ")),
+            detail_row_text(
+                "Install Path",
+                curr.install_path.clone().unwrap_or("None".into())
+            ),
             detail_row_text(
                 "Release Date",
                 curr.release_date
@@ -374,9 +733,130 @@ impl LibraryPage {
                 Row::with_children(curr.genres.iter().map(|g| text(g.name.trim_end()).into()))
                     .spacing(2),
             ),
+            detail_row(
+                "Tags",
+                Row::with_children(curr.tags.iter().map(|tag| {
+                    text(tag.name.as_str())
+                        .color(Color::parse(&tag.color).unwrap_or(Color::WHITE))
+                        .into()
+                }))
+                .spacing(6),
+            ),
+            detail_row_text(
+                "Favourite",
+                if curr.favourite { "Yes" } else { "No" }.into()
+            ),
+            Self::game_sources(curr),
+            self.field_sources_view(curr),
         ]
     }
-    pub fn view(&self) -> Element<Message> {
+    /// Where each field's value came from, with a lock against syncs and refreshes
+    fn field_sources_view<'a>(&'a self, curr: &'a GameData) -> Column<'a, Message> {
+        column![text("Field sources").font(Font {
+            weight: Weight::Semibold,
+            ..Font::default()
+        })]
+        .extend(GameField::ALL.into_iter().map(|field| {
+            let source = self.field_sources.iter().find(|s| s.field == field);
+            let origin = match source {
+                Some(s) if s.source.is_empty() => "Unknown".to_string(),
+                Some(s) => format!(
+                    "{}, {}",
+                    if s.is_user() {
+                        "Edited by you"
+                    } else {
+                        &s.source
+                    },
+                    s.updated_at.with_timezone(&Local).format("%Y-%m-%d %H:%M")
+                ),
+                None => "Unknown".to_string(),
+            };
+            let locked = source.is_some_and(|s| s.locked);
+            row![
+                text(field.to_string()).width(Length::FillPortion(3)),
+                text(origin).width(Length::FillPortion(5)),
+                button(text(if locked { "Unlock" } else { "Lock" }))
+                    .style(if locked {
+                        button::primary
+                    } else {
+                        button::secondary
+                    })
+                    .on_press(Message::SetFieldLocked(curr.id, field, !locked)),
+            ]
+            .spacing(4)
+            .align_y(Vertical::Center)
+            .into()
+        }))
+        .spacing(2)
+    }
+    fn open_metadata_review(&mut self, game_ids: Vec<i32>) -> Task<Message> {
+        let review = MetadataReview::new(game_ids, db::ops::get_metadata_sources(&ADDONS));
+        let task = Self::fetch_metadata_diffs(&review);
+        self.metadata_review = Some(review);
+        task
+    }
+    fn fetch_metadata_diffs(review: &MetadataReview) -> Task<Message> {
+        db_task(
+            db::store().refresh_metadata(
+                &ADDONS,
+                review.game_ids.clone(),
+                review.selected_sources(),
+            ),
+            |diffs| Message::MetadataReview(MetadataReviewMessage::Loaded(diffs)),
+        )
+    }
+    fn load_field_sources(&self) -> Task<Message> {
+        let Some(game_id) = self.games.get(self.curr_index).map(|game| game.id) else {
+            return Task::none();
+        };
+        db_task(db::store().get_field_sources(game_id), move |sources| {
+            Message::FieldSourcesLoaded(game_id, sources)
+        })
+    }
+    fn game_sources(curr: &GameData) -> Column<'_, Message> {
+        if curr.sources.len() < 2 {
+            return column![];
+        }
+        let preferred = curr.preferred_source();
+        column![text("Sources").font(Font {
+            weight: Weight::Semibold,
+            ..Font::default()
+        })]
+        .extend(curr.sources.iter().map(|source| {
+            let label = if source.account_id.is_empty() {
+                format!("{}: {}", source.library_type, source.install_status)
+            } else {
+                format!(
+                    "{} ({}): {}",
+                    source.library_type, source.account_id, source.install_status
+                )
+            };
+            let action = get_actions(source.install_status, false)[0];
+            row![
+                text(label).width(Length::Fill),
+                button(text(action.name))
+                    .style(move |theme, status| action.color.button_style::<Message>(theme, status))
+                    .on_press(Message::GameAction(action.kind, curr.for_source(source))),
+                button(text("Prefer")).on_press_maybe(if preferred.id == source.id {
+                    None
+                } else {
+                    Some(Message::SetPreferredSource(curr.id, source.id))
+                }),
+                button(text("Unmerge"))
+                    .style(button::secondary)
+                    .on_press_maybe(if source.id == curr.id {
+                        None
+                    } else {
+                        Some(Message::UnmergeSource(source.id))
+                    }),
+            ]
+            .spacing(4)
+            .align_y(Vertical::Center)
+            .into()
+        }))
+        .spacing(2)
+    }
+    pub fn view(&self) -> Element<'_, Message> {
         let curr: Option<&GameData> = self.games.as_slice().get(self.curr_index);
 
         let items: Scrollable<Message> = scrollable(match self.view_type {
@@ -387,37 +867,33 @@ impl LibraryPage {
                     .iter()
                     .enumerate()
                     .map(|(index, game)| {
-                        let raw_icon_url = game.icon_url.as_ref().map(String::as_str).unwrap_or("");
-                        let icon_url: Option<Url> = if raw_icon_url.is_empty() {
-                            None
-                        } else {
-                            Some(Url::parse(raw_icon_url).unwrap())
-                        };
-                        let icon_path = if let Some(url) = icon_url.as_ref() {
-                            let mut raw = urlencoding::decode(url.path()).unwrap();
-                            if cfg!(windows) {
-                                let my_path: String = (&raw[1..]).into();
-                                raw = my_path.into();
-                            }
-                            raw
-                        } else {
-                            "".into()
-                        };
+                        let icon_path = game
+                            .icon_url
+                            .as_deref()
+                            .and_then(local_image_path)
+                            .unwrap_or_default();
 
                         (
                             game,
                             Element::from(
                                 button(
                                     row![
-                                        image(icon_path.as_ref()).width(32),
-                                        text(&game.name).width(Fill),
+                                        image(icon_path).width(32),
+                                        row![text(&game.name)]
+                                            .push_maybe(
+                                                self.running
+                                                    .contains_key(&game.id)
+                                                    .then(now_playing_badge)
+                                            )
+                                            .spacing(6)
+                                            .width(Fill),
                                         Svg::new(Self::auto_installer_icon(game.install_status))
                                             .width(Length::Shrink),
                                     ]
                                     .width(Fill)
                                     .spacing(2),
                                 )
-                                .style(if index == self.curr_index {
+                                .style(if self.is_selected(index, game) {
                                     button::primary
                                 } else {
                                     button::text
@@ -434,127 +910,580 @@ impl LibraryPage {
         });
         let toolbar = self.toolbar();
 
-        let raw_side_content = if let Some(game) = self.edit_game.as_ref() {
-            Some(Self::editor(game))
-        } else if let Some(curr) = curr {
-            Some(self.game_details(curr))
+        let raw_side_content = if let Some(review) = self.metadata_review.as_ref() {
+            Some(column![
+                Element::from(review.view()).map(Message::MetadataReview)
+            ])
+        } else if let Some(editor) = self.game_editor.as_ref() {
+            Some(column![
+                Element::from(editor.view()).map(Message::GameEditor)
+            ])
+        } else if let Some(game) = self.tag_game.as_ref() {
+            Some(self.tag_picker(game))
+        } else if let Some(editor) = self.collection_editor.as_ref() {
+            Some(column![
+                Element::from(editor.view()).map(Message::CollectionEditor)
+            ])
+        } else if self.selected.len() > 1 {
+            Some(self.bulk_editor())
+        } else if self.show_column_editor && self.view_type == LibraryViewType::Table {
+            Some(column![
+                Element::from(self.table.column_editor()).map(Message::Table)
+            ])
         } else {
-            None
+            curr.map(|curr| self.game_details(curr))
         };
 
-        let wrapped_items: Element<Message> = if let Some(side) = raw_side_content {
-            row![
-                items.width(Length::FillPortion(3)),
-                scrollable(side).width(Length::FillPortion(7)),
-            ]
-            .into()
+        let mut wrapped_items = row![self.collections_sidebar()];
+        if self.show_facets {
+            wrapped_items = wrapped_items.push(
+                self.facet_sidebar
+                    .view(&self.filters.facets, &self.facet_counts)
+                    .map(Message::Facets),
+            );
+        }
+        wrapped_items = if let Some(side) = raw_side_content {
+            wrapped_items
+                .push(items.width(Length::FillPortion(3)))
+                .push(scrollable(side).width(Length::FillPortion(7)))
         } else {
-            items.into()
+            wrapped_items.push(items.width(Length::FillPortion(10)))
         };
-        column![toolbar, wrapped_items].into()
+        column![toolbar, self.filter_bar(), wrapped_items]
+            .spacing(3)
+            .into()
     }
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
+            Message::Table(TableMessage::SortBy(kind)) => {
+                let Some(field) = kind.sort_field() else {
+                    return Task::none();
+                };
+                match self.filters.sort.first_mut() {
+                    // clicking the primary column again flips its direction
+                    Some(primary) if primary.field == field => {
+                        primary.order = match primary.order {
+                            SortOrder::Ascending => SortOrder::Descending,
+                            SortOrder::Descending => SortOrder::Ascending,
+                        }
+                    }
+                    _ => {
+                        self.filters.sort.retain(|s| s.field != field);
+                        self.filters.sort.insert(
+                            0,
+                            Sort {
+                                field,
+                                ..Sort::default()
+                            },
+                        );
+                    }
+                }
+                return self.update(Message::ReloadCache);
+            }
+            Message::Table(TableMessage::RowClicked(index)) => {
+                return self.update(Message::SelectGame(index));
+            }
             Message::Table(tbl) => {
-                return self.table.update(tbl).map(Message::Table);
+                let task = self.table.update(tbl).map(Message::Table);
+                if matches!(
+                    tbl,
+                    TableMessage::Resized
+                        | TableMessage::ToggleColumn(..)
+                        | TableMessage::MoveColumn(..)
+                ) {
+                    self.save_library_settings();
+                }
+                return task;
             }
             Message::RefreshGames => {
-                return Task::perform(db::ops::sync_library(), |_| Message::ReloadCache);
+                return db_task(db::store().sync_library(&ADDONS), |_| Message::ReloadCache);
             }
             Message::SearchChanged(query) => {
                 self.filters.search = query;
                 return self.update(Message::ReloadCache);
             }
             Message::ReloadCache => {
-                return Task::perform(
-                    db::ops::get_games(self.filters.clone()),
-                    Message::CacheReloaded,
+                return Task::batch([
+                    db_task(
+                        db::store().get_games(self.filters.clone()),
+                        Message::CacheReloaded,
+                    ),
+                    db_task(db::store().get_accounts(), Message::AccountsLoaded),
+                    db_task(db::store().get_tags(), Message::TagsLoaded),
+                    db_task(db::store().get_collections(), Message::CollectionsLoaded),
+                    db_task(
+                        db::store().get_facet_counts(self.filters.clone()),
+                        Message::FacetCountsLoaded,
+                    ),
+                ]);
+            }
+            Message::FacetCountsLoaded(counts) => {
+                self.facet_counts = counts;
+            }
+            Message::ToggleFacets => {
+                self.show_facets = !self.show_facets;
+                self.save_library_settings();
+            }
+            Message::Facets(msg) => {
+                let previous = self.filters.facets.clone();
+                self.facet_sidebar.update(&mut self.filters.facets, msg);
+                if self.filters.facets != previous {
+                    self.save_library_settings();
+                    self.curr_index = 0;
+                    return self.update(Message::ReloadCache);
+                }
+            }
+            Message::CollectionsLoaded(collections) => {
+                self.collections = collections;
+            }
+            Message::CollectionSelected(id) => {
+                self.active_collection = id;
+                self.filters.expr = id.and_then(|id| {
+                    self.collections
+                        .iter()
+                        .find(|c| c.id == id)
+                        .map(|c| c.filter.clone())
+                });
+                self.curr_index = 0;
+                return self.update(Message::ReloadCache);
+            }
+            Message::NewCollection => {
+                self.game_editor = None;
+                self.tag_game = None;
+                self.collection_editor = Some(CollectionEditor::new());
+            }
+            Message::EditCollection(id) => {
+                if let Some(collection) = self.collections.iter().find(|c| c.id == id) {
+                    self.game_editor = None;
+                    self.tag_game = None;
+                    self.collection_editor = Some(CollectionEditor::edit(collection));
+                }
+            }
+            Message::DeleteCollection(id) => {
+                self.collections.retain(|c| c.id != id);
+                let deleted = db_task(db::store().delete_collection(id), |_| Message::NoOp);
+                if self.active_collection == Some(id) {
+                    return deleted.chain(self.update(Message::CollectionSelected(None)));
+                }
+                return deleted;
+            }
+            Message::CollectionEditor(EditorMessage::Cancel) => {
+                self.collection_editor = None;
+            }
+            Message::CollectionEditor(EditorMessage::Save) => {
+                let Some(editor) = self.collection_editor.take() else {
+                    return Task::none();
+                };
+                let Some(collection) = editor.collection() else {
+                    return Task::none();
+                };
+                if editor.is_new() {
+                    return db_task(
+                        db::store().create_collection(collection.name, collection.filter),
+                        Message::CollectionSaved,
+                    );
+                }
+                return db_task(
+                    db::store().update_collection(collection.clone()),
+                    move |_| Message::CollectionSaved(collection.clone()),
                 );
             }
+            Message::CollectionEditor(msg) => {
+                if let Some(editor) = self.collection_editor.as_mut() {
+                    editor.update(msg);
+                }
+            }
+            Message::CollectionSaved(collection) => {
+                let id = collection.id;
+                self.collections.retain(|c| c.id != id);
+                self.collections.push(collection);
+                self.collections.sort_by(|a, b| a.name.cmp(&b.name));
+                return self.update(Message::CollectionSelected(Some(id)));
+            }
+            Message::TagsLoaded(tags) => {
+                self.tag_filters = [TagFilter::All]
+                    .into_iter()
+                    .chain(tags.iter().cloned().map(TagFilter::Tag))
+                    .collect();
+                self.tags = tags;
+            }
+            Message::TagFilterSelected(filter) => {
+                self.filters.tags = match filter {
+                    TagFilter::All => Vec::new(),
+                    TagFilter::Tag(tag) => vec![tag.id],
+                };
+                return self.update(Message::ReloadCache);
+            }
+            Message::HiddenFilterChanged(hidden) => {
+                self.filters.hidden = hidden;
+                return self.update(Message::ReloadCache);
+            }
+            Message::FavouritesOnlyToggled(favourites_only) => {
+                self.filters.favourites_only = favourites_only;
+                return self.update(Message::ReloadCache);
+            }
+            Message::SetFavourite(game_id, favourite) => {
+                return db_task(db::store().set_favourite(game_id, favourite), |_| {
+                    Message::ReloadCache
+                });
+            }
+            Message::SetHidden(game_id, hidden) => {
+                return db_task(db::store().set_hidden(game_id, hidden), |_| {
+                    Message::ReloadCache
+                });
+            }
+            Message::ShowTagPicker(game) => {
+                self.game_editor = None;
+                self.collection_editor = None;
+                self.tag_game = Some(game);
+            }
+            Message::CloseTagPicker => {
+                self.tag_game = None;
+            }
+            Message::SetGameTag(tag_id, checked) => {
+                if let Some(game) = self.tag_game.as_mut() {
+                    let game_ids = vec![game.id];
+                    if checked {
+                        if let Some(tag) = self.tags.iter().find(|t| t.id == tag_id) {
+                            game.tags.push(tag.clone());
+                        }
+                        return db_task(db::store().add_tag_to_games(tag_id, game_ids), |_| {
+                            Message::ReloadCache
+                        });
+                    } else {
+                        game.tags.retain(|t| t.id != tag_id);
+                        return db_task(
+                            db::store().remove_tag_from_games(tag_id, game_ids),
+                            |_| Message::ReloadCache,
+                        );
+                    }
+                }
+            }
+            Message::NewTagNameChanged(name) => {
+                self.new_tag_name = name;
+            }
+            Message::NewTagColorSelected(index) => {
+                self.new_tag_color = index;
+            }
+            Message::CreateTag => {
+                let name = self.new_tag_name.trim().to_string();
+                if name.is_empty() || self.tags.iter().any(|t| t.name == name) {
+                    return Task::none();
+                }
+                self.new_tag_name.clear();
+                let color = TAG_COLORS[self.new_tag_color].to_string();
+                return db_task(db::store().create_tag(name, color), Message::TagCreated);
+            }
+            Message::TagCreated(tag) => {
+                let tag_id = tag.id;
+                self.tags.push(tag);
+                return self.update(Message::SetGameTag(tag_id, true));
+            }
+            Message::AccountsLoaded(accounts) => {
+                self.accounts = [AccountFilter::All]
+                    .into_iter()
+                    .chain(accounts.into_iter().map(AccountFilter::Account))
+                    .collect();
+            }
+            Message::AccountSelected(filter) => {
+                self.filters.account = match filter {
+                    AccountFilter::All => None,
+                    AccountFilter::Account(account) => Some(account),
+                };
+                return self.update(Message::ReloadCache);
+            }
             Message::ViewSelected(view_type) => {
                 self.view_type = view_type;
             }
             Message::CacheReloaded(cache) => {
+                self.selected
+                    .retain(|id| cache.iter().any(|game| game.id == *id));
                 self.games = cache.clone();
-                self.table.rows = cache;
+                self.table.set_rows(cache);
+                self.table.set_sort(&self.filters.sort);
+                self.table
+                    .set_running(|game| self.running.contains_key(&game.id));
+                self.sync_table_selection();
+                return self.load_field_sources();
             }
             Message::GameAction(GameAction::Delete, game) => {
-                return Task::perform(db::ops::delete_game(game.id), |_| Message::ReloadCache)
+                return db_task(db::store().delete_game(game.id), |_| Message::ReloadCache)
             }
             Message::GameAction(GameAction::Play, game) => {
-                let addon = ADDONS
-                    .get_game_library(&game.library_type)
-                    .cloned()
-                    .expect("Failed to load library");
-                addon.launch(game.get_ref());
+                self.launch_notice = None;
                 let game_id = game.id;
-
-                return Task::perform(db::ops::update_game_played(game.id), move |dt| {
-                    Message::UpdateLastPlayed(game_id, dt)
+                let default_action: PostLaunchAction = settings::load()
+                    .unwrap_or_default()
+                    .general
+                    .post_launch_action
+                    .into();
+                return Task::stream(stream::channel(2, move |mut output| async move {
+                    let launched = match launch::launch_game(db::store(), &ADDONS, &game).await {
+                        Ok(launched) => launched,
+                        Err(err) => {
+                            let _ = output.send(Message::ActionFailed(err.to_string())).await;
+                            return;
+                        }
+                    };
+                    let action = launched.post_launch_action.unwrap_or(default_action);
+                    let tracked = launched.exit.is_some();
+                    let _ = output
+                        .send(Message::GameLaunched(
+                            game_id,
+                            launched.last_played,
+                            action,
+                            tracked,
+                        ))
+                        .await;
+                    if !launched.ignored.is_empty() {
+                        let _ = output
+                            .send(Message::LaunchSettingsIgnored(launched.ignored))
+                            .await;
+                    }
+                    if let Some(exit) = launched.exit {
+                        let _ = exit.await;
+                        let _ = output.send(Message::GameExited(action)).await;
+                    }
+                }));
+            }
+            Message::GameAction(GameAction::Stop, game) => {
+                let Some(running) = self.running.get(&game.id).cloned() else {
+                    return Task::none();
+                };
+                return Task::future(async move {
+                    let stopped =
+                        tokio::task::spawn_blocking(move || process_monitor::stop_game(&running))
+                            .await;
+                    match stopped {
+                        Ok(Ok(())) => Message::CheckRunningGames,
+                        Ok(Err(err)) => Message::ActionFailed(err.to_string()),
+                        Err(err) => Message::ActionFailed(err.to_string()),
+                    }
                 });
             }
             Message::GameAction(GameAction::Install, game) => {
-                let addon = ADDONS
-                    .get_game_library(&game.library_type)
-                    .cloned()
-                    .expect("Failed to load library");
-                addon.install(game.get_ref());
+                return library_action_task(LibraryAction::Install, game);
             }
             Message::GameAction(GameAction::Uninstall, game) => {
-                let addon = ADDONS
-                    .get_game_library(&game.library_type)
-                    .cloned()
-                    .expect("Failed to load library");
-                addon.uninstall(game.get_ref());
+                return library_action_task(LibraryAction::Uninstall, game);
+            }
+            Message::SetPreferredSource(game_id, source_id) => {
+                return db_task(db::store().set_preferred_source(game_id, source_id), |_| {
+                    Message::ReloadCache
+                });
+            }
+            Message::UnmergeSource(source_id) => {
+                return db_task(db::store().unmerge_game(source_id), |_| {
+                    Message::ReloadCache
+                });
             }
             Message::GameAction(GameAction::Edit, game) => {
-                self.edit_game = Some(game);
+                let game_id = game.id;
+                self.game_editor = Some(GameEditor::new(game));
+                return db_task(
+                    db::store().get_game_edit_state(&ADDONS, game_id),
+                    move |state| {
+                        Message::GameEditor(GameEditorMessage::StateLoaded(
+                            game_id,
+                            Box::new(state),
+                        ))
+                    },
+                );
             }
-            Message::SaveEditor => {
-                if let Some(game) = self.edit_game.clone() {
-                    return Task::perform(db::ops::update_game(game), |_| Message::ReloadCache);
-                }
+            Message::GameAction(GameAction::RefreshMetadata, game) => {
+                return self.open_metadata_review(vec![game.id]);
             }
-            Message::CloseEditor => {
-                self.edit_game = None;
+            Message::MetadataReview(MetadataReviewMessage::Close) => {
+                self.metadata_review = None;
             }
-            Message::EditorTextChanged(field, value) => {
-                fn map_opt_empty(opt: String) -> Option<String> {
-                    if opt.is_empty() {
-                        None
-                    } else {
-                        Some(opt)
-                    }
+            Message::MetadataReview(MetadataReviewMessage::Apply) => {
+                if let Some(diffs) = self.metadata_review.take().and_then(|r| r.result()) {
+                    return db_task(db::store().apply_metadata_diffs(diffs), |_| {
+                        Message::ReloadCache
+                    });
                 }
-                if let Some(edit_game) = self.edit_game.as_mut() {
-                    match field {
-                        GameTextField::Name => edit_game.name = value,
-                        GameTextField::Description => edit_game.description = value,
-                        GameTextField::HeaderUrl => edit_game.header_url = map_opt_empty(value),
-                        GameTextField::IconUrl => edit_game.icon_url = map_opt_empty(value),
-                        GameTextField::CoverUrl => edit_game.cover_url = map_opt_empty(value),
+            }
+            Message::MetadataReview(msg) => {
+                if let Some(review) = self.metadata_review.as_mut() {
+                    let fetch = matches!(msg, MetadataReviewMessage::Fetch);
+                    review.update(msg);
+                    if fetch {
+                        return Self::fetch_metadata_diffs(review);
                     }
                 }
             }
-            Message::EditorCompletionStatusChanged(status) => {
-                if let Some(edit_game) = self.edit_game.as_mut() {
-                    edit_game.completion_status = status;
+            Message::GameEditor(GameEditorMessage::Close) => {
+                self.game_editor = None;
+            }
+            Message::GameEditor(GameEditorMessage::Save) => {
+                let Some(editor) = self.game_editor.as_ref() else {
+                    return Task::none();
+                };
+                if let Some((game, manual_fields)) = editor.result() {
+                    let profile = editor.launch_profile();
+                    return db_task(
+                        async move {
+                            let game_id = game.id;
+                            db::store().update_game(game, manual_fields).await?;
+                            match profile {
+                                Some(profile) => {
+                                    db::store().set_launch_profile(Some(game_id), profile).await
+                                }
+                                None => Ok(()),
+                            }
+                        },
+                        |_| Message::ReloadCache,
+                    );
+                }
+            }
+            Message::FieldSourcesLoaded(game_id, sources) => {
+                if self.games.get(self.curr_index).map(|game| game.id) == Some(game_id) {
+                    self.field_sources = sources;
+                }
+            }
+            Message::SetFieldLocked(game_id, field, locked) => {
+                return db_task(db::store().set_field_locked(game_id, field, locked), |_| {
+                    Message::ReloadCache
+                });
+            }
+            Message::GameEditor(msg) => {
+                if let Some(editor) = self.game_editor.as_mut() {
+                    editor.update(msg);
                 }
             }
             Message::SelectGame(index) => {
+                let Some(game_id) = self.games.get(index).map(|game| game.id) else {
+                    return Task::none();
+                };
+                if self.modifiers.shift() {
+                    self.select_range(index);
+                } else if self.modifiers.command() {
+                    if self.selected.is_empty() {
+                        if let Some(curr) = self.games.get(self.curr_index) {
+                            self.selected.insert(curr.id);
+                        }
+                    }
+                    if !self.selected.remove(&game_id) {
+                        self.selected.insert(game_id);
+                    }
+                    self.anchor = index;
+                } else {
+                    self.selected.clear();
+                    self.anchor = index;
+                }
                 self.curr_index = index;
+                self.sync_table_selection();
+                return self.load_field_sources();
             }
-            Message::SortFieldChanged(field) => {
-                self.filters.sort.field = field;
-                return self.update(Message::ReloadCache);
+            Message::ModifiersChanged(modifiers) => {
+                self.modifiers = modifiers;
+            }
+            Message::SelectAll => {
+                self.selected = self.games.iter().map(|game| game.id).collect();
+                self.sync_table_selection();
+            }
+            Message::ClearSelection => {
+                self.selected.clear();
+                self.anchor = self.curr_index;
+                self.sync_table_selection();
+            }
+            Message::BulkCompletionStatus(status) => {
+                return db_task(
+                    db::store().set_completion_status_bulk(self.selected_ids(), status),
+                    Message::BulkApplied,
+                );
+            }
+            Message::BulkAddTag(tag) => {
+                return db_task(
+                    db::store().add_tag_bulk(tag.id, self.selected_ids()),
+                    Message::BulkApplied,
+                );
+            }
+            Message::BulkRemoveTag(tag) => {
+                return db_task(
+                    db::store().remove_tag_bulk(tag.id, self.selected_ids()),
+                    Message::BulkApplied,
+                );
             }
-            Message::ToggleSortDirection => {
-                self.filters.sort.order = match self.filters.sort.order {
-                    SortOrder::Ascending => SortOrder::Descending,
-                    SortOrder::Descending => SortOrder::Ascending,
+            Message::BulkSetHidden(hidden) => {
+                return db_task(
+                    db::store().set_hidden_bulk(self.selected_ids(), hidden),
+                    Message::BulkApplied,
+                );
+            }
+            Message::BulkDelete => {
+                let game_ids = self.selected_ids();
+                self.selected.clear();
+                return db_task(db::store().delete_games(game_ids), Message::BulkApplied);
+            }
+            Message::BulkRefreshMetadata => {
+                return self.open_metadata_review(self.selected_ids());
+            }
+            Message::BulkInstall | Message::BulkUninstall => {
+                let (action, status) = if matches!(message, Message::BulkInstall) {
+                    (LibraryAction::Install, GameInstallStatus::InLibrary)
+                } else {
+                    (LibraryAction::Uninstall, GameInstallStatus::Installed)
                 };
+                return Task::batch(
+                    self.games
+                        .iter()
+                        .filter(|g| self.selected.contains(&g.id))
+                        .filter(|g| g.preferred_source().install_status == status)
+                        .map(|game| library_action_task(action, game.clone())),
+                );
+            }
+            Message::BulkApplied(undo) => {
+                self.bulk_undo = Some(undo);
+                return self.update(Message::ReloadCache);
+            }
+            Message::UndoBulk => {
+                if let Some(undo) = self.bulk_undo.take() {
+                    return db_task(db::store().undo_bulk(undo), |_| Message::ReloadCache);
+                }
+            }
+            Message::SortFieldChanged(index, field) => {
+                self.filters.sort.retain(|s| s.field != field);
+                let index = index.min(self.filters.sort.len().saturating_sub(1));
+                match self.filters.sort.get_mut(index) {
+                    Some(sort) => sort.field = field,
+                    None => self.filters.sort.push(Sort {
+                        field,
+                        ..Sort::default()
+                    }),
+                }
+                return self.update(Message::ReloadCache);
+            }
+            Message::ToggleSortDirection(index) => {
+                if let Some(sort) = self.filters.sort.get_mut(index) {
+                    sort.order = match sort.order {
+                        SortOrder::Ascending => SortOrder::Descending,
+                        SortOrder::Descending => SortOrder::Ascending,
+                    };
+                }
+                return self.update(Message::ReloadCache);
+            }
+            Message::AddSortKey => {
+                if let Some(field) = SortField::ALL
+                    .into_iter()
+                    .find(|field| !self.filters.sort.iter().any(|s| s.field == *field))
+                {
+                    self.filters.sort.push(Sort {
+                        field,
+                        ..Sort::default()
+                    });
+                    return self.update(Message::ReloadCache);
+                }
+            }
+            Message::RemoveSortKey(index) => {
+                if index < self.filters.sort.len() {
+                    self.filters.sort.remove(index);
+                }
                 return self.update(Message::ReloadCache);
             }
+            Message::ToggleColumnEditor => {
+                self.show_column_editor = !self.show_column_editor;
+            }
             Message::MoveInDir(direction) => {
                 match self.view_type {
                     LibraryViewType::Table | LibraryViewType::List => {
@@ -566,11 +1495,74 @@ impl LibraryPage {
                             }
                             _ => {}
                         }
+                        if self.modifiers.shift() {
+                            self.select_range(self.curr_index);
+                        } else {
+                            self.selected.clear();
+                            self.anchor = self.curr_index;
+                        }
+                        self.sync_table_selection();
+                        return self.load_field_sources();
                     }
                     LibraryViewType::Grid => todo!(),
                 }
             }
+            Message::GameLaunched(game_id, last_played, action, tracked) => {
+                let update = self.update(Message::UpdateLastPlayed(game_id, last_played));
+                let window = match action {
+                    PostLaunchAction::DoNothing => Task::none(),
+                    PostLaunchAction::Minimize => {
+                        window::get_oldest().and_then(|id| window::minimize(id, true))
+                    }
+                    // the post-exit script runs in this process, so it waits hidden for the game
+                    PostLaunchAction::Exit if tracked => {
+                        window::get_oldest().and_then(|id| window::change_mode(id, Mode::Hidden))
+                    }
+                    PostLaunchAction::Exit => iced::exit(),
+                };
+                return Task::batch([update, window]);
+            }
+            Message::LaunchSettingsIgnored(ignored) => {
+                self.launch_notice = Some(format!(
+                    "Started by the library's client, {} not applied",
+                    ignored.join(", ")
+                ));
+            }
+            Message::GameExited(action) => {
+                let check = self.update(Message::CheckRunningGames);
+                let window = match action {
+                    PostLaunchAction::DoNothing => Task::none(),
+                    PostLaunchAction::Minimize => window::get_oldest().and_then(|id| {
+                        Task::batch([window::minimize(id, false), window::gain_focus(id)])
+                    }),
+                    PostLaunchAction::Exit => iced::exit(),
+                };
+                return Task::batch([check, window]);
+            }
+            Message::CheckRunningGames => {
+                let games = self.games.clone();
+                return Task::future(async move {
+                    tokio::task::spawn_blocking(move || process_monitor::running_games(&games))
+                        .await
+                        .unwrap_or_default()
+                })
+                .map(Message::RunningGamesFound);
+            }
+            Message::RunningGamesFound(running) => {
+                self.running = running
+                    .into_iter()
+                    .map(|game| (game.game_id, game))
+                    .collect();
+                self.table
+                    .set_running(|game| self.running.contains_key(&game.id));
+            }
+            Message::ActionFailed(err) => {
+                log::error!("Game action failed: {}", err);
+                self.action_error = Some(err);
+            }
+            Message::ActionSucceeded => self.action_error = None,
             Message::UpdateLastPlayed(game_id, dt) => {
+                self.action_error = None;
                 log::info!(
                     "Process game details update raw: {:?}; id: {:?}",
                     game_id,
@@ -589,3 +1581,19 @@ impl LibraryPage {
         Task::none()
     }
 }
+
+/// Runs a library action in the background, reporting how it went
+fn library_action_task(action: LibraryAction, game: GameData) -> Task<Message> {
+    Task::future(async move {
+        match run_library_action(&ADDONS, action, &game).await {
+            Ok(()) => Message::ActionSucceeded,
+            Err(err) => {
+                Message::ActionFailed(format!("Could not {} {}: {}", action, game.name, err))
+            }
+        }
+    })
+}
+
+fn now_playing_badge<'a>() -> Text<'a> {
+    text("Now playing").size(12).style(text::success)
+}
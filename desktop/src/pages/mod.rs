@@ -4,3 +4,25 @@ pub mod app_page;
 pub mod library;
 pub mod settings;
 pub mod tools;
+
+use gami_backend::db::DbResult;
+use iced::Task;
+use std::future::Future;
+
+/// Runs a database operation, a failure is logged and produces no message so the page carries on
+pub fn db_task<T, M>(
+    operation: impl Future<Output = DbResult<T>> + Send + 'static,
+    f: impl Fn(T) -> M + Send + 'static,
+) -> Task<M>
+where
+    T: Send + 'static,
+    M: Send + 'static,
+{
+    Task::future(async move {
+        operation
+            .await
+            .inspect_err(|err| log::error!("Database operation failed: {}", err))
+            .ok()
+    })
+    .and_then(move |value| Task::done(f(value)))
+}
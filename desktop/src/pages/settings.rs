@@ -1,8 +1,16 @@
-use crate::models::{MyTheme, PostLaunchAction};
+use crate::models::MyTheme;
+use crate::pages::db_task;
 use crate::settings;
-use crate::settings::{AppearanceSettings, GeneralSettings, Settings};
+use crate::settings::{AppearanceSettings, BackupSettings, GeneralSettings, Settings};
+use crate::widgets::launch_profile_editor::{LaunchProfileEditor, LaunchProfileMessage};
+use crate::widgets::number_input::number_input;
+use gami_backend::db;
+use gami_backend::launch::{self, LaunchProfile};
+use gami_backend::metadata::MetadataPriority;
+use gami_backend::{GameField, ADDONS};
+use iced::alignment::Vertical;
 use iced::font::Weight;
-use iced::widget::{column, pick_list, row, text};
+use iced::widget::{button, column, pick_list, row, scrollable, text, Column};
 use iced::{Element, Font, Length, Task};
 use iced_aw::{TabLabel, Tabs};
 
@@ -12,6 +20,7 @@ pub enum TabId {
     General,
     Appearance,
     Metadata,
+    Launch,
 }
 
 #[derive(Clone, Debug)]
@@ -20,20 +29,42 @@ pub enum Message {
     TabSelected(TabId),
     Changed(Settings),
     Loaded(Settings),
+    MetadataLoaded(MetadataPriority, Vec<String>),
+    /// Moves the source at an index of a field's ranking one place up, or down when `false`
+    MoveMetadataSource(GameField, usize, bool),
+    ResetMetadataPriority(GameField),
+    LaunchProfileLoaded(LaunchProfile),
+    LaunchProfile(LaunchProfileMessage),
+    SaveLaunchProfile,
+    NoOp,
 }
 #[derive(Default, Clone, Debug)]
 pub struct SettingsPage {
     active_tab: TabId,
     settings: Settings,
+    metadata_priority: MetadataPriority,
+    /// Keys of the installed metadata scanners
+    metadata_sources: Vec<String>,
+    /// The global launch profile every game starts from
+    launch_profile: LaunchProfileEditor,
 }
 impl SettingsPage {
     pub fn update(&mut self, msg: Message) -> Task<Message> {
         match msg {
             Message::LoadSettings => {
-                return Task::perform(
-                    async { settings::load_async().await.unwrap() },
-                    Message::Loaded,
-                )
+                return Task::batch([
+                    Task::perform(
+                        async { settings::load_async().await.unwrap() },
+                        Message::Loaded,
+                    ),
+                    db_task(db::store().get_metadata_priority(), |priority| {
+                        Message::MetadataLoaded(priority, db::ops::get_metadata_sources(&ADDONS))
+                    }),
+                    db_task(
+                        db::store().get_launch_profile(None),
+                        Message::LaunchProfileLoaded,
+                    ),
+                ])
             }
             Message::TabSelected(tab) => {
                 self.active_tab = tab;
@@ -45,11 +76,188 @@ impl SettingsPage {
             Message::Loaded(settings) => {
                 self.settings = settings;
             }
+            Message::MetadataLoaded(priority, sources) => {
+                self.metadata_priority = priority;
+                self.metadata_sources = sources;
+            }
+            Message::MoveMetadataSource(field, index, up) => {
+                let mut ranking = self.ranking(field);
+                let other = if up {
+                    index.checked_sub(1)
+                } else {
+                    Some(index + 1)
+                };
+                if let Some(other) = other.filter(|other| *other < ranking.len()) {
+                    ranking.swap(index, other);
+                    self.metadata_priority.fields.insert(field, ranking);
+                    return self.save_metadata_priority();
+                }
+            }
+            Message::ResetMetadataPriority(field) => {
+                self.metadata_priority.fields.remove(&field);
+                return self.save_metadata_priority();
+            }
+            Message::LaunchProfileLoaded(profile) => {
+                self.launch_profile = LaunchProfileEditor::global(&profile);
+            }
+            Message::LaunchProfile(msg) => self.launch_profile.update(msg),
+            Message::SaveLaunchProfile => {
+                if let Some(profile) = self.launch_profile.profile() {
+                    return db_task(db::store().set_launch_profile(None, profile), |_| {
+                        Message::NoOp
+                    });
+                }
+            }
+            Message::NoOp => {}
         }
 
-        return Task::none();
+        Task::none()
+    }
+    fn save_metadata_priority(&self) -> Task<Message> {
+        db_task(
+            db::store().set_metadata_priority(self.metadata_priority.clone()),
+            |_| Message::NoOp,
+        )
+    }
+    /// Installed scanners in the order the user ranked them for a field, unranked ones last
+    fn ranking(&self, field: GameField) -> Vec<String> {
+        let mut ranking: Vec<String> = self
+            .metadata_priority
+            .for_field(field)
+            .iter()
+            .filter(|source| self.metadata_sources.contains(source))
+            .cloned()
+            .collect();
+        ranking.extend(
+            self.metadata_sources
+                .iter()
+                .filter(|source| !ranking.contains(source))
+                .cloned()
+                .collect::<Vec<_>>(),
+        );
+        ranking
+    }
+    fn metadata_tab(&self) -> Element<'_, Message> {
+        if self.metadata_sources.is_empty() {
+            return text("No metadata add-ons installed").into();
+        }
+        let fields = GameField::ALL
+            .into_iter()
+            .filter(|field| field.from_metadata())
+            .map(|field| {
+                let ranking = self.ranking(field);
+                let last = ranking.len() - 1;
+                let sources = Column::with_children(ranking.into_iter().enumerate().map(
+                    |(index, source)| {
+                        row![
+                            text(format!("{}. {}", index + 1, source)).width(Length::Fill),
+                            button(text("Up")).on_press_maybe(
+                                (index > 0)
+                                    .then_some(Message::MoveMetadataSource(field, index, true))
+                            ),
+                            button(text("Down")).on_press_maybe(
+                                (index < last)
+                                    .then_some(Message::MoveMetadataSource(field, index, false))
+                            ),
+                        ]
+                        .spacing(4)
+                        .align_y(Vertical::Center)
+                        .into()
+                    },
+                ))
+                .spacing(2)
+                .width(Length::FillPortion(7));
+                row![
+                    column![
+                        text(field.to_string()).font(Font {
+                            weight: Weight::Semibold,
+                            ..Font::default()
+                        }),
+                        button(text("Reset"))
+                            .style(button::secondary)
+                            .on_press_maybe(
+                                self.metadata_priority
+                                    .fields
+                                    .contains_key(&field)
+                                    .then_some(Message::ResetMetadataPriority(field))
+                            ),
+                    ]
+                    .spacing(4)
+                    .width(Length::FillPortion(3)),
+                    sources,
+                ]
+                .into()
+            });
+        scrollable(
+            column![text(
+                "Each field is taken from the highest ranked add-on that knows it. \
+                 Unranked fields prefer the game's own library."
+            )]
+            .extend(fields)
+            .spacing(12),
+        )
+        .into()
+    }
+    fn launch_tab(&self) -> Element<'_, Message> {
+        column![
+            text(
+                "Applied to every game, a game's own profile adds wrappers and variables \
+                 and replaces the scripts and working directory."
+            ),
+            Element::from(self.launch_profile.view("Global launch profile", &[]))
+                .map(Message::LaunchProfile),
+            button(text("Save")).style(button::success).on_press_maybe(
+                self.launch_profile
+                    .profile()
+                    .map(|_| Message::SaveLaunchProfile)
+            ),
+        ]
+        .spacing(10)
+        .into()
+    }
+    fn backups_settings(&self) -> Element<'_, Message> {
+        let backups = self.settings.backups.clone();
+        let label = |label| {
+            text(label)
+                .font(Font {
+                    weight: Weight::Semibold,
+                    ..Font::default()
+                })
+                .width(Length::FillPortion(3))
+        };
+        let settings = self.settings.clone();
+        let with_backups = move |backups: BackupSettings| {
+            Message::Changed(Settings {
+                backups,
+                ..settings.clone()
+            })
+        };
+        let daily_settings = with_backups.clone();
+        let daily_backups = backups.clone();
+        column![
+            row![
+                label("Daily database snapshots to keep:"),
+                number_input("Daily", backups.keep_daily).map(move |keep_daily| daily_settings(
+                    BackupSettings {
+                        keep_daily: keep_daily.max(0),
+                        ..daily_backups.clone()
+                    }
+                )),
+            ],
+            row![
+                label("Weekly database snapshots to keep:"),
+                number_input("Weekly", backups.keep_weekly).map(move |keep_weekly| {
+                    with_backups(BackupSettings {
+                        keep_weekly: keep_weekly.max(0),
+                        ..backups.clone()
+                    })
+                }),
+            ],
+        ]
+        .spacing(10)
+        .into()
     }
-    pub fn view(&self) -> Element<Message> {
+    pub fn view(&self) -> Element<'_, Message> {
         Tabs::new_with_tabs(
             vec![
                 (
@@ -63,11 +271,13 @@ impl SettingsPage {
                             })
                             .width(Length::FillPortion(3)),
                         pick_list(
-                            PostLaunchAction::ALL,
-                            Some(self.settings.general.post_launch_action),
+                            launch::PostLaunchAction::ALL,
+                            Some(launch::PostLaunchAction::from(
+                                self.settings.general.post_launch_action
+                            )),
                             |action| Message::Changed(Settings {
                                 general: GeneralSettings {
-                                    post_launch_action: action
+                                    post_launch_action: action.into()
                                 },
                                 ..self.settings.clone()
                             }),
@@ -75,6 +285,8 @@ impl SettingsPage {
                         .placeholder("Select your theme")
                         .width(Length::FillPortion(7)),
                     ]]
+                    .push(self.backups_settings())
+                    .spacing(10)
                     .into(),
                 ),
                 (
@@ -103,7 +315,12 @@ impl SettingsPage {
                 (
                     TabId::Metadata,
                     TabLabel::Text("Metadata".into()),
-                    text("TODO: Metadata").into(),
+                    self.metadata_tab(),
+                ),
+                (
+                    TabId::Launch,
+                    TabLabel::Text("Launch".into()),
+                    self.launch_tab(),
                 ),
             ],
             Message::TabSelected,
@@ -1,25 +1,605 @@
+use crate::pages::db_task;
 use gami_backend::db;
-use iced::widget::{button, column};
-use iced::{Element, Task};
+use gami_backend::db::backup::{Snapshot, SnapshotKind};
+use gami_backend::db::ops::{DuplicateGroup, ImportPreview, LauncherImportSummary};
+use gami_backend::dedupe::DuplicateReason;
+use gami_backend::export;
+use gami_backend::export::{Backup, ExportFormat, ImportError, ImportMode};
+use gami_backend::importers::{ImportedGame, LauncherSource};
+use iced::font::Weight;
+use iced::widget::{
+    button, checkbox, column, pick_list, row, scrollable, text, text_input, Column,
+};
+use iced::{Element, Font, Length, Task};
+use std::path::{Path, PathBuf};
 
-#[derive(Debug, Clone, Copy)]
+#[derive(Debug, Clone)]
 pub enum Message {
     ClearDatabase,
+    FindDuplicates,
+    DuplicatesFound(Vec<DuplicateGroup>),
+    MergeDuplicates(usize),
+    ExportPathChanged(String),
+    ExportFormatChanged(ExportFormat),
+    Export,
+    Exported(Result<(), String>),
+    ImportPathChanged(String),
+    ImportModeChanged(ImportMode),
+    LoadImport,
+    ImportLoaded(Result<Box<Backup>, ImportError>),
+    PreviewLoaded(ImportPreview),
+    Import,
+    Imported(Result<(), String>),
+    LauncherSourceChanged(LauncherSource),
+    LauncherPathChanged(String),
+    ReadLauncher,
+    LauncherRead(Result<Vec<ImportedGame>, ImportError>),
+    LauncherMatched(Vec<Option<i32>>),
+    LauncherAddMissingChanged(bool),
+    ImportLauncher,
+    LauncherImported(LauncherImportSummary),
+    LoadSnapshots,
+    SnapshotsLoaded(Vec<Snapshot>),
+    BackUpNow,
+    SnapshotCreated(Result<(), String>),
+    /// Asks to restore the snapshot at an index
+    RestoreSnapshot(usize),
+    Restored(Result<(), String>),
+    Confirm,
+    CancelConfirm,
+    Cleared(Result<(), String>),
     NoOp,
 }
 
-#[derive(Debug, Clone, Copy, Default)]
-pub struct ToolsPage;
+/// Destructive action awaiting confirmation
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PendingAction {
+    Clear,
+    /// Index of the snapshot to restore
+    Restore(usize),
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ToolsPage {
+    duplicates: Option<Vec<DuplicateGroup>>,
+    export_path: String,
+    export_format: Option<ExportFormat>,
+    import_path: String,
+    import_mode: ImportMode,
+    /// Loaded file awaiting confirmation
+    import: Option<Backup>,
+    import_preview: Option<ImportPreview>,
+    status: Option<String>,
+    launcher_source: LauncherSource,
+    launcher_path: String,
+    /// Games read from the launcher, with the library game each matches
+    launcher_games: Option<Vec<(ImportedGame, Option<i32>)>>,
+    launcher_add_missing: bool,
+    launcher_status: Option<String>,
+    snapshots: Option<Vec<Snapshot>>,
+    pending: Option<PendingAction>,
+    backup_status: Option<String>,
+}
 
 impl ToolsPage {
-    pub fn view(&self) -> Element<Message> {
-        column![button("Clear Data").on_press(Message::ClearDatabase)].into()
+    fn duplicates_view(duplicates: &[DuplicateGroup]) -> Column<'_, Message> {
+        if duplicates.is_empty() {
+            return column![text("No duplicates found")];
+        }
+        Column::with_children(duplicates.iter().enumerate().map(|(index, group)| {
+            let reason = match group.reason {
+                DuplicateReason::ExternalId => "Same store ID".to_string(),
+                DuplicateReason::Name { similarity } => {
+                    format!("Similar name ({:.0}%)", similarity * 100.0)
+                }
+            };
+            row![
+                column(
+                    group.games.iter().map(|game| {
+                        text(format!("{} ({})", game.name, game.library_type)).into()
+                    })
+                )
+                .width(Length::FillPortion(6)),
+                text(reason).width(Length::FillPortion(2)),
+                button("Merge")
+                    .style(button::success)
+                    .on_press(Message::MergeDuplicates(index)),
+            ]
+            .spacing(10)
+            .into()
+        }))
+        .spacing(6)
+    }
+    fn import_preview_view(&self) -> Column<'_, Message> {
+        let (Some(backup), Some(preview)) = (self.import.as_ref(), self.import_preview.as_ref())
+        else {
+            return column![];
+        };
+        column![
+            text(format!(
+                "{} new games, {} updated, {} removed",
+                preview.new_games, preview.updated_games, preview.removed_games
+            )),
+            text(format!(
+                "{} genres, {} tags, {} collections, {} launch profiles, {} settings and addon files",
+                preview.genres,
+                preview.tags,
+                preview.collections,
+                preview.launch_profiles,
+                backup.files.len()
+            )),
+            button("Import")
+                .style(if self.import_mode == ImportMode::Replace {
+                    button::danger
+                } else {
+                    button::success
+                })
+                .on_press(Message::Import),
+        ]
+        .spacing(6)
+    }
+    fn export_view(&self) -> Column<'_, Message> {
+        let heading = |label| {
+            text(label).font(Font {
+                weight: Weight::Semibold,
+                ..Font::default()
+            })
+        };
+        column![
+            heading("Export"),
+            row![
+                text_input("Export file path", &self.export_path)
+                    .on_input(Message::ExportPathChanged),
+                pick_list(
+                    ExportFormat::ALL,
+                    self.export_format,
+                    Message::ExportFormatChanged
+                )
+                .placeholder("Format"),
+                button("Export").on_press_maybe(
+                    (!self.export_path.is_empty() && self.export_format.is_some())
+                        .then_some(Message::Export)
+                ),
+            ]
+            .spacing(10),
+            heading("Import"),
+            row![
+                text_input("JSON, CSV or .gami file path", &self.import_path)
+                    .on_input(Message::ImportPathChanged),
+                pick_list(
+                    ImportMode::ALL,
+                    Some(self.import_mode),
+                    Message::ImportModeChanged
+                ),
+                button("Load")
+                    .on_press_maybe((!self.import_path.is_empty()).then_some(Message::LoadImport)),
+            ]
+            .spacing(10),
+            self.import_preview_view(),
+            text(self.status.clone().unwrap_or_default()),
+        ]
+        .spacing(10)
+    }
+    fn launcher_games_view(games: &[(ImportedGame, Option<i32>)]) -> Column<'_, Message> {
+        Column::with_children(games.iter().map(|(imported, matched)| {
+            row![
+                text(imported.game.name.clone()).width(Length::FillPortion(4)),
+                text(imported.game.library_type.clone()).width(Length::FillPortion(1)),
+                text(
+                    imported
+                        .completion_status
+                        .map(|status| status.to_string())
+                        .unwrap_or_default()
+                )
+                .width(Length::FillPortion(1)),
+                text(imported.tags.join(", ")).width(Length::FillPortion(2)),
+                text(if matched.is_some() {
+                    "In library"
+                } else {
+                    "New"
+                })
+                .width(Length::FillPortion(1)),
+            ]
+            .spacing(10)
+            .into()
+        }))
+        .spacing(4)
+    }
+    fn launcher_import_view(&self) -> Column<'_, Message> {
+        let mut content = column![
+            text("Import from another launcher").font(Font {
+                weight: Weight::Semibold,
+                ..Font::default()
+            }),
+            row![
+                pick_list(
+                    LauncherSource::ALL,
+                    Some(self.launcher_source),
+                    Message::LauncherSourceChanged
+                ),
+                text_input(self.launcher_source.expected_path(), &self.launcher_path)
+                    .on_input(Message::LauncherPathChanged),
+                button("Read").on_press_maybe(
+                    (!self.launcher_path.is_empty()).then_some(Message::ReadLauncher)
+                ),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10);
+        if let Some(games) = self.launcher_games.as_deref() {
+            let matched = games.iter().filter(|(_, id)| id.is_some()).count();
+            content = content.extend([
+                text(format!(
+                    "{} games read, {} already in the library",
+                    games.len(),
+                    matched
+                ))
+                .into(),
+                scrollable(Self::launcher_games_view(games))
+                    .height(Length::Fixed(200.0))
+                    .into(),
+                row![
+                    checkbox("Add games not in the library", self.launcher_add_missing)
+                        .on_toggle(Message::LauncherAddMissingChanged),
+                    button("Import")
+                        .style(button::success)
+                        .on_press(Message::ImportLauncher),
+                ]
+                .spacing(10)
+                .into(),
+            ]);
+        }
+        content.push(text(self.launcher_status.clone().unwrap_or_default()))
+    }
+    fn confirm_view(&self) -> Column<'_, Message> {
+        let question = match self.pending {
+            None => return column![],
+            Some(PendingAction::Clear) => {
+                "Delete every game, genre and tag? A snapshot is taken first.".to_string()
+            }
+            Some(PendingAction::Restore(index)) => {
+                let Some(snapshot) = self.snapshots.as_ref().and_then(|s| s.get(index)) else {
+                    return column![];
+                };
+                format!(
+                    "Replace the library with the snapshot from {}? \
+                     A snapshot of the current library is taken first.",
+                    snapshot.created.format("%Y-%m-%d %H:%M")
+                )
+            }
+        };
+        column![
+            text(question),
+            row![
+                button("Confirm")
+                    .style(button::danger)
+                    .on_press(Message::Confirm),
+                button("Cancel")
+                    .style(button::secondary)
+                    .on_press(Message::CancelConfirm),
+            ]
+            .spacing(10),
+        ]
+        .spacing(6)
+    }
+    fn snapshots_view(&self) -> Column<'_, Message> {
+        let mut content = column![
+            text("Database snapshots").font(Font {
+                weight: Weight::Semibold,
+                ..Font::default()
+            }),
+            row![
+                button("Back up now").on_press(Message::BackUpNow),
+                text(self.backup_status.clone().unwrap_or_default()),
+            ]
+            .spacing(10),
+        ]
+        .spacing(10);
+        content = match self.snapshots.as_deref() {
+            None => content.push(text("Loading snapshots...")),
+            Some([]) => content.push(text("No snapshots yet")),
+            Some(snapshots) => content.push(
+                scrollable(
+                    Column::with_children(snapshots.iter().enumerate().map(|(index, snapshot)| {
+                        row![
+                            text(snapshot.created.format("%Y-%m-%d %H:%M").to_string())
+                                .width(Length::FillPortion(2)),
+                            text(snapshot.kind.to_string()).width(Length::FillPortion(2)),
+                            text(match snapshot.games {
+                                Some(games) => format!("{} games", games),
+                                None => "Unreadable".to_string(),
+                            })
+                            .width(Length::FillPortion(2)),
+                            text(format!("{:.1} MB", snapshot.size as f64 / 1_000_000.0))
+                                .width(Length::FillPortion(1)),
+                            button("Restore").style(button::secondary).on_press_maybe(
+                                snapshot
+                                    .games
+                                    .is_some()
+                                    .then_some(Message::RestoreSnapshot(index))
+                            ),
+                        ]
+                        .spacing(10)
+                        .into()
+                    }))
+                    .spacing(4),
+                )
+                .height(Length::Fixed(200.0)),
+            ),
+        };
+        content
+    }
+    fn load_preview(&self) -> Task<Message> {
+        let Some(backup) = self.import.as_ref() else {
+            return Task::none();
+        };
+        let library = backup.library.clone();
+        let mode = self.import_mode;
+        db_task(
+            async move { db::store().preview_import(&library, mode).await },
+            Message::PreviewLoaded,
+        )
+    }
+    pub fn view(&self) -> Element<'_, Message> {
+        column![
+            row![
+                button("Clear Data")
+                    .style(button::danger)
+                    .on_press(Message::ClearDatabase),
+                button("Find duplicates").on_press(Message::FindDuplicates),
+            ]
+            .spacing(10),
+            self.confirm_view(),
+            self.snapshots_view(),
+            self.export_view(),
+            self.launcher_import_view(),
+            scrollable(
+                self.duplicates
+                    .as_deref()
+                    .map(Self::duplicates_view)
+                    .unwrap_or_else(|| column![])
+            ),
+        ]
+        .spacing(10)
+        .into()
     }
 
     pub fn update(&mut self, message: Message) -> Task<Message> {
         match message {
             Message::NoOp => Task::none(),
-            Message::ClearDatabase => Task::future(db::ops::clear_all()).map(|_| Message::NoOp),
+            Message::ClearDatabase => {
+                self.pending = Some(PendingAction::Clear);
+                Task::none()
+            }
+            Message::CancelConfirm => {
+                self.pending = None;
+                Task::none()
+            }
+            Message::Confirm => match self.pending.take() {
+                Some(PendingAction::Clear) => {
+                    self.backup_status = Some("Snapshotting and clearing...".into());
+                    Task::perform(db::store().clear_all(), |res| {
+                        Message::Cleared(res.map_err(|err| err.to_string()))
+                    })
+                }
+                Some(PendingAction::Restore(index)) => {
+                    let Some(snapshot) = self.snapshots.as_ref().and_then(|s| s.get(index)) else {
+                        return Task::none();
+                    };
+                    self.backup_status = Some("Restoring...".into());
+                    Task::perform(db::store().restore_snapshot(snapshot.path.clone()), |res| {
+                        Message::Restored(res.map_err(|err| err.to_string()))
+                    })
+                }
+                None => Task::none(),
+            },
+            Message::Cleared(res) => {
+                self.backup_status = Some(match res {
+                    Ok(()) => "Library cleared".into(),
+                    Err(err) => format!("Not cleared: {}", err),
+                });
+                self.update(Message::LoadSnapshots)
+            }
+            Message::LoadSnapshots => {
+                Task::perform(db::store().list_snapshots(), Message::SnapshotsLoaded)
+            }
+            Message::SnapshotsLoaded(snapshots) => {
+                self.snapshots = Some(snapshots);
+                Task::none()
+            }
+            Message::BackUpNow => {
+                self.backup_status = Some("Backing up...".into());
+                Task::perform(db::store().create_snapshot(SnapshotKind::Manual), |res| {
+                    Message::SnapshotCreated(res.map(|_| ()).map_err(|err| err.to_string()))
+                })
+            }
+            Message::SnapshotCreated(res) => {
+                self.backup_status = Some(match res {
+                    Ok(()) => "Snapshot created".into(),
+                    Err(err) => format!("Backup failed: {}", err),
+                });
+                self.update(Message::LoadSnapshots)
+            }
+            Message::RestoreSnapshot(index) => {
+                self.pending = Some(PendingAction::Restore(index));
+                Task::none()
+            }
+            Message::Restored(res) => {
+                self.backup_status = Some(match res {
+                    Ok(()) => "Snapshot restored".into(),
+                    Err(err) => format!("Restore failed: {}", err),
+                });
+                self.update(Message::LoadSnapshots)
+            }
+            Message::FindDuplicates => {
+                db_task(db::store().find_duplicates(), Message::DuplicatesFound)
+            }
+            Message::DuplicatesFound(duplicates) => {
+                self.duplicates = Some(duplicates);
+                Task::none()
+            }
+            Message::MergeDuplicates(index) => {
+                let Some(group) = self.duplicates.as_mut().map(|d| d.remove(index)) else {
+                    return Task::none();
+                };
+                // the most played copy keeps its details and becomes the preferred launcher
+                let primary = group
+                    .games
+                    .iter()
+                    .max_by_key(|game| game.play_time)
+                    .map(|game| game.id)
+                    .unwrap_or_default();
+                let others = group
+                    .games
+                    .iter()
+                    .map(|game| game.id)
+                    .filter(|id| *id != primary)
+                    .collect();
+                db_task(db::store().merge_games(primary, others), |_| Message::NoOp)
+            }
+            Message::ExportPathChanged(path) => {
+                if let Some(format) = ExportFormat::from_path(Path::new(&path)) {
+                    self.export_format = Some(format);
+                }
+                self.export_path = path;
+                Task::none()
+            }
+            Message::ExportFormatChanged(format) => {
+                let path = PathBuf::from(&self.export_path).with_extension(format.extension());
+                self.export_path = path.to_string_lossy().into_owned();
+                self.export_format = Some(format);
+                Task::none()
+            }
+            Message::Export => {
+                let Some(format) = self.export_format else {
+                    return Task::none();
+                };
+                self.status = Some("Exporting...".into());
+                Task::perform(
+                    db::store().export_to_file(PathBuf::from(&self.export_path), format),
+                    |res| Message::Exported(res.map_err(|err| err.to_string())),
+                )
+            }
+            Message::Exported(res) => {
+                self.status = Some(match res {
+                    Ok(()) => format!("Exported to {}", self.export_path),
+                    Err(err) => format!("Export failed: {}", err),
+                });
+                Task::none()
+            }
+            Message::ImportPathChanged(path) => {
+                self.import_path = path;
+                Task::none()
+            }
+            Message::ImportModeChanged(mode) => {
+                self.import_mode = mode;
+                self.load_preview()
+            }
+            Message::LoadImport => {
+                self.import = None;
+                self.import_preview = None;
+                let path = PathBuf::from(&self.import_path);
+                Task::perform(
+                    async move { export::read_file(&path).map(Box::new) },
+                    Message::ImportLoaded,
+                )
+            }
+            Message::ImportLoaded(Ok(backup)) => {
+                self.status = None;
+                self.import = Some(*backup);
+                self.load_preview()
+            }
+            Message::ImportLoaded(Err(err)) => {
+                self.status = Some(err.to_string());
+                Task::none()
+            }
+            Message::PreviewLoaded(preview) => {
+                self.import_preview = Some(preview);
+                Task::none()
+            }
+            Message::Import => {
+                let Some(backup) = self.import.take() else {
+                    return Task::none();
+                };
+                self.import_preview = None;
+                self.status = Some("Importing...".into());
+                Task::perform(db::store().import_backup(backup, self.import_mode), |res| {
+                    Message::Imported(res.map_err(|err| err.to_string()))
+                })
+            }
+            Message::Imported(res) => {
+                self.status = Some(match res {
+                    Ok(()) => "Import finished".into(),
+                    Err(err) => format!("Not imported: {}", err),
+                });
+                Task::none()
+            }
+            Message::LauncherSourceChanged(source) => {
+                self.launcher_source = source;
+                self.launcher_path = source
+                    .default_path()
+                    .map(|path| path.to_string_lossy().into_owned())
+                    .unwrap_or_default();
+                self.launcher_games = None;
+                Task::none()
+            }
+            Message::LauncherPathChanged(path) => {
+                self.launcher_path = path;
+                Task::none()
+            }
+            Message::ReadLauncher => {
+                self.launcher_games = None;
+                self.launcher_status = Some(format!("Reading {} library...", self.launcher_source));
+                let source = self.launcher_source;
+                let path = PathBuf::from(&self.launcher_path);
+                Task::perform(
+                    async move { source.read(&path).await },
+                    Message::LauncherRead,
+                )
+            }
+            Message::LauncherRead(Ok(games)) => {
+                self.launcher_status = None;
+                let matches_for = games.clone();
+                self.launcher_games = Some(games.into_iter().map(|game| (game, None)).collect());
+                db_task(
+                    async move { db::store().match_launcher_games(&matches_for).await },
+                    Message::LauncherMatched,
+                )
+            }
+            Message::LauncherRead(Err(err)) => {
+                self.launcher_status = Some(err.to_string());
+                Task::none()
+            }
+            Message::LauncherMatched(matches) => {
+                for ((_, matched), id) in self.launcher_games.iter_mut().flatten().zip(matches) {
+                    *matched = id;
+                }
+                Task::none()
+            }
+            Message::LauncherAddMissingChanged(add_missing) => {
+                self.launcher_add_missing = add_missing;
+                Task::none()
+            }
+            Message::ImportLauncher => {
+                let Some(games) = self.launcher_games.take() else {
+                    return Task::none();
+                };
+                self.launcher_status = Some("Importing...".into());
+                db_task(
+                    db::store().import_launcher_games(
+                        games.into_iter().map(|(game, _)| game).collect(),
+                        self.launcher_add_missing,
+                    ),
+                    Message::LauncherImported,
+                )
+            }
+            Message::LauncherImported(summary) => {
+                self.launcher_status = Some(format!(
+                    "Updated {} games, added {}",
+                    summary.updated, summary.added
+                ));
+                Task::none()
+            }
         }
     }
 }
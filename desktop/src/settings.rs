@@ -1,15 +1,19 @@
 use crate::models::{MyTheme, PostLaunchAction};
+use crate::widgets::library_table::ColumnKind;
 use bitcode::{Decode, Encode};
+use gami_backend::db::backup::BackupPolicy;
 use gami_sdk::BASE_DATA_DIR;
-use std::cell::LazyCell;
 use std::path::PathBuf;
+use std::sync::LazyLock;
 
-const SETTINGS_PATH: LazyCell<PathBuf> =
-    LazyCell::new(|| BASE_DATA_DIR.join("desktop_settings.bin"));
+static SETTINGS_PATH: LazyLock<PathBuf> =
+    LazyLock::new(|| BASE_DATA_DIR.join("desktop_settings.bin"));
 #[derive(Encode, Decode, Default, Debug, PartialEq, Clone)]
 pub struct Settings {
     pub appearance: AppearanceSettings,
     pub general: GeneralSettings,
+    pub library: LibrarySettings,
+    pub backups: BackupSettings,
 }
 #[derive(Encode, Decode, Default, Debug, PartialEq, Clone)]
 pub struct GeneralSettings {
@@ -17,14 +21,45 @@ pub struct GeneralSettings {
 }
 
 #[derive(Encode, Decode, Default, Debug, PartialEq, Clone)]
-pub struct AppearanceSettings {
-    pub theme: MyTheme,
+pub struct LibrarySettings {
+    pub show_facets: bool,
+    /// JSON serialized `FacetFilters` of the facet sidebar
+    pub facets: String,
+    pub table_columns: Vec<TableColumnSettings>,
 }
 
-pub async fn save_async(settings: &Settings) -> std::io::Result<()> {
-    let encoded: Vec<u8> = bitcode::encode(settings);
-    tokio::fs::write(&*SETTINGS_PATH, &encoded).await?;
-    Ok(())
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+pub struct TableColumnSettings {
+    pub kind: ColumnKind,
+    pub width: f32,
+}
+
+#[derive(Encode, Decode, Debug, PartialEq, Clone)]
+pub struct BackupSettings {
+    pub keep_daily: i32,
+    pub keep_weekly: i32,
+}
+impl Default for BackupSettings {
+    fn default() -> Self {
+        let policy = BackupPolicy::default();
+        Self {
+            keep_daily: policy.keep_daily as i32,
+            keep_weekly: policy.keep_weekly as i32,
+        }
+    }
+}
+impl BackupSettings {
+    pub fn policy(&self) -> BackupPolicy {
+        BackupPolicy {
+            keep_daily: self.keep_daily.max(0) as usize,
+            keep_weekly: self.keep_weekly.max(0) as usize,
+        }
+    }
+}
+
+#[derive(Encode, Decode, Default, Debug, PartialEq, Clone)]
+pub struct AppearanceSettings {
+    pub theme: MyTheme,
 }
 
 pub fn save(settings: &Settings) -> std::io::Result<()> {
@@ -33,13 +68,20 @@ pub fn save(settings: &Settings) -> std::io::Result<()> {
     Ok(())
 }
 
+/// Settings saved by an older version with different fields are reset to defaults
+fn decode(bytes: &[u8]) -> Settings {
+    bitcode::decode(bytes).unwrap_or_else(|err| {
+        log::warn!("resetting unreadable settings: {}", err);
+        Settings::default()
+    })
+}
+
 pub async fn load_async() -> std::io::Result<Settings> {
     if !SETTINGS_PATH.exists() {
         return Ok(Settings::default());
     }
     let bytes = tokio::fs::read(&*SETTINGS_PATH).await?;
-    let decoded: Settings = bitcode::decode(&bytes).unwrap();
-    Ok(decoded)
+    Ok(decode(&bytes))
 }
 
 pub fn load() -> std::io::Result<Settings> {
@@ -47,6 +89,5 @@ pub fn load() -> std::io::Result<Settings> {
         return Ok(Settings::default());
     }
     let bytes = std::fs::read(&*SETTINGS_PATH)?;
-    let decoded: Settings = bitcode::decode(&bytes).unwrap();
-    Ok(decoded)
+    Ok(decode(&bytes))
 }
@@ -0,0 +1,380 @@
+use gami_backend::db::filter::{CompareOp, FilterExpr};
+use gami_backend::db::Collection;
+use gami_sdk::{CompletionStatus, EditableEnum, GameInstallStatus};
+use iced::alignment::Vertical;
+use iced::font::Weight;
+use iced::widget::{button, checkbox, column, pick_list, row, text, text_input, Column};
+use iced::{Element, Font, Length};
+use std::fmt;
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum RuleKind {
+    NameContains,
+    LibraryType,
+    Installed,
+    CompletionStatus,
+    Playtime,
+    NeverPlayed,
+    PlayedWithinDays,
+    ReleaseYear,
+    Genre,
+    Tag,
+    Favourite,
+}
+impl RuleKind {
+    pub const ALL: [RuleKind; 11] = [
+        RuleKind::NameContains,
+        RuleKind::LibraryType,
+        RuleKind::Installed,
+        RuleKind::CompletionStatus,
+        RuleKind::Playtime,
+        RuleKind::NeverPlayed,
+        RuleKind::PlayedWithinDays,
+        RuleKind::ReleaseYear,
+        RuleKind::Genre,
+        RuleKind::Tag,
+        RuleKind::Favourite,
+    ];
+    fn has_op(self) -> bool {
+        matches!(self, Self::Playtime | Self::ReleaseYear)
+    }
+    fn value_hint(self) -> Option<&'static str> {
+        match self {
+            Self::NameContains => Some("Name"),
+            Self::LibraryType => Some("Library"),
+            Self::Playtime => Some("Minutes"),
+            Self::PlayedWithinDays => Some("Days"),
+            Self::ReleaseYear => Some("Year"),
+            Self::Genre => Some("Genre"),
+            Self::Tag => Some("Tag"),
+            Self::Installed | Self::CompletionStatus | Self::NeverPlayed | Self::Favourite => None,
+        }
+    }
+}
+impl fmt::Display for RuleKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::NameContains => "Name contains",
+            Self::LibraryType => "Library",
+            Self::Installed => "Installed",
+            Self::CompletionStatus => "Completion status",
+            Self::Playtime => "Playtime (minutes)",
+            Self::NeverPlayed => "Never played",
+            Self::PlayedWithinDays => "Played in last days",
+            Self::ReleaseYear => "Release year",
+            Self::Genre => "Genre",
+            Self::Tag => "Tag",
+            Self::Favourite => "Favourite",
+        })
+    }
+}
+
+/// Single condition row of the editor
+#[derive(Clone, Debug)]
+pub struct CollectionRule {
+    kind: RuleKind,
+    negate: bool,
+    op: CompareOp,
+    value: String,
+    completion_status: CompletionStatus,
+}
+impl Default for CollectionRule {
+    fn default() -> Self {
+        Self {
+            kind: RuleKind::NameContains,
+            negate: false,
+            op: CompareOp::Lt,
+            value: String::new(),
+            completion_status: CompletionStatus::Backlog,
+        }
+    }
+}
+impl CollectionRule {
+    fn to_expr(&self) -> Option<FilterExpr> {
+        let value = self.value.trim();
+        let expr = match self.kind {
+            RuleKind::NameContains if !value.is_empty() => FilterExpr::NameContains(value.into()),
+            RuleKind::LibraryType if !value.is_empty() => FilterExpr::LibraryType(value.into()),
+            RuleKind::Genre if !value.is_empty() => FilterExpr::Genre(value.into()),
+            RuleKind::Tag if !value.is_empty() => FilterExpr::Tag(value.into()),
+            RuleKind::Installed => FilterExpr::InstallStatus(GameInstallStatus::Installed),
+            RuleKind::CompletionStatus => FilterExpr::CompletionStatus(self.completion_status),
+            RuleKind::Playtime => FilterExpr::Playtime {
+                op: self.op,
+                minutes: value.parse().ok()?,
+            },
+            RuleKind::NeverPlayed => FilterExpr::NeverPlayed,
+            RuleKind::PlayedWithinDays => FilterExpr::PlayedWithinDays(value.parse().ok()?),
+            RuleKind::ReleaseYear => FilterExpr::ReleaseYear {
+                op: self.op,
+                year: value.parse().ok()?,
+            },
+            RuleKind::Favourite => FilterExpr::Favourite,
+            _ => return None,
+        };
+        Some(if self.negate {
+            FilterExpr::Not(Box::new(expr))
+        } else {
+            expr
+        })
+    }
+    fn from_expr(expr: &FilterExpr) -> Option<Self> {
+        let rule = Self::default();
+        Some(match expr {
+            FilterExpr::Not(inner) if !matches!(**inner, FilterExpr::Not(_)) => Self {
+                negate: true,
+                ..Self::from_expr(inner)?
+            },
+            FilterExpr::NameContains(value) => Self {
+                kind: RuleKind::NameContains,
+                value: value.clone(),
+                ..rule
+            },
+            FilterExpr::LibraryType(value) => Self {
+                kind: RuleKind::LibraryType,
+                value: value.clone(),
+                ..rule
+            },
+            FilterExpr::Genre(value) => Self {
+                kind: RuleKind::Genre,
+                value: value.clone(),
+                ..rule
+            },
+            FilterExpr::Tag(value) => Self {
+                kind: RuleKind::Tag,
+                value: value.clone(),
+                ..rule
+            },
+            FilterExpr::InstallStatus(GameInstallStatus::Installed) => Self {
+                kind: RuleKind::Installed,
+                ..rule
+            },
+            FilterExpr::CompletionStatus(status) => Self {
+                kind: RuleKind::CompletionStatus,
+                completion_status: *status,
+                ..rule
+            },
+            FilterExpr::Playtime { op, minutes } => Self {
+                kind: RuleKind::Playtime,
+                op: *op,
+                value: minutes.to_string(),
+                ..rule
+            },
+            FilterExpr::NeverPlayed => Self {
+                kind: RuleKind::NeverPlayed,
+                ..rule
+            },
+            FilterExpr::PlayedWithinDays(days) => Self {
+                kind: RuleKind::PlayedWithinDays,
+                value: days.to_string(),
+                ..rule
+            },
+            FilterExpr::ReleaseYear { op, year } => Self {
+                kind: RuleKind::ReleaseYear,
+                op: *op,
+                value: year.to_string(),
+                ..rule
+            },
+            FilterExpr::Favourite => Self {
+                kind: RuleKind::Favourite,
+                ..rule
+            },
+            _ => return None,
+        })
+    }
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum MatchMode {
+    All,
+    Any,
+}
+impl fmt::Display for MatchMode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::All => "Match all rules",
+            Self::Any => "Match any rule",
+        })
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum EditorMessage {
+    NameChanged(String),
+    MatchModeChanged(MatchMode),
+    AddRule,
+    RemoveRule(usize),
+    RuleKindChanged(usize, RuleKind),
+    RuleNegated(usize, bool),
+    RuleOpChanged(usize, CompareOp),
+    RuleValueChanged(usize, String),
+    RuleCompletionChanged(usize, CompletionStatus),
+    Save,
+    Cancel,
+}
+
+/// Editor for flat smart collections, i.e. a list of rules combined with AND or OR
+#[derive(Clone, Debug)]
+pub struct CollectionEditor {
+    id: Option<i32>,
+    name: String,
+    mode: MatchMode,
+    rules: Vec<CollectionRule>,
+    /// Set when the stored filter is nested deeper than the editor can show
+    unsupported: bool,
+}
+
+impl CollectionEditor {
+    pub fn new() -> Self {
+        Self {
+            id: None,
+            name: String::new(),
+            mode: MatchMode::All,
+            rules: vec![CollectionRule::default()],
+            unsupported: false,
+        }
+    }
+    pub fn edit(collection: &Collection) -> Self {
+        let (mode, children) = match &collection.filter {
+            FilterExpr::And(children) => (MatchMode::All, children.as_slice()),
+            FilterExpr::Or(children) => (MatchMode::Any, children.as_slice()),
+            other => (MatchMode::All, std::slice::from_ref(other)),
+        };
+        let rules: Option<Vec<CollectionRule>> =
+            children.iter().map(CollectionRule::from_expr).collect();
+        Self {
+            id: Some(collection.id),
+            name: collection.name.clone(),
+            mode,
+            unsupported: rules.is_none(),
+            rules: rules.unwrap_or_default(),
+        }
+    }
+    /// The edited collection, `None` while the name or any rule is incomplete
+    pub fn collection(&self) -> Option<Collection> {
+        let name = self.name.trim();
+        if name.is_empty() || self.unsupported {
+            return None;
+        }
+        let children = self
+            .rules
+            .iter()
+            .map(CollectionRule::to_expr)
+            .collect::<Option<Vec<_>>>()?;
+        Some(Collection {
+            id: self.id.unwrap_or_default(),
+            name: name.into(),
+            filter: match self.mode {
+                MatchMode::All => FilterExpr::And(children),
+                MatchMode::Any => FilterExpr::Or(children),
+            },
+        })
+    }
+    pub fn is_new(&self) -> bool {
+        self.id.is_none()
+    }
+    pub fn update(&mut self, message: EditorMessage) {
+        match message {
+            EditorMessage::NameChanged(name) => self.name = name,
+            EditorMessage::MatchModeChanged(mode) => self.mode = mode,
+            EditorMessage::AddRule => self.rules.push(CollectionRule::default()),
+            EditorMessage::RemoveRule(index) => {
+                self.rules.remove(index);
+            }
+            EditorMessage::RuleKindChanged(index, kind) => {
+                self.rules[index] = CollectionRule {
+                    kind,
+                    ..CollectionRule::default()
+                }
+            }
+            EditorMessage::RuleNegated(index, negate) => self.rules[index].negate = negate,
+            EditorMessage::RuleOpChanged(index, op) => self.rules[index].op = op,
+            EditorMessage::RuleValueChanged(index, value) => self.rules[index].value = value,
+            EditorMessage::RuleCompletionChanged(index, status) => {
+                self.rules[index].completion_status = status
+            }
+            EditorMessage::Save | EditorMessage::Cancel => {}
+        }
+    }
+    fn rule_view(index: usize, rule: &CollectionRule) -> Element<'_, EditorMessage> {
+        let mut line = row![
+            checkbox("Not", rule.negate).on_toggle(move |v| EditorMessage::RuleNegated(index, v)),
+            pick_list(&RuleKind::ALL[..], Some(rule.kind), move |kind| {
+                EditorMessage::RuleKindChanged(index, kind)
+            }),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center);
+        if rule.kind.has_op() {
+            line = line.push(pick_list(&CompareOp::ALL[..], Some(rule.op), move |op| {
+                EditorMessage::RuleOpChanged(index, op)
+            }));
+        }
+        if let Some(hint) = rule.kind.value_hint() {
+            line = line.push(
+                text_input(hint, &rule.value)
+                    .on_input(move |value| EditorMessage::RuleValueChanged(index, value))
+                    .width(Length::Fill),
+            );
+        }
+        if rule.kind == RuleKind::CompletionStatus {
+            line = line.push(pick_list(
+                CompletionStatus::ALL,
+                Some(rule.completion_status),
+                move |status| EditorMessage::RuleCompletionChanged(index, status),
+            ));
+        }
+        line.push(
+            button(text("Remove"))
+                .style(button::danger)
+                .on_press(EditorMessage::RemoveRule(index)),
+        )
+        .into()
+    }
+    pub fn view(&self) -> Column<'_, EditorMessage> {
+        let rules: Element<EditorMessage> = if self.unsupported {
+            text("This collection uses nested rules and can't be edited here").into()
+        } else {
+            Column::with_children(
+                self.rules
+                    .iter()
+                    .enumerate()
+                    .map(|(index, rule)| Self::rule_view(index, rule)),
+            )
+            .spacing(4)
+            .into()
+        };
+        column![
+            row![
+                button(text("Cancel")).on_press(EditorMessage::Cancel),
+                button(text("Save"))
+                    .style(button::success)
+                    .on_press_maybe(self.collection().map(|_| EditorMessage::Save)),
+                text(if self.is_new() {
+                    "New collection"
+                } else {
+                    "Edit collection"
+                })
+                .font(Font {
+                    weight: Weight::Semibold,
+                    ..Font::default()
+                }),
+            ]
+            .spacing(10)
+            .align_y(Vertical::Center),
+            text_input("Name", &self.name).on_input(EditorMessage::NameChanged),
+            pick_list(
+                [MatchMode::All, MatchMode::Any],
+                Some(self.mode),
+                EditorMessage::MatchModeChanged
+            ),
+            rules,
+            button(text("Add rule")).on_press_maybe(if self.unsupported {
+                None
+            } else {
+                Some(EditorMessage::AddRule)
+            }),
+        ]
+        .spacing(8)
+    }
+}
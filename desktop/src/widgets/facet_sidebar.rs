@@ -0,0 +1,255 @@
+use gami_backend::db::filter::FacetFilters;
+use gami_backend::db::ops::FacetCounts;
+use gami_sdk::{CompletionStatus, EditableEnum, GameInstallStatus};
+use iced::font::Weight;
+use iced::widget::{
+    button, checkbox, column, pick_list, row, scrollable, text, text_input, Column,
+};
+use iced::{Element, Font, Length};
+use std::fmt;
+
+const INSTALL_STATUSES: [GameInstallStatus; 4] = [
+    GameInstallStatus::Installed,
+    GameInstallStatus::Installing,
+    GameInstallStatus::Queued,
+    GameInstallStatus::InLibrary,
+];
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum FacetRange {
+    YearMin,
+    YearMax,
+    PlaytimeMin,
+    PlaytimeMax,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq)]
+struct PlayedWithin {
+    days: Option<i64>,
+    count: u64,
+}
+impl fmt::Display for PlayedWithin {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.days {
+            None => f.write_str("Any time"),
+            Some(days) => write!(f, "Last {} days ({})", days, self.count),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum FacetMessage {
+    InstallStatus(GameInstallStatus, bool),
+    CompletionStatus(CompletionStatus, bool),
+    LibraryType(String, bool),
+    Genre(String, bool),
+    Range(FacetRange, String),
+    PlayedWithin(Option<i64>),
+    Clear,
+}
+
+fn toggle<T: PartialEq>(values: &mut Vec<T>, value: T, checked: bool) {
+    values.retain(|v| *v != value);
+    if checked {
+        values.push(value);
+    }
+}
+
+fn heading(title: &str) -> Element<'_, FacetMessage> {
+    text(title)
+        .font(Font {
+            weight: Weight::Semibold,
+            ..Font::default()
+        })
+        .into()
+}
+
+/// Collapsible facet filters of the library page, with counts per value
+#[derive(Clone, Debug, Default)]
+pub struct FacetSidebar {
+    /// Raw text of the range inputs, indexed by [`FacetRange`]
+    range_text: [String; 4],
+}
+
+impl FacetSidebar {
+    pub fn new(facets: &FacetFilters) -> Self {
+        let text = |value: Option<i64>| value.map(|v| v.to_string()).unwrap_or_default();
+        Self {
+            range_text: [
+                text(facets.release_year_min.map(|year| year.get().into())),
+                text(facets.release_year_max.map(|year| year.get().into())),
+                text(facets.playtime_min_minutes),
+                text(facets.playtime_max_minutes),
+            ],
+        }
+    }
+    pub fn update(&mut self, facets: &mut FacetFilters, message: FacetMessage) {
+        match message {
+            FacetMessage::InstallStatus(status, checked) => {
+                toggle(&mut facets.install_status, status, checked)
+            }
+            FacetMessage::CompletionStatus(status, checked) => {
+                toggle(&mut facets.completion_status, status, checked)
+            }
+            FacetMessage::LibraryType(library_type, checked) => {
+                toggle(&mut facets.library_types, library_type, checked)
+            }
+            FacetMessage::Genre(genre, checked) => toggle(&mut facets.genres, genre, checked),
+            FacetMessage::Range(range, value) => {
+                let trimmed = value.trim();
+                match range {
+                    FacetRange::YearMin => facets.release_year_min = trimmed.parse().ok(),
+                    FacetRange::YearMax => facets.release_year_max = trimmed.parse().ok(),
+                    FacetRange::PlaytimeMin => facets.playtime_min_minutes = trimmed.parse().ok(),
+                    FacetRange::PlaytimeMax => facets.playtime_max_minutes = trimmed.parse().ok(),
+                }
+                self.range_text[range as usize] = value;
+            }
+            FacetMessage::PlayedWithin(days) => facets.played_within_days = days,
+            FacetMessage::Clear => {
+                *facets = FacetFilters::default();
+                self.range_text = Default::default();
+            }
+        }
+    }
+    fn range_input(
+        &self,
+        range: FacetRange,
+        placeholder: &'static str,
+    ) -> Element<'_, FacetMessage> {
+        text_input(placeholder, &self.range_text[range as usize])
+            .on_input(move |value| FacetMessage::Range(range, value))
+            .into()
+    }
+    pub fn view<'a>(
+        &'a self,
+        facets: &'a FacetFilters,
+        counts: &'a FacetCounts,
+    ) -> Element<'a, FacetMessage> {
+        fn count_of<T: PartialEq>(counts: &[(T, u64)], value: &T) -> u64 {
+            counts
+                .iter()
+                .find(|(v, _)| v == value)
+                .map(|(_, count)| *count)
+                .unwrap_or_default()
+        }
+        // selected values stay listed even when nothing matches them anymore
+        fn with_selected<'b>(counts: &'b [(String, u64)], selected: &'b [String]) -> Vec<&'b str> {
+            let mut values: Vec<&str> = counts.iter().map(|(v, _)| v.as_str()).collect();
+            for value in selected {
+                if !values.contains(&value.as_str()) {
+                    values.push(value);
+                }
+            }
+            values
+        }
+
+        let install_status = Column::with_children(INSTALL_STATUSES.iter().map(|status| {
+            let status = *status;
+            checkbox(
+                format!("{} ({})", status, count_of(&counts.install_status, &status)),
+                facets.install_status.contains(&status),
+            )
+            .on_toggle(move |checked| FacetMessage::InstallStatus(status, checked))
+            .into()
+        }));
+        let completion_status = Column::with_children(CompletionStatus::ALL.iter().map(|status| {
+            let status = *status;
+            checkbox(
+                format!(
+                    "{} ({})",
+                    status,
+                    count_of(&counts.completion_status, &status)
+                ),
+                facets.completion_status.contains(&status),
+            )
+            .on_toggle(move |checked| FacetMessage::CompletionStatus(status, checked))
+            .into()
+        }));
+        let library_types = Column::with_children(
+            with_selected(&counts.library_types, &facets.library_types)
+                .into_iter()
+                .map(|library_type| {
+                    let count = count_of(&counts.library_types, &library_type.to_string());
+                    let value = library_type.to_string();
+                    checkbox(
+                        format!("{} ({})", library_type, count),
+                        facets.library_types.contains(&value),
+                    )
+                    .on_toggle(move |checked| FacetMessage::LibraryType(value.clone(), checked))
+                    .into()
+                }),
+        );
+        let genres = Column::with_children(
+            with_selected(&counts.genres, &facets.genres)
+                .into_iter()
+                .map(|genre| {
+                    let count = count_of(&counts.genres, &genre.to_string());
+                    let value = genre.to_string();
+                    checkbox(
+                        format!("{} ({})", genre, count),
+                        facets.genres.contains(&value),
+                    )
+                    .on_toggle(move |checked| FacetMessage::Genre(value.clone(), checked))
+                    .into()
+                }),
+        );
+        let played_within: Vec<PlayedWithin> = std::iter::once(PlayedWithin {
+            days: None,
+            count: 0,
+        })
+        .chain(
+            counts
+                .played_within
+                .iter()
+                .map(|(days, count)| PlayedWithin {
+                    days: Some(*days),
+                    count: *count,
+                }),
+        )
+        .collect();
+        let selected_played_within = played_within
+            .iter()
+            .find(|p| p.days == facets.played_within_days)
+            .copied();
+
+        scrollable(
+            column![
+                button(text("Clear filters"))
+                    .style(button::secondary)
+                    .on_press_maybe(if facets.is_empty() {
+                        None
+                    } else {
+                        Some(FacetMessage::Clear)
+                    }),
+                heading("Install status"),
+                install_status,
+                heading("Completion status"),
+                completion_status,
+                heading("Library"),
+                library_types,
+                heading("Genres"),
+                genres,
+                heading("Release year"),
+                row![
+                    self.range_input(FacetRange::YearMin, "From"),
+                    self.range_input(FacetRange::YearMax, "To"),
+                ]
+                .spacing(4),
+                heading("Playtime (minutes)"),
+                row![
+                    self.range_input(FacetRange::PlaytimeMin, "Min"),
+                    self.range_input(FacetRange::PlaytimeMax, "Max"),
+                ]
+                .spacing(4),
+                heading("Last played"),
+                pick_list(played_within, selected_played_within, |p| {
+                    FacetMessage::PlayedWithin(p.days)
+                }),
+            ]
+            .spacing(4),
+        )
+        .width(Length::FillPortion(3))
+        .into()
+    }
+}
@@ -0,0 +1,521 @@
+use crate::widgets::launch_profile_editor::{LaunchProfileEditor, LaunchProfileMessage};
+use crate::widgets::number_input::number_input;
+use chrono::{DateTime, Duration, Local, NaiveDate, NaiveDateTime, TimeZone, Utc};
+use gami_backend::db::ops::GameEditState;
+use gami_backend::launch::{LaunchLogEntry, LaunchProfile};
+use gami_backend::{GameDateField, GameDurationField, GameField, GameListField, GameTextField};
+use gami_sdk::{CompletionStatus, EditableEnum, GameData, GenreData};
+use iced::advanced::svg::Handle;
+use iced::alignment::Vertical;
+use iced::font::Weight;
+use iced::widget::{
+    button, column, container, image, pick_list, row, text, text_input, Button, Column, Row, Svg,
+};
+use iced::{Element, Font, Length};
+use std::collections::HashSet;
+use std::path::Path;
+use url::Url;
+
+const RELEASE_DATE_FORMAT: &str = "%Y-%m-%d";
+const LAST_PLAYED_FORMAT: &str = "%Y-%m-%d %H:%M";
+
+#[derive(Clone, Debug)]
+pub enum GameEditorMessage {
+    TextChanged(GameTextField, String),
+    DateChanged(GameDateField, String),
+    /// Hours and minutes
+    DurationChanged(GameDurationField, i32, i32),
+    ListChanged(GameListField, String),
+    CompletionStatusChanged(CompletionStatus),
+    NotesChanged(String),
+    LaunchProfile(LaunchProfileMessage),
+    Reset(GameField),
+    StateLoaded(i32, Box<GameEditState>),
+    Save,
+    Close,
+}
+
+/// Path of an image URL the `image` widget can load, i.e. a local file
+pub fn local_image_path(url: &str) -> Option<String> {
+    let url = Url::parse(url).ok()?;
+    if url.scheme() != "file" {
+        return None;
+    }
+    let raw = urlencoding::decode(url.path()).ok()?;
+    Some(if cfg!(windows) {
+        raw[1..].to_string()
+    } else {
+        raw.into_owned()
+    })
+}
+
+fn split_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|v| !v.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+fn opt_text(value: &Option<String>) -> &str {
+    value.as_deref().unwrap_or("")
+}
+
+/// Editor for every user-editable field of a game
+#[derive(Clone, Debug)]
+pub struct GameEditor {
+    game: GameData,
+    /// Raw text of inputs that are parsed on save
+    release_date: String,
+    last_played: String,
+    genres: String,
+    developers: String,
+    publishers: String,
+    play_time: (i32, i32),
+    /// Fields edited by hand, before or during this edit
+    manual: HashSet<GameField>,
+    /// The game as its metadata scanner describes it, once loaded
+    metadata: Option<GameData>,
+    /// The game's own launch profile, once loaded
+    launch_profile: Option<LaunchProfileEditor>,
+    launch_log: Vec<LaunchLogEntry>,
+}
+
+impl GameEditor {
+    pub fn new(game: GameData) -> Self {
+        let minutes = game.play_time.num_minutes();
+        Self {
+            release_date: game
+                .release_date
+                .map(|d| d.format(RELEASE_DATE_FORMAT).to_string())
+                .unwrap_or_default(),
+            last_played: game
+                .last_played
+                .map(|t| {
+                    t.with_timezone(&Local)
+                        .format(LAST_PLAYED_FORMAT)
+                        .to_string()
+                })
+                .unwrap_or_default(),
+            genres: game
+                .genres
+                .iter()
+                .map(|g| g.name.trim_end())
+                .collect::<Vec<_>>()
+                .join(", "),
+            developers: game.developers.join(", "),
+            publishers: game.publishers.join(", "),
+            play_time: ((minutes / 60) as i32, (minutes % 60) as i32),
+            manual: HashSet::new(),
+            metadata: None,
+            launch_profile: None,
+            launch_log: Vec::new(),
+            game,
+        }
+    }
+
+    fn parse_last_played(&self) -> Result<Option<DateTime<Utc>>, &'static str> {
+        let value = self.last_played.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        let local = NaiveDateTime::parse_from_str(value, LAST_PLAYED_FORMAT)
+            .ok()
+            .and_then(|dt| Local.from_local_datetime(&dt).single())
+            .ok_or("Expected YYYY-MM-DD HH:MM")?;
+        if local > Local::now() {
+            return Err("Can't be in the future");
+        }
+        Ok(Some(local.with_timezone(&Utc)))
+    }
+    fn parse_release_date(&self) -> Result<Option<NaiveDate>, &'static str> {
+        let value = self.release_date.trim();
+        if value.is_empty() {
+            return Ok(None);
+        }
+        NaiveDate::parse_from_str(value, RELEASE_DATE_FORMAT)
+            .map(Some)
+            .map_err(|_| "Expected YYYY-MM-DD")
+    }
+
+    /// Validation error of a field, if any
+    fn error(&self, field: GameField) -> Option<&'static str> {
+        let url_error = |value: &Option<String>| match value.as_deref() {
+            Some(url) if Url::parse(url).is_err() => Some("Not a valid URL"),
+            _ => None,
+        };
+        match field {
+            GameField::Name if self.game.name.trim().is_empty() => Some("Name is required"),
+            GameField::IconUrl => url_error(&self.game.icon_url),
+            GameField::HeaderUrl => url_error(&self.game.header_url),
+            GameField::CoverUrl => url_error(&self.game.cover_url),
+            GameField::InstallPath => match self.game.install_path.as_deref() {
+                Some(path) if !Path::new(path).exists() => Some("Path does not exist"),
+                _ => None,
+            },
+            GameField::ReleaseDate => self.parse_release_date().err(),
+            GameField::LastPlayed => self.parse_last_played().err(),
+            GameField::PlayTime if self.play_time.0 < 0 => Some("Can't be negative"),
+            GameField::PlayTime if !(0..60).contains(&self.play_time.1) => {
+                Some("Minutes must be between 0 and 59")
+            }
+            _ => None,
+        }
+    }
+
+    /// The edited launch profile, `None` until it is loaded
+    pub fn launch_profile(&self) -> Option<LaunchProfile> {
+        self.launch_profile.as_ref()?.profile()
+    }
+
+    /// The edited game and the fields edited by hand, `None` while any field is invalid
+    pub fn result(&self) -> Option<(GameData, HashSet<GameField>)> {
+        let invalid_profile = self
+            .launch_profile
+            .as_ref()
+            .is_some_and(|editor| editor.profile().is_none());
+        if invalid_profile
+            || GameField::ALL
+                .iter()
+                .any(|field| self.error(*field).is_some())
+        {
+            return None;
+        }
+        let mut game = self.game.clone();
+        game.release_date = self.parse_release_date().ok()?;
+        game.last_played = self.parse_last_played().ok()?;
+        game.play_time = Duration::minutes(self.play_time.0 as i64 * 60 + self.play_time.1 as i64);
+        // known genres keep their metadata ID, new ones are created by name
+        game.genres = split_list(&self.genres)
+            .into_iter()
+            .map(|name| {
+                self.game
+                    .genres
+                    .iter()
+                    .find(|g| g.name.trim_end() == name)
+                    .cloned()
+                    .unwrap_or_else(|| GenreData {
+                        name: name.clone().into(),
+                        library_id: name.into(),
+                    })
+            })
+            .collect();
+        game.developers = split_list(&self.developers);
+        game.publishers = split_list(&self.publishers);
+        Some((game, self.manual.clone()))
+    }
+
+    fn reset(&mut self, field: GameField) {
+        let Some(metadata) = self.metadata.clone() else {
+            return;
+        };
+        match field {
+            GameField::Description => self.game.description = metadata.description,
+            GameField::IconUrl => self.game.icon_url = metadata.icon_url,
+            GameField::HeaderUrl => self.game.header_url = metadata.header_url,
+            GameField::CoverUrl => self.game.cover_url = metadata.cover_url,
+            GameField::ReleaseDate => {
+                self.release_date = metadata
+                    .release_date
+                    .map(|d| d.format(RELEASE_DATE_FORMAT).to_string())
+                    .unwrap_or_default()
+            }
+            GameField::Genres => {
+                self.game.genres = metadata.genres;
+                self.genres = self
+                    .game
+                    .genres
+                    .iter()
+                    .map(|g| g.name.trim_end())
+                    .collect::<Vec<_>>()
+                    .join(", ");
+            }
+            GameField::Developers => self.developers = metadata.developers.join(", "),
+            GameField::Publishers => self.publishers = metadata.publishers.join(", "),
+            _ => return,
+        }
+        self.manual.remove(&field);
+    }
+
+    pub fn update(&mut self, message: GameEditorMessage) {
+        fn map_opt_empty(opt: String) -> Option<String> {
+            if opt.is_empty() {
+                None
+            } else {
+                Some(opt)
+            }
+        }
+        match message {
+            GameEditorMessage::TextChanged(field, value) => {
+                match field {
+                    GameTextField::Name => self.game.name = value,
+                    GameTextField::Description => self.game.description = value,
+                    GameTextField::HeaderUrl => self.game.header_url = map_opt_empty(value),
+                    GameTextField::IconUrl => self.game.icon_url = map_opt_empty(value),
+                    GameTextField::CoverUrl => self.game.cover_url = map_opt_empty(value),
+                    GameTextField::InstallPath => self.game.install_path = map_opt_empty(value),
+                }
+                self.manual.insert(field.into());
+            }
+            GameEditorMessage::DateChanged(field, value) => {
+                match field {
+                    GameDateField::LastPlayed => self.last_played = value,
+                    GameDateField::ReleaseDate => self.release_date = value,
+                }
+                self.manual.insert(field.into());
+            }
+            GameEditorMessage::DurationChanged(field, hours, minutes) => {
+                self.play_time = (hours, minutes);
+                self.manual.insert(field.into());
+            }
+            GameEditorMessage::ListChanged(field, value) => {
+                match field {
+                    GameListField::Genres => self.genres = value,
+                    GameListField::Developers => self.developers = value,
+                    GameListField::Publishers => self.publishers = value,
+                }
+                self.manual.insert(field.into());
+            }
+            GameEditorMessage::CompletionStatusChanged(status) => {
+                self.game.completion_status = status;
+                self.manual.insert(GameField::CompletionStatus);
+            }
+            // notes are the user's own, so they never count as an edited field
+            GameEditorMessage::NotesChanged(notes) => self.game.notes = notes,
+            GameEditorMessage::LaunchProfile(msg) => {
+                if let Some(editor) = self.launch_profile.as_mut() {
+                    editor.update(msg);
+                }
+            }
+            GameEditorMessage::Reset(field) => self.reset(field),
+            // the state may arrive after another game was opened
+            GameEditorMessage::StateLoaded(game_id, state) if game_id == self.game.id => {
+                self.manual.extend(state.manual_fields);
+                self.metadata = state.metadata;
+                self.launch_profile = Some(LaunchProfileEditor::for_game(&state.launch_profile));
+                self.launch_log = state.launch_log;
+            }
+            GameEditorMessage::StateLoaded(..) => {}
+            GameEditorMessage::Save | GameEditorMessage::Close => {}
+        }
+    }
+
+    /// Labelled input with its validation error and, for metadata fields, a reset button
+    fn field_row<'a>(
+        &'a self,
+        field: GameField,
+        name: &'a str,
+        input: impl Into<Element<'a, GameEditorMessage>>,
+    ) -> Column<'a, GameEditorMessage> {
+        let label = if self.manual.contains(&field) {
+            format!("{} (edited):", name)
+        } else {
+            format!("{}:", name)
+        };
+        let mut line = row![
+            text(label)
+                .font(Font {
+                    weight: Weight::Semibold,
+                    ..Font::default()
+                })
+                .width(Length::FillPortion(3)),
+            container(input.into()).width(Length::FillPortion(6)),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center);
+        if field.from_metadata() {
+            line = line.push(
+                button(text("Reset"))
+                    .style(button::secondary)
+                    .on_press_maybe(if self.metadata.is_some() && self.manual.contains(&field) {
+                        Some(GameEditorMessage::Reset(field))
+                    } else {
+                        None
+                    })
+                    .width(Length::FillPortion(1)),
+            );
+        }
+        let mut field_column = column![line];
+        if let Some(error) = self.error(field) {
+            field_column = field_column.push(text(error).style(text::danger));
+        }
+        field_column
+    }
+    fn text_row<'a>(
+        &'a self,
+        field: GameTextField,
+        name: &'a str,
+        curr: &'a str,
+        placeholder: &'a str,
+    ) -> Column<'a, GameEditorMessage> {
+        self.field_row(
+            field.into(),
+            name,
+            text_input(placeholder, curr)
+                .on_input(move |txt| GameEditorMessage::TextChanged(field, txt)),
+        )
+    }
+    fn image_row<'a>(
+        &'a self,
+        field: GameTextField,
+        name: &'a str,
+        curr: &'a Option<String>,
+        placeholder: &'a str,
+    ) -> Column<'a, GameEditorMessage> {
+        let preview: Element<GameEditorMessage> = match curr.as_deref().and_then(local_image_path) {
+            Some(path) => image(path).height(64).into(),
+            None if curr.is_some() => text("No preview for remote images").into(),
+            None => column![].into(),
+        };
+        self.text_row(field, name, opt_text(curr), placeholder)
+            .push(preview)
+    }
+    fn list_row<'a>(
+        &'a self,
+        field: GameListField,
+        name: &'a str,
+        curr: &'a str,
+    ) -> Column<'a, GameEditorMessage> {
+        self.field_row(
+            field.into(),
+            name,
+            text_input("Comma separated", curr)
+                .on_input(move |txt| GameEditorMessage::ListChanged(field, txt)),
+        )
+    }
+    fn date_row<'a>(
+        &'a self,
+        field: GameDateField,
+        name: &'a str,
+        curr: &'a str,
+        placeholder: &'a str,
+    ) -> Column<'a, GameEditorMessage> {
+        self.field_row(
+            field.into(),
+            name,
+            text_input(placeholder, curr)
+                .on_input(move |txt| GameEditorMessage::DateChanged(field, txt)),
+        )
+    }
+
+    pub fn view(&self) -> Column<'_, GameEditorMessage> {
+        fn editor_btn(
+            text_content: &'static str,
+            bytes: &'static [u8],
+        ) -> Button<'static, GameEditorMessage> {
+            button(
+                row![
+                    Svg::new(Handle::from_memory(bytes)).width(Length::FillPortion(1)),
+                    text(text_content).width(Length::FillPortion(9)),
+                ]
+                .spacing(10.0),
+            )
+        }
+        let (hours, minutes) = self.play_time;
+        let field = GameDurationField::TimePlayed;
+        let play_time: Row<GameEditorMessage> = row![
+            Element::from(number_input("Hours", hours))
+                .map(move |h| GameEditorMessage::DurationChanged(field, h, minutes)),
+            text("h"),
+            Element::from(number_input("Minutes", minutes))
+                .map(move |m| GameEditorMessage::DurationChanged(field, hours, m)),
+            text("m"),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center);
+        column![
+            row![
+                editor_btn(
+                    "Close",
+                    include_bytes!("../icons/tabler--arrow-back.svg").as_slice()
+                )
+                .on_press(GameEditorMessage::Close),
+                editor_btn(
+                    "Save",
+                    include_bytes!("../icons/tabler--device-floppy.svg").as_slice()
+                )
+                .style(button::success)
+                .on_press_maybe(self.result().map(|_| GameEditorMessage::Save)),
+            ]
+            .padding(6)
+            .spacing(20.0),
+            self.text_row(GameTextField::Name, "Name", &self.game.name, "Enter name"),
+            self.text_row(
+                GameTextField::Description,
+                "Description",
+                &self.game.description,
+                "Enter description"
+            ),
+            self.image_row(
+                GameTextField::IconUrl,
+                "Icon URL",
+                &self.game.icon_url,
+                "Enter icon URL"
+            ),
+            self.image_row(
+                GameTextField::CoverUrl,
+                "Cover URL",
+                &self.game.cover_url,
+                "Enter hero URL"
+            ),
+            self.image_row(
+                GameTextField::HeaderUrl,
+                "Header URL",
+                &self.game.header_url,
+                "Enter header URL"
+            ),
+            self.date_row(
+                GameDateField::ReleaseDate,
+                "Release Date",
+                &self.release_date,
+                "YYYY-MM-DD"
+            ),
+            self.date_row(
+                GameDateField::LastPlayed,
+                "Last Played",
+                &self.last_played,
+                "YYYY-MM-DD HH:MM"
+            ),
+            self.field_row(GameField::PlayTime, "Playtime", play_time),
+            self.field_row(
+                GameField::CompletionStatus,
+                "Completion Status",
+                pick_list(
+                    CompletionStatus::ALL,
+                    Some(self.game.completion_status),
+                    GameEditorMessage::CompletionStatusChanged
+                )
+            ),
+            self.list_row(GameListField::Genres, "Genres", &self.genres),
+            self.list_row(GameListField::Developers, "Developers", &self.developers),
+            self.list_row(GameListField::Publishers, "Publishers", &self.publishers),
+            self.text_row(
+                GameTextField::InstallPath,
+                "Install Path",
+                opt_text(&self.game.install_path),
+                "Enter install path"
+            ),
+            row![
+                text("Notes:")
+                    .font(Font {
+                        weight: Weight::Semibold,
+                        ..Font::default()
+                    })
+                    .width(Length::FillPortion(3)),
+                container(
+                    text_input("Anything to remember about this game", &self.game.notes)
+                        .on_input(GameEditorMessage::NotesChanged)
+                )
+                .width(Length::FillPortion(7)),
+            ]
+            .spacing(4)
+            .align_y(Vertical::Center),
+        ]
+        .push_maybe(self.launch_profile.as_ref().map(|editor| {
+            Element::from(editor.view("Launch", &self.launch_log))
+                .map(GameEditorMessage::LaunchProfile)
+        }))
+        .spacing(6)
+    }
+}
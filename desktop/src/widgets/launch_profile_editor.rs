@@ -0,0 +1,310 @@
+use chrono::Local;
+use gami_backend::launch::{split_wrapper, LaunchLogEntry, LaunchProfile, PostLaunchAction};
+use iced::advanced::svg::Handle;
+use iced::alignment::Vertical;
+use iced::font::Weight;
+use iced::widget::{column, container, pick_list, row, text, text_input, Column, Svg};
+use iced::{Element, Font, Length};
+use std::fmt;
+use std::path::Path;
+
+/// Launch log entries listed below the profile
+const LOG_ENTRIES_SHOWN: usize = 5;
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub enum LaunchField {
+    PreLaunch,
+    PostExit,
+    Wrappers,
+    Env,
+    WorkingDir,
+}
+
+/// A game's post-launch action, or none to follow the launcher's setting
+#[derive(Copy, Clone, Debug, PartialEq, Eq)]
+pub struct PostLaunchChoice(Option<PostLaunchAction>);
+impl PostLaunchChoice {
+    const ALL: [PostLaunchChoice; 4] = [
+        PostLaunchChoice(None),
+        PostLaunchChoice(Some(PostLaunchAction::DoNothing)),
+        PostLaunchChoice(Some(PostLaunchAction::Minimize)),
+        PostLaunchChoice(Some(PostLaunchAction::Exit)),
+    ];
+}
+impl fmt::Display for PostLaunchChoice {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.0 {
+            Some(action) => action.fmt(f),
+            None => f.write_str("Launcher setting"),
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub enum LaunchProfileMessage {
+    Changed(LaunchField, String),
+    PostLaunchActionChanged(PostLaunchChoice),
+}
+
+fn split_list(value: &str) -> impl Iterator<Item = &str> {
+    value.split(',').map(str::trim).filter(|v| !v.is_empty())
+}
+
+/// Splits wrappers on the commas outside of quotes, keeping `taskset -c "0,2"` whole
+fn split_wrappers(value: &str) -> Vec<&str> {
+    let mut wrappers = Vec::new();
+    let mut quote = None;
+    let mut start = 0;
+    for (i, c) in value.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(open), c) if c == open => quote = None,
+            (None, ',') => {
+                wrappers.push(&value[start..i]);
+                start = i + 1;
+            }
+            _ => {}
+        }
+    }
+    wrappers.push(&value[start..]);
+    wrappers
+        .into_iter()
+        .map(str::trim)
+        .filter(|w| !w.is_empty())
+        .collect()
+}
+
+fn opt_string(value: &str) -> Option<String> {
+    let value = value.trim();
+    (!value.is_empty()).then(|| value.to_string())
+}
+
+/// Inputs of a launch profile, lists are edited as comma separated text
+#[derive(Clone, Debug, Default)]
+pub struct LaunchProfileEditor {
+    pre_launch: String,
+    post_exit: String,
+    wrappers: String,
+    env: String,
+    working_dir: String,
+    post_launch_action: Option<PostLaunchAction>,
+    /// Whether this is a game's profile, which may override the post-launch action
+    per_game: bool,
+}
+
+impl LaunchProfileEditor {
+    pub fn global(profile: &LaunchProfile) -> Self {
+        Self::new(profile, false)
+    }
+    pub fn for_game(profile: &LaunchProfile) -> Self {
+        Self::new(profile, true)
+    }
+    fn new(profile: &LaunchProfile, per_game: bool) -> Self {
+        Self {
+            pre_launch: profile.pre_launch.clone().unwrap_or_default(),
+            post_exit: profile.post_exit.clone().unwrap_or_default(),
+            wrappers: profile.wrappers.join(", "),
+            env: profile
+                .env
+                .iter()
+                .map(|(key, value)| format!("{}={}", key, value))
+                .collect::<Vec<_>>()
+                .join(", "),
+            working_dir: profile.working_dir.clone().unwrap_or_default(),
+            post_launch_action: profile.post_launch_action.filter(|_| per_game),
+            per_game,
+        }
+    }
+
+    fn error(&self, field: LaunchField) -> Option<&'static str> {
+        match field {
+            LaunchField::Wrappers
+                if split_wrappers(&self.wrappers)
+                    .into_iter()
+                    .any(|wrapper| split_wrapper(wrapper).is_err()) =>
+            {
+                Some("A wrapper has an unclosed quote")
+            }
+            LaunchField::Env
+                if split_list(&self.env)
+                    .any(|pair| pair.split_once('=').is_none_or(|(key, _)| key.is_empty())) =>
+            {
+                Some("Expected KEY=VALUE pairs")
+            }
+            LaunchField::WorkingDir => match opt_string(&self.working_dir) {
+                Some(dir) if !Path::new(&dir).is_dir() => Some("Directory does not exist"),
+                _ => None,
+            },
+            _ => None,
+        }
+    }
+
+    /// The edited profile, `None` while any input is invalid
+    pub fn profile(&self) -> Option<LaunchProfile> {
+        if [
+            LaunchField::Wrappers,
+            LaunchField::Env,
+            LaunchField::WorkingDir,
+        ]
+        .into_iter()
+        .any(|field| self.error(field).is_some())
+        {
+            return None;
+        }
+        Some(LaunchProfile {
+            pre_launch: opt_string(&self.pre_launch),
+            post_exit: opt_string(&self.post_exit),
+            wrappers: split_wrappers(&self.wrappers)
+                .into_iter()
+                .map(String::from)
+                .collect(),
+            env: split_list(&self.env)
+                .filter_map(|pair| pair.split_once('='))
+                .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+                .collect(),
+            working_dir: opt_string(&self.working_dir),
+            post_launch_action: self.post_launch_action,
+        })
+    }
+
+    pub fn update(&mut self, message: LaunchProfileMessage) {
+        match message {
+            LaunchProfileMessage::Changed(field, value) => match field {
+                LaunchField::PreLaunch => self.pre_launch = value,
+                LaunchField::PostExit => self.post_exit = value,
+                LaunchField::Wrappers => self.wrappers = value,
+                LaunchField::Env => self.env = value,
+                LaunchField::WorkingDir => self.working_dir = value,
+            },
+            LaunchProfileMessage::PostLaunchActionChanged(PostLaunchChoice(action)) => {
+                self.post_launch_action = action
+            }
+        }
+    }
+
+    fn labelled_row<'a>(
+        &'a self,
+        name: &'a str,
+        input: impl Into<Element<'a, LaunchProfileMessage>>,
+    ) -> Column<'a, LaunchProfileMessage> {
+        column![row![
+            text(format!("{}:", name))
+                .font(Font {
+                    weight: Weight::Semibold,
+                    ..Font::default()
+                })
+                .width(Length::FillPortion(3)),
+            container(input.into()).width(Length::FillPortion(7)),
+        ]
+        .spacing(4)
+        .align_y(Vertical::Center)]
+    }
+    fn input_row<'a>(
+        &'a self,
+        field: LaunchField,
+        name: &'a str,
+        curr: &'a str,
+        placeholder: &'a str,
+    ) -> Column<'a, LaunchProfileMessage> {
+        let mut field_column = self.labelled_row(
+            name,
+            text_input(placeholder, curr)
+                .on_input(move |txt| LaunchProfileMessage::Changed(field, txt)),
+        );
+        if let Some(error) = self.error(field) {
+            field_column = field_column.push(text(error).style(text::danger));
+        }
+        field_column
+    }
+
+    /// Inputs under a header, followed by the most recent entries of `log`
+    pub fn view<'a>(
+        &'a self,
+        title: &'a str,
+        log: &'a [LaunchLogEntry],
+    ) -> Column<'a, LaunchProfileMessage> {
+        let header = row![
+            Svg::new(Handle::from_memory(
+                include_bytes!("../icons/tabler--script.svg").as_slice()
+            ))
+            .width(20),
+            text(title).font(Font {
+                weight: Weight::Bold,
+                ..Font::default()
+            }),
+        ]
+        .spacing(6)
+        .align_y(Vertical::Center);
+        let mut content = column![
+            header,
+            self.input_row(
+                LaunchField::PreLaunch,
+                "Pre-launch script",
+                &self.pre_launch,
+                "Shell command run before the game starts"
+            ),
+            self.input_row(
+                LaunchField::PostExit,
+                "Post-exit script",
+                &self.post_exit,
+                "Shell command run after the game exits"
+            ),
+            self.input_row(
+                LaunchField::Wrappers,
+                "Wrappers",
+                &self.wrappers,
+                "Comma separated, e.g. gamemoderun, mangohud"
+            ),
+            self.input_row(
+                LaunchField::Env,
+                "Environment",
+                &self.env,
+                "Comma separated, e.g. DXVK_HUD=fps"
+            ),
+            self.input_row(
+                LaunchField::WorkingDir,
+                "Working directory",
+                &self.working_dir,
+                "Defaults to the library's"
+            ),
+        ]
+        .spacing(6)
+        .push(
+            text("Games a library's client starts, like Steam's, only run the pre-launch script")
+                .style(text::secondary),
+        );
+        if self.per_game {
+            content = content.push(self.labelled_row(
+                "After launch",
+                pick_list(
+                    PostLaunchChoice::ALL,
+                    Some(PostLaunchChoice(self.post_launch_action)),
+                    LaunchProfileMessage::PostLaunchActionChanged,
+                ),
+            ));
+        }
+        if !log.is_empty() {
+            content = content.push(text("Recent launches:").font(Font {
+                weight: Weight::Semibold,
+                ..Font::default()
+            }));
+        }
+        for entry in log.iter().take(LOG_ENTRIES_SHOWN) {
+            let summary = text(format!(
+                "{} {}: {}",
+                entry.at.with_timezone(&Local).format("%Y-%m-%d %H:%M"),
+                entry.stage,
+                if entry.success { "ok" } else { "failed" }
+            ));
+            content = content.push(if entry.success {
+                summary
+            } else {
+                summary.style(text::danger)
+            });
+            if !entry.output.is_empty() {
+                content = content.push(text(entry.output.as_str()).size(12));
+            }
+        }
+        content
+    }
+}
@@ -1,9 +1,14 @@
 use std::fmt;
 
+use crate::settings::TableColumnSettings;
+use bitcode::{Decode, Encode};
+use chrono::Local;
+use gami_backend::db::ops::{Sort, SortField, SortOrder};
 use gami_sdk::GameData;
 use iced::{
+    alignment::Vertical,
     font::Weight,
-    widget::{container, responsive, scrollable, text},
+    widget::{button, checkbox, column, container, responsive, row, scrollable, text, Column},
     Element, Font, Length, Renderer, Task, Theme,
 };
 use iced_table::table;
@@ -13,14 +18,49 @@ pub enum TableMessage {
     SyncHeader(scrollable::AbsoluteOffset),
     Resizing(usize, f32),
     Resized,
+    SortBy(ColumnKind),
+    ToggleColumn(ColumnKind, bool),
+    MoveColumn(usize, bool),
+    RowClicked(usize),
 }
 
-#[derive(Copy, Clone, Debug)]
+#[derive(Encode, Decode, Copy, Clone, Debug, PartialEq)]
 pub enum ColumnKind {
     Name,
     LastPlayed,
     Playtime,
     Description,
+    InstallStatus,
+    ReleaseDate,
+    Genres,
+    Library,
+    CompletionStatus,
+}
+
+impl ColumnKind {
+    pub const ALL: [ColumnKind; 9] = [
+        ColumnKind::Name,
+        ColumnKind::LastPlayed,
+        ColumnKind::Playtime,
+        ColumnKind::Description,
+        ColumnKind::InstallStatus,
+        ColumnKind::ReleaseDate,
+        ColumnKind::Genres,
+        ColumnKind::Library,
+        ColumnKind::CompletionStatus,
+    ];
+    pub fn sort_field(self) -> Option<SortField> {
+        match self {
+            ColumnKind::Name => Some(SortField::Name),
+            ColumnKind::LastPlayed => Some(SortField::LastPlayed),
+            ColumnKind::Playtime => Some(SortField::Playtime),
+            ColumnKind::InstallStatus => Some(SortField::InstallStatus),
+            ColumnKind::ReleaseDate => Some(SortField::ReleaseDate),
+            ColumnKind::Library => Some(SortField::Library),
+            ColumnKind::CompletionStatus => Some(SortField::CompletionStatus),
+            ColumnKind::Description | ColumnKind::Genres => None,
+        }
+    }
 }
 
 impl fmt::Display for ColumnKind {
@@ -30,37 +70,65 @@ impl fmt::Display for ColumnKind {
             ColumnKind::LastPlayed => "Last Played",
             ColumnKind::Playtime => "Time Played",
             ColumnKind::Description => "Description",
+            ColumnKind::InstallStatus => "Install Status",
+            ColumnKind::ReleaseDate => "Release Date",
+            ColumnKind::Genres => "Genres",
+            ColumnKind::Library => "Library",
+            ColumnKind::CompletionStatus => "Completion Status",
         })
     }
 }
 #[derive(Clone, Debug)]
-struct Column {
+pub struct TableRow {
+    pub game: GameData,
+    pub selected: bool,
+    pub running: bool,
+}
+
+#[derive(Clone, Debug)]
+struct TableColumn {
     kind: ColumnKind,
     width: f32,
     resize_offset: Option<f32>,
+    /// Direction and priority (0 is primary) when the table is sorted by this column
+    sort: Option<(SortOrder, usize)>,
 }
 
-impl Column {
-    fn new(kind: ColumnKind) -> Self {
+impl TableColumn {
+    fn new(kind: ColumnKind, width: f32) -> Self {
         Self {
             kind,
-            width: 100.,
+            width,
             resize_offset: None,
+            sort: None,
         }
     }
 }
 
-impl<'a, Message> table::Column<'a, Message, Theme, Renderer> for Column
-where
-    Message: 'a,
-{
-    type Row = GameData;
-
-    fn header(&'a self, _col_index: usize) -> Element<'a, Message, Theme, Renderer> {
-        container(text(self.kind.to_string()).font(Font {
-            weight: Weight::Bold,
-            ..Font::DEFAULT
-        }))
+impl<'a> table::Column<'a, TableMessage, Theme, Renderer> for TableColumn {
+    type Row = TableRow;
+
+    fn header(&'a self, _col_index: usize) -> Element<'a, TableMessage, Theme, Renderer> {
+        let label = match self.sort {
+            Some((SortOrder::Ascending, 0)) => format!("{} ▲", self.kind),
+            Some((SortOrder::Descending, 0)) => format!("{} ▼", self.kind),
+            Some((SortOrder::Ascending, priority)) => format!("{} ▲{}", self.kind, priority + 1),
+            Some((SortOrder::Descending, priority)) => format!("{} ▼{}", self.kind, priority + 1),
+            None => self.kind.to_string(),
+        };
+        container(
+            button(text(label).font(Font {
+                weight: Weight::Bold,
+                ..Font::DEFAULT
+            }))
+            .style(button::text)
+            .padding(0)
+            .on_press_maybe(
+                self.kind
+                    .sort_field()
+                    .map(|_| TableMessage::SortBy(self.kind)),
+            ),
+        )
         .center_y(24)
         .into()
     }
@@ -68,24 +136,60 @@ where
     fn cell(
         &'a self,
         _col_index: usize,
-        _row_index: usize,
-        row: &'a Self::Row,
-    ) -> Element<'a, Message, Theme, Renderer> {
+        row_index: usize,
+        table_row: &'a Self::Row,
+    ) -> Element<'a, TableMessage, Theme, Renderer> {
+        let row = &table_row.game;
         let content: Element<_> = match self.kind {
             ColumnKind::Description => text(&row.description).into(),
+            ColumnKind::Name if table_row.running => row![
+                text(&row.name),
+                text("Now playing").size(12).style(text::success)
+            ]
+            .spacing(6)
+            .align_y(Vertical::Center)
+            .into(),
             ColumnKind::Name => text(&row.name).into(),
-            ColumnKind::LastPlayed => {
-                /*
-                let d= row.last_played;
-
-                text(d.map(|t| format("{}", t.duration_since(earlier)))).into()
-                */
-                text("TODO").into()
-            }
-            ColumnKind::Playtime => text(row.play_time.to_string()).into(),
+            ColumnKind::LastPlayed => text(
+                row.last_played
+                    .map(|t| t.with_timezone(&Local).format("%Y-%m-%d %H:%M").to_string())
+                    .unwrap_or("Never".into()),
+            )
+            .into(),
+            ColumnKind::Playtime => text(format!(
+                "{}h {}m",
+                row.play_time.num_hours(),
+                row.play_time.num_minutes() % 60
+            ))
+            .into(),
+            ColumnKind::InstallStatus => text(row.install_status.to_string()).into(),
+            ColumnKind::ReleaseDate => text(
+                row.release_date
+                    .map(|v| v.format("%Y-%m-%d").to_string())
+                    .unwrap_or_default(),
+            )
+            .into(),
+            ColumnKind::Genres => text(
+                row.genres
+                    .iter()
+                    .map(|g| g.name.trim_end())
+                    .collect::<Vec<_>>()
+                    .join(", "),
+            )
+            .into(),
+            ColumnKind::Library => text(&row.library_type).into(),
+            ColumnKind::CompletionStatus => text(row.completion_status.to_string()).into(),
         };
 
-        container(content).width(Length::Fill).center_y(32).into()
+        button(container(content).width(Length::Fill).center_y(32))
+            .style(if table_row.selected {
+                button::primary
+            } else {
+                button::text
+            })
+            .padding(0)
+            .on_press(TableMessage::RowClicked(row_index))
+            .into()
     }
 
     fn width(&self) -> f32 {
@@ -99,31 +203,78 @@ where
 
 #[derive(Clone, Debug)]
 pub struct LibraryTable {
-    columns: Vec<Column>,
+    columns: Vec<TableColumn>,
     header: scrollable::Id,
     body: scrollable::Id,
     footer: scrollable::Id,
-    pub rows: Vec<GameData>,
+    rows: Vec<TableRow>,
 }
 
 impl LibraryTable {
-    pub fn new() -> Self {
-        Self {
-            columns: [
+    /// Restores the saved columns, or the default set when there are none
+    pub fn new(saved: &[TableColumnSettings]) -> Self {
+        let columns = if saved.is_empty() {
+            [
                 ColumnKind::Name,
                 ColumnKind::Playtime,
                 ColumnKind::LastPlayed,
                 ColumnKind::Description,
             ]
             .into_iter()
-            .map(Column::new)
-            .collect(),
+            .map(|kind| TableColumn::new(kind, 100.))
+            .collect()
+        } else {
+            saved
+                .iter()
+                .map(|column| TableColumn::new(column.kind, column.width))
+                .collect()
+        };
+        Self {
+            columns,
             header: scrollable::Id::unique(),
             body: scrollable::Id::unique(),
             footer: scrollable::Id::unique(),
             rows: vec![],
         }
     }
+    pub fn column_settings(&self) -> Vec<TableColumnSettings> {
+        self.columns
+            .iter()
+            .map(|column| TableColumnSettings {
+                kind: column.kind,
+                width: column.width,
+            })
+            .collect()
+    }
+    pub fn set_rows(&mut self, games: Vec<GameData>) {
+        self.rows = games
+            .into_iter()
+            .map(|game| TableRow {
+                game,
+                selected: false,
+                running: false,
+            })
+            .collect();
+    }
+    pub fn set_selected(&mut self, is_selected: impl Fn(usize, &GameData) -> bool) {
+        for (index, row) in self.rows.iter_mut().enumerate() {
+            row.selected = is_selected(index, &row.game);
+        }
+    }
+    pub fn set_running(&mut self, is_running: impl Fn(&GameData) -> bool) {
+        for row in self.rows.iter_mut() {
+            row.running = is_running(&row.game);
+        }
+    }
+    pub fn set_sort(&mut self, sort: &[Sort]) {
+        for column in self.columns.iter_mut() {
+            column.sort = column.kind.sort_field().and_then(|field| {
+                sort.iter()
+                    .position(|s| s.field == field)
+                    .map(|priority| (sort[priority].order, priority))
+            });
+        }
+    }
     pub fn update(&mut self, msg: TableMessage) -> Task<TableMessage> {
         match msg {
             TableMessage::SyncHeader(offset) => {
@@ -140,11 +291,65 @@ impl LibraryTable {
                     column.width += offset;
                 }
             }),
+            TableMessage::SortBy(_) | TableMessage::RowClicked(_) => {}
+            TableMessage::ToggleColumn(kind, visible) => {
+                self.columns.retain(|column| column.kind != kind);
+                if visible {
+                    self.columns.push(TableColumn::new(kind, 100.));
+                }
+            }
+            TableMessage::MoveColumn(index, up) => {
+                if up && index > 0 {
+                    self.columns.swap(index, index - 1);
+                } else if !up && index + 1 < self.columns.len() {
+                    self.columns.swap(index, index + 1);
+                }
+            }
         };
         Task::none()
     }
 
-    pub fn view(&self) -> Element<TableMessage> {
+    /// Lets the user pick and reorder the visible columns
+    pub fn column_editor(&self) -> Column<'_, TableMessage> {
+        let visible = self.columns.iter().enumerate().map(|(index, column)| {
+            let kind = column.kind;
+            row![
+                checkbox(kind.to_string(), true)
+                    .on_toggle(move |checked| TableMessage::ToggleColumn(kind, checked))
+                    .width(Length::Fill),
+                button(text("↑")).on_press_maybe(if index > 0 {
+                    Some(TableMessage::MoveColumn(index, true))
+                } else {
+                    None
+                }),
+                button(text("↓")).on_press_maybe(if index + 1 < self.columns.len() {
+                    Some(TableMessage::MoveColumn(index, false))
+                } else {
+                    None
+                }),
+            ]
+            .spacing(4)
+            .align_y(Vertical::Center)
+            .into()
+        });
+        let hidden = ColumnKind::ALL
+            .into_iter()
+            .filter(|kind| !self.columns.iter().any(|column| column.kind == *kind))
+            .map(|kind| {
+                checkbox(kind.to_string(), false)
+                    .on_toggle(move |checked| TableMessage::ToggleColumn(kind, checked))
+                    .into()
+            });
+        column![text("Columns").font(Font {
+            weight: Weight::Semibold,
+            ..Font::default()
+        })]
+        .extend(visible)
+        .extend(hidden)
+        .spacing(4)
+    }
+
+    pub fn view(&self) -> Element<'_, TableMessage> {
         responsive(|size| {
             table(
                 self.header.clone(),
@@ -0,0 +1,165 @@
+use gami_backend::db::ops::GameMetadataDiff;
+use iced::alignment::Vertical;
+use iced::font::Weight;
+use iced::widget::{button, checkbox, column, row, scrollable, text, Column};
+use iced::{Element, Font, Length};
+
+#[derive(Clone, Debug)]
+pub enum MetadataReviewMessage {
+    ToggleSource(String, bool),
+    Fetch,
+    Loaded(Vec<GameMetadataDiff>),
+    /// Game and change index
+    ToggleChange(usize, usize, bool),
+    SetAll(bool),
+    Apply,
+    Close,
+}
+
+/// Lets the user pick metadata scanners, then accept or reject each fetched change
+#[derive(Clone, Debug)]
+pub struct MetadataReview {
+    pub game_ids: Vec<i32>,
+    sources: Vec<(String, bool)>,
+    /// `None` while fetching
+    diffs: Option<Vec<GameMetadataDiff>>,
+}
+
+impl MetadataReview {
+    pub fn new(game_ids: Vec<i32>, sources: Vec<String>) -> Self {
+        Self {
+            game_ids,
+            sources: sources.into_iter().map(|source| (source, true)).collect(),
+            diffs: None,
+        }
+    }
+    /// Scanners to ask, empty when all of them are selected
+    pub fn selected_sources(&self) -> Vec<String> {
+        if self.sources.iter().all(|(_, selected)| *selected) {
+            return Vec::new();
+        }
+        self.sources
+            .iter()
+            .filter(|(_, selected)| *selected)
+            .map(|(source, _)| source.clone())
+            .collect()
+    }
+    pub fn result(&self) -> Option<Vec<GameMetadataDiff>> {
+        self.diffs.clone()
+    }
+    pub fn update(&mut self, message: MetadataReviewMessage) {
+        match message {
+            MetadataReviewMessage::ToggleSource(source, selected) => {
+                if let Some(entry) = self.sources.iter_mut().find(|(s, _)| *s == source) {
+                    entry.1 = selected;
+                }
+            }
+            MetadataReviewMessage::Fetch => {
+                self.diffs = None;
+            }
+            MetadataReviewMessage::Loaded(diffs) => {
+                self.diffs = Some(diffs);
+            }
+            MetadataReviewMessage::ToggleChange(game, change, accepted) => {
+                if let Some(change) = self
+                    .diffs
+                    .as_mut()
+                    .and_then(|diffs| diffs.get_mut(game))
+                    .and_then(|diff| diff.changes.get_mut(change))
+                {
+                    change.accepted = accepted;
+                }
+            }
+            MetadataReviewMessage::SetAll(accepted) => {
+                for change in self.diffs.iter_mut().flatten().flat_map(|d| &mut d.changes) {
+                    change.accepted = accepted;
+                }
+            }
+            MetadataReviewMessage::Apply | MetadataReviewMessage::Close => {}
+        }
+    }
+    fn changes_view(&self, diffs: &[GameMetadataDiff]) -> Element<'_, MetadataReviewMessage> {
+        let games = diffs
+            .iter()
+            .enumerate()
+            .filter(|(_, diff)| !diff.changes.is_empty())
+            .map(|(game_index, diff)| {
+                column![text(diff.name.clone()).font(Font {
+                    weight: Weight::Semibold,
+                    ..Font::default()
+                })]
+                .extend(diff.changes.iter().enumerate().map(|(index, change)| {
+                    row![
+                        checkbox(
+                            format!("{} ({})", change.field, change.source),
+                            change.accepted
+                        )
+                        .on_toggle(move |accepted| {
+                            MetadataReviewMessage::ToggleChange(game_index, index, accepted)
+                        })
+                        .width(Length::FillPortion(3)),
+                        text(change.old.clone()).width(Length::FillPortion(4)),
+                        text("->"),
+                        text(change.new.clone()).width(Length::FillPortion(4)),
+                    ]
+                    .spacing(6)
+                    .align_y(Vertical::Center)
+                    .into()
+                }))
+                .spacing(4)
+                .into()
+            })
+            .collect::<Vec<Element<MetadataReviewMessage>>>();
+        if games.is_empty() {
+            return text("Metadata is up to date").into();
+        }
+        scrollable(Column::with_children(games).spacing(12)).into()
+    }
+    pub fn view(&self) -> Column<'_, MetadataReviewMessage> {
+        let sources = row(self.sources.iter().map(|(source, selected)| {
+            let source = source.clone();
+            checkbox(source.clone(), *selected)
+                .on_toggle(move |selected| {
+                    MetadataReviewMessage::ToggleSource(source.clone(), selected)
+                })
+                .into()
+        }))
+        .spacing(10);
+        let any_source = self.sources.iter().any(|(_, selected)| *selected);
+        let mut content = column![
+            row![
+                button(text("Close"))
+                    .style(button::secondary)
+                    .on_press(MetadataReviewMessage::Close),
+                button(text("Fetch again")).on_press_maybe(
+                    (any_source && self.diffs.is_some()).then_some(MetadataReviewMessage::Fetch)
+                ),
+                button(text("Accept all")).on_press_maybe(
+                    self.diffs
+                        .as_ref()
+                        .map(|_| MetadataReviewMessage::SetAll(true))
+                ),
+                button(text("Reject all")).on_press_maybe(
+                    self.diffs
+                        .as_ref()
+                        .map(|_| MetadataReviewMessage::SetAll(false))
+                ),
+                button(text("Apply"))
+                    .style(button::success)
+                    .on_press_maybe(self.diffs.as_ref().map(|_| MetadataReviewMessage::Apply)),
+            ]
+            .spacing(10),
+            text(format!(
+                "Refresh metadata of {} games from:",
+                self.game_ids.len()
+            )),
+            sources,
+        ]
+        .spacing(10);
+        content = match self.diffs.as_ref() {
+            None => content.push(text("Fetching metadata...")),
+            Some(diffs) => content.push(self.changes_view(diffs)),
+        };
+        content
+    }
+}
@@ -1,3 +1,8 @@
+pub mod collection_editor;
+pub mod facet_sidebar;
+pub mod game_editor;
+pub mod launch_profile_editor;
 pub mod library_table;
+pub mod metadata_review;
 pub mod nav_view;
 pub mod number_input;
@@ -54,7 +54,7 @@ impl NavView {
     pub fn get_name(&self) -> String {
         PAGES[self.active_item].name.to_string()
     }
-    pub fn view(&self) -> Column<Message> {
+    pub fn view(&self) -> Column<'_, Message> {
         let mut raw_page_items: HashMap<NavLocation, Vec<Element<Message>>> = HashMap::from_iter(
             [NavLocation::Top, NavLocation::Bottom]
                 .into_iter()
@@ -62,7 +62,7 @@ impl NavView {
                     (
                         loc,
                         PAGES
-                            .into_iter()
+                            .iter()
                             .enumerate()
                             .filter(|(_, info)| info.location == loc)
                             .map(|(index, &PageInfo { name, icon, .. })| {
@@ -90,13 +90,8 @@ impl NavView {
                 .remove(&NavLocation::Top)
                 .unwrap()
                 .into_iter()
-                .chain([button("").height(Fill).width(Fill).into()].into_iter())
-                .chain(
-                    raw_page_items
-                        .remove(&NavLocation::Bottom)
-                        .unwrap()
-                        .into_iter(),
-                ),
+                .chain([button("").height(Fill).width(Fill).into()])
+                .chain(raw_page_items.remove(&NavLocation::Bottom).unwrap()),
         )
         .width(50)
     }
@@ -1,7 +1,7 @@
-use std::cell::LazyCell;
 use std::future::Future;
 use std::path::PathBuf;
 use std::pin::Pin;
+use std::sync::LazyLock;
 use tokio_stream::Stream;
 
 mod models;
@@ -11,7 +11,7 @@ pub use plugin::*;
 
 pub use models::*;
 
-pub const BASE_DATA_DIR: LazyCell<PathBuf> = LazyCell::new(|| {
+pub static BASE_DATA_DIR: LazyLock<PathBuf> = LazyLock::new(|| {
     dirs::data_dir()
         .expect("No data directory set!")
         .join("gami")
@@ -21,7 +21,12 @@ pub fn resolve_addon_config_json_path(key: &str) -> PathBuf {
     std::fs::create_dir_all(&parent).unwrap();
     parent.join("config.json")
 }
-pub const BASE__DIR: LazyCell<PathBuf> = LazyCell::new(|| {
+pub fn resolve_account_config_json_path(key: &str, account_id: &str) -> PathBuf {
+    let parent = ADDONS_DIR.join(key).join("accounts");
+    std::fs::create_dir_all(&parent).unwrap();
+    parent.join(format!("{}.json", account_id))
+}
+pub static BASE__DIR: LazyLock<PathBuf> = LazyLock::new(|| {
     dirs::data_dir()
         .expect("No data directory set!")
         .join("gami")
@@ -51,7 +56,7 @@ macro_rules! register_plugin {
 }
 
 pub trait GameCommon {
-    fn get_ref(&self) -> GameLibraryRef;
+    fn get_ref(&self) -> GameLibraryRef<'_>;
     fn get_owned_ref(&self) -> GameLibraryRefOwned {
         self.get_ref().to_owned()
     }
@@ -14,6 +14,7 @@ pub trait IsGameLibraryRef {
     fn get_name(&self) -> &str;
     fn get_library_type(&self) -> &str;
     fn get_library_id(&self) -> &str;
+    fn get_account_id(&self) -> &str;
 }
 #[derive(Debug, Copy, Clone)]
 #[derive_ReprC]
@@ -22,6 +23,7 @@ pub struct GameLibraryRef<'a> {
     pub name: str_ref<'a>,
     pub library_type: str_ref<'a>,
     pub library_id: str_ref<'a>,
+    pub account_id: str_ref<'a>,
 }
 
 impl<'a> GameLibraryRef<'a> {
@@ -30,6 +32,7 @@ impl<'a> GameLibraryRef<'a> {
             library_id: self.library_id.into(),
             library_type: self.library_type.into(),
             name: self.name.into(),
+            account_id: self.account_id.into(),
         }
     }
 }
@@ -39,6 +42,7 @@ pub struct GameLibraryRefOwned {
     pub name: RString,
     pub library_type: RString,
     pub library_id: RString,
+    pub account_id: RString,
 }
 
 impl From<GameLibraryRef<'_>> for GameLibraryRefOwned {
@@ -47,28 +51,27 @@ impl From<GameLibraryRef<'_>> for GameLibraryRefOwned {
             name: game_library.name.into(),
             library_type: game_library.library_type.into(),
             library_id: game_library.library_id.into(),
+            account_id: game_library.account_id.into(),
         }
     }
 }
 impl GameLibraryRefOwned {
-    pub fn as_ref(&self) -> GameLibraryRef {
+    pub fn as_ref(&self) -> GameLibraryRef<'_> {
         GameLibraryRef {
             name: self.name.as_str().into(),
             library_type: self.library_type.as_str().into(),
             library_id: self.library_id.as_str().into(),
+            account_id: self.account_id.as_str().into(),
         }
     }
 }
 
 impl<'a> PartialEq for GameLibraryRef<'a> {
     fn eq(&self, other: &Self) -> bool {
-        &*self.name == &*other.name
-            && &*self.library_type == &*other.library_type
-            && &*self.library_id == &*other.library_id
-    }
-
-    fn ne(&self, other: &Self) -> bool {
-        !Self::eq(self, other)
+        *self.name == *other.name
+            && *self.library_type == *other.library_type
+            && *self.library_id == *other.library_id
+            && *self.account_id == *other.account_id
     }
 }
 impl<'a> Eq for GameLibraryRef<'a> {}
@@ -77,6 +80,7 @@ impl<'a> Hash for GameLibraryRef<'a> {
         self.name.hash(state);
         self.library_type.hash(state);
         self.library_id.hash(state);
+        self.account_id.hash(state);
     }
 }
 impl<'a> fmt::Display for GameLibraryRef<'a> {
@@ -87,16 +91,21 @@ impl<'a> fmt::Display for GameLibraryRef<'a> {
             self.name.as_str(),
             self.library_type.as_str(),
             self.library_id.as_str()
-        )
+        )?;
+        if !self.account_id.is_empty() {
+            write!(f, " [{}]", self.account_id.as_str())?;
+        }
+        Ok(())
     }
 }
 
-#[derive(Debug, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
+#[derive(Debug, Default, Copy, Clone, Eq, PartialEq, Serialize, Deserialize)]
 #[repr(u8)]
 #[derive_ReprC]
 pub enum GameInstallStatus {
     Installed,
     Installing,
+    #[default]
     InLibrary,
     Queued,
 }
@@ -111,11 +120,15 @@ impl fmt::Display for GameInstallStatus {
         })
     }
 }
-impl Default for GameInstallStatus {
-    fn default() -> Self {
-        GameInstallStatus::InLibrary
-    }
+/// An account (or instance) of a library, e.g. one of several Steam users logged in on this machine
+#[derive_ReprC]
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct LibraryAccount {
+    pub account_id: String,
+    pub name: String,
 }
+
 #[derive_ReprC]
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -123,6 +136,7 @@ pub struct ScannedGameLibraryMetadata {
     pub name: String,
     pub library_type: String,
     pub library_id: String,
+    pub account_id: String,
 
     pub last_played_epoch: TaggedOption<u64>,
     pub install_status: GameInstallStatus,
@@ -135,6 +149,7 @@ impl Default for ScannedGameLibraryMetadata {
             name: "".into(),
             library_type: "".into(),
             library_id: "".into(),
+            account_id: "".into(),
             last_played_epoch: TaggedOption::None,
             install_status: GameInstallStatus::InLibrary,
             playtime_secs: 0,
@@ -143,32 +158,35 @@ impl Default for ScannedGameLibraryMetadata {
     }
 }
 
-impl Into<GameData> for ScannedGameLibraryMetadata {
-    fn into(self) -> GameData {
+impl From<ScannedGameLibraryMetadata> for GameData {
+    fn from(scanned: ScannedGameLibraryMetadata) -> Self {
         GameData {
-            name: self.name.into(),
-            library_type: self.library_type.into(),
-            library_id: self.library_id.into(),
-            last_played: self
+            name: scanned.name.into(),
+            library_type: scanned.library_type.into(),
+            library_id: scanned.library_id.into(),
+            account_id: scanned.account_id.into(),
+            last_played: scanned
                 .last_played_epoch
                 .into_rust()
                 .and_then(|v| DateTime::from_timestamp(v as i64, 0)),
-            install_status: self.install_status,
-            play_time: Duration::from(TimeDelta::seconds(self.playtime_secs as i64)),
-            icon_url: self.icon_url.into_rust().map(RString::from),
+            install_status: scanned.install_status,
+            play_time: Duration::from(TimeDelta::seconds(scanned.playtime_secs as i64)),
+            icon_url: scanned.icon_url.into_rust().map(RString::from),
             ..GameData::default()
         }
     }
 }
 impl GameCommon for ScannedGameLibraryMetadata {
-    fn get_ref(&self) -> GameLibraryRef {
+    fn get_ref(&self) -> GameLibraryRef<'_> {
         let id_str: &str = &self.library_id;
         let ty_str: &str = &self.library_type;
         let name_str: &str = &self.name;
+        let account_str: &str = &self.account_id;
         GameLibraryRef {
             library_id: id_str.into(),
             library_type: ty_str.into(),
             name: name_str.into(),
+            account_id: account_str.into(),
         }
     }
 }
@@ -215,6 +233,15 @@ impl Hash for GenreData {
         self.library_id.hash(state);
     }
 }
+/// An ID of the game in another store or database, e.g. a Steam app ID found by a generic scanner
+#[derive_ReprC]
+#[repr(C)]
+#[derive(Debug, Clone)]
+pub struct ExternalId {
+    pub source: String,
+    pub id: String,
+}
+
 #[derive_ReprC]
 #[repr(C)]
 #[derive(Debug, Clone)]
@@ -231,6 +258,7 @@ pub struct GameMetadata {
     pub icon_url: TaggedOption<String>,
     pub cover_url: TaggedOption<String>,
     pub header_url: TaggedOption<String>,
+    pub external_ids: Vec<ExternalId>,
 }
 
 impl Default for GameMetadata {
@@ -248,6 +276,7 @@ impl Default for GameMetadata {
             icon_url: TaggedOption::None,
             cover_url: TaggedOption::None,
             header_url: TaggedOption::None,
+            external_ids: Vec::EMPTY,
         }
     }
 }
@@ -255,7 +284,7 @@ pub trait EditableEnum: fmt::Display + Sized + PartialEq + 'static {
     const ALL: &'static [Self];
 }
 
-#[derive(Copy, Clone, Debug, Default, PartialEq)]
+#[derive(Copy, Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
 pub enum CompletionStatus {
     #[default]
     Backlog,
@@ -278,6 +307,31 @@ impl fmt::Display for CompletionStatus {
     }
 }
 
+/// A user-defined tag, `color` is a `#rrggbb` hex string
+#[derive(Clone, Debug, Eq, PartialEq, Default)]
+pub struct TagData {
+    pub id: i32,
+    pub name: RString,
+    pub color: RString,
+}
+impl fmt::Display for TagData {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.name)
+    }
+}
+
+/// One library entry backing a (possibly merged) game
+#[derive(Clone, Debug, Default)]
+pub struct GameSource {
+    pub id: i32,
+    pub library_type: RString,
+    pub library_id: RString,
+    pub account_id: RString,
+    pub install_status: GameInstallStatus,
+    pub play_time: Duration,
+    pub last_played: Option<DateTime<Utc>>,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct GameData {
     pub id: i32,
@@ -293,18 +347,59 @@ pub struct GameData {
     pub cover_url: Option<RString>,
     pub library_type: RString,
     pub library_id: RString,
+    pub account_id: RString,
     pub completion_status: CompletionStatus,
+    /// Every library entry merged into this game, including its own
+    pub sources: RVec<GameSource>,
+    pub preferred_source: Option<i32>,
+    pub tags: RVec<TagData>,
+    pub favourite: bool,
+    pub hidden: bool,
+    pub developers: RVec<RString>,
+    pub publishers: RVec<RString>,
+    pub install_path: Option<RString>,
+    pub notes: RString,
 }
 impl GameData {
+    /// Narrows this game down to a single one of its sources
+    pub fn for_source(&self, source: &GameSource) -> GameData {
+        GameData {
+            id: source.id,
+            library_type: source.library_type.clone(),
+            library_id: source.library_id.clone(),
+            account_id: source.account_id.clone(),
+            install_status: source.install_status,
+            play_time: source.play_time,
+            last_played: source.last_played,
+            sources: RVec::new(),
+            preferred_source: None,
+            ..self.clone()
+        }
+    }
+    /// The source to launch, install or uninstall through: the preferred one, else any installed one
+    pub fn preferred_source(&self) -> GameData {
+        self.preferred_source
+            .and_then(|id| self.sources.iter().find(|s| s.id == id))
+            .or_else(|| {
+                self.sources
+                    .iter()
+                    .find(|s| s.install_status == GameInstallStatus::Installed)
+            })
+            .or(self.sources.first())
+            .map(|source| self.for_source(source))
+            .unwrap_or_else(|| self.clone())
+    }
     pub fn extend(&mut self, metadata: GameMetadata) {
-        self.genres = metadata
-            .genres
-            .into_iter()
-            .map(|v| v.clone().into())
-            .collect();
+        self.genres = metadata.genres.iter().cloned().collect();
         if let TaggedOption::Some(description) = metadata.description {
             self.description = description.into();
         }
+        if !metadata.developers.is_empty() {
+            self.developers = metadata.developers.iter().map(|v| v.to_string()).collect();
+        }
+        if !metadata.publishers.is_empty() {
+            self.publishers = metadata.publishers.iter().map(|v| v.to_string()).collect();
+        }
         if let TaggedOption::Some(icon_url) = metadata.icon_url {
             self.icon_url = Some(icon_url.into());
         }
@@ -324,11 +419,12 @@ impl GameData {
     }
 }
 impl GameCommon for GameData {
-    fn get_ref(&self) -> GameLibraryRef {
+    fn get_ref(&self) -> GameLibraryRef<'_> {
         GameLibraryRef {
             library_id: self.library_id.as_str().into(),
             library_type: self.library_type.as_str().into(),
             name: self.name.as_str().into(),
+            account_id: self.account_id.as_str().into(),
         }
     }
 }
@@ -343,4 +439,13 @@ pub enum ConfigSchemaKind {
     String,
     Int,
     Boolean,
+    /// Structured settings, such as a list, stored as JSON text
+    Json,
+}
+/// Process that starts a game, for libraries able to run their games without a client
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LaunchCommand {
+    pub program: RString,
+    pub args: RVec<RString>,
+    pub working_dir: Option<std::path::PathBuf>,
 }
@@ -1,16 +1,17 @@
 use crate::{
     models::ConfigSchemaMetadata, GameInstallStatus, GameLibraryRef, GameLibraryRefOwned,
-    GameMetadata, ScannedGameLibraryMetadata, BASE_DATA_DIR,
+    GameMetadata, LaunchCommand, LibraryAccount, ScannedGameLibraryMetadata, BASE_DATA_DIR,
 };
 use safer_ffi::string::String;
-use std::cell::LazyCell;
 use std::collections::HashMap;
+use std::fmt;
 use std::path::PathBuf;
-use std::sync::Arc;
+use std::sync::{Arc, LazyLock};
 
 pub struct PluginDeclaration {
     pub rustc_version: &'static str,
     pub core_version: &'static str,
+    #[allow(improper_ctypes_definitions)]
     pub register: unsafe extern "C" fn(&mut dyn PluginRegistrar),
 }
 
@@ -20,7 +21,7 @@ pub struct PluginMetadata {
     pub name: String,
 }
 
-pub const ADDONS_DIR: LazyCell<PathBuf> = LazyCell::new(|| BASE_DATA_DIR.join("addons"));
+pub static ADDONS_DIR: LazyLock<PathBuf> = LazyLock::new(|| BASE_DATA_DIR.join("addons"));
 pub type ConfigsSchema = HashMap<std::string::String, ConfigSchemaMetadata>;
 
 pub fn load_schema(id: &str) -> ConfigsSchema {
@@ -44,18 +45,64 @@ pub trait PluginRegistrar {
     );
 }
 
+/// Library type a metadata scanner declares to enrich games of every library
+pub const ANY_LIBRARY_TYPE: &str = "*";
+
 pub trait GameMetadataScanner: Send {
+    /// Library types whose games this scanner can describe, [`ANY_LIBRARY_TYPE`] for all of them
+    ///
+    /// A scanner always supports the library registered under its own key.
+    fn supported_library_types(&self) -> Vec<std::string::String> {
+        Vec::new()
+    }
     fn get_metadata(&self, game: GameLibraryRef) -> Option<GameMetadata>;
     fn get_metadatas<'a>(
         &self,
         games: &[GameLibraryRef<'a>],
     ) -> HashMap<GameLibraryRefOwned, GameMetadata>;
 }
+/// Why a library could not hand a launch, install or uninstall over to its client
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum ActionError {
+    /// The library's client, by name, isn't installed
+    ClientMissing(std::string::String),
+    /// No application opens links of this URL scheme
+    NoUrlHandler(std::string::String),
+    Failed(std::string::String),
+}
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ActionError::ClientMissing(client) => write!(f, "{} is not installed", client),
+            ActionError::NoUrlHandler(scheme) => {
+                write!(f, "no application is set up to open {}:// links", scheme)
+            }
+            ActionError::Failed(err) => f.write_str(err),
+        }
+    }
+}
+impl std::error::Error for ActionError {}
+
 pub trait GameLibrary: Send {
-    fn scan(&self) -> Vec<ScannedGameLibraryMetadata>;
-    fn launch(&self, game: GameLibraryRef);
-    fn install(&self, game: GameLibraryRef);
-    fn uninstall(&self, game: GameLibraryRef);
+    /// Lists the accounts this library can scan, an empty list means the library has no accounts
+    fn get_accounts(&self) -> Vec<LibraryAccount>;
+    fn scan(&self, account_id: &str) -> Vec<ScannedGameLibraryMetadata>;
+    /// Hands the game to the library's client, returning once it was handed over
+    fn launch(&self, game: GameLibraryRef) -> Result<(), ActionError>;
+    /// Command running the game directly, so Gami can wrap it and notice when it exits
+    ///
+    /// Libraries that hand games to their own client keep the default and are started with
+    /// [`GameLibrary::launch`].
+    fn launch_command(&self, _game: GameLibraryRef) -> Option<LaunchCommand> {
+        None
+    }
+    /// Whether scans carry the play time and last played date of games, synced games are only
+    /// updated with them then
+    fn reports_play_stats(&self) -> bool {
+        false
+    }
+    fn install(&self, game: GameLibraryRef) -> Result<(), ActionError>;
+    fn uninstall(&self, game: GameLibraryRef) -> Result<(), ActionError>;
     fn check_install_status(&self, game: GameLibraryRef) -> GameInstallStatus;
 }
 pub static CORE_VERSION: &str = env!("CARGO_PKG_VERSION");